@@ -0,0 +1,499 @@
+use anyhow::{anyhow, Result};
+use eframe::egui;
+use rodio::{
+    cpal::{
+        self,
+        traits::{DeviceTrait, HostTrait, StreamTrait},
+        FromSample, Sample, SizedSample,
+    },
+    OutputStream, OutputStreamHandle,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute::{
+        bus,
+        extern_in::{self, Capture},
+        quarantine::Quarantine,
+        smoothing,
+    },
+    jack_io::JackSettings,
+};
+
+// Rates offered in the settings, if the device supports them
+const SAMPLE_RATES: [u32; 4] = [44100, 48000, 88200, 96000];
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AudioSettings {
+    /// Output device name, the host's default if `None`
+    pub device: Option<String>,
+    /// Sample rate of the device, its default if `None`
+    pub sample_rate: Option<u32>,
+    /// Samples rendered by the runtime at a time
+    pub block_size: usize,
+    /// Seconds of audio kept queued ahead of the device
+    pub latency: f32,
+    /// Renders small blocks just ahead of the device, buffering up to
+    /// `latency` only when the runtime can't keep up
+    #[serde(default)]
+    pub low_latency: bool,
+    /// Converts to the device rate with rubato instead of rodio's linear
+    /// interpolation
+    #[serde(default = "default_resample")]
+    pub resample: bool,
+    /// Whether live input is captured for Audio In nodes
+    #[serde(default)]
+    pub input_enabled: bool,
+    /// Input device name, the host's default if `None`
+    #[serde(default)]
+    pub input_device: Option<String>,
+    #[serde(default)]
+    pub jack: JackSettings,
+    /// Time constant in milliseconds of the ramps to edited input values
+    #[serde(default = "default_smoothing")]
+    pub smoothing: f32,
+    /// What's done with NaN and infinite values nodes output
+    #[serde(default)]
+    pub quarantine: Quarantine,
+    /// Keeps decaying tails out of the slow denormal range
+    #[serde(default)]
+    pub denormal_protection: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            device: None,
+            sample_rate: None,
+            block_size: 512,
+            latency: 0.1,
+            low_latency: false,
+            resample: true,
+            input_enabled: false,
+            input_device: None,
+            jack: JackSettings::default(),
+            smoothing: smoothing::DEFAULT_MS,
+            quarantine: Quarantine::Off,
+            denormal_protection: false,
+        }
+    }
+}
+
+fn default_resample() -> bool {
+    true
+}
+
+fn default_smoothing() -> f32 {
+    smoothing::DEFAULT_MS
+}
+
+fn find_device(name: Option<&str>) -> Result<cpal::Device> {
+    let host = cpal::default_host();
+
+    match name {
+        Some(name) => host
+            .output_devices()?
+            .find(|device| device.name().ok().as_deref() == Some(name))
+            .ok_or(anyhow!("Output device {name} not found")),
+        None => host
+            .default_output_device()
+            .ok_or(anyhow!("No default output device")),
+    }
+}
+
+pub fn output_devices() -> Vec<String> {
+    cpal::default_host()
+        .output_devices()
+        .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+        .unwrap_or_default()
+}
+
+fn find_input_device(name: Option<&str>) -> Result<cpal::Device> {
+    let host = cpal::default_host();
+
+    match name {
+        Some(name) => host
+            .input_devices()?
+            .find(|device| device.name().ok().as_deref() == Some(name))
+            .ok_or(anyhow!("Input device {name} not found")),
+        None => host
+            .default_input_device()
+            .ok_or(anyhow!("No default input device")),
+    }
+}
+
+pub fn input_devices() -> Vec<String> {
+    cpal::default_host()
+        .input_devices()
+        .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+        .unwrap_or_default()
+}
+
+pub fn sample_rates(device: Option<&str>) -> Vec<u32> {
+    let Ok(configs) = find_device(device).and_then(|device| Ok(device.supported_output_configs()?))
+    else {
+        return Vec::new();
+    };
+
+    let ranges: Vec<_> = configs
+        .map(|range| range.min_sample_rate().0..=range.max_sample_rate().0)
+        .collect();
+
+    SAMPLE_RATES
+        .into_iter()
+        .filter(|rate| ranges.iter().any(|range| range.contains(rate)))
+        .collect()
+}
+
+/// Opens the output device, also returns the sample rate it runs at.
+pub fn open_stream(settings: &AudioSettings) -> Result<(OutputStream, OutputStreamHandle, u32)> {
+    let device = find_device(settings.device.as_deref())?;
+
+    let rate = match settings.sample_rate {
+        Some(rate) => rate,
+        None => device.default_output_config()?.sample_rate().0,
+    };
+
+    let (stream, handle) = match settings.sample_rate {
+        Some(rate) => {
+            let config = device
+                .supported_output_configs()?
+                .find(|range| {
+                    (range.min_sample_rate().0..=range.max_sample_rate().0).contains(&rate)
+                })
+                .ok_or(anyhow!("Sample rate of {rate} Hz isn't supported"))?
+                .with_sample_rate(cpal::SampleRate(rate));
+
+            OutputStream::try_from_device_config(&device, config)?
+        }
+        None => OutputStream::try_from_device(&device)?,
+    };
+
+    Ok((stream, handle, rate))
+}
+
+/// Downmixes captured frames to mono, converts them to 44.1 kHz by linear
+/// interpolation and pushes them to an external input channel.
+pub struct CaptureConverter {
+    capture: Option<&'static Capture>,
+    channels: usize,
+    step: f64,
+    phase: f64,
+    prev: f32,
+}
+
+impl CaptureConverter {
+    pub fn new(channel: usize, channels: usize, sample_rate: u32) -> Self {
+        CaptureConverter {
+            capture: extern_in::capture(channel),
+            channels: channels.max(1),
+            step: sample_rate as f64 / 44100.0,
+            phase: 0.0,
+            prev: 0.0,
+        }
+    }
+
+    pub fn convert<T>(&mut self, data: &[T])
+    where
+        T: Sample,
+        f32: FromSample<T>,
+    {
+        let Some(capture) = self.capture else {
+            return;
+        };
+
+        for frame in data.chunks(self.channels) {
+            let sum: f32 = frame.iter().map(|sample| sample.to_sample::<f32>()).sum();
+            let next = sum / frame.len() as f32;
+
+            while self.phase < 1.0 {
+                let t = self.phase as f32;
+                capture.push(self.prev + (next - self.prev) * t);
+                self.phase += self.step;
+            }
+
+            self.phase -= 1.0;
+            self.prev = next;
+        }
+    }
+}
+
+fn build_input<T>(device: &cpal::Device, config: &cpal::StreamConfig) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let mut converter = CaptureConverter::new(
+        extern_in::DEVICE,
+        config.channels as usize,
+        config.sample_rate.0,
+    );
+
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _| converter.convert(data),
+        |e| println!("Audio input error: {e}"),
+        None,
+    )?;
+
+    Ok(stream)
+}
+
+/// Starts capturing live input, which runs until the stream is dropped.
+pub fn open_input(settings: &AudioSettings) -> Result<cpal::Stream> {
+    let device = find_input_device(settings.input_device.as_deref())?;
+    let supported = device.default_input_config()?;
+    let config = supported.config();
+
+    let stream = match supported.sample_format() {
+        cpal::SampleFormat::F32 => build_input::<f32>(&device, &config)?,
+        cpal::SampleFormat::I16 => build_input::<i16>(&device, &config)?,
+        cpal::SampleFormat::U16 => build_input::<u16>(&device, &config)?,
+        format => return Err(anyhow!("Unsupported input sample format {format:?}")),
+    };
+    stream.play()?;
+
+    Ok(stream)
+}
+
+/// Window for picking the audio devices and buffering.
+#[derive(Debug, Default)]
+pub struct AudioDialog {
+    pub open: bool,
+    pub settings: AudioSettings,
+    /// Rate of the open output device
+    pub output_rate: Option<u32>,
+    draft: Option<AudioSettings>,
+    devices: Vec<String>,
+    input_devices: Vec<String>,
+    rates: Vec<u32>,
+    error: Option<String>,
+}
+
+impl AudioDialog {
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+
+    fn refresh(&mut self, draft: &AudioSettings) {
+        self.devices = output_devices();
+        self.input_devices = input_devices();
+        self.rates = sample_rates(draft.device.as_deref());
+    }
+
+    /// Shows the window, returns the settings to switch to once applied.
+    pub fn show(&mut self, ctx: &egui::Context) -> Option<AudioSettings> {
+        if !self.open {
+            self.draft = None;
+            return None;
+        }
+
+        let mut draft = match self.draft.take() {
+            Some(draft) => draft,
+            None => {
+                let draft = self.settings.clone();
+                self.refresh(&draft);
+                draft
+            }
+        };
+
+        let mut open = self.open;
+        let mut apply = false;
+
+        egui::Window::new("Audio Settings")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("audio-settings")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Device");
+                        let old_device = draft.device.clone();
+                        egui::ComboBox::from_id_salt("audio-device")
+                            .selected_text(draft.device.as_deref().unwrap_or("Default"))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut draft.device, None, "Default");
+                                for device in &self.devices {
+                                    ui.selectable_value(
+                                        &mut draft.device,
+                                        Some(device.clone()),
+                                        device,
+                                    );
+                                }
+                            });
+                        if draft.device != old_device {
+                            self.rates = sample_rates(draft.device.as_deref());
+                            draft.sample_rate = None;
+                        }
+                        ui.end_row();
+
+                        ui.label("Sample rate");
+                        let rate_text = match draft.sample_rate {
+                            Some(rate) => format!("{rate} Hz"),
+                            None => "Default".to_string(),
+                        };
+                        egui::ComboBox::from_id_salt("audio-sample-rate")
+                            .selected_text(rate_text)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut draft.sample_rate, None, "Default");
+                                for rate in &self.rates {
+                                    ui.selectable_value(
+                                        &mut draft.sample_rate,
+                                        Some(*rate),
+                                        format!("{rate} Hz"),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label("Resampling");
+                        ui.checkbox(&mut draft.resample, "High quality");
+                        ui.end_row();
+
+                        if let Some(rate) = self.output_rate {
+                            ui.label("Device rate");
+                            let converted = match (rate, self.settings.resample) {
+                                (44100, _) => "no conversion",
+                                (_, true) => "resampled by rubato",
+                                (_, false) => "resampled by rodio",
+                            };
+                            ui.label(format!("{rate} Hz, {converted}"));
+                            ui.end_row();
+                        }
+
+                        ui.label("Block size");
+                        ui.add(
+                            egui::DragValue::new(&mut draft.block_size)
+                                .range(64..=4096)
+                                .suffix(" samples"),
+                        );
+                        ui.end_row();
+
+                        ui.label("Latency");
+                        let mut latency_ms = draft.latency * 1000.0;
+                        ui.add(
+                            egui::DragValue::new(&mut latency_ms)
+                                .range(20.0..=500.0)
+                                .suffix(" ms"),
+                        );
+                        draft.latency = latency_ms / 1000.0;
+                        ui.end_row();
+
+                        ui.label("Low latency");
+                        ui.checkbox(&mut draft.low_latency, "").on_hover_text(
+                            "Play with about 15 ms of buffering, falling back \
+                                 towards the latency above on underruns",
+                        );
+                        ui.end_row();
+
+                        ui.label("Smoothing");
+                        ui.add(
+                            egui::DragValue::new(&mut draft.smoothing)
+                                .range(0.0..=100.0)
+                                .suffix(" ms"),
+                        )
+                        .on_hover_text("Time edited input values ramp over, 0 to jump");
+                        ui.end_row();
+
+                        ui.label("NaN guard");
+                        egui::ComboBox::from_id_salt("audio-quarantine")
+                            .selected_text(draft.quarantine.name())
+                            .show_ui(ui, |ui| {
+                                for quarantine in Quarantine::ALL {
+                                    ui.selectable_value(
+                                        &mut draft.quarantine,
+                                        quarantine,
+                                        quarantine.name(),
+                                    );
+                                }
+                            })
+                            .response
+                            .on_hover_text(
+                                "Fix NaN and infinite node outputs before they spread, \
+                                 marking the nodes that produced them",
+                            );
+                        ui.end_row();
+
+                        ui.label("Denormals");
+                        ui.checkbox(&mut draft.denormal_protection, "Protect")
+                            .on_hover_text(
+                                "Flush denormals to zero and keep feedback paths out of \
+                                 them, against CPU spikes on long reverb and filter tails",
+                            );
+                        ui.end_row();
+
+                        ui.label("Input");
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut draft.input_enabled, "");
+                            ui.add_enabled_ui(draft.input_enabled, |ui| {
+                                egui::ComboBox::from_id_salt("audio-input-device")
+                                    .selected_text(
+                                        draft.input_device.as_deref().unwrap_or("Default"),
+                                    )
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(
+                                            &mut draft.input_device,
+                                            None,
+                                            "Default",
+                                        );
+                                        for device in &self.input_devices {
+                                            ui.selectable_value(
+                                                &mut draft.input_device,
+                                                Some(device.clone()),
+                                                device,
+                                            );
+                                        }
+                                    });
+                            });
+                        });
+                        ui.end_row();
+                    });
+
+                ui.separator();
+                let jack = &mut draft.jack;
+                ui.checkbox(&mut jack.enabled, "JACK ports");
+                ui.add_enabled_ui(jack.enabled, |ui| {
+                    egui::Grid::new("jack-settings")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            ui.label("Audio inputs");
+                            ui.add(egui::DragValue::new(&mut jack.audio_ins).range(0..=16));
+                            ui.end_row();
+
+                            ui.label("Audio outputs");
+                            ui.add(egui::DragValue::new(&mut jack.audio_outs).range(0..=16));
+                            ui.end_row();
+
+                            ui.label("Aux outputs");
+                            ui.add(egui::DragValue::new(&mut jack.aux_outs).range(0..=bus::AUX));
+                            ui.end_row();
+
+                            ui.label("Sidechain input");
+                            ui.checkbox(&mut jack.sidechain_in, "");
+                            ui.end_row();
+
+                            ui.label("MIDI outputs");
+                            ui.add(egui::DragValue::new(&mut jack.midi_outs).range(0..=16));
+                            ui.end_row();
+                        });
+                    ui.checkbox(&mut jack.auto_connect, "Connect to system ports");
+                });
+
+                if let Some(error) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Refresh").clicked() {
+                        self.refresh(&draft);
+                    }
+                    apply = ui.button("Apply").clicked();
+                });
+            });
+
+        self.open = open;
+        self.draft = Some(draft.clone());
+
+        apply.then_some(draft)
+    }
+}
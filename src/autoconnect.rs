@@ -0,0 +1,148 @@
+use eframe::egui;
+use egui_graph_edit::{InputId, NodeId, OutputId};
+use serde::{Deserialize, Serialize};
+
+use crate::graph::SynthGraph;
+
+/// Connection made between two kinds of node, with how often it was made.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Choice {
+    src: String,
+    port: usize,
+    dst: String,
+    input: String,
+    count: usize,
+}
+
+/// Connections made so far, keyed by the labels of the nodes at both ends.
+/// Saved with the app settings rather than the patch.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ConnectHistory {
+    choices: Vec<Choice>,
+}
+
+impl ConnectHistory {
+    pub fn learn(&mut self, graph: &SynthGraph, output: OutputId, input: InputId) {
+        let (Some((src, port, _)), Some((dst, input))) =
+            (describe_output(graph, output), describe_input(graph, input))
+        else {
+            return;
+        };
+
+        match self
+            .choices
+            .iter_mut()
+            .find(|c| c.src == src && c.port == port && c.dst == dst && c.input == input)
+        {
+            Some(choice) => choice.count += 1,
+            None => self.choices.push(Choice {
+                src,
+                port,
+                dst,
+                input,
+                count: 1,
+            }),
+        }
+    }
+
+    /// Most frequent earlier choice between nodes like `src` and `dst`,
+    /// otherwise the first output of `src` into the first free input of
+    /// `dst` of the same type.
+    fn propose(&self, graph: &SynthGraph, src: NodeId, dst: NodeId) -> Option<(OutputId, InputId)> {
+        let src_node = graph.nodes.get(src)?;
+        let dst_node = graph.nodes.get(dst)?;
+        let free = |input: &InputId| graph.connection(*input).is_none();
+
+        let learned = self
+            .choices
+            .iter()
+            .filter(|c| c.src == src_node.label && c.dst == dst_node.label)
+            .filter_map(|c| {
+                let (_, output) = *src_node.outputs.get(c.port)?;
+                let (_, input) = *dst_node.inputs.iter().find(|(name, _)| *name == c.input)?;
+                free(&input).then_some((c.count, output, input))
+            })
+            .max_by_key(|(count, _, _)| *count);
+        if let Some((_, output, input)) = learned {
+            return Some((output, input));
+        }
+
+        let (_, output) = *src_node.outputs.first()?;
+        let typ = graph.try_get_output(output)?.typ;
+        let (_, input) = *dst_node.inputs.iter().find(|(_, input)| {
+            free(input)
+                && graph
+                    .try_get_input(*input)
+                    .is_some_and(|param| param.typ == typ)
+        })?;
+
+        Some((output, input))
+    }
+}
+
+fn describe_output(graph: &SynthGraph, output: OutputId) -> Option<(String, usize, String)> {
+    let node = graph.nodes.get(graph.try_get_output(output)?.node)?;
+    let port = node.output_ids().position(|id| id == output)?;
+
+    Some((node.label.clone(), port, node.outputs[port].0.clone()))
+}
+
+fn describe_input(graph: &SynthGraph, input: InputId) -> Option<(String, String)> {
+    let node = graph.nodes.get(graph.try_get_input(input)?.node)?;
+    let (name, _) = node.inputs.iter().find(|(_, id)| *id == input)?;
+
+    Some((node.label.clone(), name.clone()))
+}
+
+/// Offers to wire a node created from the finder to the node selected at the
+/// time, accepted with Enter.
+#[derive(Debug, Default)]
+pub struct AutoConnect {
+    pub history: ConnectHistory,
+    pending: Option<(OutputId, InputId)>,
+    // Keys of the frame the node was created in belong to the finder
+    fresh: bool,
+}
+
+impl AutoConnect {
+    pub fn node_created(&mut self, graph: &SynthGraph, selected: Option<NodeId>, created: NodeId) {
+        self.pending = selected
+            .filter(|src| *src != created)
+            .and_then(|src| self.history.propose(graph, src, created));
+        self.fresh = true;
+    }
+
+    /// Shows the pending suggestion, returns it once accepted.
+    pub fn show(&mut self, ctx: &egui::Context, graph: &SynthGraph) -> Option<(OutputId, InputId)> {
+        let (output, input) = self.pending?;
+        let (Some((src, _, out_name)), Some((dst, in_name))) =
+            (describe_output(graph, output), describe_input(graph, input))
+        else {
+            // Either end was deleted in the meantime
+            self.pending = None;
+            return None;
+        };
+
+        let fresh = std::mem::take(&mut self.fresh);
+        let mut accept = !fresh && ctx.input(|state| state.key_pressed(egui::Key::Enter));
+        let mut dismiss = !fresh && ctx.input(|state| state.key_pressed(egui::Key::Escape));
+
+        egui::Window::new("Suggested connection")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -16.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{src} {out_name} → {dst} {in_name}"));
+                    accept |= ui.button("Connect (Enter)").clicked();
+                    dismiss |= ui.button("Dismiss (Esc)").clicked();
+                });
+            });
+
+        if accept || dismiss {
+            self.pending = None;
+        }
+
+        accept.then_some((output, input))
+    }
+}
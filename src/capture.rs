@@ -0,0 +1,327 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{channel, Sender},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use eframe::egui;
+use egui_graph_edit::NodeId;
+use rfd::FileDialog;
+
+use crate::{
+    graph::SynthGraph,
+    notify,
+    remote::RuntimeRemote,
+    sink::{Sink, SinkId},
+};
+
+const RATE: u32 = 44100;
+
+// Mono 32-bit float header, `len` being the number of samples after it
+fn write_header(out: &mut impl Write, len: usize) -> io::Result<()> {
+    let data = len as u32 * 4;
+
+    out.write_all(b"RIFF")?;
+    out.write_all(&(36 + data).to_le_bytes())?;
+    out.write_all(b"WAVEfmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;
+    // IEEE float, one channel
+    out.write_all(&3u16.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?;
+    out.write_all(&RATE.to_le_bytes())?;
+    out.write_all(&(RATE * 4).to_le_bytes())?;
+    out.write_all(&4u16.to_le_bytes())?;
+    out.write_all(&32u16.to_le_bytes())?;
+    out.write_all(b"data")?;
+    out.write_all(&data.to_le_bytes())?;
+
+    Ok(())
+}
+
+/// Sink streaming into a WAV file. The file is written on a thread of its
+/// own, so the runtime never waits for the disk.
+#[derive(Debug)]
+struct WavSink {
+    tx: Sender<Vec<f32>>,
+}
+
+impl Sink for WavSink {
+    fn consume(&mut self, block: &[f32]) {
+        // The writer only goes away after the sink does
+        let _ = self.tx.send(block.to_vec());
+    }
+}
+
+/// Recording of one port, or of playback, into its own file.
+#[derive(Debug)]
+struct Take {
+    path: PathBuf,
+    sink: SinkId,
+    samples: Arc<AtomicUsize>,
+    // Completes the header once the runtime drops the sink
+    writer: JoinHandle<io::Result<()>>,
+}
+
+impl Take {
+    fn start(
+        path: PathBuf,
+        port: Option<(NodeId, usize)>,
+        remote: &mut RuntimeRemote,
+    ) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(&path)?);
+        write_header(&mut out, 0)?;
+
+        let (tx, rx) = channel::<Vec<f32>>();
+        let samples = Arc::new(AtomicUsize::new(0));
+        let written = Arc::clone(&samples);
+        let writer = std::thread::spawn(move || {
+            for block in rx {
+                for sample in &block {
+                    out.write_all(&sample.to_le_bytes())?;
+                }
+                written.fetch_add(block.len(), Ordering::Relaxed);
+            }
+
+            out.seek(SeekFrom::Start(0))?;
+            write_header(&mut out, written.load(Ordering::Relaxed))?;
+            out.flush()
+        });
+
+        let sink = Box::new(WavSink { tx });
+        let sink = match port {
+            Some(port) => remote.add_sink(Some(port), 1.0, sink),
+            None => remote.add_tap(sink),
+        };
+
+        Ok(Take {
+            path,
+            sink,
+            samples,
+            writer,
+        })
+    }
+
+    fn secs(&self) -> f32 {
+        self.samples.load(Ordering::Relaxed) as f32 / RATE as f32
+    }
+}
+
+// Local time isn't known without a time zone database, takes are named in UTC
+fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default();
+    let (days, time) = (secs / 86400, secs % 86400);
+
+    // Civil date from days since the epoch, after Howard Hinnant
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+fn file_name(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Records playback and armed output ports to timestamped WAV files, from
+/// the top bar.
+#[derive(Debug)]
+pub struct Capture {
+    // Asked for on the first recording
+    folder: Option<PathBuf>,
+    // Whether what's heard is recorded, besides the armed ports
+    playback: bool,
+    armed: Vec<(NodeId, usize)>,
+    takes: Vec<Take>,
+    // Takes gone from the runtime whose files are still being completed
+    finishing: Vec<Take>,
+}
+
+impl Default for Capture {
+    fn default() -> Self {
+        Capture {
+            folder: None,
+            playback: true,
+            armed: Vec::new(),
+            takes: Vec::new(),
+            finishing: Vec::new(),
+        }
+    }
+}
+
+impl Capture {
+    pub fn recording(&self) -> bool {
+        !self.takes.is_empty()
+    }
+
+    pub fn toggle(&mut self, graph: &SynthGraph, remote: &mut RuntimeRemote) {
+        if self.recording() {
+            self.stop(remote);
+        } else {
+            self.start(graph, remote);
+        }
+    }
+
+    fn start(&mut self, graph: &SynthGraph, remote: &mut RuntimeRemote) {
+        if self.folder.is_none() {
+            self.folder = FileDialog::new().pick_folder();
+        }
+        let Some(folder) = self.folder.clone() else {
+            return;
+        };
+
+        let stamp = timestamp();
+        let mut ports: Vec<_> = self
+            .armed
+            .iter()
+            .filter_map(|(node_id, port)| {
+                let node = graph.nodes.get(*node_id)?;
+                let (name, _) = node.outputs.get(*port)?;
+                let path = folder.join(format!(
+                    "modal-{stamp}-{}-{}.wav",
+                    file_name(&node.label),
+                    file_name(name)
+                ));
+                Some((path, Some((*node_id, *port))))
+            })
+            .collect();
+        if self.playback {
+            ports.insert(0, (folder.join(format!("modal-{stamp}.wav")), None));
+        }
+
+        for (path, port) in ports {
+            match Take::start(path, port, remote) {
+                Ok(take) => self.takes.push(take),
+                Err(e) => notify::error("Couldn't start recording", e),
+            }
+        }
+    }
+
+    pub fn stop(&mut self, remote: &mut RuntimeRemote) {
+        for take in self.takes.drain(..) {
+            remote.remove_sink(take.sink);
+            self.finishing.push(take);
+        }
+    }
+
+    /// Blocks until the stopped takes are written, for when the editor exits.
+    pub fn wait(&mut self) {
+        for take in self.finishing.drain(..) {
+            let _ = take.writer.join();
+        }
+    }
+
+    // Reports takes once their files are complete
+    fn collect(&mut self) {
+        let (done, finishing): (Vec<_>, Vec<_>) = self
+            .finishing
+            .drain(..)
+            .partition(|take| take.writer.is_finished());
+        self.finishing = finishing;
+
+        for take in done {
+            match take.writer.join() {
+                Ok(Ok(())) => notify::report(format!("Recorded {}", take.path.display())),
+                Ok(Err(e)) => notify::error(&format!("Couldn't write {}", take.path.display()), e),
+                Err(_) => notify::report(format!("Writing {} failed", take.path.display())),
+            }
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, graph: &SynthGraph, remote: &mut RuntimeRemote) {
+        self.collect();
+
+        // Ports of removed nodes can't be recorded anymore
+        self.armed
+            .retain(|(node_id, _)| graph.nodes.contains_key(*node_id));
+
+        let recording = self.recording();
+        let label = if recording { "⏹ Stop" } else { "⏺ Record" };
+        if ui
+            .add(egui::SelectableLabel::new(recording, label))
+            .on_hover_text("Record playback and the armed ports to WAV files")
+            .clicked()
+        {
+            self.toggle(graph, remote);
+        }
+
+        if let Some(take) = self.takes.first() {
+            let secs = take.secs();
+            ui.label(
+                egui::RichText::new(format!("{:02}:{:04.1}", (secs / 60.0) as u32, secs % 60.0))
+                    .color(egui::Color32::RED)
+                    .monospace(),
+            );
+            ui.ctx().request_repaint();
+        }
+
+        ui.add_enabled_ui(!recording, |ui| {
+            ui.menu_button("⏷", |ui| self.show_arming(ui, graph));
+        });
+    }
+
+    fn show_arming(&mut self, ui: &mut egui::Ui, graph: &SynthGraph) {
+        ui.checkbox(&mut self.playback, "Playback")
+            .on_hover_text("What's heard, before the master volume");
+        ui.separator();
+
+        let mut nodes: Vec<_> = graph.nodes.iter().collect();
+        nodes.sort_by(|(_, a), (_, b)| a.label.cmp(&b.label));
+        egui::ScrollArea::vertical()
+            .max_height(240.0)
+            .show(ui, |ui| {
+                for (node_id, node) in nodes {
+                    for (port, (name, _)) in node.outputs.iter().enumerate() {
+                        let key = (node_id, port);
+                        let mut armed = self.armed.contains(&key);
+                        if ui
+                            .checkbox(&mut armed, format!("{} - {name}", node.label))
+                            .changed()
+                        {
+                            if armed {
+                                self.armed.push(key);
+                            } else {
+                                self.armed.retain(|other| *other != key);
+                            }
+                        }
+                    }
+                }
+            });
+
+        ui.separator();
+        let folder = match &self.folder {
+            Some(folder) => format!("Folder: {}", folder.display()),
+            None => "Folder…".into(),
+        };
+        if ui.button(folder).clicked() {
+            if let Some(folder) = FileDialog::new().pick_folder() {
+                self.folder = Some(folder);
+            }
+            ui.close_menu();
+        }
+    }
+}
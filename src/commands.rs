@@ -0,0 +1,338 @@
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+
+/// Editor action reachable from the palette and from shortcuts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, strum::EnumIter)]
+pub enum Command {
+    Palette,
+    AddNode,
+    Save,
+    Load,
+    OpenMidi,
+    DeleteSelection,
+    BypassSelection,
+    SoloSelection,
+    MutateSelection,
+    Freeze,
+    Arrange,
+    AlignLeft,
+    AlignTop,
+    DistributeHorizontally,
+    DistributeVertically,
+    FitAll,
+    FitSelection,
+    StickyNote,
+    Frame,
+    Perform,
+    Table,
+    Inspector,
+    Links,
+    Snapshots,
+    Audio,
+    Settings,
+    ZoomIn,
+    ZoomOut,
+    ResetZoom,
+    Keyboard,
+    Shortcuts,
+    Record,
+    Mute,
+    Panic,
+}
+
+impl Command {
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::Palette => "Command palette",
+            Command::AddNode => "Add node",
+            Command::Save => "Save patch",
+            Command::Load => "Load patch",
+            Command::OpenMidi => "Open MIDI file",
+            Command::DeleteSelection => "Delete selected nodes",
+            Command::BypassSelection => "Bypass selected nodes",
+            Command::SoloSelection => "Solo selected nodes",
+            Command::MutateSelection => "Mutate selected nodes",
+            Command::Freeze => "Toggle freeze",
+            Command::Arrange => "Arrange by signal flow",
+            Command::AlignLeft => "Align left",
+            Command::AlignTop => "Align top",
+            Command::DistributeHorizontally => "Distribute horizontally",
+            Command::DistributeVertically => "Distribute vertically",
+            Command::FitAll => "Fit all",
+            Command::FitSelection => "Fit selection",
+            Command::StickyNote => "Add sticky note",
+            Command::Frame => "Frame selected nodes",
+            Command::Perform => "Toggle perform mode",
+            Command::Table => "Toggle parameter table",
+            Command::Inspector => "Toggle inspector",
+            Command::Links => "Toggle links",
+            Command::Snapshots => "Toggle snapshots",
+            Command::Audio => "Toggle audio settings",
+            Command::Settings => "Toggle settings",
+            Command::ZoomIn => "Zoom the interface in",
+            Command::ZoomOut => "Zoom the interface out",
+            Command::ResetZoom => "Reset the interface zoom",
+            Command::Keyboard => "Toggle virtual keyboard",
+            Command::Shortcuts => "Edit shortcuts",
+            Command::Record => "Start or stop recording",
+            Command::Mute => "Toggle master mute",
+            Command::Panic => "Panic",
+        }
+    }
+
+    /// Whether the command is available while performing.
+    pub fn in_perform(self) -> bool {
+        matches!(
+            self,
+            Command::Palette
+                | Command::Perform
+                | Command::ZoomIn
+                | Command::ZoomOut
+                | Command::ResetZoom
+                | Command::Keyboard
+                | Command::Shortcuts
+                | Command::Record
+                | Command::Mute
+                | Command::Panic
+        )
+    }
+}
+
+/// Shortcuts of the commands, saved with the app settings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Keymap {
+    bindings: Vec<(Command, KeyboardShortcut)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let key = |modifiers, key| KeyboardShortcut::new(modifiers, key);
+
+        Keymap {
+            bindings: vec![
+                (Command::Palette, key(Modifiers::COMMAND, Key::P)),
+                (Command::Save, key(Modifiers::COMMAND, Key::S)),
+                (Command::Load, key(Modifiers::COMMAND, Key::O)),
+                (Command::DeleteSelection, key(Modifiers::NONE, Key::Delete)),
+                (Command::BypassSelection, key(Modifiers::NONE, Key::B)),
+                (Command::FitAll, key(Modifiers::NONE, Key::F)),
+                (Command::FitSelection, key(Modifiers::SHIFT, Key::F)),
+                (Command::ZoomIn, key(Modifiers::COMMAND, Key::Equals)),
+                (Command::ZoomOut, key(Modifiers::COMMAND, Key::Minus)),
+                (Command::ResetZoom, key(Modifiers::COMMAND, Key::Num0)),
+            ],
+        }
+    }
+}
+
+impl Keymap {
+    fn get(&self, command: Command) -> Option<KeyboardShortcut> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == command)
+            .map(|(_, shortcut)| *shortcut)
+    }
+
+    fn set(&mut self, command: Command, shortcut: Option<KeyboardShortcut>) {
+        self.bindings
+            .retain(|(bound, other)| *bound != command && Some(*other) != shortcut);
+        if let Some(shortcut) = shortcut {
+            self.bindings.push((command, shortcut));
+        }
+    }
+
+    // Commands whose shortcut was pressed this frame
+    fn pressed(&self, ctx: &egui::Context) -> Vec<Command> {
+        // Shortcuts with more modifiers go first, so Shift+F isn't taken as F
+        let mut bindings = self.bindings.clone();
+        bindings.sort_by_key(|(_, shortcut)| {
+            let m = shortcut.modifiers;
+            std::cmp::Reverse(m.alt as u8 + m.ctrl as u8 + m.shift as u8 + m.command as u8)
+        });
+
+        ctx.input_mut(|state| {
+            bindings
+                .into_iter()
+                .filter(|(_, shortcut)| state.consume_shortcut(shortcut))
+                .map(|(command, _)| command)
+                .collect()
+        })
+    }
+}
+
+/// Command palette and the shortcut editor.
+#[derive(Debug, Default)]
+pub struct Commands {
+    pub keymap: Keymap,
+    pub shortcuts_open: bool,
+    palette_open: bool,
+    query: String,
+    highlighted: usize,
+    // Command waiting for a key press to bind
+    capturing: Option<Command>,
+}
+
+impl Commands {
+    pub fn open_palette(&mut self) {
+        self.palette_open = true;
+        self.query.clear();
+        self.highlighted = 0;
+    }
+
+    /// Commands triggered this frame, by shortcut or from the palette.
+    pub fn show(&mut self, ctx: &egui::Context, perform: bool) -> Vec<Command> {
+        let mut triggered = Vec::new();
+
+        if let Some(command) = self.capturing {
+            self.capture(ctx, command);
+        } else if !ctx.wants_keyboard_input() {
+            triggered.extend(self.keymap.pressed(ctx));
+        }
+
+        if self.palette_open {
+            triggered.extend(self.show_palette(ctx, perform));
+        }
+        if self.shortcuts_open {
+            self.show_shortcuts(ctx);
+        }
+
+        triggered.retain(|command| !perform || command.in_perform());
+        triggered
+    }
+
+    fn capture(&mut self, ctx: &egui::Context, command: Command) {
+        let pressed = ctx.input(|state| {
+            state.events.iter().find_map(|event| match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } => Some((*key, *modifiers)),
+                _ => None,
+            })
+        });
+
+        match pressed {
+            Some((Key::Escape, _)) => self.capturing = None,
+            Some((key, modifiers)) => {
+                self.keymap
+                    .set(command, Some(KeyboardShortcut::new(modifiers, key)));
+                self.capturing = None;
+            }
+            None => {}
+        }
+    }
+
+    fn show_palette(&mut self, ctx: &egui::Context, perform: bool) -> Option<Command> {
+        let query = self.query.to_lowercase();
+        let matching: Vec<Command> = Command::iter()
+            .filter(|command| *command != Command::Palette)
+            .filter(|command| !perform || command.in_perform())
+            .filter(|command| fuzzy(&command.name().to_lowercase(), &query))
+            .collect();
+        self.highlighted = self.highlighted.min(matching.len().saturating_sub(1));
+
+        let (up, down, enter, escape) = ctx.input_mut(|state| {
+            (
+                state.consume_key(Modifiers::NONE, Key::ArrowUp),
+                state.consume_key(Modifiers::NONE, Key::ArrowDown),
+                state.consume_key(Modifiers::NONE, Key::Enter),
+                state.consume_key(Modifiers::NONE, Key::Escape),
+            )
+        });
+        if up {
+            self.highlighted = self.highlighted.saturating_sub(1);
+        }
+        if down && self.highlighted + 1 < matching.len() {
+            self.highlighted += 1;
+        }
+
+        let mut chosen = enter
+            .then(|| matching.get(self.highlighted).copied())
+            .flatten();
+        egui::Window::new("Commands")
+            .title_bar(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 48.0])
+            .show(ctx, |ui| {
+                ui.set_width(360.0);
+                ui.text_edit_singleline(&mut self.query).request_focus();
+                ui.separator();
+
+                for (k, command) in matching.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .selectable_label(k == self.highlighted, command.name())
+                            .clicked()
+                        {
+                            chosen = Some(*command);
+                        }
+                        if let Some(shortcut) = self.keymap.get(*command) {
+                            ui.weak(ctx.format_shortcut(&shortcut));
+                        }
+                    });
+                }
+            });
+
+        if chosen.is_some() || escape {
+            self.palette_open = false;
+        }
+
+        chosen
+    }
+
+    fn show_shortcuts(&mut self, ctx: &egui::Context) {
+        let mut open = self.shortcuts_open;
+
+        egui::Window::new("Shortcuts")
+            .open(&mut open)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("shortcuts")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for command in Command::iter() {
+                                ui.label(command.name());
+
+                                if self.capturing == Some(command) {
+                                    ui.weak("Press a key, Esc to cancel");
+                                } else {
+                                    let text = self
+                                        .keymap
+                                        .get(command)
+                                        .map(|shortcut| ctx.format_shortcut(&shortcut))
+                                        .unwrap_or_default();
+                                    if ui.button(text).on_hover_text("Change").clicked() {
+                                        self.capturing = Some(command);
+                                    }
+                                }
+
+                                if ui.small_button("Clear").clicked() {
+                                    self.keymap.set(command, None);
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+
+                if ui.button("Restore defaults").clicked() {
+                    self.keymap = Keymap::default();
+                }
+            });
+
+        self.shortcuts_open = open;
+    }
+}
+
+/// Whether the characters of `query` appear in `name` in order.
+pub fn fuzzy(name: &str, query: &str) -> bool {
+    let mut chars = name.chars();
+    query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|c| chars.any(|n| n == c))
+}
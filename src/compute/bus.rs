@@ -0,0 +1,126 @@
+//! Mixes fed by the output nodes. Bus 0 is the main mix heard on playback,
+//! the others are aux outs sent to JACK. Named buses carry the signals of
+//! Send nodes to the Receive nodes of the same name, a step later.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+};
+
+/// Numbered aux buses, after the main one.
+pub const AUX: usize = 8;
+// Aux bus k is played on this external output channel, past the ones
+// reachable from the Jack Out node
+const AUX_CHANNELS: usize = 64;
+
+/// Buses of one runtime, fed by its output and Send nodes through
+/// [`InputData::bus`](super::node::InputData::bus).
+#[derive(Clone, Debug, Default)]
+pub struct Buses {
+    // Sums of the current step, `None` while no node fed the bus
+    sums: [Cell<Option<f32>>; AUX + 1],
+    // Sums of the last completed step
+    mixed: [Option<f32>; AUX + 1],
+    named: RefCell<BTreeMap<String, Named>>,
+    // New names of renamed buses, followed by their receivers
+    renamed: RefCell<BTreeMap<String, String>>,
+    renames: Cell<usize>,
+    // Whether buses came or went since the names were last taken
+    names_changed: Cell<bool>,
+}
+
+// Entries stay while sent to, so sending allocates only for a new name
+#[derive(Clone, Copy, Debug, Default)]
+struct Named {
+    sum: Option<f32>,
+    mixed: Option<f32>,
+}
+
+impl Buses {
+    /// Adds a sample to the current step of `bus`.
+    pub fn add(&self, bus: usize, sample: f32) {
+        if let Some(sum) = self.sums.get(bus) {
+            sum.set(Some(sum.get().unwrap_or_default() + sample));
+        }
+    }
+
+    /// Completes the mix of the current step.
+    pub fn step(&mut self) {
+        for (mixed, sum) in self.mixed.iter_mut().zip(&self.sums) {
+            *mixed = sum.take();
+        }
+
+        let named = self.named.get_mut();
+        let len = named.len();
+        named.retain(|_, bus| {
+            bus.mixed = bus.sum.take();
+            bus.mixed.is_some()
+        });
+        if named.len() != len {
+            self.names_changed.set(true);
+        }
+    }
+
+    /// Mix of the last step of `bus`, `None` while no node fed it.
+    pub fn mixed(&self, bus: usize) -> Option<f32> {
+        self.mixed.get(bus).copied().flatten()
+    }
+
+    /// Adds a sample to the current step of the bus called `name`.
+    pub fn send(&self, name: &str, sample: f32) {
+        let mut named = self.named.borrow_mut();
+        match named.get_mut(name) {
+            Some(bus) => bus.sum = Some(bus.sum.unwrap_or_default() + sample),
+            None => {
+                let bus = Named {
+                    sum: Some(sample),
+                    mixed: None,
+                };
+                named.insert(name.to_string(), bus);
+                self.names_changed.set(true);
+            }
+        }
+    }
+
+    /// Mix of the last step of the bus called `name`, `None` while nothing
+    /// sends to it.
+    pub fn receive(&self, name: &str) -> Option<f32> {
+        self.named.borrow().get(name).and_then(|bus| bus.mixed)
+    }
+
+    /// Names of the buses sent to, if they changed since the last call.
+    pub fn take_names(&self) -> Option<Vec<String>> {
+        self.names_changed
+            .take()
+            .then(|| self.named.borrow().keys().cloned().collect())
+    }
+
+    /// Moves receivers of `old` over to `new`.
+    pub fn rename(&self, old: &str, new: &str) {
+        let mut renamed = self.renamed.borrow_mut();
+        renamed.remove(new);
+        renamed.insert(old.to_string(), new.to_string());
+        // Receivers of a bus renamed before follow it all the way
+        for target in renamed.values_mut() {
+            if target == old {
+                *target = new.to_string();
+            }
+        }
+        self.renames.set(self.renames.get() + 1);
+    }
+
+    /// Name the bus called `name` was renamed to, if it was.
+    pub fn renamed(&self, name: &str) -> Option<String> {
+        self.renamed.borrow().get(name).cloned()
+    }
+
+    /// Renames so far, for receivers to tell when to look theirs up.
+    pub fn renames(&self) -> usize {
+        self.renames.get()
+    }
+}
+
+/// External output channel playing aux bus `k`, counted from 1.
+pub fn aux_channel(k: usize) -> usize {
+    AUX_CHANNELS + k - 1
+}
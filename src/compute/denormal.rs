@@ -0,0 +1,91 @@
+use std::{
+    cell::Cell,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+// Magnitude of the noise added to feedback paths while protected, far
+// above the denormal range and far below anything audible
+const NOISE: f32 = 1e-18;
+
+static PROTECTED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // 16-bit Galois LFSR, its low bit picks the sign of the next sample
+    static LFSR: Cell<u16> = const { Cell::new(0xACE1) };
+}
+
+// Noise rather than a constant offset, which DC blockers take right back
+// out and feedback loops pile up
+fn noise() -> f32 {
+    LFSR.with(|lfsr| {
+        let state = lfsr.get();
+        lfsr.set((state >> 1) ^ (0u16.wrapping_sub(state & 1) & 0xB400));
+
+        if state & 1 == 1 {
+            NOISE
+        } else {
+            -NOISE
+        }
+    })
+}
+
+/// Keeps a feedback path out of the denormal range while protection is on.
+/// Denormals, left by long reverb and filter tails decaying towards zero,
+/// are many times slower to compute with on x86.
+pub fn protect(x: f32) -> f32 {
+    if PROTECTED.load(Ordering::Relaxed) {
+        x + noise()
+    } else {
+        x
+    }
+}
+
+/// Turns protection on or off, for the nodes of every runtime and for the
+/// calling thread, which flushes denormals to zero where the CPU can.
+pub fn set_protected(protected: bool) {
+    PROTECTED.store(protected, Ordering::Relaxed);
+    set_flush_to_zero(protected);
+}
+
+/// Flushes denormals to zero on the calling thread if protection is on,
+/// called by threads running nodes when they start.
+pub fn enter_thread() {
+    if PROTECTED.load(Ordering::Relaxed) {
+        set_flush_to_zero(true);
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[allow(deprecated)]
+fn set_flush_to_zero(on: bool) {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::{_mm_getcsr, _mm_setcsr};
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::{_mm_getcsr, _mm_setcsr};
+
+    // Flush to zero and denormals are zero bits of MXCSR
+    const FTZ_DAZ: u32 = 0x8040;
+
+    // Safety: only changes how the calling thread treats denormals
+    unsafe {
+        let csr = _mm_getcsr();
+        _mm_setcsr(if on { csr | FTZ_DAZ } else { csr & !FTZ_DAZ });
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn set_flush_to_zero(on: bool) {
+    // Flush to zero bit of FPCR, covering inputs as well
+    const FZ: u64 = 1 << 24;
+
+    // Safety: only changes how the calling thread treats denormals
+    unsafe {
+        let mut fpcr: u64;
+        std::arch::asm!("mrs {}, fpcr", out(reg) fpcr);
+        fpcr = if on { fpcr | FZ } else { fpcr & !FZ };
+        std::arch::asm!("msr fpcr, {}", in(reg) fpcr);
+    }
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+fn set_flush_to_zero(_on: bool) {}
@@ -0,0 +1,58 @@
+use std::cell::Cell;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Deterministic mode of a thread, left when dropped.
+#[must_use]
+pub struct Enabled {
+    previous: bool,
+}
+
+impl Drop for Enabled {
+    fn drop(&mut self) {
+        ENABLED.with(|enabled| enabled.set(self.previous));
+    }
+}
+
+/// Makes nodes loaded and run on the calling thread depend only on the
+/// samples they were fed, never on the wall clock, so offline renders of a
+/// patch are reproducible. Other threads, like the editor's runtime, aren't
+/// affected.
+pub fn enable() -> Enabled {
+    Enabled {
+        previous: ENABLED.with(|enabled| enabled.replace(true)),
+    }
+}
+
+pub fn enabled() -> bool {
+    ENABLED.with(Cell::get)
+}
+
+/// Seed of the first node in deterministic mode, the others count up from
+/// it.
+pub const SEED: u64 = 0xFE;
+
+/// Seed of a new random node, fixed while deterministic and drawn from
+/// entropy otherwise so that random nodes don't play in lockstep.
+pub fn seed() -> u64 {
+    if enabled() {
+        SEED
+    } else {
+        rand::random()
+    }
+}
+
+/// Generator of a new random node.
+pub fn rng() -> ChaCha12Rng {
+    seeded(seed())
+}
+
+/// Generator starting from `seed`, the same sequence for the same seed.
+pub fn seeded(seed: u64) -> ChaCha12Rng {
+    ChaCha12Rng::seed_from_u64(seed)
+}
@@ -0,0 +1,72 @@
+use std::sync::{
+    atomic::{AtomicU32, AtomicU64, Ordering},
+    OnceLock,
+};
+
+/// Channel of the capture device picked in the audio settings. JACK input
+/// ports follow it, starting at 1.
+pub const DEVICE: usize = 0;
+/// Channel of the JACK sidechain port, past the numbered inputs.
+pub const SIDECHAIN: usize = 65;
+
+// One second of audio, older samples are overwritten
+const CAPACITY: u64 = 44100;
+// Distance new readers keep behind the newest sample, absorbs capture jitter
+const LAG: u64 = 1024;
+
+/// Live audio of one channel, written by a single capture callback and
+/// followed by any number of readers.
+pub struct Capture {
+    samples: Box<[AtomicU32]>,
+    // Samples written so far, sample n lives in slot n % CAPACITY
+    written: AtomicU64,
+}
+
+impl Capture {
+    /// Appends a sample captured at 44.1 kHz. Only called by the capture
+    /// callback of the channel.
+    pub fn push(&self, sample: f32) {
+        let written = self.written.load(Ordering::Relaxed);
+        self.samples[(written % CAPACITY) as usize].store(sample.to_bits(), Ordering::Relaxed);
+        self.written.store(written + 1, Ordering::Release);
+    }
+}
+
+static CAPTURE: [OnceLock<Capture>; SIDECHAIN + 1] = [const { OnceLock::new() }; SIDECHAIN + 1];
+
+/// Capture of `channel`, allocated the first time. Called when setting up
+/// a capture, never from its callback.
+pub fn capture(channel: usize) -> Option<&'static Capture> {
+    let capture = CAPTURE.get(channel)?;
+    Some(capture.get_or_init(|| Capture {
+        samples: (0..CAPACITY).map(|_| AtomicU32::new(0)).collect(),
+        written: AtomicU64::new(0),
+    }))
+}
+
+/// Reads the sample at `pos` and advances it. Readers without a position,
+/// or ones that fell behind, are moved close to the newest sample, so any
+/// number of them can follow the same stream.
+pub fn read(channel: usize, pos: &mut Option<u64>) -> f32 {
+    let Some(capture) = CAPTURE.get(channel).and_then(OnceLock::get) else {
+        return 0.0;
+    };
+    let end = capture.written.load(Ordering::Acquire);
+    // Half the samples are kept as a margin, the writer may be overwriting
+    // the oldest ones right now
+    let start = end.saturating_sub(CAPACITY / 2);
+
+    let at = match *pos {
+        Some(at) if at >= start => at,
+        _ => end.saturating_sub(LAG).max(start),
+    };
+
+    if at >= end {
+        // Nothing captured yet, wait in place
+        *pos = Some(at);
+        return 0.0;
+    }
+
+    *pos = Some(at + 1);
+    f32::from_bits(capture.samples[(at % CAPACITY) as usize].load(Ordering::Relaxed))
+}
@@ -0,0 +1,142 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::ring::Channel;
+
+/// MIDI output ports nodes can send to, at most as many as the settings
+/// offer.
+pub const PORTS: usize = 16;
+
+// Messages queued per port, far more than a reader falling half a second
+// behind would need
+const CAPACITY: usize = 4096;
+// Half a second, a reader further behind skips ahead and drops the
+// messages it missed
+const STALE: u64 = 22050;
+
+/// MIDI message of up to three bytes, anything but system exclusive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Message {
+    bytes: [u8; 3],
+    len: u8,
+}
+
+impl Message {
+    /// `None` if `bytes` is empty or too long.
+    pub fn new(bytes: &[u8]) -> Option<Self> {
+        if bytes.is_empty() || bytes.len() > 3 {
+            return None;
+        }
+
+        let mut message = Message {
+            bytes: [0; 3],
+            len: bytes.len() as u8,
+        };
+        message.bytes[..bytes.len()].copy_from_slice(bytes);
+
+        Some(message)
+    }
+
+    /// Message of a single status byte, like the real time ones.
+    pub const fn status(status: u8) -> Self {
+        Message {
+            bytes: [status, 0, 0],
+            len: 1,
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    // Dropping one would leave its note hanging
+    fn is_note_off(&self) -> bool {
+        match self.bytes[0] & 0xF0 {
+            0x80 => true,
+            0x90 => self.len == 3 && self.bytes[2] == 0,
+            _ => false,
+        }
+    }
+
+    fn pack(self) -> u64 {
+        u32::from_le_bytes([self.bytes[0], self.bytes[1], self.bytes[2], self.len]) as u64
+    }
+
+    fn unpack(packed: u64) -> Self {
+        let [b0, b1, b2, len] = (packed as u32).to_le_bytes();
+        Message {
+            bytes: [b0, b1, b2],
+            len,
+        }
+    }
+}
+
+// Entries are the sample a message was sent on and the packed message
+static QUEUES: [Channel<2>; PORTS] = [const { Channel::new(CAPACITY) }; PORTS];
+// Sample reached by the reader of each port
+static READ: [AtomicU64; PORTS] = [const { AtomicU64::new(0) }; PORTS];
+// Samples stepped so far, shared by all ports so several nodes can send to
+// the same one
+static WRITTEN: AtomicU64 = AtomicU64::new(0);
+
+/// Queues a message sent on the current sample for a MIDI output played
+/// outside of the runtime. Dropped while the port has no reader.
+pub fn push(port: usize, message: Message) {
+    if let Some(queue) = QUEUES.get(port) {
+        let written = WRITTEN.load(Ordering::Relaxed);
+        queue.push([written, message.pack()]);
+    }
+}
+
+/// Moves on to the next sample, called by the runtime once per step.
+pub fn step() {
+    WRITTEN.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Starts queueing messages for `port`, called before reading it.
+pub fn open(port: usize) {
+    if let Some(queue) = QUEUES.get(port) {
+        READ[port].store(WRITTEN.load(Ordering::Relaxed), Ordering::Relaxed);
+        queue.open();
+    }
+}
+
+/// Stops queueing messages for `port`, once its reader is gone.
+pub fn close(port: usize) {
+    if let Some(queue) = QUEUES.get(port) {
+        queue.close();
+    }
+}
+
+/// Hands over the messages falling into the next `frames` samples, with
+/// their offset into the block. Only called by the reader of `port`.
+pub fn pull(port: usize, frames: u64, mut f: impl FnMut(u32, &[u8])) {
+    let Some(queue) = QUEUES.get(port).and_then(Channel::reader) else {
+        return;
+    };
+    let written = WRITTEN.load(Ordering::Relaxed);
+
+    // Fell too far behind, catch up rather than play everything late
+    let mut read = READ[port].load(Ordering::Relaxed);
+    if written.saturating_sub(read) > STALE {
+        read = written;
+    }
+
+    let end = read + frames;
+    while let Some([time, packed]) = queue.peek() {
+        if time >= end {
+            break;
+        }
+        queue.pop();
+
+        // Stale messages are dropped, but note-offs still play
+        let message = Message::unpack(packed);
+        if time < read && !message.is_note_off() {
+            continue;
+        }
+
+        f(time.saturating_sub(read) as u32, message.bytes());
+    }
+
+    // On underrun the next messages are played as soon as they arrive
+    READ[port].store(end.min(written), Ordering::Relaxed);
+}
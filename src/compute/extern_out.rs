@@ -0,0 +1,48 @@
+use super::{bus, ring::Channel};
+
+/// Channels the runtime can play on, the Jack Out ports followed by the
+/// aux buses.
+pub const CHANNELS: usize = 64 + bus::AUX;
+
+// Half a second of audio, samples pushed while full are dropped
+const CAPACITY: usize = 22050;
+
+static PLAYBACK: [Channel<1>; CHANNELS] = [const { Channel::new(CAPACITY) }; CHANNELS];
+
+/// Queues one sample for an output played outside of the runtime. Dropped
+/// while the channel has no reader.
+pub fn push(channel: usize, sample: f32) {
+    if let Some(playback) = PLAYBACK.get(channel) {
+        playback.push([sample.to_bits() as u64]);
+    }
+}
+
+/// Starts queueing samples for `channel`, called before reading it.
+pub fn open(channel: usize) {
+    if let Some(playback) = PLAYBACK.get(channel) {
+        playback.open();
+    }
+}
+
+/// Stops queueing samples for `channel`, once its reader is gone.
+pub fn close(channel: usize) {
+    if let Some(playback) = PLAYBACK.get(channel) {
+        playback.close();
+    }
+}
+
+/// Fills `out` with queued samples, padding with silence on underrun. Only
+/// called by the reader of `channel`.
+pub fn pull(channel: usize, out: &mut [f32]) {
+    let Some(queue) = PLAYBACK.get(channel).and_then(Channel::reader) else {
+        out.fill(0.0);
+        return;
+    };
+
+    for sample in out {
+        *sample = queue
+            .pop()
+            .map(|[bits]| f32::from_bits(bits as u32))
+            .unwrap_or_default();
+    }
+}
@@ -1,32 +1,129 @@
+pub mod bus;
+pub mod denormal;
+pub mod deterministic;
+pub mod extern_in;
+pub mod extern_midi;
+pub mod extern_out;
 pub mod node;
+pub mod quarantine;
+pub mod ring;
+pub mod smoothing;
+#[cfg(test)]
+pub mod testing;
+pub mod transport;
+pub mod tuning;
 
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use midly::MidiMessage;
 use node::Node;
 use serde::{Deserialize, Serialize};
 use thunderdome::{Arena, Index};
 
-use self::node::NodeEvent;
+use self::{
+    bus::Buses,
+    node::{InputData, NodeEvent},
+    quarantine::{non_finite, Quarantine},
+    smoothing::Smoothing,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Entry {
     inputs: Vec<Option<OutputPort>>,
+    // Names of `inputs`, in order. Refreshed whenever the connections are
+    // replaced, so they always describe the layout the GUI sent.
+    #[serde(skip)]
+    input_names: Option<Vec<String>>,
+    // Positions of the ports of the node among `inputs`, resolved along with
+    // the names
+    #[serde(skip)]
+    ports: Vec<Option<usize>>,
+    // Ramps of the disconnected inputs, refreshed along with the names
+    #[serde(skip)]
+    smoothing: Smoothing,
+    #[serde(
+        serialize_with = "node::missing::serialize",
+        deserialize_with = "node::aliases::deserialize"
+    )]
     node: Box<dyn Node>,
+    // Ports silenced while other nodes are soloed
+    #[serde(skip)]
+    muted: Vec<usize>,
+    // Passes its first input on instead of running
+    #[serde(skip)]
+    bypassed: bool,
+    #[serde(skip)]
+    profile: NodeProfile,
+    // Samples with NaN or infinite outputs since last taken
+    #[serde(skip)]
+    quarantined: usize,
 }
 
 impl Clone for Entry {
     fn clone(&self) -> Self {
         Entry {
             inputs: self.inputs.clone(),
+            input_names: self.input_names.clone(),
+            ports: self.ports.clone(),
+            smoothing: self.smoothing.clone(),
             node: dyn_clone::clone_box(&*self.node),
+            muted: self.muted.clone(),
+            bypassed: self.bypassed,
+            profile: self.profile,
+            quarantined: self.quarantined,
         }
     }
 }
 
 impl Entry {
     fn new(inputs: Vec<Option<OutputPort>>, node: Box<dyn Node>) -> Self {
-        Entry { inputs, node }
+        let mut entry = Entry {
+            inputs,
+            input_names: None,
+            ports: Vec::new(),
+            smoothing: Smoothing::default(),
+            node,
+            muted: Vec::new(),
+            bypassed: false,
+            profile: NodeProfile::default(),
+            quarantined: 0,
+        };
+        entry.refresh_inputs();
+
+        entry
+    }
+
+    fn refresh_inputs(&mut self) {
+        let inputs = self.node.inputs();
+        self.smoothing = Smoothing::new(&inputs);
+        let names: Vec<_> = inputs.into_iter().map(|input| input.name).collect();
+        self.ports = node::resolve_ports(&*self.node, &names);
+        self.input_names = Some(names);
+    }
+
+    // Takes on inputs the node declared anew, keeping connections by name,
+    // so it never reads a layout it left behind. The editor then sends the
+    // connections it kept
+    fn follow_inputs(&mut self, evs: &[NodeEvent]) {
+        let Some(inputs) = evs.iter().rev().find_map(|ev| match ev {
+            NodeEvent::RecalcInputs(inputs) => Some(inputs),
+            NodeEvent::RecalcOutputs(_) => None,
+        }) else {
+            return;
+        };
+
+        let names = self.input_names.take().unwrap_or_default();
+        self.inputs = inputs
+            .iter()
+            .map(|input| {
+                let port = names.iter().position(|name| *name == input.name)?;
+                self.inputs.get(port).copied().flatten()
+            })
+            .collect();
+        self.smoothing = Smoothing::new(inputs);
+        let names: Vec<_> = inputs.iter().map(|input| input.name.clone()).collect();
+        self.ports = node::resolve_ports(&*self.node, &names);
+        self.input_names = Some(names);
     }
 }
 
@@ -60,6 +157,7 @@ impl OutputPort {
     }
 }
 
+#[derive(Debug)]
 pub struct Output {
     pub name: String,
     pub kind: ValueKind,
@@ -97,6 +195,40 @@ impl Value {
         }
     }
 
+    /// Sets an array of `floats`, in place of the array already held so that
+    /// nodes don't allocate on every read.
+    pub fn set_float_array(&mut self, floats: impl IntoIterator<Item = f32>) {
+        match self {
+            Value::FloatArray(array) => {
+                array.clear();
+                array.extend(floats);
+            }
+            other => *other = Value::FloatArray(floats.into_iter().collect()),
+        }
+    }
+
+    /// Element `k` of an array, for nodes working on each channel. Floats
+    /// and single element arrays apply to every channel, missing elements
+    /// read as disconnected.
+    pub fn channel(&self, k: usize) -> Value {
+        match self {
+            Value::FloatArray(s) if s.len() == 1 => Value::Float(s[0]),
+            Value::FloatArray(s) => s
+                .get(k)
+                .map(|s| Value::Float(*s))
+                .unwrap_or(Value::Disconnected),
+            other => other.clone(),
+        }
+    }
+
+    /// Number of channels of an array, 1 for anything else.
+    pub fn channels(&self) -> usize {
+        match self {
+            Value::FloatArray(s) => s.len(),
+            _ => 1,
+        }
+    }
+
     pub fn as_beat(&self) -> Option<Duration> {
         match self {
             Value::Beat(dur) => Some(*dur),
@@ -109,12 +241,48 @@ impl Value {
     }
 }
 
+/// Time a node spent running and the NaN and infinite values it output,
+/// while the runtime was profiled.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NodeProfile {
+    pub busy: Duration,
+    pub non_finite: usize,
+}
+
+/// Profiles of every node over `steps` samples.
+#[derive(Clone, Debug, Default)]
+pub struct Profile {
+    pub nodes: Vec<(Index, NodeProfile)>,
+    pub steps: usize,
+}
+
+impl Profile {
+    /// Share of real time taken by `busy`, 1 being all of it.
+    pub fn load(&self, busy: Duration) -> f32 {
+        busy.as_secs_f32() * 44100.0 / self.steps.max(1) as f32
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Runtime {
     #[serde(skip)]
     values: Vec<Vec<Value>>,
     #[serde(with = "crate::util::serde_arena")]
     nodes: Arena<Entry>,
+    // Part of the way to an edited value inputs ramp every sample
+    #[serde(skip, default = "default_smoothing")]
+    smoothing: f32,
+    // Steps profiled since the profile was last taken, None when not profiling
+    #[serde(skip)]
+    profiled: Option<usize>,
+    #[serde(skip)]
+    quarantine: Quarantine,
+    #[serde(skip)]
+    buses: Buses,
+}
+
+fn default_smoothing() -> f32 {
+    smoothing::coefficient(smoothing::DEFAULT_MS)
 }
 
 impl Runtime {
@@ -122,6 +290,10 @@ impl Runtime {
         Runtime {
             values: Vec::new(),
             nodes: Arena::new(),
+            smoothing: default_smoothing(),
+            profiled: None,
+            quarantine: Quarantine::Off,
+            buses: Buses::default(),
         }
     }
 
@@ -137,6 +309,9 @@ impl Runtime {
 
     pub fn remove(&mut self, index: Index) {
         self.nodes.remove(index);
+        if let Some(values) = self.values.get_mut(index.slot() as usize) {
+            values.clear();
+        }
         for (_, entry) in &mut self.nodes {
             for input in &mut entry.inputs {
                 if let Some(port) = *input {
@@ -153,15 +328,16 @@ impl Runtime {
     }
 
     pub fn set_all_inputs(&mut self, index: Index, new_inputs: Vec<Option<OutputPort>>) {
-        self.nodes[index].inputs = new_inputs;
+        let entry = &mut self.nodes[index];
+        entry.inputs = new_inputs;
+        entry.refresh_inputs();
     }
 
     pub fn step(&mut self) -> Vec<(Index, Vec<NodeEvent>)> {
         let mut evs = Vec::new();
         let mut buf = Vec::new();
 
-        self.values.clear();
-
+        // Outputs are kept from the last step, nodes write into them in place
         for (idx, entry) in &self.nodes {
             while self.values.len() <= idx.slot() as usize {
                 self.values.push(Vec::default());
@@ -174,20 +350,194 @@ impl Runtime {
                 self.values[idx] = vec![Value::None; target_len];
             }
 
-            entry.node.read(&mut self.values[idx]);
+            if entry.bypassed {
+                self.values[idx].fill(Value::None);
+            } else {
+                entry.node.read(&mut self.values[idx]);
+            }
+        }
+
+        // Before bypassed nodes take them on, so they pass fixed values too
+        if self.profiled.is_some() || self.quarantine != Quarantine::Off {
+            for (idx, entry) in &mut self.nodes {
+                let values = &mut self.values[idx.slot() as usize];
+                if !values.iter().any(non_finite) {
+                    continue;
+                }
+
+                if self.profiled.is_some() {
+                    entry.profile.non_finite += 1;
+                }
+                if self.quarantine != Quarantine::Off {
+                    entry.quarantined += 1;
+                    for value in values {
+                        self.quarantine.apply(value);
+                    }
+                }
+            }
+        }
+
+        // Bypassed nodes hand their first input on, following chains of them
+        for (idx, entry) in &self.nodes {
+            if !entry.bypassed {
+                continue;
+            }
+
+            let mut src = entry.inputs.first().copied().flatten();
+            for _ in 0..self.nodes.len() {
+                match src.and_then(|port| self.nodes.get(port.node)) {
+                    Some(upstream) if upstream.bypassed => {
+                        src = upstream.inputs.first().copied().flatten();
+                    }
+                    _ => break,
+                }
+            }
+
+            let value = src
+                .map(|port| self.values[port.node.slot() as usize][port.port].clone())
+                .unwrap_or(Value::None);
+            if let Some(out) = self.values[idx.slot() as usize].first_mut() {
+                *out = value;
+            }
         }
 
         for (idx, entry) in &mut self.nodes {
+            if entry.bypassed {
+                continue;
+            }
+
+            if entry.input_names.is_none() {
+                entry.refresh_inputs();
+            }
+
             buf.clear();
-            for input in &mut entry.inputs {
+            for (port, input) in entry.inputs.iter().enumerate() {
+                if input.is_some() {
+                    entry.smoothing.connected(port);
+                }
                 buf.push(match input {
+                    Some(_) if entry.muted.contains(&port) => Value::Float(0.0),
                     Some(input) => self.values[input.node.slot() as usize][input.port].clone(),
-                    None => Value::Disconnected,
+                    None => entry.smoothing.next(port, self.smoothing),
                 });
             }
 
-            let evs_one = entry.node.feed(&buf);
-            evs.push((idx, evs_one));
+            let start = self.profiled.map(|_| Instant::now());
+            let evs_one = entry
+                .node
+                .feed(&InputData::new(&entry.ports, &buf, &self.buses));
+            if let Some(start) = start {
+                entry.profile.busy += start.elapsed();
+            }
+            if !evs_one.is_empty() {
+                entry.follow_inputs(&evs_one);
+                evs.push((idx, evs_one));
+            }
+        }
+        extern_midi::step();
+        self.buses.step();
+        if let Some(steps) = &mut self.profiled {
+            *steps += 1;
+        }
+
+        evs
+    }
+
+    /// Sets what's done with NaN and infinite values nodes output.
+    pub fn set_quarantine(&mut self, quarantine: Quarantine) {
+        self.quarantine = quarantine;
+    }
+
+    /// Nodes that output NaN or infinity since the last call, with the
+    /// number of samples they did in.
+    pub fn take_quarantined(&mut self) -> Vec<(Index, usize)> {
+        self.nodes
+            .iter_mut()
+            .filter(|(_, entry)| entry.quarantined > 0)
+            .map(|(idx, entry)| (idx, std::mem::take(&mut entry.quarantined)))
+            .collect()
+    }
+
+    /// Starts or stops timing the nodes and counting the samples they
+    /// output NaN or infinity in.
+    pub fn set_profiling(&mut self, profiling: bool) {
+        self.profiled = profiling.then_some(0);
+        for (_, entry) in &mut self.nodes {
+            entry.profile = NodeProfile::default();
+        }
+    }
+
+    /// Profile since the last call, once it covers at least `min_steps`.
+    pub fn take_profile(&mut self, min_steps: usize) -> Option<Profile> {
+        let steps = self.profiled.filter(|steps| *steps >= min_steps)?;
+        self.profiled = Some(0);
+
+        let nodes = self
+            .nodes
+            .iter_mut()
+            .map(|(idx, entry)| (idx, std::mem::take(&mut entry.profile)))
+            .collect();
+        Some(Profile { nodes, steps })
+    }
+
+    /// Sets the time constant in milliseconds of the ramps inputs take to
+    /// values edited while they're disconnected, 0 to jump right to them.
+    pub fn set_smoothing(&mut self, ms: f32) {
+        self.smoothing = smoothing::coefficient(ms);
+    }
+
+    /// Silences every node at once, as if all notes were released and all
+    /// delays ran out.
+    pub fn panic(&mut self) {
+        for (_, entry) in &mut self.nodes {
+            entry.node.panic();
+        }
+    }
+
+    /// Restarts the random generators of every node, from `base` plus the
+    /// index of the node so that no two nodes play the same sequence.
+    pub fn reseed(&mut self, base: u64) {
+        for (idx, entry) in &mut self.nodes {
+            entry.node.reseed(base.wrapping_add(idx.slot() as u64));
+        }
+    }
+
+    /// Makes the given nodes pass their first input straight to their first
+    /// output, sources output silence.
+    pub fn set_bypassed(&mut self, bypassed: &[Index]) {
+        for (index, entry) in &mut self.nodes {
+            let was_bypassed = entry.bypassed;
+            entry.bypassed = bypassed.contains(&index);
+
+            // Nodes reading nothing would keep putting out what they passed
+            if entry.bypassed != was_bypassed {
+                if let Some(values) = self.values.get_mut(index.slot() as usize) {
+                    values.clear();
+                }
+            }
+        }
+    }
+
+    /// Feeds silence to the given inputs instead of their connections.
+    pub fn set_muted(&mut self, muted: &[(Index, usize)]) {
+        for (index, entry) in &mut self.nodes {
+            entry.muted = muted
+                .iter()
+                .filter(|(muted_index, _)| *muted_index == index)
+                .map(|(_, port)| *port)
+                .collect();
+        }
+    }
+
+    pub fn sync_configs(&mut self) -> Vec<(Index, Vec<NodeEvent>)> {
+        let mut evs = Vec::new();
+
+        for (idx, entry) in &mut self.nodes {
+            let evs_one = entry.node.sync_config();
+            if !evs_one.is_empty() {
+                entry.follow_inputs(&evs_one);
+                evs.push((idx, evs_one));
+            }
         }
 
         evs
@@ -196,10 +546,22 @@ impl Runtime {
     pub fn peek(&self, input: OutputPort) -> Value {
         self.values
             .get(input.node.slot() as usize)
-            .map(|vec| vec[input.port].clone())
+            .and_then(|vec| vec.get(input.port).cloned())
             .unwrap_or(Value::None)
     }
 
+    /// Mix of the last step of `bus`, `None` while no node fed it. Bus 0 is
+    /// the main mix, `None` when the patch has no Main Out.
+    pub fn bus(&self, bus: usize) -> Option<f32> {
+        self.buses.mixed(bus)
+    }
+
+    /// Names of the buses Send nodes feed, if they changed since the last
+    /// call.
+    pub fn bus_names(&self) -> Option<Vec<String>> {
+        self.buses.take_names()
+    }
+
     pub fn nodes(&self) -> impl Iterator<Item = (Index, &Box<dyn Node>)> {
         self.nodes.iter().map(|(idx, entry)| (idx, &entry.node))
     }
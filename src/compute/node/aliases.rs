@@ -0,0 +1,149 @@
+//! Old names of node types, so patches saved before a node was renamed or
+//! merged into another one keep loading. Types unknown even after that load
+//! as a [`MissingNode`](super::missing::MissingNode).
+
+use std::{collections::HashSet, fmt, sync::OnceLock};
+
+use serde::{
+    de::{value::StringDeserializer, DeserializeSeed, MapAccess, Visitor},
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+
+use super::{
+    basic::Basic, effects::Effects, filters::Filters, instruments::Instruments, midi::Midi,
+    missing, noise::Noise, Node, NodeList,
+};
+
+/// Pairs of `(old, new)` type names, as written to the `__ty` tag. A renamed node
+/// adds its previous name here, a merged one the names of the nodes it
+/// replaces, as long as the serialized fields stay compatible.
+const ALIASES: &[(&str, &str)] = &[];
+
+fn resolve(name: String) -> String {
+    match ALIASES.iter().find(|(old, _)| *old == name) {
+        Some((_, new)) => new.to_string(),
+        None => name,
+    }
+}
+
+// Type names registered by the nodes of this build
+fn known(name: &str) -> bool {
+    static KNOWN: OnceLock<HashSet<String>> = OnceLock::new();
+
+    KNOWN
+        .get_or_init(|| {
+            let lists: [&dyn NodeList; 6] =
+                [&Basic, &Effects, &Filters, &Instruments, &Midi, &Noise];
+            lists
+                .iter()
+                .flat_map(|list| list.all())
+                .filter_map(|(node, _, _)| {
+                    let value = serde_json::to_value(&*node).ok()?;
+                    Some(value.get("__ty")?.as_str()?.to_string())
+                })
+                .collect()
+        })
+        .contains(name)
+}
+
+/// Deserializes a node, accepting any of the aliased type names.
+pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Box<dyn Node>, D::Error> {
+    Box::<dyn Node>::deserialize(Aliased(d))
+}
+
+// Nodes are internally tagged, so the only thing to rewrite is the value of
+// their `__ty` field.
+struct Aliased<D>(D);
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Aliased<D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.0.deserialize_any(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.0.deserialize_map(AliasedVisitor(visitor))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct struct enum identifier ignored_any
+    }
+}
+
+struct AliasedVisitor<V>(V);
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for AliasedVisitor<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.expecting(f)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.0.visit_map(AliasedMap { map, tag: false })
+    }
+}
+
+struct AliasedMap<A> {
+    map: A,
+    // Whether the last key was the type tag
+    tag: bool,
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for AliasedMap<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        self.map.next_key_seed(KeySeen {
+            seed,
+            tag: &mut self.tag,
+        })
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value, A::Error> {
+        if std::mem::take(&mut self.tag) {
+            self.map.next_value_seed(Renamed(seed))
+        } else {
+            self.map.next_value_seed(seed)
+        }
+    }
+}
+
+// Passes a key on, noting whether it's the type tag
+struct KeySeen<'a, K> {
+    seed: K,
+    tag: &'a mut bool,
+}
+
+impl<'de, K: DeserializeSeed<'de>> DeserializeSeed<'de> for KeySeen<'_, K> {
+    type Value = K::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<K::Value, D::Error> {
+        let key = String::deserialize(d)?;
+        *self.tag = key == "__ty";
+        self.seed
+            .deserialize(StringDeserializer::<D::Error>::new(key))
+    }
+}
+
+struct Renamed<T>(T);
+
+impl<'de, T: DeserializeSeed<'de>> DeserializeSeed<'de> for Renamed<T> {
+    type Value = T::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, d: D) -> Result<T::Value, D::Error> {
+        let mut name = resolve(String::deserialize(d)?);
+        if !known(&name) {
+            missing::expect(std::mem::replace(&mut name, "MissingNode".into()));
+        }
+
+        self.0
+            .deserialize(StringDeserializer::<D::Error>::new(name))
+    }
+}
@@ -0,0 +1,37 @@
+// Time constant of the loudness tracking
+const WINDOW_SECS: f32 = 0.3;
+// Limits the boost applied to a nearly silent output, about 24 dB
+const MAX_GAIN: f32 = 16.0;
+
+/// Scales a processed signal to the loudness of its dry input.
+///
+/// Both signals are tracked with an exponential RMS, so the gain follows the
+/// average level rather than single peaks. Effects use it to make comparing
+/// them to the dry signal fair.
+#[derive(Clone, Debug, Default)]
+pub struct AutoGain {
+    dry_ms: f32,
+    wet_ms: f32,
+}
+
+impl AutoGain {
+    pub fn process(&mut self, dry: f32, wet: f32) -> f32 {
+        let a = (-1.0 / (WINDOW_SECS * 44100.0)).exp();
+
+        self.dry_ms = a * self.dry_ms + (1.0 - a) * dry * dry;
+        self.wet_ms = a * self.wet_ms + (1.0 - a) * wet * wet;
+
+        if !self.wet_ms.is_finite() || !self.dry_ms.is_finite() {
+            *self = AutoGain::default();
+            return wet;
+        }
+
+        if self.wet_ms <= f32::EPSILON {
+            return wet;
+        }
+
+        let gain = (self.dry_ms / self.wet_ms).sqrt().min(MAX_GAIN);
+
+        wet * gain
+    }
+}
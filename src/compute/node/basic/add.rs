@@ -10,7 +10,7 @@ use eframe::egui::DragValue;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::real::RealInput, Input, Node, NodeConfig, NodeEvent},
+    node::{inputs::real::RealInput, ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
     Value,
 };
 
@@ -18,6 +18,8 @@ use crate::compute::{
 struct AddConfig {
     new_ins: AtomicU32,
     ins: AtomicU32,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl NodeConfig for AddConfig {
@@ -32,6 +34,7 @@ impl NodeConfig for AddConfig {
                 .lost_focus()
             {
                 self.ins.store(ins, Ordering::Release);
+                self.dirty.mark();
             }
         });
 
@@ -53,6 +56,7 @@ impl Add {
             config: Arc::new(AddConfig {
                 new_ins: AtomicU32::new(ins),
                 ins: AtomicU32::new(ins),
+                dirty: ConfigDirty::new(),
             }),
             defaults: (0..ins).map(|_| Arc::new(RealInput::new(0.0))).collect(),
             ins,
@@ -63,13 +67,21 @@ impl Add {
 
 #[typetag::serde]
 impl Node for Add {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
         self.out = data
             .iter()
             .zip(self.defaults.iter())
             .map(|(sample, default)| default.get_f32(sample))
             .sum();
 
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
         let new_ins = self.config.ins.load(Ordering::Relaxed);
         let emit_ev = new_ins != self.ins;
         self.ins = new_ins;
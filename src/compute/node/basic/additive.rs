@@ -0,0 +1,341 @@
+use std::{
+    any::Any,
+    f32::consts::TAU,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use eframe::egui::{self, DragValue};
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{
+        inputs::{
+            freq::FreqInput,
+            positive::PositiveInput,
+            time::TimeInput,
+            trigger::{TriggerInput, TriggerMode},
+        },
+        ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
+    },
+    Value,
+};
+
+crate::ports! {
+    FREQ = "freq",
+    STRETCH = "stretch",
+    TRIGGER = "trigger",
+    DECAY = "decay",
+}
+
+const MAX_PARTIALS: usize = 64;
+// Samples between renormalizations of the phasors, against rounding drift
+const RENORMALIZE: u32 = 4096;
+
+// Amplitudes of a sawtooth, 1/n for the n-th harmonic
+fn saw(partials: usize) -> Vec<f32> {
+    (1..=partials).map(|n| 1.0 / n as f32).collect()
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AdditiveConfig {
+    #[serde(with = "crate::util::serde_mutex")]
+    amps: Mutex<Vec<f32>>,
+    decay: AtomicBool,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl AdditiveConfig {
+    // Bars of the spectrum, dragging over them draws the amplitudes
+    fn show_spectrum(&self, ui: &mut egui::Ui) -> bool {
+        let mut amps = self.amps.lock().unwrap();
+        let size = egui::vec2(ui.available_width().max(160.0), 80.0);
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+
+        let mut changed = false;
+        if let Some(pos) = response
+            .interact_pointer_pos()
+            .filter(|_| response.clicked() || response.dragged())
+        {
+            let k = ((pos.x - rect.left()) / rect.width() * amps.len() as f32) as usize;
+            if let Some(amp) = amps.get_mut(k) {
+                *amp = ((rect.bottom() - pos.y) / rect.height()).clamp(0.0, 1.0);
+                changed = true;
+            }
+        }
+
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+        let width = rect.width() / amps.len().max(1) as f32;
+        for (k, amp) in amps.iter().enumerate() {
+            let left = rect.left() + k as f32 * width;
+            let bar = egui::Rect::from_min_max(
+                egui::pos2(left + 1.0, rect.bottom() - amp * rect.height()),
+                egui::pos2(left + width - 1.0, rect.bottom()),
+            );
+            painter.rect_filled(bar, 0.0, visuals.selection.bg_fill);
+        }
+
+        changed
+    }
+}
+
+impl NodeConfig for AdditiveConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mut decay = self.decay.load(Ordering::Acquire);
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            let mut amps = self.amps.lock().unwrap();
+            let mut partials = amps.len();
+
+            ui.label("Partials");
+            if ui
+                .add(DragValue::new(&mut partials).range(1..=MAX_PARTIALS))
+                .changed()
+            {
+                amps.truncate(partials);
+                let kept = amps.len();
+                amps.extend((kept + 1..=partials).map(|n| 1.0 / n as f32));
+                changed = true;
+            }
+
+            let presets: [(&str, Vec<f32>); 3] = [
+                (
+                    "Sine",
+                    (0..partials)
+                        .map(|k| if k == 0 { 1.0 } else { 0.0 })
+                        .collect(),
+                ),
+                ("Saw", saw(partials)),
+                (
+                    "Square",
+                    (1..=partials)
+                        .map(|n| if n % 2 == 1 { 1.0 / n as f32 } else { 0.0 })
+                        .collect(),
+                ),
+            ];
+            for (name, preset) in presets {
+                if ui.small_button(name).clicked() {
+                    *amps = preset;
+                    changed = true;
+                }
+            }
+        });
+
+        changed |= self.show_spectrum(ui);
+        changed |= ui
+            .checkbox(&mut decay, "Partial decay")
+            .on_hover_text("Fade the partials out after a trigger, higher ones faster")
+            .changed();
+
+        self.decay.store(decay, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
+// Sine of one partial, rotated by a fixed angle every sample
+#[derive(Clone, Copy, Debug)]
+struct Partial {
+    re: f32,
+    im: f32,
+    cos: f32,
+    sin: f32,
+    // Zero above Nyquist
+    audible: f32,
+    env: f32,
+    // Envelope multiplier per sample while decaying
+    fall: f32,
+}
+
+impl Default for Partial {
+    fn default() -> Self {
+        Partial {
+            re: 1.0,
+            im: 0.0,
+            cos: 1.0,
+            sin: 0.0,
+            audible: 0.0,
+            env: 1.0,
+            fall: 1.0,
+        }
+    }
+}
+
+/// Bank of harmonics with drawn amplitudes. `stretch` moves the n-th
+/// partial to `n * f * sqrt(1 + stretch * n^2)`, like the stiffness of a
+/// piano string.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Additive {
+    config: Arc<AdditiveConfig>,
+    freq: Arc<FreqInput>,
+    stretch: Arc<PositiveInput>,
+    trigger: Arc<TriggerInput>,
+    decay: Arc<TimeInput>,
+    // Copied from the config, normalized to a peak within ±1
+    #[serde(skip)]
+    amps: Vec<f32>,
+    decaying: bool,
+    #[serde(skip)]
+    partials: Vec<Partial>,
+    // Frequency and stretch the partials are tuned to
+    #[serde(skip)]
+    tuned: Option<(f32, f32)>,
+    #[serde(skip)]
+    fall_time: f32,
+    #[serde(skip)]
+    t: u32,
+    #[serde(skip)]
+    out: f32,
+}
+
+impl Additive {
+    fn tune(&mut self, f0: f32, stretch: f32) {
+        for (k, partial) in self.partials.iter_mut().enumerate() {
+            let n = (k + 1) as f32;
+            let f = n * f0 * (1.0 + stretch * n * n).sqrt();
+            let w = TAU * f / 44100.0;
+
+            partial.cos = w.cos();
+            partial.sin = w.sin();
+            partial.audible = if f.abs() < 22050.0 { 1.0 } else { 0.0 };
+        }
+        self.tuned = Some((f0, stretch));
+    }
+
+    fn set_fall_time(&mut self, samples: f32) {
+        for (k, partial) in self.partials.iter_mut().enumerate() {
+            partial.fall = (-((k + 1) as f32) / samples.max(1.0)).exp();
+        }
+        self.fall_time = samples;
+    }
+}
+
+#[typetag::serde]
+impl Node for Additive {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if self.partials.len() != self.amps.len() {
+            self.partials.resize(self.amps.len(), Partial::default());
+            self.tuned = None;
+            self.fall_time = 0.0;
+        }
+
+        let f0 = self.freq.get_f32(&data[FREQ]);
+        let stretch = self.stretch.get_f32(&data[STRETCH]);
+        if self.tuned != Some((f0, stretch)) {
+            self.tune(f0, stretch);
+        }
+
+        if self.decaying {
+            let fall_time = self.decay.get_samples(&data[DECAY]);
+            if fall_time != self.fall_time {
+                self.set_fall_time(fall_time);
+            }
+            if self.trigger.trigger(&data[TRIGGER]) {
+                for partial in &mut self.partials {
+                    partial.env = 1.0;
+                }
+            }
+        }
+
+        let renormalize = self.t % RENORMALIZE == 0;
+        self.t = self.t.wrapping_add(1);
+
+        let mut out = 0.0;
+        for (partial, amp) in self.partials.iter_mut().zip(&self.amps) {
+            out += amp * partial.audible * partial.env * partial.im;
+
+            let (re, im) = (partial.re, partial.im);
+            partial.re = re * partial.cos - im * partial.sin;
+            partial.im = re * partial.sin + im * partial.cos;
+            if renormalize {
+                let norm = partial.re.hypot(partial.im);
+                partial.re /= norm;
+                partial.im /= norm;
+            }
+            if self.decaying {
+                partial.env *= partial.fall;
+            }
+        }
+        self.out = out;
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
+        let amps = self.config.amps.lock().unwrap().clone();
+        let total: f32 = amps.iter().sum();
+        self.amps = amps.iter().map(|amp| amp / total.max(1.0)).collect();
+
+        let decaying = self.config.decay.load(Ordering::Relaxed);
+        if decaying == self.decaying {
+            return Default::default();
+        }
+        self.decaying = decaying;
+        for partial in &mut self.partials {
+            partial.env = 1.0;
+        }
+
+        vec![NodeEvent::RecalcInputs(self.inputs())]
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn panic(&mut self) {
+        for partial in &mut self.partials {
+            partial.env = 0.0;
+        }
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        let mut inputs = vec![
+            Input::stateful("freq", &self.freq),
+            Input::stateful("stretch", &self.stretch),
+        ];
+        if self.decaying {
+            inputs.push(Input::stateful("trigger", &self.trigger));
+            inputs.push(Input::stateful("decay", &self.decay));
+        }
+
+        inputs
+    }
+}
+
+pub fn additive() -> Box<dyn Node> {
+    Box::new(Additive {
+        config: Arc::new(AdditiveConfig {
+            amps: Mutex::new(saw(16)),
+            decay: AtomicBool::new(false),
+            dirty: ConfigDirty::new(),
+        }),
+        freq: Arc::new(FreqInput::new(220.0)),
+        stretch: Arc::new(PositiveInput::new(0.0)),
+        trigger: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
+        decay: Arc::new(TimeInput::new(44100.0)),
+        amps: Vec::new(),
+        decaying: false,
+        partials: Vec::new(),
+        tuned: None,
+        fall_time: 0.0,
+        t: 0,
+        out: 0.0,
+    })
+}
@@ -8,16 +8,23 @@ use eframe::egui::DragValue;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::gate::GateInput, Input, Node, NodeConfig, NodeEvent},
+    node::{inputs::gate::GateInput, ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
     Value, ValueKind,
 };
 
+crate::ports! {
+    GATE = "gate",
+    SIGNAL = "signal",
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct AdsrConfig {
     attack: AtomicF32,
     decay: AtomicF32,
     sustain_ratio: AtomicF32,
     release: AtomicF32,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl NodeConfig for AdsrConfig {
@@ -27,21 +34,30 @@ impl NodeConfig for AdsrConfig {
         let mut sustain_ratio = self.sustain_ratio.load(Ordering::Acquire) * 100.0;
         let mut release = self.release.load(Ordering::Acquire);
 
+        let mut changed = false;
         ui.horizontal(|ui| {
             ui.label("attack");
-            ui.add(DragValue::new(&mut attack).range(0.01..=1.0));
+            changed |= ui
+                .add(DragValue::new(&mut attack).range(0.01..=1.0))
+                .changed();
         });
         ui.horizontal(|ui| {
             ui.label("decay");
-            ui.add(DragValue::new(&mut decay).range(0.01..=1.0));
+            changed |= ui
+                .add(DragValue::new(&mut decay).range(0.01..=1.0))
+                .changed();
         });
         ui.horizontal(|ui| {
             ui.label("sustain %");
-            ui.add(DragValue::new(&mut sustain_ratio).range(0.0..=100.0));
+            changed |= ui
+                .add(DragValue::new(&mut sustain_ratio).range(0.0..=100.0))
+                .changed();
         });
         ui.horizontal(|ui| {
             ui.label("release");
-            ui.add(DragValue::new(&mut release).range(0.01..=1.0));
+            changed |= ui
+                .add(DragValue::new(&mut release).range(0.01..=1.0))
+                .changed();
         });
 
         self.attack.store(attack, Ordering::Release);
@@ -49,6 +65,7 @@ impl NodeConfig for AdsrConfig {
         self.sustain_ratio
             .store(sustain_ratio / 100.0, Ordering::Release);
         self.release.store(release, Ordering::Release);
+        self.dirty.mark_if(changed);
     }
 }
 
@@ -64,6 +81,15 @@ enum AdsrState {
 pub struct Adsr {
     config: Arc<AdsrConfig>,
     gate: Arc<GateInput>,
+    // Copies of the config, refreshed when it changes
+    #[serde(skip)]
+    attack: f32,
+    #[serde(skip)]
+    decay: f32,
+    #[serde(skip)]
+    sustain_ratio: f32,
+    #[serde(skip)]
+    release: f32,
     state: AdsrState,
     attack_start_gain: f32,
     release_start_gain: f32,
@@ -80,8 +106,13 @@ impl Adsr {
                 decay: AtomicF32::new(0.05),
                 sustain_ratio: AtomicF32::new(0.7),
                 release: AtomicF32::new(0.5),
+                dirty: ConfigDirty::new(),
             }),
             gate: Arc::new(GateInput::new(0.5)),
+            attack: 0.05,
+            decay: 0.05,
+            sustain_ratio: 0.7,
+            release: 0.5,
             state: AdsrState::Release,
             attack_start_gain: 0.0,
             release_start_gain: 0.0,
@@ -94,14 +125,14 @@ impl Adsr {
 
 #[typetag::serde]
 impl Node for Adsr {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let _ = self.gate.gate(&data[0]);
-        let sig = data[1].as_float().unwrap_or(0.0);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let _ = self.gate.gate(&data[GATE]);
+        let sig = data[SIGNAL].as_float().unwrap_or(0.0);
 
-        let conf_attack = self.config.attack.load(Ordering::Relaxed);
-        let conf_decay = self.config.decay.load(Ordering::Relaxed);
-        let conf_sustain_r = self.config.sustain_ratio.load(Ordering::Relaxed);
-        let conf_release = self.config.release.load(Ordering::Relaxed);
+        let conf_attack = self.attack;
+        let conf_decay = self.decay;
+        let conf_sustain_r = self.sustain_ratio;
+        let conf_release = self.release;
 
         if self.gate.positive_edge() {
             self.state = AdsrState::Attack;
@@ -155,6 +186,17 @@ impl Node for Adsr {
         Default::default()
     }
 
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.attack = self.config.attack.load(Ordering::Relaxed);
+            self.decay = self.config.decay.load(Ordering::Relaxed);
+            self.sustain_ratio = self.config.sustain_ratio.load(Ordering::Relaxed);
+            self.release = self.config.release.load(Ordering::Relaxed);
+        }
+
+        Default::default()
+    }
+
     fn read(&self, out: &mut [Value]) {
         out[0] = Value::Float(self.out)
     }
@@ -163,6 +205,10 @@ impl Node for Adsr {
         Some(Arc::clone(&self.config) as Arc<_>)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::stateful("gate", &self.gate),
@@ -9,7 +9,7 @@ use serde::{Deserialize, Serialize};
 use crate::compute::{
     node::{
         inputs::trigger::{TriggerInput, TriggerMode},
-        Input, Node, NodeConfig, NodeEvent,
+        ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
     },
     Value,
 };
@@ -18,6 +18,8 @@ use crate::compute::{
 struct AnyConfig {
     new_ins: AtomicU32,
     ins: AtomicU32,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl NodeConfig for AnyConfig {
@@ -32,6 +34,7 @@ impl NodeConfig for AnyConfig {
                 .lost_focus()
             {
                 self.ins.store(ins, Ordering::Release);
+                self.dirty.mark();
             }
         });
 
@@ -53,6 +56,7 @@ impl Any {
             config: Arc::new(AnyConfig {
                 new_ins: AtomicU32::new(ins),
                 ins: AtomicU32::new(ins),
+                dirty: ConfigDirty::new(),
             }),
             defaults: (0..ins)
                 .map(|_| Arc::new(TriggerInput::new(TriggerMode::Change, 0.5)))
@@ -65,7 +69,7 @@ impl Any {
 
 #[typetag::serde]
 impl Node for Any {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
         let emit = data
             .iter()
             .zip(self.defaults.iter())
@@ -73,6 +77,14 @@ impl Node for Any {
             .any(|trig| trig);
         self.out = if emit { 1.0 } else { 0.0 };
 
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
         let new_ins = self.config.ins.load(Ordering::Relaxed);
         let emit_ev = new_ins != self.ins;
         self.ins = new_ins;
@@ -0,0 +1,109 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use eframe::egui::{self, DragValue};
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    extern_in,
+    node::{ConfigDirty, InputData, Node, NodeConfig, NodeEvent},
+    Value,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AudioInConfig {
+    channel: AtomicUsize,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl Default for AudioInConfig {
+    fn default() -> Self {
+        AudioInConfig {
+            channel: AtomicUsize::new(extern_in::DEVICE),
+            dirty: ConfigDirty::new(),
+        }
+    }
+}
+
+impl NodeConfig for AudioInConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mut channel = self.channel.load(Ordering::Acquire);
+
+        ui.horizontal(|ui| {
+            ui.label("Channel");
+            let changed = ui
+                .add(DragValue::new(&mut channel).range(0..=extern_in::SIDECHAIN))
+                .changed();
+
+            if channel == extern_in::DEVICE {
+                ui.weak("device");
+            } else if channel == extern_in::SIDECHAIN {
+                ui.weak("JACK sidechain");
+            } else {
+                ui.weak(format!("JACK in {channel}"));
+            }
+
+            self.channel.store(channel, Ordering::Release);
+            self.dirty.mark_if(changed);
+        });
+    }
+}
+
+/// Live audio input, enabled in the audio settings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AudioIn {
+    #[serde(default)]
+    config: Arc<AudioInConfig>,
+    #[serde(skip)]
+    channel: usize,
+    #[serde(skip)]
+    pos: Option<u64>,
+    out: f32,
+}
+
+impl AudioIn {
+    pub fn new() -> Self {
+        AudioIn {
+            config: Default::default(),
+            channel: extern_in::DEVICE,
+            pos: None,
+            out: 0.0,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Node for AudioIn {
+    fn feed(&mut self, _data: &InputData) -> Vec<NodeEvent> {
+        self.out = extern_in::read(self.channel, &mut self.pos);
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.channel = self.config.channel.load(Ordering::Relaxed);
+            self.pos = None;
+        }
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+}
+
+pub fn audio_in() -> Box<dyn Node> {
+    Box::new(AudioIn::new())
+}
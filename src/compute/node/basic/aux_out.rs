@@ -0,0 +1,94 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use eframe::egui::{self, DragValue};
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    bus,
+    node::{ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
+    Output, ValueKind,
+};
+
+crate::ports! {
+    SIG = "sig",
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AuxOutConfig {
+    // 1-based, like the JACK port names
+    bus: AtomicUsize,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for AuxOutConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mut index = self.bus.load(Ordering::Acquire);
+        ui.horizontal(|ui| {
+            ui.label("Bus");
+            let changed = ui
+                .add(DragValue::new(&mut index).range(1..=bus::AUX))
+                .changed();
+            self.bus.store(index, Ordering::Release);
+            self.dirty.mark_if(changed);
+        });
+    }
+}
+
+/// Adds its input to one of the aux buses, played on the `aux_*` JACK ports.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuxOut {
+    config: Arc<AuxOutConfig>,
+    #[serde(skip)]
+    bus: usize,
+}
+
+#[typetag::serde]
+impl Node for AuxOut {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sig = data[SIG].as_float().unwrap_or_default();
+        data.bus().add(self.bus.clamp(1, bus::AUX), sig);
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.bus = self.config.bus.load(Ordering::Relaxed);
+        }
+
+        Default::default()
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::new("sig", ValueKind::Float)]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        Vec::new()
+    }
+}
+
+pub fn aux_out() -> Box<dyn Node> {
+    Box::new(AuxOut {
+        config: Arc::new(AuxOutConfig {
+            bus: AtomicUsize::new(1),
+            dirty: ConfigDirty::new(),
+        }),
+        bus: 1,
+    })
+}
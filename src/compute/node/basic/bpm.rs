@@ -1,38 +1,243 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
+use atomic_float::AtomicF32;
+use eframe::egui;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::slider::SliderInput, Input, Node, NodeEvent},
+    node::{
+        inputs::slider::SliderInput, ConfigDirty, Input, InputData, InputUi, Node, NodeConfig,
+        NodeEvent,
+    },
+    transport::{self, Follower},
     Output, Value, ValueKind,
 };
 
+crate::ports! {
+    BPM = "BPM",
+}
+
+// Divisions of the beat besides the beat itself: output name, ticks per beat
+// and whether swing moves every other tick
+const DIVISIONS: [(&str, f64, bool); 4] = [
+    ("1/8", 2.0, true),
+    ("1/8T", 3.0, false),
+    ("1/4.", 2.0 / 3.0, false),
+    ("1/16", 4.0, true),
+];
+
+// Taps further apart than this start counting anew
+const TAP_GAP: Duration = Duration::from_secs(2);
+
+fn default_swing() -> AtomicF32 {
+    AtomicF32::new(0.5)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BpmConfig {
+    follow: AtomicBool,
+    /// Where in a pair of swung ticks the second one falls, 0.5 plays them
+    /// straight
+    #[serde(default = "default_swing")]
+    swing: AtomicF32,
+    #[serde(skip)]
+    taps: Mutex<Vec<Instant>>,
+    // Tempo tapped since the node last looked, 0 for none
+    #[serde(skip)]
+    tapped: AtomicF32,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl Default for BpmConfig {
+    fn default() -> Self {
+        BpmConfig {
+            follow: AtomicBool::new(false),
+            swing: default_swing(),
+            taps: Mutex::new(Vec::new()),
+            tapped: AtomicF32::new(0.0),
+            dirty: ConfigDirty::new(),
+        }
+    }
+}
+
+impl BpmConfig {
+    fn tap(&self) {
+        let now = Instant::now();
+        let mut taps = self.taps.lock().unwrap();
+        if taps.last().is_some_and(|last| now - *last > TAP_GAP) {
+            taps.clear();
+        }
+        taps.push(now);
+
+        // The last few taps follow a change of tempo quicker than all of them
+        let skip = taps.len().saturating_sub(5);
+        taps.drain(..skip);
+
+        if let (Some(first), Some(last)) = (taps.first(), taps.last()) {
+            if taps.len() > 1 {
+                let interval = (*last - *first).as_secs_f32() / (taps.len() - 1) as f32;
+                self.tapped
+                    .store((60.0 / interval).round(), Ordering::Relaxed);
+                self.dirty.mark();
+            }
+        }
+    }
+}
+
+impl NodeConfig for BpmConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mut follow = self.follow.load(Ordering::Acquire);
+        let mut swing = self.swing.load(Ordering::Acquire) * 100.0;
+
+        let mut changed = ui
+            .checkbox(&mut follow, "Follow JACK transport")
+            .on_hover_text("Falls back to the BPM input while no transport is running")
+            .changed();
+        ui.horizontal(|ui| {
+            ui.label("swing %");
+            changed |= ui
+                .add(egui::DragValue::new(&mut swing).range(50.0..=75.0))
+                .on_hover_text("Delays every other tick of 1/8 and 1/16, 50 plays them straight")
+                .changed();
+        });
+        if ui
+            .button("Tap")
+            .on_hover_text("Sets the BPM to the tempo of the last few taps")
+            .clicked()
+        {
+            self.tap();
+        }
+
+        self.follow.store(follow, Ordering::Release);
+        self.swing.store(swing / 100.0, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
+/// Index of the tick at `beats`, `per_beat` ticks to a beat, with every
+/// second one moved to `swing` of the pair.
+fn tick_index(beats: f64, per_beat: f64, swing: Option<f64>) -> i64 {
+    match swing {
+        Some(swing) => {
+            let pairs = beats * per_beat / 2.0;
+            let second = pairs.fract() >= swing;
+            pairs.floor() as i64 * 2 + second as i64
+        }
+        None => (beats * per_beat).floor() as i64,
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Bpm {
     bpm: Arc<SliderInput>,
+    #[serde(default)]
+    config: Arc<BpmConfig>,
+    #[serde(skip)]
+    follow: bool,
+    #[serde(skip)]
+    follower: Follower,
+    #[serde(skip, default = "default_swing_value")]
+    swing: f64,
     out: Value,
-    t: usize,
+    #[serde(skip)]
+    divisions: [Value; DIVISIONS.len()],
+    /// Beats since the start, every output ticks off of it so they stay in
+    /// phase through changes of tempo
+    #[serde(default)]
+    beats: f64,
+}
+
+fn default_swing_value() -> f64 {
+    0.5
+}
+
+impl Bpm {
+    // Ticks of the outputs between the positions `prev` and `beats`
+    fn tick(&mut self, prev: f64, beats: f64, bpm: f32) {
+        let period = 60.0 / bpm as f64;
+
+        for (out, (_, per_beat, swung)) in self.divisions.iter_mut().zip(DIVISIONS) {
+            let swing = Some(self.swing).filter(|_| swung);
+            *out = if tick_index(beats, per_beat, swing) > tick_index(prev, per_beat, swing) {
+                Value::Beat(Duration::from_secs_f64(period / per_beat))
+            } else {
+                Value::None
+            };
+        }
+    }
 }
 
 #[typetag::serde]
 impl Node for Bpm {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let bpm = self.bpm.as_f32(&data[0]);
-
-        self.t += 1;
-        let mins = (self.t as f32) / 44100.0 / 60.0;
-        if mins >= 1.0 / bpm {
-            self.t = 0;
-            self.out = Value::Beat(Duration::from_secs_f32(60.0 / bpm));
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let bpm = self.bpm.as_f32(&data[BPM]);
+        let prev = self.beats;
+
+        if let Some(snapshot) = transport::snapshot().filter(|_| self.follow) {
+            let (beats, beat) = self.follower.step(&snapshot);
+            self.beats = beats;
+            self.out = if beat && snapshot.bpm > 0.0 {
+                Value::Beat(Duration::from_secs_f32(60.0 / snapshot.bpm))
+            } else {
+                Value::None
+            };
+
+            // Moving the transport doesn't fire the divisions it skips over
+            if beats > prev && beats - prev < 0.25 && snapshot.bpm > 0.0 {
+                self.tick(prev, beats, snapshot.bpm);
+            } else {
+                self.divisions = Default::default();
+            }
+
+            return Default::default();
+        }
+
+        self.beats += bpm as f64 / 60.0 / 44100.0;
+        self.out = if self.beats.floor() > prev.floor() {
+            Value::Beat(Duration::from_secs_f32(60.0 / bpm))
         } else {
-            self.out = Value::None;
+            Value::None
+        };
+        self.tick(prev, self.beats, bpm);
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            let follow = self.config.follow.load(Ordering::Relaxed);
+            if follow != self.follow {
+                self.follow = follow;
+                self.follower = Follower::default();
+            }
+            self.swing = self.config.swing.load(Ordering::Relaxed) as f64;
+
+            let tapped = self.config.tapped.swap(0.0, Ordering::Relaxed);
+            if tapped > 0.0 {
+                self.bpm.set_value(tapped);
+            }
         }
 
         Default::default()
     }
 
     fn read(&self, out: &mut [Value]) {
-        out[0] = self.out.clone()
+        out[0] = self.out.clone();
+        for (out, value) in out[1..].iter_mut().zip(&self.divisions) {
+            *out = value.clone();
+        }
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
     }
 
     fn inputs(&self) -> Vec<Input> {
@@ -40,14 +245,30 @@ impl Node for Bpm {
     }
 
     fn output(&self) -> Vec<Output> {
-        vec![Output::new("", ValueKind::Beat)]
+        let mut outputs = vec![Output::new("", ValueKind::Beat)];
+        outputs.extend(
+            DIVISIONS
+                .iter()
+                .map(|(name, _, _)| Output::new(*name, ValueKind::Beat)),
+        );
+
+        outputs
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
     }
 }
 
 pub fn bpm() -> Box<dyn Node> {
     Box::new(Bpm {
         bpm: Arc::new(SliderInput::new(60.0, 60.0, 300.0).integral(true)),
+        config: Default::default(),
+        follow: false,
+        follower: Follower::default(),
+        swing: default_swing_value(),
         out: Value::None,
-        t: 0,
+        divisions: Default::default(),
+        beats: 0.0,
     })
 }
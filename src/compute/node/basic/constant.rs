@@ -3,10 +3,14 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::real::RealInput, Input, Node, NodeEvent},
+    node::{inputs::real::RealInput, Input, InputData, Node, NodeEvent},
     Value,
 };
 
+crate::ports! {
+    VALUE = "value",
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Constant {
     value: Arc<RealInput>,
@@ -15,8 +19,8 @@ pub struct Constant {
 
 #[typetag::serde]
 impl Node for Constant {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        self.out = self.value.get_f32(&data[0]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        self.out = self.value.get_f32(&data[VALUE]);
 
         Default::default()
     }
@@ -25,6 +29,10 @@ impl Node for Constant {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![Input::stateful("value", &self.value)]
     }
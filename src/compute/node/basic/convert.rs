@@ -4,13 +4,17 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     compute::{
-        node::{Input, Node, NodeConfig, NodeEvent},
+        node::{ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
         Value, ValueKind,
     },
     serde_atomic_enum,
     util::enum_combo_box,
 };
 
+crate::ports! {
+    IN = "in",
+}
+
 #[atomic_enum::atomic_enum]
 #[derive(PartialEq, Eq, derive_more::Display, strum::EnumIter)]
 pub enum ConvTy {
@@ -20,15 +24,24 @@ pub enum ConvTy {
 
 serde_atomic_enum!(AtomicConvTy);
 
+impl Default for ConvTy {
+    fn default() -> Self {
+        ConvTy::FreqToTime
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ConvertConfig {
     ty: AtomicConvTy,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl ConvertConfig {
     fn new(ty: ConvTy) -> Self {
         ConvertConfig {
             ty: AtomicConvTy::new(ty),
+            dirty: ConfigDirty::new(),
         }
     }
 
@@ -41,15 +54,18 @@ impl NodeConfig for ConvertConfig {
     fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn std::any::Any) {
         let mut ty = self.ty.load(Ordering::Acquire);
 
-        enum_combo_box(ui, &mut ty);
+        let changed = enum_combo_box(ui, &mut ty);
 
         self.ty.store(ty, Ordering::Release);
+        self.dirty.mark_if(changed);
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Convert {
     conf: Arc<ConvertConfig>,
+    #[serde(skip)]
+    ty: ConvTy,
     out: f32,
 }
 
@@ -57,6 +73,7 @@ impl Convert {
     fn new(ty: ConvTy) -> Self {
         Convert {
             conf: Arc::new(ConvertConfig::new(ty)),
+            ty,
             out: 0.0,
         }
     }
@@ -64,14 +81,22 @@ impl Convert {
 
 #[typetag::serde]
 impl Node for Convert {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        self.out = match self.conf.convert_type() {
-            ConvTy::FreqToTime => data[0].as_float().map(|f| 44100.0 / f).unwrap_or(0.0),
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        self.out = match self.ty {
+            ConvTy::FreqToTime => data[IN].as_float().map(|f| 44100.0 / f).unwrap_or(0.0),
         };
 
         Default::default()
     }
 
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.conf.dirty.take() {
+            self.ty = self.conf.convert_type();
+        }
+
+        Default::default()
+    }
+
     fn read(&self, out: &mut [Value]) {
         out[0] = Value::Float(self.out)
     }
@@ -80,6 +105,10 @@ impl Node for Convert {
         Some(Arc::clone(&self.conf) as Arc<_>)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![Input::new("in", ValueKind::Float)]
     }
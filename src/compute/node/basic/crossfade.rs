@@ -0,0 +1,59 @@
+use std::{f32::consts::FRAC_PI_2, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{inputs::slider::SliderInput, Input, InputData, Node, NodeEvent},
+    Value, ValueKind,
+};
+
+crate::ports! {
+    A = "a",
+    B = "b",
+    FADE = "fade",
+}
+
+/// Equal-power blend from `a` to `b`, the level holds steady halfway
+/// through where a linear mix dips.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Crossfade {
+    fade: Arc<SliderInput>,
+    out: f32,
+}
+
+#[typetag::serde]
+impl Node for Crossfade {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let a = data[A].as_float().unwrap_or_default();
+        let b = data[B].as_float().unwrap_or_default();
+        let fade = self.fade.as_f32(&data[FADE]).clamp(0.0, 1.0);
+
+        let (gain_b, gain_a) = (fade * FRAC_PI_2).sin_cos();
+        self.out = a * gain_a + b * gain_b;
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::new("a", ValueKind::Float),
+            Input::new("b", ValueKind::Float),
+            Input::stateful("fade", &self.fade),
+        ]
+    }
+}
+
+pub fn crossfade() -> Box<dyn Node> {
+    Box::new(Crossfade {
+        fade: Arc::new(SliderInput::new(0.5, 0.0, 1.0)),
+        out: 0.0,
+    })
+}
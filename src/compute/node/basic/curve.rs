@@ -3,30 +3,81 @@ use std::sync::{
     Arc, RwLock, RwLockReadGuard, RwLockWriteGuard,
 };
 
-use crate::compute::{
-    node::{
-        inputs::{
-            gate::GateInput,
-            real::RealInput,
-            time::TimeInput,
-            trigger::{TriggerInput, TriggerMode},
+use crate::{
+    compute::{
+        node::{
+            inputs::{
+                gate::GateInput,
+                real::RealInput,
+                time::TimeInput,
+                trigger::{TriggerInput, TriggerMode},
+            },
+            ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
         },
-        Input, Node, NodeConfig, NodeEvent,
+        Value, ValueKind,
     },
-    Value,
+    serde_atomic_enum,
+    util::enum_combo_box,
 };
+use atomic_float::AtomicF32;
 use eframe::{
     egui,
     epaint::{Color32, Vec2},
 };
 use egui_curve_edit as egui_curve;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+
+crate::ports! {
+    TRIGGER = "trigger",
+    LENGTH = "length",
+    MIN = "min",
+    MAX = "max",
+    REPEAT = "repeat",
+    RESETTABLE = "resettable",
+    HOLD = "hold",
+    SYNC = "sync",
+}
+
+/// What the Curve node does once it reaches the end, or the sustain point.
+#[atomic_enum::atomic_enum]
+#[derive(PartialEq, Eq, Default, derive_more::Display, strum::EnumIter)]
+pub enum CurveMode {
+    #[default]
+    #[display(fmt = "One shot")]
+    OneShot,
+    Loop,
+    Sustain,
+}
+
+serde_atomic_enum!(AtomicCurveMode);
+
+fn default_mode() -> AtomicCurveMode {
+    AtomicCurveMode::new(CurveMode::OneShot)
+}
+
+fn default_sustain() -> AtomicF32 {
+    AtomicF32::new(0.5)
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CurveConfig {
     curve: RwLock<egui_curve::Curve>,
     sampled: RwLock<Vec<f32>>,
     edit: AtomicBool,
+    #[serde(default = "default_mode")]
+    mode: AtomicCurveMode,
+    /// Point the curve holds at in Sustain mode, in 0..1 of its length
+    #[serde(default = "default_sustain")]
+    sustain: AtomicF32,
+    /// Length in beats of the sync input, none while 0
+    #[serde(default)]
+    beats: AtomicF32,
+    // Only the Curve node plays the curve over time, Transform maps through
+    // it and has no use for the settings of playback
+    #[serde(skip)]
+    playback: AtomicBool,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl CurveConfig {
@@ -35,9 +86,19 @@ impl CurveConfig {
             curve: RwLock::new(egui_curve::Curve::new([0.0, 50.0], [100.0, 50.0])),
             sampled: RwLock::new(vec![0.0, 0.0, 0.0]),
             edit: AtomicBool::new(false),
+            mode: default_mode(),
+            sustain: default_sustain(),
+            beats: AtomicF32::new(0.0),
+            playback: AtomicBool::new(false),
+            dirty: ConfigDirty::new(),
         }
     }
 
+    fn with_playback(self) -> Self {
+        self.playback.store(true, Ordering::Relaxed);
+        self
+    }
+
     pub fn values(&self) -> RwLockReadGuard<'_, Vec<f32>> {
         self.sampled.read().unwrap()
     }
@@ -45,6 +106,43 @@ impl CurveConfig {
     pub fn values_mut(&self) -> RwLockWriteGuard<'_, Vec<f32>> {
         self.sampled.write().unwrap()
     }
+
+    fn show_playback(&self, ui: &mut egui::Ui) {
+        let mut mode = self.mode.load(Ordering::Acquire);
+        let mut sustain = self.sustain.load(Ordering::Acquire) * 100.0;
+        let mut beats = self.beats.load(Ordering::Acquire);
+
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Mode");
+            changed |= enum_combo_box(ui, &mut mode);
+        });
+        if mode == CurveMode::Sustain {
+            ui.horizontal(|ui| {
+                ui.label("sustain at %");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut sustain).range(0.0..=100.0))
+                    .on_hover_text("Held here while the hold gate is open")
+                    .changed();
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label("beats");
+            changed |= ui
+                .add(
+                    egui::DragValue::new(&mut beats)
+                        .range(0.0..=64.0)
+                        .speed(0.25),
+                )
+                .on_hover_text("Length in beats of the sync input, 0 takes the length input")
+                .changed();
+        });
+
+        self.mode.store(mode, Ordering::Release);
+        self.sustain.store(sustain / 100.0, Ordering::Release);
+        self.beats.store(beats, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
 }
 
 impl NodeConfig for CurveConfig {
@@ -105,6 +203,10 @@ impl NodeConfig for CurveConfig {
         }
 
         self.edit.store(edit, Ordering::Release);
+
+        if self.playback.load(Ordering::Relaxed) {
+            self.show_playback(ui);
+        }
     }
 
     fn show_short(&self, ui: &mut egui::Ui, data: &dyn std::any::Any) {
@@ -112,6 +214,18 @@ impl NodeConfig for CurveConfig {
     }
 }
 
+// Saved curves come back with their playback settings shown
+fn deserialize_config<'de, D>(d: D) -> Result<Arc<CurveConfig>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Arc::new(CurveConfig::deserialize(d)?.with_playback()))
+}
+
+fn default_hold() -> Arc<GateInput> {
+    Arc::new(GateInput::new(0.5).with_default(true))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 enum CurveStatus {
     Playing,
@@ -120,6 +234,7 @@ enum CurveStatus {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Curve {
+    #[serde(deserialize_with = "deserialize_config")]
     config: Arc<CurveConfig>,
 
     trigger: Arc<TriggerInput>,
@@ -128,16 +243,31 @@ pub struct Curve {
     max: Arc<RealInput>,
     repeat: Arc<GateInput>,
     resettable: Arc<GateInput>,
+    #[serde(default = "default_hold")]
+    hold: Arc<GateInput>,
 
     status: CurveStatus,
     t: f32,
     out: f32,
+    // Seconds between the last two beats at the sync input
+    #[serde(skip)]
+    period: Option<f32>,
+    // Whether the hold gate closed since the last trigger
+    #[serde(skip)]
+    released: bool,
+    // Copies of the config, refreshed when it changes
+    #[serde(skip)]
+    mode: CurveMode,
+    #[serde(skip)]
+    beats: f32,
+    #[serde(skip)]
+    sustain: f32,
 }
 
 impl Curve {
     pub fn new() -> Self {
         Curve {
-            config: Arc::new(CurveConfig::new()),
+            config: Arc::new(CurveConfig::new().with_playback()),
 
             trigger: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
             length: Arc::new(TimeInput::new(44100.0)),
@@ -145,26 +275,55 @@ impl Curve {
             max: Arc::new(RealInput::new(1.0)),
             repeat: Arc::new(GateInput::new(0.5)),
             resettable: Arc::new(GateInput::new(0.5)),
+            hold: default_hold(),
 
             status: CurveStatus::Done,
             t: 0.0,
             out: 0.0,
+            period: None,
+            released: false,
+            mode: CurveMode::default(),
+            beats: 0.0,
+            sustain: 0.5,
         }
     }
 }
 
 #[typetag::serde]
 impl Node for Curve {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let trigger = self.trigger.trigger(&data[0]);
-        let length = self.length.get_samples(&data[1]);
-        let min = self.min.get_f32(&data[2]);
-        let max = self.max.get_f32(&data[3]);
-        let repeat = self.repeat.gate(&data[4]);
-        let resettable = self.resettable.gate(&data[5]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let mode = self.mode;
+        let trigger = self.trigger.trigger(&data[TRIGGER]);
+        let min = self.min.get_f32(&data[MIN]);
+        let max = self.max.get_f32(&data[MAX]);
+        let repeat = self.repeat.gate(&data[REPEAT]) || mode == CurveMode::Loop;
+        let resettable = self.resettable.gate(&data[RESETTABLE]);
+        // Notes that fired the trigger hold it for as long as they're down
+        let hold = self.hold.gate(&data[HOLD]) && self.trigger.held();
+
+        if let Some(period) = data[SYNC].as_beat() {
+            self.period = Some(period.as_secs_f32());
+        }
+        let length = match self.period.filter(|_| self.beats > 0.0) {
+            Some(period) => self.beats * period * 44100.0,
+            None => self.length.get_samples(&data[LENGTH]),
+        };
+
         if trigger && (self.status == CurveStatus::Done || resettable) {
             self.status = CurveStatus::Playing;
             self.t = 0.0;
+            self.released = false;
+        }
+
+        let mut step = 1.0;
+        if mode == CurveMode::Sustain && self.status == CurveStatus::Playing && !self.released {
+            let sustain = self.sustain * length;
+            if !hold {
+                self.released = true;
+            } else if self.t >= sustain {
+                self.t = sustain;
+                step = 0.0;
+            }
         }
 
         if self.t > length {
@@ -180,7 +339,8 @@ impl Node for Curve {
             CurveStatus::Done => self.config.values()[0],
             CurveStatus::Playing => {
                 let values = self.config.values();
-                let t = self.t as f32 / length as f32;
+                // A curve of no length is over as soon as it starts
+                let t = if length > 0.0 { self.t / length } else { 1.0 };
 
                 let idx_f32 = t * values.len() as f32;
                 let idx = idx_f32 as usize;
@@ -195,7 +355,17 @@ impl Node for Curve {
         };
 
         self.out = raw_out / 100.0 * (max - min) + min;
-        self.t += 1.0;
+        self.t += step;
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.mode = self.config.mode.load(Ordering::Relaxed);
+            self.beats = self.config.beats.load(Ordering::Relaxed);
+            self.sustain = self.config.sustain.load(Ordering::Relaxed);
+        }
 
         Default::default()
     }
@@ -208,6 +378,10 @@ impl Node for Curve {
         Some(Arc::clone(&self.config) as Arc<_>)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::stateful("trigger", &self.trigger),
@@ -216,6 +390,8 @@ impl Node for Curve {
             Input::stateful("max", &self.max),
             Input::stateful("repeat", &self.repeat),
             Input::stateful("resettable", &self.resettable),
+            Input::stateful("hold", &self.hold),
+            Input::new("sync", ValueKind::Beat),
         ]
     }
 }
@@ -6,13 +6,20 @@ pub use delay_impl::{RawDelay, ResizeStrategy};
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
+    denormal,
     node::{
         inputs::{percentage::PercentageInput, time::TimeInput},
-        Input, Node, NodeEvent,
+        Input, InputData, Node, NodeEvent,
     },
     Value, ValueKind,
 };
 
+crate::ports! {
+    SIG = "sig",
+    TIME = "time",
+    FEEDBACK = "feedback",
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Delay {
     time_in: Arc<TimeInput>,
@@ -32,11 +39,11 @@ impl Delay {
 
 #[typetag::serde]
 impl Node for Delay {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let target_len = self.time_in.get_samples(&data[1]);
-        let feedback_gain = self.feedback.get_f32(&data[2]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let target_len = self.time_in.get_samples(&data[TIME]);
+        let feedback_gain = self.feedback.get_f32(&data[FEEDBACK]);
 
-        let input = data[0].as_float().unwrap_or(0.0);
+        let input = data[SIG].as_float().unwrap_or(0.0);
         let feedback = feedback_gain * self.delay_impl.last_out();
 
         let new_size = target_len as f32;
@@ -44,15 +51,23 @@ impl Node for Delay {
             self.delay_impl.resize(new_size);
         }
 
-        self.delay_impl.push(input + feedback);
+        self.delay_impl.push(denormal::protect(input + feedback));
 
         Default::default()
     }
 
+    fn panic(&mut self) {
+        self.delay_impl.clear();
+    }
+
     fn read(&self, out: &mut [Value]) {
         out[0] = Value::Float(self.delay_impl.last_out())
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::new("sig", ValueKind::Float),
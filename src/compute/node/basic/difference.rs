@@ -3,10 +3,15 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::real::RealInput, Input, Node, NodeEvent},
+    node::{inputs::real::RealInput, Input, InputData, Node, NodeEvent},
     Output, Value, ValueKind,
 };
 
+crate::ports! {
+    A = "a",
+    B = "b",
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Difference {
     a: Arc<RealInput>,
@@ -16,8 +21,8 @@ pub struct Difference {
 
 #[typetag::serde]
 impl Node for Difference {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        self.out = self.a.get_f32(&data[0]) - self.b.get_f32(&data[1]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        self.out = self.a.get_f32(&data[A]) - self.b.get_f32(&data[B]);
 
         Default::default()
     }
@@ -26,6 +31,10 @@ impl Node for Difference {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![Input::stateful("a", &self.a), Input::stateful("b", &self.b)]
     }
@@ -1,44 +1,139 @@
-use std::sync::Arc;
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::real::RealInput, Input, Node, NodeEvent},
-    Value, ValueKind,
+    node::{inputs::real::RealInput, ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
+    Output, Value, ValueKind,
 };
 
+crate::ports! {
+    SIG_0 = "sig 0",
+    SIG_1 = "sig 1",
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GainConfig {
+    per_channel: AtomicBool,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for GainConfig {
+    fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn Any) {
+        let mut per_channel = self.per_channel.load(Ordering::Acquire);
+        if ui
+            .checkbox(&mut per_channel, "Per channel")
+            .on_hover_text("Multiply array inputs channel by channel")
+            .changed()
+        {
+            self.per_channel.store(per_channel, Ordering::Release);
+            self.dirty.mark();
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct Gain {
+    #[serde(default)]
+    config: Arc<GainConfig>,
     s1: Arc<RealInput>,
     out: f32,
+    #[serde(default)]
+    per_channel: bool,
+    #[serde(skip)]
+    outs: Vec<f32>,
+}
+
+impl Gain {
+    fn kind(&self) -> ValueKind {
+        if self.per_channel {
+            ValueKind::FloatArray
+        } else {
+            ValueKind::Float
+        }
+    }
 }
 
 #[typetag::serde]
 impl Node for Gain {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let s0 = data[0].as_float().unwrap_or(0.0);
-        let s1 = self.s1.get_f32(&data[1]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if self.per_channel {
+            let (s0, s1) = (&data[SIG_0], &data[SIG_1]);
+            let channels = s0.channels().max(s1.channels());
+            self.outs.clear();
+            self.outs.extend((0..channels).map(|k| {
+                s0.channel(k).as_float().unwrap_or(0.0) * self.s1.get_f32(&s1.channel(k))
+            }));
+
+            return Default::default();
+        }
+
+        let s0 = data[SIG_0].as_float().unwrap_or(0.0);
+        let s1 = self.s1.get_f32(&data[SIG_1]);
 
         self.out = s0 * s1;
 
         Default::default()
     }
 
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
+        let per_channel = self.config.per_channel.load(Ordering::Relaxed);
+        if per_channel == self.per_channel {
+            return Default::default();
+        }
+        self.per_channel = per_channel;
+
+        vec![
+            NodeEvent::RecalcOutputs(self.output()),
+            NodeEvent::RecalcInputs(self.inputs()),
+        ]
+    }
+
     fn read(&self, out: &mut [Value]) {
-        out[0] = Value::Float(self.out)
+        if self.per_channel {
+            out[0].set_float_array(self.outs.iter().copied());
+        } else {
+            out[0] = Value::Float(self.out);
+        }
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
     }
 
     fn inputs(&self) -> Vec<Input> {
         vec![
-            Input::new("sig 0", ValueKind::Float),
-            Input::stateful("sig 1", &self.s1),
+            Input::new("sig 0", self.kind()),
+            Input::stateful("sig 1", &self.s1).with_kind(self.kind()),
         ]
     }
+
+    fn output(&self) -> Vec<Output> {
+        vec![Output::new("", self.kind())]
+    }
 }
 
 pub fn gain() -> Box<dyn Node> {
     Box::new(Gain {
+        config: Default::default(),
         s1: Arc::new(RealInput::new(1.0)),
         out: 0.0,
+        per_channel: false,
+        outs: Vec::new(),
     })
 }
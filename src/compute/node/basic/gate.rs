@@ -3,10 +3,14 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::gate::GateInput, Input, Node, NodeEvent},
+    node::{inputs::gate::GateInput, Input, InputData, Node, NodeEvent},
     Value,
 };
 
+crate::ports! {
+    GATE = "gate",
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Gate {
     gate: Arc<GateInput>,
@@ -24,8 +28,12 @@ impl Gate {
 
 #[typetag::serde]
 impl Node for Gate {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        self.out = if self.gate.gate(&data[0]) { 1.0 } else { 0.0 };
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        self.out = if self.gate.gate(&data[GATE]) {
+            1.0
+        } else {
+            0.0
+        };
 
         Default::default()
     }
@@ -34,6 +42,10 @@ impl Node for Gate {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![Input::stateful("gate", &self.gate)]
     }
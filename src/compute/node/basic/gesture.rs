@@ -0,0 +1,163 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{
+        inputs::{
+            gate::GateInput,
+            positive::PositiveInput,
+            trigger::{TriggerInput, TriggerMode},
+        },
+        Input, InputData, Node, NodeEvent,
+    },
+    Output, Value, ValueKind,
+};
+
+crate::ports! {
+    VALUE = "value",
+    RECORD = "record",
+    PLAY = "play",
+    LOOP = "loop",
+    SPEED = "speed",
+}
+
+// Samples per recorded point
+const DECIMATION: usize = 64;
+// Thirty seconds of movement
+const MAX_POINTS: usize = 30 * 44100 / DECIMATION;
+
+/// Records a movement of its value input while `record` is high and plays
+/// it back, smoothly interpolated, on each `play` trigger.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Gesture {
+    record: Arc<GateInput>,
+    play: Arc<TriggerInput>,
+    looped: Arc<GateInput>,
+    speed: Arc<PositiveInput>,
+    points: Vec<f32>,
+    recording: bool,
+    since_point: usize,
+    playing: bool,
+    // Playback position, in points
+    pos: f32,
+    out: f32,
+}
+
+impl Gesture {
+    pub fn new() -> Self {
+        Gesture {
+            record: Arc::new(GateInput::new(0.5)),
+            play: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
+            looped: Arc::new(GateInput::new(0.5)),
+            speed: Arc::new(PositiveInput::new(1.0)),
+            points: Vec::new(),
+            recording: false,
+            since_point: 0,
+            playing: false,
+            pos: 0.0,
+            out: 0.0,
+        }
+    }
+
+    fn point(&self, idx: isize) -> f32 {
+        let last = self.points.len() as isize - 1;
+        self.points[idx.clamp(0, last) as usize]
+    }
+
+    // Catmull-Rom spline through the recorded points
+    fn sample(&self, pos: f32) -> f32 {
+        let idx = pos.floor() as isize;
+        let t = pos - pos.floor();
+
+        let p0 = self.point(idx - 1);
+        let p1 = self.point(idx);
+        let p2 = self.point(idx + 1);
+        let p3 = self.point(idx + 2);
+
+        0.5 * (2.0 * p1
+            + (p2 - p0) * t
+            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+            + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t)
+    }
+}
+
+#[typetag::serde]
+impl Node for Gesture {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let value = data[VALUE].as_float().unwrap_or_default();
+
+        let record = self.record.gate(&data[RECORD]);
+        if record && !self.recording {
+            self.points.clear();
+            self.since_point = 0;
+            self.playing = false;
+        }
+        self.recording = record;
+
+        if self.recording {
+            if self.since_point == 0 && self.points.len() < MAX_POINTS {
+                self.points.push(value);
+            }
+            self.since_point = (self.since_point + 1) % DECIMATION;
+            self.out = value;
+
+            return Default::default();
+        }
+
+        if self.play.trigger(&data[PLAY]) && !self.points.is_empty() {
+            self.playing = true;
+            self.pos = 0.0;
+        }
+
+        let looped = self.looped.gate(&data[LOOP]);
+        if self.playing {
+            self.out = self.sample(self.pos);
+
+            let speed = self.speed.get_f32(&data[SPEED]);
+            self.pos += speed / DECIMATION as f32;
+
+            let len = (self.points.len() - 1) as f32;
+            if self.pos >= len {
+                if looped && len > 0.0 {
+                    self.pos %= len;
+                } else {
+                    self.playing = false;
+                    self.out = self.point(len as isize);
+                }
+            }
+        }
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out);
+        out[1] = Value::Float(if self.playing { 1.0 } else { 0.0 });
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::new("value", ValueKind::Float),
+            Input::stateful("record", &self.record),
+            Input::stateful("play", &self.play),
+            Input::stateful("loop", &self.looped),
+            Input::stateful("speed", &self.speed),
+        ]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![
+            Output::new("value", ValueKind::Float),
+            Output::new("playing", ValueKind::Float),
+        ]
+    }
+}
+
+pub fn gesture() -> Box<dyn Node> {
+    Box::new(Gesture::new())
+}
@@ -0,0 +1,98 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{
+        inputs::{gate::GateInput, time::TimeInput},
+        Input, InputData, Node, NodeEvent,
+    },
+    Value, ValueKind,
+};
+
+crate::ports! {
+    SIG = "sig",
+    FREEZE = "freeze",
+    LENGTH = "length",
+}
+
+// Longest block that can be held, 10 seconds
+const MAX_LEN: usize = 441000;
+
+/// Loops the last block of its input while frozen.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Hold {
+    freeze: Arc<GateInput>,
+    length: Arc<TimeInput>,
+    #[serde(skip)]
+    history: VecDeque<f32>,
+    #[serde(skip)]
+    held: Vec<f32>,
+    #[serde(skip)]
+    pos: usize,
+    out: f32,
+}
+
+#[typetag::serde]
+impl Node for Hold {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sig = data[SIG].as_float().unwrap_or_default();
+        let len = (self.length.get_samples(&data[LENGTH]) as usize).clamp(1, MAX_LEN);
+        let frozen = self.freeze.gate(&data[FREEZE]);
+
+        self.history.push_back(sig);
+        while self.history.len() > len {
+            self.history.pop_front();
+        }
+
+        if !frozen {
+            self.held.clear();
+            self.out = sig;
+
+            return Default::default();
+        }
+
+        // Captured once the block is full, so freezing right after a load
+        // doesn't loop a few samples
+        if self.held.is_empty() && self.history.len() == len {
+            self.held.extend(self.history.iter().copied());
+            self.pos = 0;
+        }
+
+        if self.held.is_empty() {
+            self.out = sig;
+        } else {
+            self.out = self.held[self.pos];
+            self.pos = (self.pos + 1) % self.held.len();
+        }
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::new("sig", ValueKind::Float),
+            Input::stateful("freeze", &self.freeze),
+            Input::stateful("length", &self.length),
+        ]
+    }
+}
+
+pub fn hold() -> Box<dyn Node> {
+    Box::new(Hold {
+        freeze: Arc::new(GateInput::new(0.5)),
+        length: Arc::new(TimeInput::from_ms(100.0)),
+        history: VecDeque::new(),
+        held: Vec::new(),
+        pos: 0,
+        out: 0.0,
+    })
+}
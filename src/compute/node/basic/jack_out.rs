@@ -0,0 +1,94 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use eframe::egui::{self, DragValue};
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    extern_out,
+    node::{ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
+    Output, ValueKind,
+};
+
+crate::ports! {
+    SIG = "sig",
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JackOutConfig {
+    // 1-based, like the port names
+    port: AtomicUsize,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for JackOutConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mut port = self.port.load(Ordering::Acquire);
+
+        ui.horizontal(|ui| {
+            ui.label("Port");
+            let changed = ui.add(DragValue::new(&mut port).range(1..=64)).changed();
+
+            self.port.store(port, Ordering::Release);
+            self.dirty.mark_if(changed);
+        });
+    }
+}
+
+/// Sends its input to one of the JACK audio outputs from the audio settings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct JackOut {
+    config: Arc<JackOutConfig>,
+    #[serde(skip)]
+    port: usize,
+}
+
+#[typetag::serde]
+impl Node for JackOut {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sig = data[SIG].as_float().unwrap_or_default();
+        extern_out::push(self.port.saturating_sub(1), sig);
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.port = self.config.port.load(Ordering::Relaxed);
+        }
+
+        Default::default()
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::new("sig", ValueKind::Float)]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        Vec::new()
+    }
+}
+
+pub fn jack_out() -> Box<dyn Node> {
+    Box::new(JackOut {
+        config: Arc::new(JackOutConfig {
+            port: AtomicUsize::new(1),
+            dirty: ConfigDirty::new(),
+        }),
+        port: 1,
+    })
+}
@@ -0,0 +1,105 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use eframe::egui::DragValue;
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
+    Output, Value, ValueKind,
+};
+
+/// Most channels the array nodes let one set up.
+pub const MAX_CHANNELS: usize = 64;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JoinConfig {
+    ins: AtomicUsize,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for JoinConfig {
+    fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn Any) {
+        let mut ins = self.ins.load(Ordering::Acquire);
+
+        ui.horizontal(|ui| {
+            ui.label("inputs");
+            if ui
+                .add(DragValue::new(&mut ins).range(1..=MAX_CHANNELS))
+                .changed()
+            {
+                self.ins.store(ins, Ordering::Release);
+                self.dirty.mark();
+            }
+        });
+    }
+}
+
+/// Signals gathered into the channels of an array.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Join {
+    config: Arc<JoinConfig>,
+    ins: usize,
+    #[serde(skip)]
+    out: Vec<f32>,
+}
+
+#[typetag::serde]
+impl Node for Join {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        self.out.clear();
+        self.out
+            .extend((0..self.ins).map(|k| data[k].as_float().unwrap_or(0.0)));
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
+        let ins = self.config.ins.load(Ordering::Relaxed);
+        if ins == self.ins {
+            return Default::default();
+        }
+        self.ins = ins;
+
+        vec![NodeEvent::RecalcInputs(self.inputs())]
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0].set_float_array(self.out.iter().copied())
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        (0..self.ins)
+            .map(|k| Input::new(format!("ch {k}"), ValueKind::Float))
+            .collect()
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![Output::new("", ValueKind::FloatArray)]
+    }
+}
+
+pub fn join() -> Box<dyn Node> {
+    Box::new(Join {
+        config: Arc::new(JoinConfig {
+            ins: AtomicUsize::new(2),
+            dirty: ConfigDirty::new(),
+        }),
+        ins: 2,
+        out: Vec::new(),
+    })
+}
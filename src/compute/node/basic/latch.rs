@@ -5,11 +5,16 @@ use serde::{Deserialize, Serialize};
 use crate::compute::{
     node::{
         inputs::trigger::{TriggerInput, TriggerMode},
-        Input, Node, NodeEvent,
+        Input, InputData, Node, NodeEvent,
     },
     Value, ValueKind,
 };
 
+crate::ports! {
+    TRIGGER = "trigger",
+    SIGNAL = "signal",
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Latch {
     trigger: Arc<TriggerInput>,
@@ -27,9 +32,9 @@ impl Latch {
 
 #[typetag::serde]
 impl Node for Latch {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        if self.trigger.trigger(&data[0]) {
-            self.out = data[1].as_float().unwrap_or_default();
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if self.trigger.trigger(&data[TRIGGER]) {
+            self.out = data[SIGNAL].as_float().unwrap_or_default();
         }
 
         Default::default()
@@ -39,6 +44,10 @@ impl Node for Latch {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::stateful("trigger", &self.trigger),
@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{Input, InputData, Node, NodeEvent},
+    Output, ValueKind,
+};
+
+crate::ports! {
+    SIG = "sig",
+}
+
+/// Adds its input to the main mix, played instead of the output toggled
+/// with Play once the patch has one.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MainOut;
+
+#[typetag::serde]
+impl Node for MainOut {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sig = data[SIG].as_float().unwrap_or_default();
+        data.bus().add(0, sig);
+
+        Default::default()
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::new("sig", ValueKind::Float)]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        Vec::new()
+    }
+}
+
+pub fn main_out() -> Box<dyn Node> {
+    Box::new(MainOut)
+}
@@ -0,0 +1,128 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute::{
+        node::{ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
+        Output, Value, ValueKind,
+    },
+    serde_atomic_enum,
+    util::enum_combo_box,
+};
+
+crate::ports! {
+    SIG = "sig",
+}
+
+#[atomic_enum::atomic_enum]
+#[derive(PartialEq, Eq, derive_more::Display, strum::EnumIter)]
+pub enum MapTy {
+    Abs,
+    Negate,
+    Square,
+    #[display(fmt = "Square Root")]
+    SquareRoot,
+    Tanh,
+    Clip,
+}
+
+serde_atomic_enum!(AtomicMapTy);
+
+impl Default for MapTy {
+    fn default() -> Self {
+        MapTy::Abs
+    }
+}
+
+impl MapTy {
+    fn apply(self, x: f32) -> f32 {
+        match self {
+            MapTy::Abs => x.abs(),
+            MapTy::Negate => -x,
+            MapTy::Square => x * x,
+            MapTy::SquareRoot => x.abs().sqrt(),
+            MapTy::Tanh => x.tanh(),
+            MapTy::Clip => x.clamp(-1.0, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MapConfig {
+    ty: AtomicMapTy,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for MapConfig {
+    fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn std::any::Any) {
+        let mut ty = self.ty.load(Ordering::Acquire);
+
+        let changed = enum_combo_box(ui, &mut ty);
+
+        self.ty.store(ty, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
+/// Function applied to every channel of an array.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Map {
+    conf: Arc<MapConfig>,
+    #[serde(skip)]
+    ty: MapTy,
+    #[serde(skip)]
+    out: Vec<f32>,
+}
+
+#[typetag::serde]
+impl Node for Map {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        self.out.clear();
+        if let Value::FloatArray(channels) = &data[SIG] {
+            self.out.extend(channels.iter().map(|x| self.ty.apply(*x)));
+        }
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.conf.dirty.take() {
+            self.ty = self.conf.ty.load(Ordering::Relaxed);
+        }
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0].set_float_array(self.out.iter().copied())
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.conf) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::new("sig", ValueKind::FloatArray)]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![Output::new("", ValueKind::FloatArray)]
+    }
+}
+
+pub fn map() -> Box<dyn Node> {
+    Box::new(Map {
+        conf: Arc::new(MapConfig {
+            ty: AtomicMapTy::new(MapTy::Abs),
+            dirty: ConfigDirty::new(),
+        }),
+        ty: MapTy::Abs,
+        out: Vec::new(),
+    })
+}
@@ -0,0 +1,151 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute::{
+        node::{
+            inputs::real::RealInput, ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
+        },
+        Value, ValueKind,
+    },
+    serde_atomic_enum,
+    util::enum_combo_box,
+};
+
+crate::ports! {
+    A = "a",
+    B = "b",
+}
+
+#[atomic_enum::atomic_enum]
+#[derive(PartialEq, Eq, derive_more::Display, strum::EnumIter)]
+pub enum MathTy {
+    Min,
+    Max,
+    #[display(fmt = "Abs / Full Rectify")]
+    Abs,
+    #[display(fmt = "Half Rectify")]
+    HalfRectify,
+    Floor,
+    Round,
+    Modulo,
+    Sign,
+}
+
+serde_atomic_enum!(AtomicMathTy);
+
+impl MathTy {
+    fn binary(self) -> bool {
+        matches!(self, MathTy::Min | MathTy::Max | MathTy::Modulo)
+    }
+
+    fn apply(self, a: f32, b: f32) -> f32 {
+        match self {
+            MathTy::Min => a.min(b),
+            MathTy::Max => a.max(b),
+            MathTy::Abs => a.abs(),
+            MathTy::HalfRectify => a.max(0.0),
+            MathTy::Floor => a.floor(),
+            MathTy::Round => a.round(),
+            // Always in 0..b, also for negative `a`
+            MathTy::Modulo if b != 0.0 => a.rem_euclid(b),
+            MathTy::Modulo => 0.0,
+            MathTy::Sign if a == 0.0 => 0.0,
+            MathTy::Sign => a.signum(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MathConfig {
+    ty: AtomicMathTy,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for MathConfig {
+    fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn std::any::Any) {
+        let mut ty = self.ty.load(Ordering::Acquire);
+
+        let changed = enum_combo_box(ui, &mut ty);
+
+        self.ty.store(ty, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
+/// Small math functions of one or two inputs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Math {
+    conf: Arc<MathConfig>,
+    b: Arc<RealInput>,
+    #[serde(skip)]
+    ty: Option<MathTy>,
+    out: f32,
+}
+
+#[typetag::serde]
+impl Node for Math {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let ty = self.ty.unwrap_or(MathTy::Min);
+        let a = data[A].as_float().unwrap_or_default();
+        let b = if ty.binary() {
+            self.b.get_f32(&data[B])
+        } else {
+            0.0
+        };
+
+        self.out = ty.apply(a, b);
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.conf.dirty.take() {
+            return Default::default();
+        }
+
+        let ty = self.conf.ty.load(Ordering::Relaxed);
+        let was_binary = self.ty.map(MathTy::binary);
+        self.ty = Some(ty);
+        if was_binary == Some(ty.binary()) {
+            return Default::default();
+        }
+
+        vec![NodeEvent::RecalcInputs(self.inputs())]
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.conf) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        let mut inputs = vec![Input::new("a", ValueKind::Float)];
+        if self.conf.ty.load(Ordering::Relaxed).binary() {
+            inputs.push(Input::stateful("b", &self.b));
+        }
+
+        inputs
+    }
+}
+
+pub fn math() -> Box<dyn Node> {
+    Box::new(Math {
+        conf: Arc::new(MathConfig {
+            ty: AtomicMathTy::new(MathTy::Min),
+            dirty: ConfigDirty::new(),
+        }),
+        b: Arc::new(RealInput::new(0.0)),
+        ty: None,
+        out: 0.0,
+    })
+}
@@ -1,7 +1,7 @@
 use std::{
     any::Any,
     sync::{
-        atomic::{AtomicU32, Ordering},
+        atomic::{AtomicBool, AtomicU32, Ordering},
         Arc,
     },
 };
@@ -10,14 +10,20 @@ use eframe::egui::DragValue;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::slider::SliderInput, Input, Node, NodeConfig, NodeEvent},
-    Value,
+    node::{
+        inputs::slider::SliderInput, ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
+    },
+    Output, Value, ValueKind,
 };
 
 #[derive(Debug, Serialize, Deserialize)]
 struct MixConfig {
     new_ins: AtomicU32,
     ins: AtomicU32,
+    #[serde(default)]
+    per_channel: AtomicBool,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl NodeConfig for MixConfig {
@@ -32,9 +38,20 @@ impl NodeConfig for MixConfig {
                 .lost_focus()
             {
                 self.ins.store(ins, Ordering::Release);
+                self.dirty.mark();
             }
         });
 
+        let mut per_channel = self.per_channel.load(Ordering::Acquire);
+        if ui
+            .checkbox(&mut per_channel, "Per channel")
+            .on_hover_text("Mix array inputs channel by channel")
+            .changed()
+        {
+            self.per_channel.store(per_channel, Ordering::Release);
+            self.dirty.mark();
+        }
+
         self.new_ins.store(ins, Ordering::Release);
     }
 }
@@ -45,6 +62,10 @@ pub struct Mix {
     weights: Vec<Arc<SliderInput>>,
     ins: u32,
     out: f32,
+    #[serde(default)]
+    per_channel: bool,
+    #[serde(skip)]
+    outs: Vec<f32>,
 }
 
 impl Mix {
@@ -53,19 +74,49 @@ impl Mix {
             config: Arc::new(MixConfig {
                 new_ins: AtomicU32::new(ins),
                 ins: AtomicU32::new(ins),
+                per_channel: AtomicBool::new(false),
+                dirty: ConfigDirty::new(),
             }),
             weights: (0..ins)
                 .map(|_| Arc::new(SliderInput::new(1.0, 0.0, 1.0).show_connected(true)))
                 .collect(),
             ins,
             out: 0.0,
+            per_channel: false,
+            outs: Vec::new(),
+        }
+    }
+
+    fn kind(&self) -> ValueKind {
+        if self.per_channel {
+            ValueKind::FloatArray
+        } else {
+            ValueKind::Float
         }
     }
 }
 
 #[typetag::serde]
 impl Node for Mix {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if self.per_channel {
+            let channels = data.iter().map(Value::channels).max().unwrap_or_default();
+            let weights = &self.weights;
+            self.outs.clear();
+            self.outs.extend((0..channels).map(|k| {
+                data.iter()
+                    .zip(weights.iter())
+                    .map(|(sample, weight)| {
+                        sample.channel(k).as_float().unwrap_or(0.0)
+                            * weight.as_f32(&Value::Disconnected)
+                    })
+                    .sum::<f32>()
+                    / weights.len() as f32
+            }));
+
+            return Default::default();
+        }
+
         self.out = data
             .iter()
             .zip(self.weights.iter())
@@ -75,9 +126,20 @@ impl Node for Mix {
             .sum::<f32>()
             / self.weights.len() as f32;
 
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
         let new_ins = self.config.ins.load(Ordering::Relaxed);
-        let emit_ev = new_ins != self.ins;
+        let per_channel = self.config.per_channel.load(Ordering::Relaxed);
+        let kind_changed = per_channel != self.per_channel;
+        let emit_ev = new_ins != self.ins || kind_changed;
         self.ins = new_ins;
+        self.per_channel = per_channel;
 
         if self.ins as usize != self.weights.len() {
             self.weights.resize_with(self.ins as usize, || {
@@ -85,15 +147,23 @@ impl Node for Mix {
             });
         }
 
+        let mut events = Vec::new();
+        if kind_changed {
+            events.push(NodeEvent::RecalcOutputs(self.output()));
+        }
         if emit_ev {
-            vec![NodeEvent::RecalcInputs(self.inputs())]
-        } else {
-            Default::default()
+            events.push(NodeEvent::RecalcInputs(self.inputs()));
         }
+
+        events
     }
 
     fn read(&self, out: &mut [Value]) {
-        out[0] = Value::Float(self.out)
+        if self.per_channel {
+            out[0].set_float_array(self.outs.iter().copied());
+        } else {
+            out[0] = Value::Float(self.out);
+        }
     }
 
     fn config(&self) -> Option<Arc<dyn NodeConfig>> {
@@ -102,9 +172,16 @@ impl Node for Mix {
 
     fn inputs(&self) -> Vec<Input> {
         (0..self.ins)
-            .map(|i| Input::stateful(format!("sig {i}"), &self.weights[i as usize]))
+            .map(|i| {
+                Input::stateful(format!("sig {i}"), &self.weights[i as usize])
+                    .with_kind(self.kind())
+            })
             .collect()
     }
+
+    fn output(&self) -> Vec<Output> {
+        vec![Output::new("", self.kind())]
+    }
 }
 
 pub fn mix() -> Box<dyn Node> {
@@ -3,10 +3,16 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::slider::SliderInput, Input, Node, NodeEvent},
+    node::{inputs::slider::SliderInput, Input, InputData, Node, NodeEvent},
     Value, ValueKind,
 };
 
+crate::ports! {
+    SIG_0 = "sig 0",
+    SIG_1 = "sig 1",
+    MIX2 = "mix2",
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Mix2 {
     ratio: Arc<SliderInput>,
@@ -24,10 +30,10 @@ impl Mix2 {
 
 #[typetag::serde]
 impl Node for Mix2 {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let sig0 = data[0].as_float().unwrap_or_default();
-        let sig1 = data[1].as_float().unwrap_or_default();
-        let ratio = self.ratio.as_f32(&data[2]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sig0 = data[SIG_0].as_float().unwrap_or_default();
+        let sig1 = data[SIG_1].as_float().unwrap_or_default();
+        let ratio = self.ratio.as_f32(&data[MIX2]);
 
         self.out = sig0 * ratio + sig1 * (1.0 - ratio);
 
@@ -38,6 +44,10 @@ impl Node for Mix2 {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::new("sig 0", ValueKind::Float),
@@ -1,21 +1,44 @@
 pub mod add;
+pub mod additive;
 pub mod adsr;
 pub mod any;
+pub mod audio_in;
+pub mod aux_out;
 pub mod bpm;
 pub mod constant;
 pub mod convert;
+pub mod crossfade;
 pub mod curve;
 pub mod delay;
 pub mod difference;
 pub mod gain;
 pub mod gate;
+pub mod gesture;
+pub mod hold;
+pub mod jack_out;
+pub mod join;
 pub mod latch;
+pub mod main_out;
+pub mod map;
+pub mod math;
 pub mod mix;
 pub mod mix2;
 pub mod on_beat;
 pub mod oscillator;
+pub mod probability;
 pub mod pulse;
+pub mod random_route;
+pub mod sample_hold;
+pub mod sampler;
+pub mod script;
+pub mod split;
+pub mod spread;
+pub mod sum;
+pub mod switch;
 pub mod transform;
+pub mod transport;
+pub mod tuner;
+pub mod wireless;
 
 use delay::ResizeStrategy;
 
@@ -27,8 +50,19 @@ impl NodeList for Basic {
     fn all(&self) -> Vec<(Box<dyn Node>, String, Vec<String>)> {
         vec![
             (add::add(), "Add".into(), vec!["Math".into()]),
+            (
+                additive::additive(),
+                "Additive".into(),
+                vec!["Source".into()],
+            ),
             (adsr::adsr(), "Adsr".into(), vec!["Envelope".into()]),
             (any::any(), "Any".into(), vec!["Control".into()]),
+            (
+                audio_in::audio_in(),
+                "Audio In".into(),
+                vec!["Source".into()],
+            ),
+            (aux_out::aux_out(), "Aux Out".into(), vec!["Output".into()]),
             (bpm::bpm(), "BPM".into(), vec!["Control".into()]),
             (
                 constant::constant(),
@@ -36,6 +70,11 @@ impl NodeList for Basic {
                 vec!["Source".into()],
             ),
             (convert::convert(), "Convert".into(), vec!["Math".into()]),
+            (
+                crossfade::crossfade(),
+                "Crossfade".into(),
+                vec!["Math".into()],
+            ),
             (curve::curve(), "Curve".into(), vec!["Source".into()]),
             (
                 delay::delay(ResizeStrategy::ZeroFillDrain),
@@ -56,7 +95,22 @@ impl NodeList for Basic {
             ),
             (gain::gain(), "Gain".into(), vec!["Effect".into()]),
             (gate::gate(), "Gate".into(), vec!["Control".into()]),
+            (gesture::gesture(), "Gesture".into(), vec!["Control".into()]),
+            (hold::hold(), "Hold".into(), vec!["Effect".into()]),
+            (
+                jack_out::jack_out(),
+                "Jack Out".into(),
+                vec!["Output".into()],
+            ),
+            (join::join(), "Join".into(), vec!["Channels".into()]),
             (latch::latch(), "Latch".into(), vec!["Effect".into()]),
+            (
+                main_out::main_out(),
+                "Main Out".into(),
+                vec!["Output".into()],
+            ),
+            (map::map(), "Map".into(), vec!["Channels".into()]),
+            (math::math(), "Math Utility".into(), vec!["Math".into()]),
             (mix::mix(), "Mix".into(), vec!["Math".into()]),
             (mix2::mix2(), "Mix 2".into(), vec!["Math".into()]),
             (
@@ -69,12 +123,49 @@ impl NodeList for Basic {
                 "Oscillator".into(),
                 vec!["Source".into()],
             ),
+            (
+                probability::probability_gate(),
+                "Probability Gate".into(),
+                vec!["Control".into()],
+            ),
             (pulse::pulse(), "Pulse".into(), vec!["Control".into()]),
+            (
+                wireless::receive(),
+                "Receive".into(),
+                vec!["Routing".into()],
+            ),
+            (
+                random_route::random_route(),
+                "Random Route".into(),
+                vec!["Control".into()],
+            ),
+            (
+                sample_hold::sample_hold(),
+                "S&H + Slew".into(),
+                vec!["Control".into(), "Effect".into()],
+            ),
+            (sampler::sampler(), "Sampler".into(), vec!["Source".into()]),
+            (
+                script::script(),
+                "Script".into(),
+                vec!["Control".into(), "Math".into()],
+            ),
+            (wireless::send(), "Send".into(), vec!["Routing".into()]),
+            (split::split(), "Split".into(), vec!["Channels".into()]),
+            (spread::spread(), "Spread".into(), vec!["Channels".into()]),
+            (sum::sum(), "Sum".into(), vec!["Channels".into()]),
+            (switch::switch(), "Switch".into(), vec!["Control".into()]),
             (
                 transform::transform(),
                 "Transform".into(),
                 vec!["Effect".into(), "Math".into()],
             ),
+            (
+                transport::transport(),
+                "Transport".into(),
+                vec!["Control".into(), "Source".into()],
+            ),
+            (tuner::tuner(), "Tuner".into(), vec!["Analysis".into()]),
         ]
     }
 }
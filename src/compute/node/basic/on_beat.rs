@@ -3,10 +3,14 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::beat::BeatInput, Input, Node, NodeEvent},
+    node::{inputs::beat::BeatInput, Input, InputData, Node, NodeEvent},
     Value,
 };
 
+crate::ports! {
+    BEAT = "beat",
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct OnBeat {
     beat: Arc<BeatInput>,
@@ -15,8 +19,8 @@ pub struct OnBeat {
 
 #[typetag::serde]
 impl Node for OnBeat {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        self.out = self.beat.process(&data[0]).map(|_| 1.0).unwrap_or(0.0);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        self.out = self.beat.process(&data[BEAT]).map(|_| 1.0).unwrap_or(0.0);
 
         Default::default()
     }
@@ -25,6 +29,10 @@ impl Node for OnBeat {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![Input::stateful("beat", &self.beat)]
     }
@@ -16,20 +16,36 @@ use crate::{
                 angle::AngleInput,
                 beat::{BeatInput, BeatResponse},
                 freq::FreqInput,
+                percentage::PercentageInput,
                 real::RealInput,
+                trigger::{TriggerInput, TriggerMode},
                 wave::WaveInput,
             },
-            Input, Node, NodeConfig, NodeEvent,
+            ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
         },
         Value,
     },
     wave::WaveScale,
 };
 
+crate::ports! {
+    F = "f",
+    BEAT = "beat",
+    SHAPE = "shape",
+    PW = "pw",
+    PHASE = "phase",
+    PM = "pm",
+    SYNC = "sync",
+    MIN = "min",
+    MAX = "max",
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct OscillatorConfig {
     manual_range: AtomicBool,
     bpm_sync: AtomicBool,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl NodeConfig for OscillatorConfig {
@@ -37,11 +53,12 @@ impl NodeConfig for OscillatorConfig {
         let mut manual_range = self.manual_range.load(Ordering::Acquire);
         let mut bpm_sync = self.bpm_sync.load(Ordering::Acquire);
 
-        ui.checkbox(&mut manual_range, "Manual range");
-        ui.checkbox(&mut bpm_sync, "BPM Sync");
+        let changed = ui.checkbox(&mut manual_range, "Manual range").changed()
+            | ui.checkbox(&mut bpm_sync, "BPM Sync").changed();
 
         self.manual_range.store(manual_range, Ordering::Release);
         self.bpm_sync.store(bpm_sync, Ordering::Release);
+        self.dirty.mark_if(changed);
     }
 }
 
@@ -52,6 +69,12 @@ struct Oscillator {
     beat: Arc<BeatInput>,
     phase: Arc<AngleInput>,
     wave: Arc<WaveInput>,
+    #[serde(default = "default_pw")]
+    pw: Arc<PercentageInput>,
+    #[serde(default = "default_pm")]
+    pm: Arc<RealInput>,
+    #[serde(default = "default_sync")]
+    sync: Arc<TriggerInput>,
     min: Arc<RealInput>,
     max: Arc<RealInput>,
     t: f32,
@@ -60,6 +83,21 @@ struct Oscillator {
     manual_range: bool,
     bpm_sync: bool,
     hz: f32,
+    // Patches saved before an input was added get it on the first sync
+    #[serde(skip)]
+    synced: bool,
+}
+
+fn default_pw() -> Arc<PercentageInput> {
+    Arc::new(PercentageInput::new(50.0))
+}
+
+fn default_pm() -> Arc<RealInput> {
+    Arc::new(RealInput::new(0.0))
+}
+
+fn default_sync() -> Arc<TriggerInput> {
+    Arc::new(TriggerInput::new(TriggerMode::Up, 0.0))
 }
 
 impl Oscillator {
@@ -70,35 +108,57 @@ impl Oscillator {
 
 #[typetag::serde]
 impl Node for Oscillator {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
         if self.bpm_sync {
-            if let Some(BeatResponse { period_secs }) = self.beat.process(&data[0]) {
+            if let Some(BeatResponse { period_secs }) = self.beat.process(&data[BEAT]) {
                 self.hz = 1.0 / period_secs;
                 self.t = 0.0;
             }
         } else {
-            self.hz = self.freq.get_f32(&data[0]);
+            self.hz = self.freq.get_f32(&data[F]);
         }
 
-        let wave = self.wave.as_f32(&data[1]);
+        let wave = self.wave.as_f32(&data[SHAPE]);
+        let pw = self.pw.get_f32(&data[PW]).clamp(0.01, 0.99);
 
-        let phase_0_2 = self.phase.radians(&data[2]) / PI;
+        // Phase modulation is in periods, a signal of 1 shifts the wave by
+        // a whole cycle
+        let phase_0_2 = self.phase.radians(&data[PHASE]) / PI + self.pm.get_f32(&data[PM]) * 2.0;
 
-        let min = self.min.get_f32(data.get(3).unwrap_or(&Value::Float(-1.0)));
-        let max = self.max.get_f32(data.get(4).unwrap_or(&Value::Float(1.0)));
+        // Hard sync restarts the cycle, from wherever the phase sets it
+        if self.sync.trigger(&data[SYNC]) {
+            self.t = 0.0;
+        }
+
+        let min = self
+            .min
+            .get_f32(data.get(MIN).unwrap_or(&Value::Float(-1.0)));
+        let max = self
+            .max
+            .get_f32(data.get(MAX).unwrap_or(&Value::Float(1.0)));
 
         let step = self.hz * Self::hz_to_dt() * 2.0;
         self.t = (self.t + step) % 2.0;
 
-        let adjusted_t = (self.t + phase_0_2) % 2.0;
+        let adjusted_t = (self.t + phase_0_2).rem_euclid(2.0);
 
-        self.out = (WaveScale::new(wave.clamp(0.0, 0.99)).sample(adjusted_t) / 2.0 + 0.5)
+        self.out = (WaveScale::new(wave.clamp(0.0, 0.99)).sample(adjusted_t, step, pw) / 2.0 + 0.5)
             * (max - min)
             + min;
 
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
         let manual_range = self.config.manual_range.load(Ordering::Relaxed);
         let bpm_sync = self.config.bpm_sync.load(Ordering::Relaxed);
-        let emit_change = manual_range != self.manual_range || bpm_sync != self.bpm_sync;
+        let emit_change =
+            manual_range != self.manual_range || bpm_sync != self.bpm_sync || !self.synced;
+        self.synced = true;
         self.manual_range = manual_range;
         self.bpm_sync = bpm_sync;
 
@@ -117,6 +177,10 @@ impl Node for Oscillator {
         Some(Arc::clone(&self.config) as Arc<dyn NodeConfig>)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         let mut inputs = Vec::new();
 
@@ -127,7 +191,10 @@ impl Node for Oscillator {
         }
 
         inputs.push(Input::stateful("shape", &self.wave));
+        inputs.push(Input::stateful("pw", &self.pw));
         inputs.push(Input::stateful("phase", &self.phase));
+        inputs.push(Input::stateful("pm", &self.pm));
+        inputs.push(Input::stateful("sync", &self.sync));
 
         if self.manual_range {
             inputs.extend([
@@ -145,10 +212,14 @@ pub fn oscillator() -> Box<dyn Node> {
         config: Arc::new(OscillatorConfig {
             manual_range: AtomicBool::new(false),
             bpm_sync: AtomicBool::new(false),
+            dirty: ConfigDirty::new(),
         }),
         freq: Arc::new(FreqInput::new(440.0)),
         beat: Arc::new(BeatInput::new(false)),
         wave: Arc::new(WaveInput::new(0.0)),
+        pw: default_pw(),
+        pm: default_pm(),
+        sync: default_sync(),
         phase: Arc::new(AngleInput::new(0.0)),
         min: Arc::new(RealInput::new(-1.0)),
         max: Arc::new(RealInput::new(1.0)),
@@ -157,5 +228,6 @@ pub fn oscillator() -> Box<dyn Node> {
         manual_range: false,
         bpm_sync: false,
         hz: 440.0,
+        synced: false,
     })
 }
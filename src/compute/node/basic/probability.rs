@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use rand::Rng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    deterministic,
+    node::{
+        inputs::{
+            percentage::PercentageInput,
+            trigger::{TriggerInput, TriggerMode},
+        },
+        Input, InputData, Node, NodeEvent,
+    },
+    Value,
+};
+
+crate::ports! {
+    TRIGGER = "trigger",
+    CHANCE = "chance",
+}
+
+/// Passes each trigger with a chance, a gate that passed stays open for as
+/// long as it's held.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProbabilityGate {
+    trigger: Arc<TriggerInput>,
+    chance: Arc<PercentageInput>,
+    rng: ChaCha12Rng,
+    pass: bool,
+    out: f32,
+}
+
+#[typetag::serde]
+impl Node for ProbabilityGate {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let trigger = self.trigger.trigger(&data[TRIGGER]);
+        if trigger {
+            let chance = self.chance.get_f32(&data[CHANCE]);
+            self.pass = self.rng.gen::<f32>() < chance;
+        }
+
+        // Beats and notes have no level to pass, they pass as a tick
+        self.out = if self.pass {
+            data[TRIGGER]
+                .as_float()
+                .unwrap_or(if trigger { 1.0 } else { 0.0 })
+        } else {
+            0.0
+        };
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::stateful("trigger", &self.trigger),
+            Input::stateful("chance", &self.chance),
+        ]
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = deterministic::seeded(seed);
+    }
+}
+
+pub fn probability_gate() -> Box<dyn Node> {
+    Box::new(ProbabilityGate {
+        trigger: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
+        chance: Arc::new(PercentageInput::new(50.0)),
+        rng: deterministic::rng(),
+        pass: false,
+        out: 0.0,
+    })
+}
@@ -9,11 +9,17 @@ use crate::compute::{
             time::TimeInput,
             trigger::{TriggerInput, TriggerMode},
         },
-        Input, Node, NodeEvent,
+        Input, InputData, Node, NodeEvent,
     },
     Value,
 };
 
+crate::ports! {
+    TRIGGER = "trigger",
+    LENGTH = "length",
+    VALUE = "value",
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 enum PulseState {
     Idle,
@@ -60,13 +66,13 @@ impl Pulse {
 
 #[typetag::serde]
 impl Node for Pulse {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        if self.trigger.trigger(&data[0]) {
-            self.state = PulseState::Up(self.time.get_samples(&data[1]));
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if self.trigger.trigger(&data[TRIGGER]) {
+            self.state = PulseState::Up(self.time.get_samples(&data[LENGTH]));
         }
 
         let gain = self.state.step();
-        self.out = gain * self.value.get_f32(&data[2]);
+        self.out = gain * self.value.get_f32(&data[VALUE]);
 
         Default::default()
     }
@@ -75,6 +81,10 @@ impl Node for Pulse {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::stateful("trigger", &self.trigger),
@@ -0,0 +1,167 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use eframe::egui::DragValue;
+use rand::{distributions::WeightedIndex, prelude::Distribution};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    deterministic,
+    node::{
+        inputs::{
+            real::RealInput,
+            trigger::{TriggerInput, TriggerMode},
+        },
+        ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
+    },
+    Output, Value, ValueKind,
+};
+
+crate::ports! {
+    TRIGGER = "trigger",
+}
+
+const MAX_OUTS: usize = 16;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RandomRouteConfig {
+    outs: AtomicUsize,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for RandomRouteConfig {
+    fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn Any) {
+        let mut outs = self.outs.load(Ordering::Acquire);
+
+        ui.horizontal(|ui| {
+            ui.label("outputs");
+            if ui
+                .add(DragValue::new(&mut outs).range(2..=MAX_OUTS))
+                .changed()
+            {
+                self.outs.store(outs, Ordering::Release);
+                self.dirty.mark();
+            }
+        });
+    }
+}
+
+fn weight() -> Arc<RealInput> {
+    Arc::new(RealInput::new(1.0))
+}
+
+/// Sends each trigger to one of its outputs, picked by the weights.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RandomRoute {
+    config: Arc<RandomRouteConfig>,
+    trigger: Arc<TriggerInput>,
+    weights: Vec<Arc<RealInput>>,
+    rng: ChaCha12Rng,
+    route: Option<usize>,
+    outs: Vec<f32>,
+}
+
+#[typetag::serde]
+impl Node for RandomRoute {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let trigger = self.trigger.trigger(&data[TRIGGER]);
+        if trigger {
+            let weights = self
+                .weights
+                .iter()
+                .enumerate()
+                // Declared right after the trigger
+                .map(|(k, weight)| weight.get_f32(&data[1 + k]).max(0.0));
+            // All weights at zero route nowhere
+            self.route = WeightedIndex::new(weights)
+                .ok()
+                .map(|index| index.sample(&mut self.rng));
+        }
+
+        // Beats and notes have no level to pass, they pass as a tick
+        let value = data[TRIGGER]
+            .as_float()
+            .unwrap_or(if trigger { 1.0 } else { 0.0 });
+        for (k, out) in self.outs.iter_mut().enumerate() {
+            *out = if self.route == Some(k) { value } else { 0.0 };
+        }
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
+        let outs = self.config.outs.load(Ordering::Relaxed);
+        if outs == self.outs.len() {
+            return Default::default();
+        }
+        self.outs.resize(outs, 0.0);
+        self.weights.resize_with(outs, weight);
+        self.route = self.route.filter(|route| *route < outs);
+
+        vec![
+            NodeEvent::RecalcInputs(self.inputs()),
+            NodeEvent::RecalcOutputs(self.output()),
+        ]
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        for (out, value) in out.iter_mut().zip(&self.outs) {
+            *out = Value::Float(*value);
+        }
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        let mut inputs = vec![Input::stateful("trigger", &self.trigger)];
+        inputs.extend(
+            self.weights
+                .iter()
+                .enumerate()
+                .map(|(k, weight)| Input::stateful(format!("w {k}"), weight)),
+        );
+
+        inputs
+    }
+
+    fn output(&self) -> Vec<Output> {
+        (0..self.outs.len())
+            .map(|k| Output::new(format!("out {k}"), ValueKind::Float))
+            .collect()
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = deterministic::seeded(seed);
+    }
+}
+
+pub fn random_route() -> Box<dyn Node> {
+    Box::new(RandomRoute {
+        config: Arc::new(RandomRouteConfig {
+            outs: AtomicUsize::new(2),
+            dirty: ConfigDirty::new(),
+        }),
+        trigger: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
+        weights: vec![weight(), weight()],
+        rng: deterministic::rng(),
+        route: None,
+        outs: vec![0.0; 2],
+    })
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{
+        inputs::{
+            time::TimeInput,
+            trigger::{TriggerInput, TriggerMode},
+        },
+        Input, InputData, Node, NodeEvent,
+    },
+    Output, Value, ValueKind,
+};
+
+crate::ports! {
+    CLOCK = "clock",
+    SIGNAL = "signal",
+    RISE = "rise",
+    FALL = "fall",
+}
+
+/// Samples the signal on each clock trigger and slews towards the held
+/// value. Rise and fall are the times needed to move by 1.0.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SampleHold {
+    clock: Arc<TriggerInput>,
+    rise: Arc<TimeInput>,
+    fall: Arc<TimeInput>,
+    held: f32,
+    out: f32,
+}
+
+impl SampleHold {
+    pub fn new() -> Self {
+        SampleHold {
+            clock: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
+            rise: Arc::new(TimeInput::from_ms(0.0)),
+            fall: Arc::new(TimeInput::from_ms(0.0)),
+            held: 0.0,
+            out: 0.0,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Node for SampleHold {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if self.clock.trigger(&data[CLOCK]) {
+            self.held = data[SIGNAL].as_float().unwrap_or_default();
+        }
+
+        let diff = self.held - self.out;
+        let time = if diff > 0.0 {
+            self.rise.get_samples(&data[RISE])
+        } else {
+            self.fall.get_samples(&data[FALL])
+        };
+        let max_step = 1.0 / time.max(1.0);
+
+        self.out += diff.clamp(-max_step, max_step);
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out);
+        out[1] = Value::Float(self.held);
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::stateful("clock", &self.clock),
+            Input::new("signal", ValueKind::Float),
+            Input::stateful("rise", &self.rise),
+            Input::stateful("fall", &self.fall),
+        ]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![
+            Output::new("out", ValueKind::Float),
+            Output::new("held", ValueKind::Float),
+        ]
+    }
+}
+
+pub fn sample_hold() -> Box<dyn Node> {
+    Box::new(SampleHold::new())
+}
@@ -0,0 +1,297 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use atomic_float::AtomicF32;
+use eframe::egui::{self, DragValue};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute::{
+        node::{
+            inputs::trigger::{TriggerInput, TriggerMode},
+            ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
+        },
+        Value, ValueKind,
+    },
+    util::load_sample,
+};
+
+crate::ports! {
+    TRIGGER = "trigger",
+    REC = "rec",
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SamplerConfig {
+    #[serde(with = "crate::util::serde_mutex")]
+    sample: Mutex<Vec<f32>>,
+    looped: AtomicBool,
+    // Seconds recorded from `rec` when armed
+    rec_secs: AtomicF32,
+    // Set by the editor, cleared by the runtime once recording starts
+    #[serde(skip)]
+    armed: AtomicBool,
+    // Written by the runtime, only displayed here
+    #[serde(skip)]
+    recording: AtomicBool,
+    #[serde(skip)]
+    error: Mutex<Option<String>>,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl SamplerConfig {
+    pub fn new(looped: bool, rec_secs: f32) -> Self {
+        SamplerConfig {
+            sample: Mutex::new(Vec::new()),
+            looped: AtomicBool::new(looped),
+            rec_secs: AtomicF32::new(rec_secs),
+            armed: AtomicBool::new(false),
+            recording: AtomicBool::new(false),
+            error: Mutex::new(None),
+            dirty: ConfigDirty::new(),
+        }
+    }
+
+    /// Records `rec` from the next trigger on, or right away while the
+    /// trigger is disconnected, replacing the sample.
+    pub fn arm(&self) {
+        self.armed.store(true, Ordering::Release);
+        self.dirty.mark();
+    }
+
+    /// Whether a recording is armed or running.
+    pub fn busy(&self) -> bool {
+        self.armed.load(Ordering::Acquire) || self.recording.load(Ordering::Acquire)
+    }
+}
+
+impl NodeConfig for SamplerConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mut looped = self.looped.load(Ordering::Acquire);
+        let mut rec_secs = self.rec_secs.load(Ordering::Acquire);
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            let len = self.sample.lock().unwrap().len();
+            ui.label(format!("{:.2} s", len as f32 / 44100.0));
+
+            if ui.button("Open").clicked() {
+                let picked = rfd::FileDialog::new()
+                    .add_filter("WAV", &["wav"])
+                    .pick_file();
+                if let Some(picked) = picked {
+                    match load_sample(&picked) {
+                        Ok((sample, rate)) => {
+                            *self.sample.lock().unwrap() = resample(&sample, rate);
+                            *self.error.lock().unwrap() = None;
+                            changed = true;
+                        }
+                        Err(e) => {
+                            *self.error.lock().unwrap() =
+                                Some(format!("Failed to load {}: {e}", picked.display()));
+                        }
+                    }
+                }
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let label = if self.recording.load(Ordering::Relaxed) {
+                "⏺ Recording"
+            } else if self.armed.load(Ordering::Relaxed) {
+                "Armed"
+            } else {
+                "⏺ Record"
+            };
+            if ui
+                .button(label)
+                .on_hover_text("Record rec from the next trigger on")
+                .clicked()
+            {
+                self.arm();
+            }
+            changed |= ui
+                .add(
+                    DragValue::new(&mut rec_secs)
+                        .range(0.1..=60.0)
+                        .speed(0.1)
+                        .suffix(" s"),
+                )
+                .changed();
+        });
+        changed |= ui
+            .checkbox(&mut looped, "Loop")
+            .on_hover_text("Play over and over instead of once per trigger")
+            .changed();
+
+        if let Some(error) = &*self.error.lock().unwrap() {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        self.looped.store(looped, Ordering::Release);
+        self.rec_secs.store(rec_secs, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
+// Linear interpolation to the runtime rate
+fn resample(sample: &[f32], rate: u32) -> Vec<f32> {
+    if rate == 44100 || sample.is_empty() {
+        return sample.to_vec();
+    }
+
+    let step = rate as f32 / 44100.0;
+    let len = (sample.len() as f32 / step) as usize;
+    (0..len)
+        .map(|k| {
+            let pos = k as f32 * step;
+            let idx = pos as usize;
+            let a = sample[idx];
+            let b = sample.get(idx + 1).copied().unwrap_or(a);
+            a + (b - a) * pos.fract()
+        })
+        .collect()
+}
+
+/// Plays a sample once per trigger, or looped. `rec` is recorded into it
+/// when armed, which is how frozen nodes are rendered.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Sampler {
+    config: Arc<SamplerConfig>,
+    trigger: Arc<TriggerInput>,
+    #[serde(skip)]
+    sample: Arc<Vec<f32>>,
+    #[serde(skip)]
+    looped: bool,
+    #[serde(skip)]
+    pos: Option<usize>,
+    // Samples recorded so far and the length to record
+    #[serde(skip)]
+    rec: Option<(Vec<f32>, usize)>,
+    #[serde(skip)]
+    armed: bool,
+    #[serde(skip)]
+    out: f32,
+}
+
+impl Sampler {
+    fn finish_recording(&mut self) {
+        let Some((recorded, _)) = self.rec.take() else {
+            return;
+        };
+
+        *self.config.sample.lock().unwrap() = recorded.clone();
+        self.sample = Arc::new(recorded);
+        self.config.recording.store(false, Ordering::Release);
+    }
+}
+
+#[typetag::serde]
+impl Node for Sampler {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let trigger = &data[TRIGGER];
+        let triggered = self.trigger.trigger(trigger);
+
+        if self.armed && (triggered || trigger.disconnected()) {
+            let len = (self.config.rec_secs.load(Ordering::Relaxed) * 44100.0) as usize;
+            self.rec = Some((Vec::with_capacity(len), len.max(1)));
+            self.armed = false;
+            self.config.armed.store(false, Ordering::Release);
+            self.config.recording.store(true, Ordering::Release);
+        }
+        if let Some((recorded, len)) = &mut self.rec {
+            recorded.push(data[REC].as_float().unwrap_or_default());
+            if recorded.len() >= *len {
+                self.finish_recording();
+                // Played from the start of the next trigger or loop
+                self.pos = self.looped.then_some(0);
+            }
+        }
+
+        if triggered {
+            self.pos = Some(0);
+        }
+
+        self.out = match self.pos {
+            Some(pos) if pos < self.sample.len() => {
+                self.pos = Some(pos + 1);
+                self.sample[pos]
+            }
+            Some(_) if self.looped && !self.sample.is_empty() => {
+                self.pos = Some(1);
+                self.sample[0]
+            }
+            _ => {
+                self.pos = None;
+                0.0
+            }
+        };
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out);
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
+        self.looped = self.config.looped.load(Ordering::Relaxed);
+        self.armed = self.config.armed.load(Ordering::Acquire);
+        if self.rec.is_none() {
+            self.sample = Arc::new(self.config.sample.lock().unwrap().clone());
+        }
+        if self.looped && self.pos.is_none() {
+            self.pos = Some(0);
+        }
+
+        Default::default()
+    }
+
+    fn panic(&mut self) {
+        self.pos = None;
+        self.out = 0.0;
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::stateful("trigger", &self.trigger),
+            Input::new("rec", ValueKind::Float),
+        ]
+    }
+}
+
+/// Sampler sharing `config`, triggered in `mode`.
+pub fn sampler_with(config: Arc<SamplerConfig>, mode: TriggerMode) -> Box<dyn Node> {
+    Box::new(Sampler {
+        config,
+        trigger: Arc::new(TriggerInput::new(mode, 0.5)),
+        sample: Arc::new(Vec::new()),
+        looped: false,
+        pos: None,
+        rec: None,
+        armed: false,
+        out: 0.0,
+    })
+}
+
+pub fn sampler() -> Box<dyn Node> {
+    sampler_with(Arc::new(SamplerConfig::new(false, 2.0)), TriggerMode::Up)
+}
@@ -0,0 +1,227 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use eframe::egui::{self, DragValue};
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute::{
+        node::{ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
+        Output, Value, ValueKind,
+    },
+    util::perlin::Perlin1D,
+};
+
+const INPUTS: [&str; 8] = ["in0", "in1", "in2", "in3", "in4", "in5", "in6", "in7"];
+const OUTPUTS: [&str; 4] = ["out0", "out1", "out2", "out3"];
+
+const DEFAULT_SOURCE: &str = "\
+// Runs once per sample. in0.. are the inputs and out0.. the outputs,
+// the outputs keep their values until assigned. `state` is a map kept
+// between samples, `sr` is the sample rate. clamp(x, lo, hi),
+// lerp(a, b, t) and noise(x) come built in.
+out0 = in0;
+";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ScriptConfig {
+    #[serde(with = "crate::util::serde_mutex")]
+    source: Mutex<String>,
+    inputs: AtomicUsize,
+    #[serde(skip)]
+    error: Mutex<Option<String>>,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl ScriptConfig {
+    fn set_error(&self, error: Option<String>) {
+        *self.error.lock().unwrap() = error;
+    }
+}
+
+impl NodeConfig for ScriptConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mut inputs = self.inputs.load(Ordering::Acquire);
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            ui.label("Inputs");
+            changed |= ui
+                .add(DragValue::new(&mut inputs).range(0..=INPUTS.len()))
+                .changed();
+        });
+
+        // Every edit is compiled right away, the previous version keeps
+        // running until the new one compiles
+        let mut source = self.source.lock().unwrap();
+        changed |= ui
+            .add(
+                egui::TextEdit::multiline(&mut *source)
+                    .code_editor()
+                    .desired_rows(8)
+                    .desired_width(320.0),
+            )
+            .changed();
+        drop(source);
+
+        if let Some(error) = &*self.error.lock().unwrap() {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        self.inputs.store(inputs, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
+fn engine() -> Arc<Engine> {
+    let mut engine = Engine::new();
+    // A runaway loop would stall the whole runtime
+    engine.set_max_operations(100_000);
+
+    // Numbers are taken as written, `clamp(x, 0, 1)` works as well as with
+    // float literals
+    engine.register_fn("clamp", |x: Dynamic, lo: Dynamic, hi: Dynamic| {
+        let (x, lo, hi) = (as_f32(&x), as_f32(&lo), as_f32(&hi));
+        x.max(lo).min(hi) as f64
+    });
+    engine.register_fn("lerp", |a: Dynamic, b: Dynamic, t: Dynamic| {
+        let (a, b, t) = (as_f32(&a), as_f32(&b), as_f32(&t));
+        (a + (b - a) * t) as f64
+    });
+    let perlin = Perlin1D::new();
+    engine.register_fn("noise", move |x: Dynamic| perlin.noise(as_f32(&x)) as f64);
+
+    Arc::new(engine)
+}
+
+fn as_f32(value: &Dynamic) -> f32 {
+    value
+        .as_float()
+        .map(|f| f as f32)
+        .or_else(|_| value.as_int().map(|i| i as f32))
+        .unwrap_or_default()
+}
+
+/// Node running a user script on every sample.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Script {
+    config: Arc<ScriptConfig>,
+    #[serde(skip, default = "engine")]
+    engine: Arc<Engine>,
+    #[serde(skip)]
+    ast: Option<AST>,
+    #[serde(skip)]
+    inputs: usize,
+    #[serde(skip)]
+    state: Map,
+    out: [f32; 4],
+}
+
+#[typetag::serde]
+impl Node for Script {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let Some(ast) = &self.ast else {
+            return Default::default();
+        };
+
+        let mut scope = Scope::new();
+        scope.push_constant("sr", 44100.0);
+        for (k, name) in INPUTS[..self.inputs].iter().enumerate() {
+            let value = data[k].as_float().unwrap_or_default();
+            scope.push(*name, value as f64);
+        }
+        for (name, value) in OUTPUTS.iter().zip(self.out) {
+            scope.push(*name, value as f64);
+        }
+        scope.push("state", std::mem::take(&mut self.state));
+
+        if let Err(e) = self.engine.run_ast_with_scope(&mut scope, ast) {
+            self.config.set_error(Some(e.to_string()));
+            // Drop the script, so one error isn't reported every sample
+            self.ast = None;
+            self.out = [0.0; 4];
+
+            return Default::default();
+        }
+
+        for (name, out) in OUTPUTS.iter().zip(&mut self.out) {
+            *out = scope.get(name).map(as_f32).unwrap_or_default();
+        }
+        self.state = scope.get_value("state").unwrap_or_default();
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
+        let source = self.config.source.lock().unwrap().clone();
+        match self.engine.compile(source) {
+            Ok(ast) => {
+                self.ast = Some(ast);
+                self.config.set_error(None);
+            }
+            Err(e) => self.config.set_error(Some(e.to_string())),
+        }
+
+        let inputs = self.config.inputs.load(Ordering::Relaxed);
+        if inputs == self.inputs {
+            return Default::default();
+        }
+
+        self.inputs = inputs;
+
+        vec![NodeEvent::RecalcInputs(self.inputs())]
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        for (out, value) in out.iter_mut().zip(self.out) {
+            *out = Value::Float(value);
+        }
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        let inputs = self.config.inputs.load(Ordering::Relaxed);
+
+        INPUTS[..inputs]
+            .iter()
+            .map(|name| Input::new(*name, ValueKind::Float))
+            .collect()
+    }
+
+    fn output(&self) -> Vec<Output> {
+        OUTPUTS
+            .iter()
+            .map(|name| Output::new(*name, ValueKind::Float))
+            .collect()
+    }
+}
+
+pub fn script() -> Box<dyn Node> {
+    Box::new(Script {
+        config: Arc::new(ScriptConfig {
+            source: Mutex::new(DEFAULT_SOURCE.to_string()),
+            inputs: AtomicUsize::new(1),
+            error: Mutex::new(None),
+            dirty: ConfigDirty::new(),
+        }),
+        engine: engine(),
+        ast: None,
+        inputs: 1,
+        state: Map::new(),
+        out: [0.0; 4],
+    })
+}
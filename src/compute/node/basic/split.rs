@@ -0,0 +1,112 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use eframe::egui::DragValue;
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
+    Output, Value, ValueKind,
+};
+
+use super::join::MAX_CHANNELS;
+
+crate::ports! {
+    SIG = "sig",
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SplitConfig {
+    outs: AtomicUsize,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for SplitConfig {
+    fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn Any) {
+        let mut outs = self.outs.load(Ordering::Acquire);
+
+        ui.horizontal(|ui| {
+            ui.label("outputs");
+            if ui
+                .add(DragValue::new(&mut outs).range(1..=MAX_CHANNELS))
+                .changed()
+            {
+                self.outs.store(outs, Ordering::Release);
+                self.dirty.mark();
+            }
+        });
+    }
+}
+
+/// Channels of an array on separate outputs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Split {
+    config: Arc<SplitConfig>,
+    values: Vec<f32>,
+}
+
+#[typetag::serde]
+impl Node for Split {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sig = &data[SIG];
+        for (k, value) in self.values.iter_mut().enumerate() {
+            *value = sig.channel(k).as_float().unwrap_or(0.0);
+        }
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
+        let outs = self.config.outs.load(Ordering::Relaxed);
+        if outs == self.values.len() {
+            return Default::default();
+        }
+        self.values.resize(outs, 0.0);
+
+        vec![NodeEvent::RecalcOutputs(self.output())]
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        for (out, value) in out.iter_mut().zip(&self.values) {
+            *out = Value::Float(*value);
+        }
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::new("sig", ValueKind::FloatArray)]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        (0..self.values.len())
+            .map(|k| Output::new(format!("ch {k}"), ValueKind::Float))
+            .collect()
+    }
+}
+
+pub fn split() -> Box<dyn Node> {
+    Box::new(Split {
+        config: Arc::new(SplitConfig {
+            outs: AtomicUsize::new(2),
+            dirty: ConfigDirty::new(),
+        }),
+        values: vec![0.0; 2],
+    })
+}
@@ -0,0 +1,135 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use eframe::egui::DragValue;
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{inputs::real::RealInput, ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
+    Output, Value, ValueKind,
+};
+
+use super::join::MAX_CHANNELS;
+
+crate::ports! {
+    BASE = "base",
+    STEP = "step",
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SpreadConfig {
+    channels: AtomicUsize,
+    geometric: AtomicBool,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for SpreadConfig {
+    fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn Any) {
+        let mut channels = self.channels.load(Ordering::Acquire);
+        let mut geometric = self.geometric.load(Ordering::Acquire);
+
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("channels");
+            changed |= ui
+                .add(DragValue::new(&mut channels).range(1..=MAX_CHANNELS))
+                .changed();
+        });
+        changed |= ui
+            .checkbox(&mut geometric, "Geometric")
+            .on_hover_text("Multiply by the step instead of adding it")
+            .changed();
+
+        self.channels.store(channels, Ordering::Release);
+        self.geometric.store(geometric, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
+/// Array of values spread from a base by a step, like the center
+/// frequencies of a filterbank or the partials of an additive voice. A step
+/// of 1 while geometric copies the base to every channel.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Spread {
+    config: Arc<SpreadConfig>,
+    base: Arc<RealInput>,
+    step: Arc<RealInput>,
+    #[serde(skip)]
+    channels: usize,
+    #[serde(skip)]
+    geometric: bool,
+    #[serde(skip)]
+    out: Vec<f32>,
+}
+
+#[typetag::serde]
+impl Node for Spread {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let base = self.base.get_f32(&data[BASE]);
+        let step = self.step.get_f32(&data[STEP]);
+
+        self.out.clear();
+        self.out.extend((0..self.channels).map(|k| {
+            if self.geometric {
+                base * step.powi(k as i32)
+            } else {
+                base + step * k as f32
+            }
+        }));
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.channels = self.config.channels.load(Ordering::Relaxed);
+            self.geometric = self.config.geometric.load(Ordering::Relaxed);
+        }
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0].set_float_array(self.out.iter().copied())
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::stateful("base", &self.base),
+            Input::stateful("step", &self.step),
+        ]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![Output::new("", ValueKind::FloatArray)]
+    }
+}
+
+pub fn spread() -> Box<dyn Node> {
+    Box::new(Spread {
+        config: Arc::new(SpreadConfig {
+            channels: AtomicUsize::new(8),
+            geometric: AtomicBool::new(true),
+            dirty: ConfigDirty::new(),
+        }),
+        base: Arc::new(RealInput::new(110.0)),
+        step: Arc::new(RealInput::new(2.0)),
+        channels: 8,
+        geometric: true,
+        out: Vec::new(),
+    })
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{Input, InputData, Node, NodeEvent},
+    Value, ValueKind,
+};
+
+crate::ports! {
+    SIG = "sig",
+}
+
+/// Channels of an array added into one signal.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Sum {
+    out: f32,
+}
+
+#[typetag::serde]
+impl Node for Sum {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        self.out = match &data[SIG] {
+            Value::FloatArray(channels) => channels.iter().sum(),
+            other => other.as_float().unwrap_or(0.0),
+        };
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::new("sig", ValueKind::FloatArray)]
+    }
+}
+
+pub fn sum() -> Box<dyn Node> {
+    Box::new(Sum { out: 0.0 })
+}
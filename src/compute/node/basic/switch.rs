@@ -0,0 +1,152 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use eframe::egui::DragValue;
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{
+        inputs::trigger::{TriggerInput, TriggerMode},
+        ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
+    },
+    Value, ValueKind,
+};
+
+crate::ports! {
+    INDEX = "index",
+    STEP = "step",
+}
+
+const MAX_INS: usize = 16;
+// Samples an input takes to fade in over the previous one, about 5 ms
+const FADE: f32 = 220.0;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SwitchConfig {
+    ins: AtomicUsize,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for SwitchConfig {
+    fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn Any) {
+        let mut ins = self.ins.load(Ordering::Acquire);
+
+        ui.horizontal(|ui| {
+            ui.label("inputs");
+            if ui
+                .add(DragValue::new(&mut ins).range(2..=MAX_INS))
+                .changed()
+            {
+                self.ins.store(ins, Ordering::Release);
+                self.dirty.mark();
+            }
+        });
+    }
+}
+
+/// Passes one of its inputs, picked by `index` while it's connected and
+/// stepped through by `step` otherwise.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Switch {
+    config: Arc<SwitchConfig>,
+    step: Arc<TriggerInput>,
+    ins: usize,
+    current: usize,
+    // Input faded out of, and how far into the fade the switch is
+    previous: usize,
+    fade: f32,
+    out: f32,
+}
+
+#[typetag::serde]
+impl Node for Switch {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let ins = self.ins.max(1);
+
+        let index = &data[INDEX];
+        let target = if !index.disconnected() {
+            let index = index.as_float().unwrap_or_default().round() as i64;
+            index.rem_euclid(ins as i64) as usize
+        } else if self.step.trigger(&data[STEP]) {
+            (self.current + 1) % ins
+        } else {
+            self.current.min(ins - 1)
+        };
+
+        if target != self.current {
+            self.previous = self.current;
+            self.current = target;
+            self.fade = 0.0;
+        }
+        self.fade = (self.fade + 1.0 / FADE).min(1.0);
+
+        // Declared after the index and step
+        let sig = |k: usize| data[2 + k].as_float().unwrap_or_default();
+        self.out = if self.fade < 1.0 && self.previous < ins {
+            sig(self.previous) * (1.0 - self.fade) + sig(self.current) * self.fade
+        } else {
+            sig(self.current)
+        };
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
+        let ins = self.config.ins.load(Ordering::Relaxed);
+        if ins == self.ins {
+            return Default::default();
+        }
+        self.ins = ins;
+
+        vec![NodeEvent::RecalcInputs(self.inputs())]
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        let ins = self.config.ins.load(Ordering::Relaxed);
+
+        let mut inputs = vec![
+            Input::new("index", ValueKind::Float),
+            Input::stateful("step", &self.step),
+        ];
+        inputs.extend((0..ins).map(|k| Input::new(format!("sig {k}"), ValueKind::Float)));
+
+        inputs
+    }
+}
+
+pub fn switch() -> Box<dyn Node> {
+    Box::new(Switch {
+        config: Arc::new(SwitchConfig {
+            ins: AtomicUsize::new(2),
+            dirty: ConfigDirty::new(),
+        }),
+        step: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
+        ins: 2,
+        current: 0,
+        previous: 0,
+        fade: 1.0,
+        out: 0.0,
+    })
+}
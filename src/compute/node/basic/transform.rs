@@ -1,13 +1,21 @@
 use std::sync::Arc;
 
 use crate::compute::{
-    node::{inputs::real::RealInput, Input, Node, NodeConfig, NodeEvent},
+    node::{inputs::real::RealInput, Input, InputData, Node, NodeConfig, NodeEvent},
     Value, ValueKind,
 };
 use serde::{Deserialize, Serialize};
 
 use super::curve::CurveConfig;
 
+crate::ports! {
+    SIG = "sig",
+    IN_MIN = "in min",
+    IN_MAX = "in max",
+    OUT_MIN = "out min",
+    OUT_MAX = "out max",
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transform {
     config: Arc<CurveConfig>,
@@ -35,12 +43,12 @@ impl Transform {
 
 #[typetag::serde]
 impl Node for Transform {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let signal = data[0].as_float().unwrap_or(0.0);
-        let in_min = self.in_min.get_f32(&data[1]);
-        let in_max = self.in_max.get_f32(&data[2]);
-        let out_min = self.out_min.get_f32(&data[3]);
-        let out_max = self.out_max.get_f32(&data[4]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let signal = data[SIG].as_float().unwrap_or(0.0);
+        let in_min = self.in_min.get_f32(&data[IN_MIN]);
+        let in_max = self.in_max.get_f32(&data[IN_MAX]);
+        let out_min = self.out_min.get_f32(&data[OUT_MIN]);
+        let out_max = self.out_max.get_f32(&data[OUT_MAX]);
 
         let idx_0_1 = (signal - in_min) / (in_max - in_min);
 
@@ -71,6 +79,10 @@ impl Node for Transform {
         Some(Arc::clone(&self.config) as Arc<_>)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::new("sig", ValueKind::Float),
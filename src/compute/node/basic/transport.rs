@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{InputData, Node, NodeEvent},
+    transport::{self, Follower},
+    Output, Value, ValueKind,
+};
+
+/// Position of the JACK transport, silent while no timebase master runs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Transport {
+    #[serde(skip)]
+    follower: Follower,
+    #[serde(skip)]
+    beat: Value,
+    #[serde(skip)]
+    bpm: f32,
+    #[serde(skip)]
+    position: f64,
+    #[serde(skip)]
+    rolling: bool,
+}
+
+#[typetag::serde]
+impl Node for Transport {
+    fn feed(&mut self, _data: &InputData) -> Vec<NodeEvent> {
+        let Some(snapshot) = transport::snapshot() else {
+            self.follower = Follower::default();
+            self.beat = Value::None;
+            self.bpm = 0.0;
+            self.position = 0.0;
+            self.rolling = false;
+
+            return Default::default();
+        };
+
+        let (position, beat) = self.follower.step(&snapshot);
+        self.beat = if beat && snapshot.bpm > 0.0 {
+            Value::Beat(Duration::from_secs_f32(60.0 / snapshot.bpm))
+        } else {
+            Value::None
+        };
+        self.bpm = snapshot.bpm;
+        self.position = position;
+        self.rolling = snapshot.rolling;
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = self.beat.clone();
+        out[1] = Value::Float(self.bpm);
+        out[2] = Value::Float(self.position as f32);
+        out[3] = Value::Float(if self.rolling { 1.0 } else { 0.0 });
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![
+            Output::new("beat", ValueKind::Beat),
+            Output::new("bpm", ValueKind::Float),
+            Output::new("position", ValueKind::Float),
+            Output::new("rolling", ValueKind::Float),
+        ]
+    }
+}
+
+pub fn transport() -> Box<dyn Node> {
+    Box::new(Transport {
+        follower: Follower::default(),
+        beat: Value::None,
+        bpm: 0.0,
+        position: 0.0,
+        rolling: false,
+    })
+}
@@ -0,0 +1,235 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use atomic_float::AtomicF32;
+use eframe::egui::{self, Color32};
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{inputs::slider::SliderInput, Input, InputData, Node, NodeConfig, NodeEvent},
+    tuning::Tuning,
+    Output, Value, ValueKind,
+};
+
+crate::ports! {
+    SIG = "sig",
+    LEVEL = "level",
+}
+
+// Samples analysed at once, half of them being the longest period found
+const FRAME: usize = 2048;
+// Samples between analyses
+const HOP: usize = 1024;
+// Highest pitch detected, bounding the shortest period
+const MAX_FREQ: f32 = 2000.0;
+// Dip of the normalized difference taken as a period
+const THRESHOLD: f32 = 0.15;
+// Pixels per second the strobe moves for every cent off pitch
+const STROBE_SPEED: f32 = 4.0;
+
+const NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Period of `frame` in samples by the YIN method, `diff` is scratch space.
+fn detect(frame: &[f32], diff: &mut Vec<f32>) -> Option<f32> {
+    let w = frame.len() / 2;
+    diff.clear();
+    diff.resize(w, 0.0);
+
+    for (tau, d) in diff.iter_mut().enumerate().skip(1) {
+        *d = (0..w).map(|i| (frame[i] - frame[i + tau]).powi(2)).sum();
+    }
+
+    // Cumulative mean normalized difference, so that short lags aren't favored
+    let mut sum = 0.0;
+    diff[0] = 1.0;
+    for (tau, d) in diff.iter_mut().enumerate().skip(1) {
+        sum += *d;
+        *d = if sum > 0.0 {
+            *d * tau as f32 / sum
+        } else {
+            1.0
+        };
+    }
+
+    let min_tau = (44100.0 / MAX_FREQ) as usize;
+    let mut tau = (min_tau..w - 1).find(|tau| diff[*tau] < THRESHOLD)?;
+    while tau + 1 < w - 1 && diff[tau + 1] < diff[tau] {
+        tau += 1;
+    }
+
+    // Parabola through the minimum and its neighbours
+    let (a, b, c) = (diff[tau - 1], diff[tau], diff[tau + 1]);
+    let den = a - 2.0 * b + c;
+    let shift = if den.abs() > f32::EPSILON {
+        0.5 * (a - c) / den
+    } else {
+        0.0
+    };
+
+    Some(tau as f32 + shift.clamp(-1.0, 1.0))
+}
+
+/// Nearest key and the deviation from it in cents, against the reference
+/// pitch only; transposition changes what keys play, not what they sound.
+fn nearest_key(freq: f32) -> (i32, f32) {
+    let key = 69.0 + 12.0 * (freq / Tuning::current().a4).log2();
+    let nearest = key.round();
+
+    (nearest as i32, (key - nearest) * 100.0)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TunerConfig {
+    // Written by the runtime, 0 without a pitch
+    #[serde(skip)]
+    freq: AtomicF32,
+    // Offset of the strobe stripes, moved by the editor
+    #[serde(skip)]
+    strobe: AtomicF32,
+}
+
+impl TunerConfig {
+    fn show_strobe(&self, ui: &mut egui::Ui, cents: Option<f32>) {
+        let size = egui::vec2(ui.available_width().max(160.0), 20.0);
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+        let Some(cents) = cents else {
+            return;
+        };
+
+        // Stripes stand still in tune and drift the way the pitch is off
+        let dt = ui.input(|input| input.stable_dt).min(0.1);
+        let period = 16.0;
+        let offset =
+            (self.strobe.load(Ordering::Relaxed) + cents * STROBE_SPEED * dt).rem_euclid(period);
+        self.strobe.store(offset, Ordering::Relaxed);
+
+        let color = if cents.abs() < 5.0 {
+            Color32::GREEN
+        } else {
+            visuals.strong_text_color()
+        };
+        let mut x = rect.left() - period + offset;
+        while x < rect.right() {
+            let stripe = egui::Rect::from_min_size(
+                egui::pos2(x, rect.top()),
+                egui::vec2(period / 2.0, rect.height()),
+            );
+            painter.rect_filled(stripe, 0.0, color);
+            x += period;
+        }
+    }
+}
+
+impl NodeConfig for TunerConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn std::any::Any) {
+        let freq = self.freq.load(Ordering::Relaxed);
+        let pitch = (freq > 0.0).then(|| nearest_key(freq));
+
+        match pitch {
+            Some((key, cents)) => {
+                let color = if cents.abs() < 5.0 {
+                    Color32::GREEN
+                } else {
+                    ui.visuals().text_color()
+                };
+                ui.horizontal(|ui| {
+                    let name = NAMES[key.rem_euclid(12) as usize];
+                    ui.label(
+                        egui::RichText::new(format!("{name}{}", key.div_euclid(12) - 1))
+                            .size(20.0)
+                            .strong(),
+                    );
+                    ui.label(egui::RichText::new(format!("{cents:+.0} ct")).color(color));
+                    ui.weak(format!("{freq:.1} Hz"));
+                });
+            }
+            None => {
+                ui.label(egui::RichText::new("—").size(20.0));
+            }
+        }
+
+        self.show_strobe(ui, pitch.map(|(_, cents)| cents));
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Tuner {
+    config: Arc<TunerConfig>,
+    level: Arc<SliderInput>,
+    #[serde(skip)]
+    frame: Vec<f32>,
+    #[serde(skip)]
+    diff: Vec<f32>,
+    freq: f32,
+}
+
+#[typetag::serde]
+impl Node for Tuner {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        self.frame.push(data[SIG].as_float().unwrap_or_default());
+        if self.frame.len() < FRAME {
+            return Default::default();
+        }
+
+        // Quiet input has no pitch worth showing
+        let rms = (self.frame.iter().map(|s| s * s).sum::<f32>() / FRAME as f32).sqrt();
+        let level = self.level.as_f32(&data[LEVEL]);
+        self.freq = if rms >= level {
+            detect(&self.frame, &mut self.diff)
+                .map(|period| 44100.0 / period)
+                .unwrap_or_default()
+        } else {
+            0.0
+        };
+        self.config.freq.store(self.freq, Ordering::Relaxed);
+        self.frame.drain(..HOP);
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.freq);
+        out[1] = Value::Float(if self.freq > 0.0 {
+            nearest_key(self.freq).1
+        } else {
+            0.0
+        });
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::new("sig", ValueKind::Float),
+            Input::stateful("level", &self.level),
+        ]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![
+            Output::new("freq", ValueKind::Float),
+            Output::new("cents", ValueKind::Float),
+        ]
+    }
+}
+
+pub fn tuner() -> Box<dyn Node> {
+    Box::new(Tuner {
+        config: Arc::new(TunerConfig::default()),
+        level: Arc::new(SliderInput::new(0.01, 0.0, 0.5)),
+        frame: Vec::with_capacity(FRAME),
+        diff: Vec::with_capacity(FRAME / 2),
+        freq: 0.0,
+    })
+}
@@ -0,0 +1,203 @@
+use std::{
+    any::Any,
+    sync::{Arc, Mutex},
+};
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{all::source::SynthCtx, ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
+    Output, Value, ValueKind,
+};
+
+crate::ports! {
+    SIG = "sig",
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BusNameConfig {
+    #[serde(with = "crate::util::serde_mutex")]
+    name: Mutex<String>,
+    // Receivers pick from the buses sent to, senders name a new one
+    pick: bool,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl BusNameConfig {
+    fn new(name: &str, pick: bool) -> Self {
+        BusNameConfig {
+            name: Mutex::new(name.to_string()),
+            pick,
+            dirty: ConfigDirty::new(),
+        }
+    }
+
+    fn name(&self) -> String {
+        self.name.lock().unwrap().clone()
+    }
+}
+
+impl NodeConfig for BusNameConfig {
+    fn show(&self, ui: &mut egui::Ui, data: &dyn Any) {
+        let ctx = data.downcast_ref::<SynthCtx>().unwrap();
+        let mut name = self.name.lock().unwrap();
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            ui.label("Bus");
+            changed |= ui
+                .add(egui::TextEdit::singleline(&mut *name).desired_width(100.0))
+                .changed();
+
+            if self.pick {
+                egui::ComboBox::from_id_salt(ui.next_auto_id())
+                    .selected_text("")
+                    .width(0.0)
+                    .show_ui(ui, |ui| {
+                        for existing in &ctx.bus_names {
+                            let current = *existing == *name;
+                            if ui.selectable_label(current, existing).clicked() {
+                                *name = existing.clone();
+                                changed = true;
+                            }
+                        }
+                    });
+            }
+        });
+
+        self.dirty.mark_if(changed);
+    }
+}
+
+/// Adds its input to the named bus heard by every `Receive` of the same
+/// name, without a cable between them. Renaming it takes the receivers
+/// along once nothing else sends to the old name.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BusSend {
+    config: Arc<BusNameConfig>,
+    #[serde(skip)]
+    name: Option<String>,
+    // Name before the last rename, until the receivers are told
+    #[serde(skip)]
+    renamed_from: Option<String>,
+}
+
+#[typetag::serde]
+impl Node for BusSend {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if let Some(name) = &self.name {
+            if let Some(old) = self.renamed_from.take().filter(|old| old != name) {
+                data.bus().rename(&old, name);
+            }
+
+            let sig = data[SIG].as_float().unwrap_or_default();
+            data.bus().send(name, sig);
+        }
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            let name = self.config.name();
+            // Renamed twice before a step, receivers go straight to the last
+            if let Some(old) = self.name.take() {
+                self.renamed_from.get_or_insert(old);
+            }
+            self.name = Some(name);
+        }
+
+        Default::default()
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::new("sig", ValueKind::Float)]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        Vec::new()
+    }
+}
+
+/// Outputs what the `Send` nodes of its bus were fed a step before.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BusReceive {
+    config: Arc<BusNameConfig>,
+    #[serde(skip)]
+    name: String,
+    #[serde(skip)]
+    out: f32,
+    // Renames of the bus looked through so far
+    #[serde(skip)]
+    renames: usize,
+    // Set once a rename was followed, until the config shows it
+    #[serde(skip)]
+    followed: bool,
+}
+
+#[typetag::serde]
+impl Node for BusReceive {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let bus = data.bus();
+        let mut out = bus.receive(&self.name);
+
+        // Follow a renamed sender, unless another one still uses the name
+        if out.is_none() && bus.renames() != self.renames {
+            self.renames = bus.renames();
+            if let Some(new) = bus.renamed(&self.name) {
+                out = bus.receive(&new);
+                self.name = new;
+                self.followed = true;
+            }
+        }
+        self.out = out.unwrap_or_default();
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out);
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.name = self.config.name();
+        } else if std::mem::take(&mut self.followed) {
+            self.config.name.lock().unwrap().clone_from(&self.name);
+        }
+
+        Default::default()
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+}
+
+pub fn send() -> Box<dyn Node> {
+    Box::new(BusSend {
+        config: Arc::new(BusNameConfig::new("bus", false)),
+        name: None,
+        renamed_from: None,
+    })
+}
+
+pub fn receive() -> Box<dyn Node> {
+    Box::new(BusReceive {
+        config: Arc::new(BusNameConfig::new("bus", true)),
+        name: String::new(),
+        out: 0.0,
+        renames: 0,
+        followed: false,
+    })
+}
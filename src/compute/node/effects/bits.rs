@@ -1,29 +1,83 @@
-use std::sync::Arc;
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
+use eframe::egui;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::slider::SliderInput, Input, Node, NodeEvent},
+    node::{
+        auto_gain::AutoGain, inputs::slider::SliderInput, ConfigDirty, Input, InputData, Node,
+        NodeConfig, NodeEvent,
+    },
     Value, ValueKind,
 };
 
+crate::ports! {
+    SIG = "sig",
+    BITS = "bits",
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BitsConfig {
+    auto_gain: AtomicBool,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for BitsConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mut auto_gain = self.auto_gain.load(Ordering::Acquire);
+
+        let changed = ui
+            .checkbox(&mut auto_gain, "Auto gain")
+            .on_hover_text("Match the output loudness to the input")
+            .changed();
+
+        self.auto_gain.store(auto_gain, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Bits {
+    #[serde(default)]
+    config: Arc<BitsConfig>,
     bits: Arc<SliderInput>,
+    #[serde(skip)]
+    auto_gain: bool,
+    #[serde(skip)]
+    gain: AutoGain,
     out: f32,
 }
 
 #[typetag::serde]
 impl Node for Bits {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let sig = data[0].as_float().unwrap_or(0.0);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sig = data[SIG].as_float().unwrap_or(0.0);
 
-        let bits = self.bits.as_f32(&data[1]);
+        let bits = self.bits.as_f32(&data[BITS]);
         let states = (2f32).powf(bits - 1.0);
 
         let quantized = (sig.clamp(-1.0, 1.0) * states) as i16;
         self.out = quantized as f32 / states;
 
+        if self.auto_gain {
+            self.out = self.gain.process(sig, self.out);
+        }
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.auto_gain = self.config.auto_gain.load(Ordering::Relaxed);
+        }
+
         Default::default()
     }
 
@@ -31,6 +85,14 @@ impl Node for Bits {
         out[0] = Value::Float(self.out)
     }
 
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::new("sig", ValueKind::Float),
@@ -41,7 +103,10 @@ impl Node for Bits {
 
 pub fn bits() -> Box<dyn Node> {
     Box::new(Bits {
+        config: Default::default(),
         bits: Arc::new(SliderInput::new(1.0, 1.0, 16.0)),
+        auto_gain: false,
+        gain: AutoGain::default(),
         out: 0.0,
     })
 }
@@ -5,11 +5,19 @@ use serde::{Deserialize, Serialize};
 use crate::compute::{
     node::{
         inputs::{percentage::PercentageInput, time::TimeInput},
-        Input, Node, NodeEvent,
+        Input, InputData, Node, NodeEvent,
     },
     Value, ValueKind,
 };
 
+crate::ports! {
+    SIG = "sig",
+    OSC = "osc",
+    DELAY = "delay",
+    WIDTH = "width",
+    MIX = "mix",
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct Chorus {
     delay: VecDeque<f32>,
@@ -34,30 +42,39 @@ impl Chorus {
 
 #[typetag::serde]
 impl Node for Chorus {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let sample = data[0].as_float().unwrap_or(0.0);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sample = data[SIG].as_float().unwrap_or(0.0);
         self.delay.push_front(sample);
         self.delay.pop_back();
 
         let delay_out = *self.delay.front().unwrap();
 
-        let osc_val = data[1].as_float().unwrap_or(0.0).clamp(-1.0, 1.0);
-        let delay = self.delay_in.get_ms(&data[2]).clamp(0.0, 50.0);
-        let width = self.width_in.get_f32(&data[3]);
+        let osc_val = data[OSC].as_float().unwrap_or(0.0).clamp(-1.0, 1.0);
+        let delay = self.delay_in.get_ms(&data[DELAY]).clamp(0.0, 50.0);
+        let width = self.width_in.get_f32(&data[WIDTH]);
         let tap_t = (delay + osc_val * delay * width).clamp(0.0, 50.0);
         let tap_out = self.tap_at(tap_t);
 
-        let mix = self.mix_in.get_f32(&data[4]);
+        let mix = self.mix_in.get_f32(&data[MIX]);
 
         self.out = delay_out * (1.0 - mix) + tap_out * mix;
 
         Default::default()
     }
 
+    fn panic(&mut self) {
+        self.delay.iter_mut().for_each(|s| *s = 0.0);
+        self.out = 0.0;
+    }
+
     fn read(&self, out: &mut [Value]) {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::new("sig", ValueKind::Float),
@@ -1,16 +1,28 @@
-use std::sync::{atomic::Ordering, Arc};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
     compute::{
-        node::{inputs::slider::SliderInput, Input, Node, NodeConfig, NodeEvent},
+        node::{
+            auto_gain::AutoGain, inputs::slider::SliderInput, ConfigDirty, Input, InputData, Node,
+            NodeConfig, NodeEvent,
+        },
         Value, ValueKind,
     },
     serde_atomic_enum,
     util::enum_combo_box,
 };
 
+crate::ports! {
+    VALUE = "value",
+    LEVEL = "level",
+    OFFSET = "offset",
+}
+
 #[atomic_enum::atomic_enum]
 #[derive(PartialEq, Eq, derive_more::Display, strum::EnumIter)]
 pub enum ClipType {
@@ -21,15 +33,27 @@ pub enum ClipType {
 
 serde_atomic_enum!(AtomicClipType);
 
+impl Default for ClipType {
+    fn default() -> Self {
+        ClipType::Hard
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ClipConfig {
     ty: AtomicClipType,
+    #[serde(default)]
+    auto_gain: AtomicBool,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl ClipConfig {
     fn new(ty: ClipType) -> Self {
         ClipConfig {
             ty: AtomicClipType::new(ty),
+            auto_gain: AtomicBool::new(false),
+            dirty: ConfigDirty::new(),
         }
     }
 
@@ -41,10 +65,17 @@ impl ClipConfig {
 impl NodeConfig for ClipConfig {
     fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn std::any::Any) {
         let mut ty = self.ty.load(Ordering::Acquire);
+        let mut auto_gain = self.auto_gain.load(Ordering::Acquire);
 
-        enum_combo_box(ui, &mut ty);
+        let mut changed = enum_combo_box(ui, &mut ty);
+        changed |= ui
+            .checkbox(&mut auto_gain, "Auto gain")
+            .on_hover_text("Match the output loudness to the input")
+            .changed();
 
         self.ty.store(ty, Ordering::Release);
+        self.auto_gain.store(auto_gain, Ordering::Release);
+        self.dirty.mark_if(changed);
     }
 }
 
@@ -53,6 +84,12 @@ pub struct Clip {
     config: Arc<ClipConfig>,
     level: Arc<SliderInput>,
     offset: Arc<SliderInput>,
+    #[serde(skip)]
+    ty: ClipType,
+    #[serde(skip)]
+    auto_gain: bool,
+    #[serde(skip)]
+    gain: AutoGain,
     out: f32,
 }
 
@@ -62,14 +99,12 @@ impl Node for Clip {
         Some(Arc::clone(&self.config) as Arc<_>)
     }
 
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let value = data[0].as_float().unwrap_or(0.0);
-        let level = self.level.as_f32(&data[1]).max(0.0);
-        let offset = self.offset.as_f32(&data[2]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let value = data[VALUE].as_float().unwrap_or(0.0);
+        let level = self.level.as_f32(&data[LEVEL]).max(0.0);
+        let offset = self.offset.as_f32(&data[OFFSET]);
 
-        let clip_ty = self.config.clip_ty();
-
-        self.out = match clip_ty {
+        self.out = match self.ty {
             ClipType::Hard => (value + offset).clamp(-level, level),
             ClipType::Poly => {
                 let mut scaled = (value + offset) / level;
@@ -93,6 +128,19 @@ impl Node for Clip {
             }
         };
 
+        if self.auto_gain {
+            self.out = self.gain.process(value, self.out);
+        }
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.ty = self.config.clip_ty();
+            self.auto_gain = self.config.auto_gain.load(Ordering::Relaxed);
+        }
+
         Default::default()
     }
 
@@ -100,6 +148,10 @@ impl Node for Clip {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::new("value", ValueKind::Float),
@@ -114,6 +166,9 @@ pub fn clip() -> Box<dyn Node> {
         config: Arc::new(ClipConfig::new(ClipType::Hard)),
         level: Arc::new(SliderInput::new(1.0, 0.0, 1.0)),
         offset: Arc::new(SliderInput::new(0.0, -0.1, 0.1)),
+        ty: ClipType::Hard,
+        auto_gain: false,
+        gain: AutoGain::default(),
         out: 0.0,
     })
 }
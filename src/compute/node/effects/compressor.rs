@@ -0,0 +1,133 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use atomic_float::AtomicF32;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{
+        inputs::{slider::SliderInput, time::TimeInput},
+        Input, InputData, Node, NodeConfig, NodeEvent,
+    },
+    Value, ValueKind,
+};
+
+crate::ports! {
+    SIG = "sig",
+    KEY = "key",
+    THRESHOLD = "threshold",
+    RATIO = "ratio",
+    ATTACK = "attack",
+    RELEASE = "release",
+    MAKEUP = "makeup",
+}
+
+// Level reported for silence, keeps the detector out of -inf
+const FLOOR_DB: f32 = -120.0;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CompressorConfig {
+    // Written by the runtime, only displayed here.
+    #[serde(skip)]
+    reduction: AtomicF32,
+}
+
+impl NodeConfig for CompressorConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn std::any::Any) {
+        let reduction = self.reduction.load(Ordering::Relaxed);
+
+        ui.horizontal(|ui| {
+            ui.label("Reduction");
+            ui.add(
+                egui::ProgressBar::new((reduction / 24.0).clamp(0.0, 1.0))
+                    .desired_width(100.0)
+                    .text(format!("{reduction:.1} dB")),
+            );
+        });
+    }
+}
+
+/// Turns its input down by `ratio` above `threshold`. The level is taken
+/// from `key` while it's connected, so any signal can duck the input.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Compressor {
+    config: Arc<CompressorConfig>,
+    threshold: Arc<SliderInput>,
+    ratio: Arc<SliderInput>,
+    attack: Arc<TimeInput>,
+    release: Arc<TimeInput>,
+    makeup: Arc<SliderInput>,
+    // Smoothed gain reduction, in dB
+    reduction: f32,
+    out: f32,
+}
+
+#[typetag::serde]
+impl Node for Compressor {
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sig = data[SIG].as_float().unwrap_or_default();
+        let key = data[KEY].as_float().unwrap_or(sig).abs();
+        let level = (20.0 * key.log10()).max(FLOOR_DB);
+
+        let threshold = self.threshold.as_f32(&data[THRESHOLD]);
+        let ratio = self.ratio.as_f32(&data[RATIO]).max(1.0);
+        let target = (level - threshold).max(0.0) * (1.0 - 1.0 / ratio);
+
+        let time = if target > self.reduction {
+            self.attack.get_samples(&data[ATTACK])
+        } else {
+            self.release.get_samples(&data[RELEASE])
+        };
+        self.reduction += (target - self.reduction) / time.max(1.0);
+
+        let makeup = self.makeup.as_f32(&data[MAKEUP]);
+        self.out = sig * 10f32.powf((makeup - self.reduction) / 20.0);
+        self.config
+            .reduction
+            .store(self.reduction, Ordering::Relaxed);
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn panic(&mut self) {
+        self.reduction = 0.0;
+        self.out = 0.0;
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::new("sig", ValueKind::Float),
+            Input::new("key", ValueKind::Float),
+            Input::stateful("threshold", &self.threshold),
+            Input::stateful("ratio", &self.ratio),
+            Input::stateful("attack", &self.attack),
+            Input::stateful("release", &self.release),
+            Input::stateful("makeup", &self.makeup),
+        ]
+    }
+}
+
+pub fn compressor() -> Box<dyn Node> {
+    Box::new(Compressor {
+        config: Arc::new(CompressorConfig::default()),
+        threshold: Arc::new(SliderInput::new(-18.0, -60.0, 0.0)),
+        ratio: Arc::new(SliderInput::new(4.0, 1.0, 20.0)),
+        attack: Arc::new(TimeInput::from_ms(5.0)),
+        release: Arc::new(TimeInput::from_ms(150.0)),
+        makeup: Arc::new(SliderInput::new(0.0, 0.0, 24.0)),
+        reduction: 0.0,
+        out: 0.0,
+    })
+}
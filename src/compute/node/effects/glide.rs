@@ -7,7 +7,7 @@ use crate::{
     compute::{
         node::{
             inputs::{positive::PositiveInput, real::RealInput},
-            Input, Node, NodeConfig, NodeEvent,
+            ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
         },
         Value, ValueKind,
     },
@@ -15,6 +15,15 @@ use crate::{
     util::enum_combo_box,
 };
 
+crate::ports! {
+    SIG = "sig",
+    LERP_R = "lerp-r",
+    RATE = "rate",
+    P = "p",
+    I = "i",
+    D = "d",
+}
+
 #[atomic_enum]
 #[derive(PartialEq, Eq, Serialize, Deserialize, derive_more::Display, strum::EnumIter)]
 enum GlideType {
@@ -28,15 +37,18 @@ serde_atomic_enum!(AtomicGlideType);
 #[derive(Debug, Serialize, Deserialize)]
 struct GlideConfig {
     ty: AtomicGlideType,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl NodeConfig for GlideConfig {
     fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn std::any::Any) {
         let mut ty = self.ty.load(Ordering::Acquire);
 
-        enum_combo_box(ui, &mut ty);
+        let changed = enum_combo_box(ui, &mut ty);
 
         self.ty.store(ty, Ordering::Release);
+        self.dirty.mark_if(changed);
     }
 }
 
@@ -57,6 +69,7 @@ impl Glide {
         Glide {
             conf: Arc::new(GlideConfig {
                 ty: AtomicGlideType::new(GlideType::Lerp),
+                dirty: ConfigDirty::new(),
             }),
             lerp_coeff: Arc::new(RealInput::new(-3.0)),
             rate_limit: Arc::new(PositiveInput::new(1.0)),
@@ -74,21 +87,17 @@ impl Glide {
 
 #[typetag::serde]
 impl Node for Glide {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let next = data[0].as_float().unwrap_or_default();
-
-        let new_ty = self.conf.ty.load(Ordering::Relaxed);
-        let emit_ev = self.ty != new_ty;
-        self.ty = new_ty;
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let next = data[SIG].as_float().unwrap_or_default();
 
         self.out = match self.ty {
             GlideType::Lerp => {
-                let lerp_r_exp = self.lerp_coeff.get_f32(data.get(1).unwrap_or(&Value::None));
+                let lerp_r_exp = self.lerp_coeff.get_f32(&data[LERP_R]);
                 let lerp_r = 10f32.powf(lerp_r_exp);
                 self.out * (1.0 - lerp_r) + next * lerp_r
             }
             GlideType::Exponential => {
-                let rate_coeff = self.rate_limit.get_f32(data.get(1).unwrap_or(&Value::None));
+                let rate_coeff = self.rate_limit.get_f32(&data[RATE]);
                 let rate = rate_coeff * self.out / 44100.0;
 
                 if rate.abs() > (self.out - next).abs() {
@@ -100,9 +109,9 @@ impl Node for Glide {
                 }
             }
             GlideType::Pid => {
-                let p = self.pid[0].get_f32(data.get(1).unwrap_or(&Value::None));
-                let i = self.pid[1].get_f32(data.get(2).unwrap_or(&Value::None));
-                let d = self.pid[2].get_f32(data.get(3).unwrap_or(&Value::None));
+                let p = self.pid[0].get_f32(&data[P]);
+                let i = self.pid[1].get_f32(&data[I]);
+                let d = self.pid[2].get_f32(&data[D]);
                 let lim = 44100.0 * 10.0;
 
                 self.pid_ctrl.output_limit = 44100.0;
@@ -112,11 +121,22 @@ impl Node for Glide {
             }
         };
 
-        if emit_ev {
-            vec![NodeEvent::RecalcInputs(self.inputs())]
-        } else {
-            Default::default()
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.conf.dirty.take() {
+            return Default::default();
         }
+
+        let new_ty = self.conf.ty.load(Ordering::Relaxed);
+        if self.ty == new_ty {
+            return Default::default();
+        }
+
+        self.ty = new_ty;
+
+        vec![NodeEvent::RecalcInputs(self.inputs())]
     }
 
     fn read(&self, out: &mut [Value]) {
@@ -127,6 +147,10 @@ impl Node for Glide {
         Some(Arc::clone(&self.conf) as Arc<_>)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         let mut ins = vec![Input::new("sig", ValueKind::Float)];
         match self.ty {
@@ -3,10 +3,16 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::real::RealInput, Input, Node, NodeEvent},
+    node::{inputs::real::RealInput, Input, InputData, Node, NodeEvent},
     Value, ValueKind,
 };
 
+crate::ports! {
+    OSCILLATOR = "oscillator",
+    DENSITY = "density",
+    MIX = "mix",
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Heart {
     osc: Arc<RealInput>,
@@ -16,10 +22,10 @@ pub struct Heart {
 
 #[typetag::serde]
 impl Node for Heart {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let osc = self.osc.get_f32(&data[0]).clamp(-1.0, 1.0) * 2.0;
-        let density = self.density.get_f32(&data[1]);
-        let mix = data[2].as_float().unwrap_or_default();
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let osc = self.osc.get_f32(&data[OSCILLATOR]).clamp(-1.0, 1.0) * 2.0;
+        let density = self.density.get_f32(&data[DENSITY]);
+        let mix = data[MIX].as_float().unwrap_or_default();
         self.out = osc.powi(2).powf(1.0 / 3.0)
             + (std::f32::consts::E / 3.0)
                 * (4.0 - osc * osc).sqrt()
@@ -32,6 +38,10 @@ impl Node for Heart {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::stateful("oscillator", &self.osc),
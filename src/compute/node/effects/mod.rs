@@ -3,8 +3,10 @@ use super::{Node, NodeList};
 pub mod bits;
 pub mod chorus;
 pub mod clip;
+pub mod compressor;
 pub mod glide;
 pub mod heart;
+pub mod noise_gate;
 pub mod reverb;
 pub mod reverse_delay;
 
@@ -16,8 +18,18 @@ impl NodeList for Effects {
             (bits::bits(), "Bits".into(), vec!["Effect".into()]),
             (chorus::chorus(), "Chorus".into(), vec!["Effect".into()]),
             (clip::clip(), "Clip".into(), vec!["Effect".into()]),
+            (
+                compressor::compressor(),
+                "Compressor".into(),
+                vec!["Effect".into(), "Dynamics".into()],
+            ),
             (glide::glide(), "Glide".into(), vec!["Effect".into()]),
             (heart::heart(), "Heart".into(), vec!["Effect".into()]),
+            (
+                noise_gate::noise_gate(),
+                "Noise Gate".into(),
+                vec!["Effect".into(), "Dynamics".into()],
+            ),
             (reverb::reverb(), "Reverb".into(), vec!["Effect".into()]),
             (
                 reverse_delay::reverse_delay(),
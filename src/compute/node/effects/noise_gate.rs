@@ -0,0 +1,150 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use eframe::egui::{self, Color32};
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{
+        inputs::{slider::SliderInput, time::TimeInput},
+        Input, InputData, Node, NodeConfig, NodeEvent,
+    },
+    Value, ValueKind,
+};
+
+crate::ports! {
+    SIG = "sig",
+    KEY = "key",
+    THRESHOLD = "threshold",
+    HYSTERESIS = "hysteresis",
+    FLOOR = "floor",
+    ATTACK = "attack",
+    HOLD = "hold",
+    RELEASE = "release",
+}
+
+// Release time of the level detector, in samples.
+const DETECTOR_RELEASE: f32 = 441.0;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct NoiseGateConfig {
+    // Written by the runtime, only displayed here.
+    #[serde(skip)]
+    open: AtomicBool,
+}
+
+impl NodeConfig for NoiseGateConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn std::any::Any) {
+        let (color, text) = if self.open.load(Ordering::Relaxed) {
+            (Color32::GREEN, "Open")
+        } else {
+            (Color32::RED, "Closed")
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Gate");
+            ui.label(egui::RichText::new(text).color(color));
+        });
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoiseGate {
+    config: Arc<NoiseGateConfig>,
+    threshold: Arc<SliderInput>,
+    hysteresis: Arc<SliderInput>,
+    floor: Arc<SliderInput>,
+    attack: Arc<TimeInput>,
+    hold: Arc<TimeInput>,
+    release: Arc<TimeInput>,
+    level: f32,
+    open: bool,
+    held_for: f32,
+    gain: f32,
+    out: f32,
+}
+
+#[typetag::serde]
+impl Node for NoiseGate {
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sig = data[SIG].as_float().unwrap_or_default();
+        let key = data[KEY].as_float().unwrap_or(sig).abs();
+
+        self.level = if key > self.level {
+            key
+        } else {
+            self.level * (1.0 - 1.0 / DETECTOR_RELEASE)
+        };
+
+        let threshold = self.threshold.as_f32(&data[THRESHOLD]).max(0.0);
+        let hysteresis = self.hysteresis.as_f32(&data[HYSTERESIS]).clamp(0.0, 1.0);
+        let floor = self.floor.as_f32(&data[FLOOR]).clamp(0.0, 1.0);
+
+        if self.level >= threshold {
+            self.open = true;
+            self.held_for = 0.0;
+        } else if self.open && self.level < threshold * (1.0 - hysteresis) {
+            self.held_for += 1.0;
+            if self.held_for >= self.hold.get_samples(&data[HOLD]) {
+                self.open = false;
+            }
+        }
+
+        let (target, time) = if self.open {
+            (1.0, self.attack.get_samples(&data[ATTACK]))
+        } else {
+            (floor, self.release.get_samples(&data[RELEASE]))
+        };
+        let max_step = (1.0 - floor).max(f32::EPSILON) / time.max(1.0);
+        self.gain += (target - self.gain).clamp(-max_step, max_step);
+
+        self.out = sig * self.gain;
+        self.config.open.store(self.open, Ordering::Relaxed);
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::new("sig", ValueKind::Float),
+            Input::new("key", ValueKind::Float),
+            Input::stateful("threshold", &self.threshold),
+            Input::stateful("hysteresis", &self.hysteresis),
+            Input::stateful("floor", &self.floor),
+            Input::stateful("attack", &self.attack),
+            Input::stateful("hold", &self.hold),
+            Input::stateful("release", &self.release),
+        ]
+    }
+}
+
+pub fn noise_gate() -> Box<dyn Node> {
+    Box::new(NoiseGate {
+        config: Arc::new(NoiseGateConfig::default()),
+        threshold: Arc::new(SliderInput::new(0.05, 0.0, 1.0)),
+        hysteresis: Arc::new(SliderInput::new(0.5, 0.0, 1.0)),
+        floor: Arc::new(SliderInput::new(0.0, 0.0, 1.0)),
+        attack: Arc::new(TimeInput::from_ms(1.0)),
+        hold: Arc::new(TimeInput::from_ms(50.0)),
+        release: Arc::new(TimeInput::from_ms(100.0)),
+        level: 0.0,
+        open: false,
+        held_for: 0.0,
+        gain: 0.0,
+        out: 0.0,
+    })
+}
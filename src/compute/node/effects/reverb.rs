@@ -3,13 +3,27 @@ use std::{collections::VecDeque, sync::Arc};
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
+    denormal,
     node::{
         inputs::{slider::SliderInput, time::TimeInput},
-        Input, Node, NodeEvent,
+        Input, InputData, Node, NodeEvent, Port,
     },
     Value, ValueKind,
 };
 
+crate::ports! {
+    SIG = "sig",
+    DRY_WET = "dry/wet",
+    FEEDBACK = "feedback",
+    T1 = "t1",
+    T2 = "t2",
+    T3 = "t3",
+    T4 = "t4",
+}
+
+const TIME_INPUTS: [&str; 4] = ["t1", "t2", "t3", "t4"];
+const TIME_PORTS: [Port; 4] = [T1, T2, T3, T4];
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct Reverb {
     delays: [VecDeque<f32>; 4],
@@ -21,10 +35,10 @@ struct Reverb {
 
 #[typetag::serde]
 impl Node for Reverb {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let sample = data[0].as_float().unwrap_or(0.0);
-        let drywet = self.drywet.as_f32(&data[1]);
-        let feedback = self.feedback.as_f32(&data[2]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sample = data[SIG].as_float().unwrap_or(0.0);
+        let drywet = self.drywet.as_f32(&data[DRY_WET]);
+        let feedback = self.feedback.as_f32(&data[FEEDBACK]);
 
         let outs: Vec<_> = self.delays.iter().map(|d| *d.back().unwrap()).collect();
 
@@ -40,8 +54,8 @@ impl Node for Reverb {
                         _ => unreachable!(),
                     };
 
-            delay.push_front(value);
-            let target_samples = self.times[k].get_samples(&data[3 + k]);
+            delay.push_front(denormal::protect(value));
+            let target_samples = self.times[k].get_samples(&data[TIME_PORTS[k]]);
 
             if delay.len() >= target_samples as usize {
                 delay.pop_back();
@@ -58,19 +72,30 @@ impl Node for Reverb {
         Default::default()
     }
 
+    fn panic(&mut self) {
+        for delay in &mut self.delays {
+            delay.iter_mut().for_each(|s| *s = 0.0);
+        }
+        self.out = 0.0;
+    }
+
     fn read(&self, out: &mut [Value]) {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::new("sig", ValueKind::Float),
             Input::stateful("dry/wet", &self.drywet),
             Input::stateful("feedback", &self.feedback),
-            Input::stateful("t1", &self.times[0]),
-            Input::stateful("t2", &self.times[1]),
-            Input::stateful("t3", &self.times[2]),
-            Input::stateful("t4", &self.times[3]),
+            Input::stateful(TIME_INPUTS[0], &self.times[0]),
+            Input::stateful(TIME_INPUTS[1], &self.times[1]),
+            Input::stateful(TIME_INPUTS[2], &self.times[2]),
+            Input::stateful(TIME_INPUTS[3], &self.times[3]),
         ]
     }
 }
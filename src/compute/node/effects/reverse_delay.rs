@@ -3,10 +3,15 @@ use std::{collections::VecDeque, sync::Arc};
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::time::TimeInput, Input, Node, NodeEvent},
+    node::{inputs::time::TimeInput, Input, InputData, Node, NodeEvent},
     Value, ValueKind,
 };
 
+crate::ports! {
+    SIG = "sig",
+    TIME = "time",
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ReverseDelay {
     time_in: Arc<TimeInput>,
@@ -31,9 +36,9 @@ impl ReverseDelay {
 
 #[typetag::serde]
 impl Node for ReverseDelay {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let sample = data[0].as_float().unwrap_or_default();
-        let len = self.time_in.get_samples(&data[1]).max(1.0) as usize;
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sample = data[SIG].as_float().unwrap_or_default();
+        let len = self.time_in.get_samples(&data[TIME]).max(1.0) as usize;
 
         self.record.push_back(sample);
         if self.record.len() >= len {
@@ -46,10 +51,20 @@ impl Node for ReverseDelay {
         Default::default()
     }
 
+    fn panic(&mut self) {
+        self.playback.clear();
+        self.record.clear();
+        self.out = 0.0;
+    }
+
     fn read(&self, out: &mut [Value]) {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::new("sig", ValueKind::Float),
@@ -6,14 +6,15 @@ use num_complex::Complex32;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    compute::{Value, ValueKind},
+    compute::{denormal, Output, Value, ValueKind},
     serde_atomic_enum,
     util::{enum_combo_box, toggle_button},
 };
 
 use crate::node::{
+    auto_gain::AutoGain,
     inputs::{freq::FreqInput, positive::PositiveInput},
-    Input, Node, NodeConfig, NodeEvent,
+    ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
 };
 
 use std::{
@@ -25,6 +26,13 @@ use std::{
     },
 };
 
+crate::ports! {
+    SIG = "sig",
+    F0 = "f0",
+    Q = "Q",
+    BW = "BW",
+}
+
 #[atomic_enum]
 #[derive(PartialEq, derive_more::Display, strum::EnumIter)]
 pub enum BiquadTy {
@@ -37,6 +45,12 @@ pub enum BiquadTy {
 
 serde_atomic_enum!(AtomicBiquadTy);
 
+impl Default for BiquadTy {
+    fn default() -> Self {
+        BiquadTy::Low
+    }
+}
+
 #[atomic_enum]
 #[derive(PartialEq, Serialize, Deserialize, derive_more::Display, strum::EnumIter)]
 enum ParamTy {
@@ -55,6 +69,12 @@ struct BiquadConfig {
     freq: AtomicF32,
     update_coeffs: AtomicBool,
     coeffs: Mutex<([f32; 3], [f32; 3])>,
+    #[serde(default)]
+    auto_gain: AtomicBool,
+    #[serde(default)]
+    per_channel: AtomicBool,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl BiquadConfig {
@@ -66,6 +86,9 @@ impl BiquadConfig {
             freq: AtomicF32::new(freq),
             update_coeffs: AtomicBool::new(true),
             coeffs: Mutex::new(([0.0; 3], [0.0; 3])),
+            auto_gain: AtomicBool::new(false),
+            per_channel: AtomicBool::new(false),
+            dirty: ConfigDirty::new(),
         }
     }
 
@@ -164,15 +187,27 @@ impl NodeConfig for BiquadConfig {
         let mut filt_ty = self.filt_ty.load(Ordering::Acquire);
         let mut param_ty = self.param_ty.load(Ordering::Acquire);
         let mut show_plot = self.show_plot.load(Ordering::Relaxed);
+        let mut auto_gain = self.auto_gain.load(Ordering::Acquire);
+        let mut per_channel = self.per_channel.load(Ordering::Acquire);
+
+        let mut changed = false;
 
         ui.horizontal(|ui| {
             ui.label("Type");
-            enum_combo_box(ui, &mut filt_ty);
+            changed |= enum_combo_box(ui, &mut filt_ty);
         });
         ui.horizontal(|ui| {
             ui.label("Parameter");
-            enum_combo_box(ui, &mut param_ty);
+            changed |= enum_combo_box(ui, &mut param_ty);
         });
+        changed |= ui
+            .checkbox(&mut auto_gain, "Auto gain")
+            .on_hover_text("Match the output loudness to the input")
+            .changed();
+        changed |= ui
+            .checkbox(&mut per_channel, "Per channel")
+            .on_hover_text("Filter every channel of array inputs with its own parameters")
+            .changed();
 
         ui.centered_and_justified(|ui| {
             if ui.add(toggle_button("Show Bode Plot", show_plot)).clicked() {
@@ -187,6 +222,9 @@ impl NodeConfig for BiquadConfig {
         self.filt_ty.store(filt_ty, Ordering::Release);
         self.param_ty.store(param_ty, Ordering::Release);
         self.show_plot.store(show_plot, Ordering::Relaxed);
+        self.auto_gain.store(auto_gain, Ordering::Release);
+        self.per_channel.store(per_channel, Ordering::Release);
+        self.dirty.mark_if(changed);
     }
 }
 
@@ -196,9 +234,47 @@ pub struct Biquad {
     f0: Arc<FreqInput>,
     q: Arc<PositiveInput>,
     bw: Arc<PositiveInput>,
+    #[serde(skip)]
+    filt_ty: BiquadTy,
     param_ty: ParamTy,
     in_hist: [f32; 3],
     out_hist: [f32; 2],
+    #[serde(skip)]
+    auto_gain: bool,
+    #[serde(skip)]
+    gain: AutoGain,
+    #[serde(skip)]
+    out: f32,
+    #[serde(default)]
+    per_channel: bool,
+    // Filters of the channels while `per_channel`
+    #[serde(skip)]
+    bank: Vec<Band>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Band {
+    in_hist: [f32; 3],
+    out_hist: [f32; 2],
+    gain: AutoGain,
+    out: f32,
+}
+
+// One step of the difference equation with coefficients `a` and `b`
+fn filter(
+    a: [f32; 3],
+    b: [f32; 3],
+    in_hist: &mut [f32; 3],
+    out_hist: &mut [f32; 2],
+    input: f32,
+) -> f32 {
+    *in_hist = [in_hist[1], in_hist[2], input];
+    let out = (b[0] / a[0]) * in_hist[2] + (b[1] / a[0]) * in_hist[1] + (b[2] / a[0]) * in_hist[0]
+        - (a[1] / a[0]) * out_hist[1]
+        - (a[2] / a[0]) * out_hist[0];
+
+    *out_hist = [out_hist[1], denormal::protect(out)];
+    out
 }
 
 impl Biquad {
@@ -209,37 +285,67 @@ impl Biquad {
             f0: Arc::new(FreqInput::new(freq)),
             q: Arc::new(PositiveInput::new(0.707)),
             bw: Arc::new(PositiveInput::new(1.0)),
+            filt_ty: ty,
             param_ty: ParamTy::Q,
             in_hist: [0.0; 3],
             out_hist: [0.0; 2],
+            auto_gain: false,
+            gain: AutoGain::default(),
+            out: 0.0,
+            per_channel: false,
+            bank: Vec::new(),
         }
     }
 
-    fn next(&mut self, input: f32, f0: &Value, param: &Value) {
+    pub fn next(&mut self, input: f32, f0: &Value, param: &Value) {
         let (a, b) = self.coeffs(f0, param);
 
         if self.config.update_coeffs.swap(false, Ordering::Relaxed) {
             *self.config.coeffs.lock().unwrap() = (a, b);
         }
 
-        self.in_hist = [self.in_hist[1], self.in_hist[2], input];
-        let out = (b[0] / a[0]) * self.in_hist[2]
-            + (b[1] / a[0]) * self.in_hist[1]
-            + (b[2] / a[0]) * self.in_hist[0]
-            - (a[1] / a[0]) * self.out_hist[1]
-            - (a[2] / a[0]) * self.out_hist[0];
+        self.out = filter(a, b, &mut self.in_hist, &mut self.out_hist, input);
+    }
+
+    // Filters each channel, with as many filters as the longest input has
+    // channels
+    fn next_bank(&mut self, sig: &Value, f0: &Value, param: &Value) {
+        let channels = [sig, f0, param]
+            .into_iter()
+            .map(Value::channels)
+            .max()
+            .unwrap_or_default();
+
+        let mut bank = std::mem::take(&mut self.bank);
+        bank.resize_with(channels, Default::default);
+        for (k, band) in bank.iter_mut().enumerate() {
+            let (a, b) = self.coeffs(&f0.channel(k), &param.channel(k));
+            if k == 0 && self.config.update_coeffs.swap(false, Ordering::Relaxed) {
+                *self.config.coeffs.lock().unwrap() = (a, b);
+            }
 
-        self.out_hist = [self.out_hist[1], out];
+            let input = sig.channel(k).as_float().unwrap_or_default();
+            band.out = filter(a, b, &mut band.in_hist, &mut band.out_hist, input);
+            if self.auto_gain {
+                band.out = band.gain.process(input, band.out);
+            }
+        }
+        self.bank = bank;
     }
 
-    fn coeffs(&self, f0: &Value, param: &Value) -> ([f32; 3], [f32; 3]) {
-        let ty = self.config.filt_ty.load(Ordering::Relaxed);
-        let param_ty = self.config.param_ty.load(Ordering::Relaxed);
+    fn kind(&self) -> ValueKind {
+        if self.per_channel {
+            ValueKind::FloatArray
+        } else {
+            ValueKind::Float
+        }
+    }
 
+    fn coeffs(&self, f0: &Value, param: &Value) -> ([f32; 3], [f32; 3]) {
         let f0 = self.f0.get_f32(f0);
         self.config.freq.store(f0, Ordering::Relaxed);
 
-        let param = match param_ty {
+        let param = match self.param_ty {
             ParamTy::Q => self.q.get_f32(param),
             ParamTy::Bw => self.bw.get_f32(param),
         };
@@ -248,12 +354,12 @@ impl Biquad {
         let w0sin = w0.sin();
         let w0cos = w0.cos();
 
-        let alpha = match param_ty {
+        let alpha = match self.param_ty {
             ParamTy::Q => w0sin / 2.0 / param,
             ParamTy::Bw => w0sin * (2f32.ln() / 2.0 * param * w0 / w0sin).sinh(),
         };
 
-        match ty {
+        match self.filt_ty {
             BiquadTy::Low => (
                 [1.0 + alpha, -2.0 * w0cos, 1.0 - alpha],
                 [(1.0 - w0cos) / 2.0, 1.0 - w0cos, (1.0 - w0cos) / 2.0],
@@ -280,36 +386,81 @@ impl Biquad {
 
 #[typetag::serde]
 impl Node for Biquad {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        self.next(data[0].as_float().unwrap_or_default(), &data[1], &data[2]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let param = match self.param_ty {
+            ParamTy::Q => &data[Q],
+            ParamTy::Bw => &data[BW],
+        };
+        if self.per_channel {
+            self.next_bank(&data[SIG], &data[F0], param);
+            return Default::default();
+        }
+
+        let sig = data[SIG].as_float().unwrap_or_default();
+        self.next(sig, &data[F0], param);
+
+        if self.auto_gain {
+            self.out = self.gain.process(sig, self.out);
+        }
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
+        self.filt_ty = self.config.filt_ty.load(Ordering::Relaxed);
+        self.auto_gain = self.config.auto_gain.load(Ordering::Relaxed);
+
+        let mut events = Vec::new();
+        let per_channel = self.config.per_channel.load(Ordering::Relaxed);
+        if self.per_channel != per_channel {
+            self.per_channel = per_channel;
+            self.bank.clear();
+            events.push(NodeEvent::RecalcOutputs(self.output()));
+        }
 
         let new_param_ty = self.config.param_ty.load(Ordering::Relaxed);
-        if self.param_ty != new_param_ty {
+        if self.param_ty != new_param_ty || !events.is_empty() {
             self.param_ty = new_param_ty;
-
-            vec![NodeEvent::RecalcInputs(self.inputs())]
-        } else {
-            Default::default()
+            events.push(NodeEvent::RecalcInputs(self.inputs()));
         }
+
+        events
     }
 
     fn read(&self, out: &mut [Value]) {
-        out[0] = Value::Float(self.out_hist[1])
+        if self.per_channel {
+            out[0].set_float_array(self.bank.iter().map(|band| band.out));
+        } else {
+            out[0] = Value::Float(self.out);
+        }
     }
     fn config(&self) -> Option<Arc<dyn NodeConfig>> {
         Some(Arc::clone(&self.config) as Arc<_>)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
-            Input::new("sig", ValueKind::Float),
-            Input::stateful("f0", &self.f0),
+            Input::new("sig", self.kind()),
+            Input::stateful("f0", &self.f0).with_kind(self.kind()),
             match &self.param_ty {
                 ParamTy::Q => Input::stateful("Q", &self.q),
                 ParamTy::Bw => Input::stateful("BW", &self.bw),
-            },
+            }
+            .with_kind(self.kind()),
         ]
     }
+
+    fn output(&self) -> Vec<Output> {
+        vec![Output::new("", self.kind())]
+    }
 }
 
 pub fn biquad() -> Box<dyn Node> {
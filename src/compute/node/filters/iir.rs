@@ -2,18 +2,23 @@ use atomic_enum::atomic_enum;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    compute::{node::inputs::percentage::PercentageInput, Value, ValueKind},
+    compute::{denormal, node::inputs::percentage::PercentageInput, Value, ValueKind},
     serde_atomic_enum,
     util::enum_combo_box,
 };
 
-use crate::node::{Input, Node, NodeConfig, NodeEvent};
+use crate::node::{ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent};
 
 use std::{
     any::Any,
     sync::{atomic::Ordering, Arc},
 };
 
+crate::ports! {
+    SIG = "sig",
+    DECAY = "decay",
+}
+
 #[atomic_enum]
 #[derive(PartialEq, derive_more::Display, strum::EnumIter)]
 enum IirTy {
@@ -23,15 +28,24 @@ enum IirTy {
 
 serde_atomic_enum!(AtomicIirTy);
 
+impl Default for IirTy {
+    fn default() -> Self {
+        IirTy::Lpf
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct IirConfig {
     filt_ty: AtomicIirTy,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl IirConfig {
     fn new(filt_ty: IirTy) -> Self {
         IirConfig {
             filt_ty: AtomicIirTy::new(filt_ty),
+            dirty: ConfigDirty::new(),
         }
     }
 }
@@ -40,9 +54,10 @@ impl NodeConfig for IirConfig {
     fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn Any) {
         let mut filt_ty = self.filt_ty.load(Ordering::Acquire);
 
-        enum_combo_box(ui, &mut filt_ty);
+        let changed = enum_combo_box(ui, &mut filt_ty);
 
         self.filt_ty.store(filt_ty, Ordering::Release);
+        self.dirty.mark_if(changed);
     }
 }
 
@@ -50,6 +65,8 @@ impl NodeConfig for IirConfig {
 pub struct Iir {
     config: Arc<IirConfig>,
     decay: Arc<PercentageInput>,
+    #[serde(skip)]
+    filt_ty: IirTy,
     prev_y: f32,
 }
 
@@ -58,6 +75,7 @@ impl Iir {
         Iir {
             config: Arc::new(IirConfig::new(IirTy::Lpf)),
             decay: Arc::new(PercentageInput::new(0.01)),
+            filt_ty: IirTy::Lpf,
             prev_y: 0.0,
         }
     }
@@ -65,19 +83,27 @@ impl Iir {
     fn next(&mut self, input: f32, decay: &Value) {
         let a = 1.0 - self.decay.get_f32(decay);
         let b = 1.0 - a;
-        let new_y = match self.config.filt_ty.load(Ordering::Relaxed) {
+        let new_y = match self.filt_ty {
             IirTy::Lpf => b * input + a * self.prev_y,
             IirTy::Hpf => b * input - a * self.prev_y,
         };
 
-        self.prev_y = new_y;
+        self.prev_y = denormal::protect(new_y);
     }
 }
 
 #[typetag::serde]
 impl Node for Iir {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        self.next(data[0].as_float().unwrap_or_default(), &data[1]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        self.next(data[SIG].as_float().unwrap_or_default(), &data[DECAY]);
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.filt_ty = self.config.filt_ty.load(Ordering::Relaxed);
+        }
 
         Default::default()
     }
@@ -90,6 +116,10 @@ impl Node for Iir {
         Some(Arc::clone(&self.config) as Arc<_>)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::new("sig", ValueKind::Float),
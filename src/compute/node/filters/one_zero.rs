@@ -1,10 +1,15 @@
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{node::inputs::slider::SliderInput, Value, ValueKind};
-use crate::node::{Input, Node, NodeEvent};
+use crate::node::{Input, InputData, Node, NodeEvent};
 
 use std::sync::Arc;
 
+crate::ports! {
+    SIG = "sig",
+    ZERO = "zero",
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct OneZero {
     zero: Arc<SliderInput>,
@@ -21,7 +26,7 @@ impl OneZero {
         }
     }
 
-    fn next(&mut self, input: f32, param: &Value) {
+    pub fn next(&mut self, input: f32, param: &Value) {
         let b = self.coeffs(param);
 
         self.in_hist[0] = input;
@@ -45,8 +50,8 @@ impl OneZero {
 
 #[typetag::serde]
 impl Node for OneZero {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        self.next(data[0].as_float().unwrap_or_default(), &data[1]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        self.next(data[SIG].as_float().unwrap_or_default(), &data[ZERO]);
 
         Default::default()
     }
@@ -55,6 +60,10 @@ impl Node for OneZero {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::new("sig", ValueKind::Float),
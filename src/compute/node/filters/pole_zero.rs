@@ -2,18 +2,24 @@ use atomic_enum::atomic_enum;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    compute::{node::inputs::slider::SliderInput, Value, ValueKind},
+    compute::{denormal, node::inputs::slider::SliderInput, Value, ValueKind},
     serde_atomic_enum,
     util::enum_combo_box,
 };
 
-use crate::node::{Input, Node, NodeConfig, NodeEvent};
+use crate::node::{ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent};
 
 use std::{
     any::Any,
     sync::{atomic::Ordering, Arc},
 };
 
+crate::ports! {
+    SIG = "sig",
+    C = "c",
+    POLE = "pole",
+}
+
 #[atomic_enum]
 #[derive(PartialEq, derive_more::Display, strum::EnumIter, Serialize, Deserialize)]
 pub enum PoleZeroTy {
@@ -26,12 +32,15 @@ serde_atomic_enum!(AtomicPoleZeroTy);
 #[derive(Debug, Serialize, Deserialize)]
 struct PoleZeroConfig {
     filt_ty: AtomicPoleZeroTy,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl PoleZeroConfig {
     fn new(filt_ty: PoleZeroTy) -> Self {
         PoleZeroConfig {
             filt_ty: AtomicPoleZeroTy::new(filt_ty),
+            dirty: ConfigDirty::new(),
         }
     }
 }
@@ -40,9 +49,10 @@ impl NodeConfig for PoleZeroConfig {
     fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn Any) {
         let mut filt_ty = self.filt_ty.load(Ordering::Acquire);
 
-        enum_combo_box(ui, &mut filt_ty);
+        let changed = enum_combo_box(ui, &mut filt_ty);
 
         self.filt_ty.store(filt_ty, Ordering::Release);
+        self.dirty.mark_if(changed);
     }
 }
 
@@ -79,9 +89,10 @@ impl RawPoleZero {
     pub fn feed(&mut self, input: f32) {
         self.in_hist = [self.in_hist[1], self.gain * input];
 
-        self.out = (self.b[0] / self.a[0]) * self.in_hist[1]
+        let out = (self.b[0] / self.a[0]) * self.in_hist[1]
             + (self.b[1] / self.a[0]) * self.in_hist[0]
             - (self.a[1] / self.a[0]) * self.out;
+        self.out = denormal::protect(out);
     }
 
     pub fn read(&self) -> f32 {
@@ -122,9 +133,7 @@ impl PoleZero {
     }
 
     fn coeffs(&self, param: &Value) -> ([f32; 2], [f32; 2]) {
-        let ty = self.config.filt_ty.load(Ordering::Relaxed);
-
-        match ty {
+        match self.filt_ty {
             PoleZeroTy::Allpass => {
                 let c = self.coeff.as_f32(param);
                 ([1.0, c], [c, 1.0])
@@ -139,17 +148,29 @@ impl PoleZero {
 
 #[typetag::serde]
 impl Node for PoleZero {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        self.next(data[0].as_float().unwrap_or_default(), &data[1]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let param = match self.filt_ty {
+            PoleZeroTy::Allpass => &data[C],
+            PoleZeroTy::BlockDC => &data[POLE],
+        };
+        self.next(data[SIG].as_float().unwrap_or_default(), param);
 
-        let new_filt_ty = self.config.filt_ty.load(Ordering::Relaxed);
-        if self.filt_ty != new_filt_ty {
-            self.filt_ty = new_filt_ty;
+        Default::default()
+    }
 
-            vec![NodeEvent::RecalcInputs(self.inputs())]
-        } else {
-            Default::default()
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
         }
+
+        let new_filt_ty = self.config.filt_ty.load(Ordering::Relaxed);
+        if self.filt_ty == new_filt_ty {
+            return Default::default();
+        }
+
+        self.filt_ty = new_filt_ty;
+
+        vec![NodeEvent::RecalcInputs(self.inputs())]
     }
 
     fn read(&self, out: &mut [Value]) {
@@ -159,6 +180,10 @@ impl Node for PoleZero {
         Some(Arc::clone(&self.config) as Arc<_>)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::new("sig", ValueKind::Float),
@@ -31,6 +31,14 @@ impl InputUi for AngleInput {
         ValueKind::Float
     }
 
+    fn value(&self) -> Option<f32> {
+        Some(self.s.load(Ordering::Relaxed))
+    }
+
+    fn set_value(&self, value: f32) {
+        self.s.store(value.rem_euclid(360.0), Ordering::Relaxed);
+    }
+
     fn show_disconnected(&self, ui: &mut eframe::egui::Ui, _verbose: bool) {
         let mut s = self.s.load(Ordering::Acquire);
 
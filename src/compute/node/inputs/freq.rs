@@ -27,6 +27,14 @@ impl InputUi for FreqInput {
         ValueKind::Float
     }
 
+    fn value(&self) -> Option<f32> {
+        Some(self.f.load(Ordering::Relaxed))
+    }
+
+    fn set_value(&self, value: f32) {
+        self.f.store(value, Ordering::Relaxed);
+    }
+
     fn show_disconnected(&self, ui: &mut eframe::egui::Ui, _verbose: bool) {
         let mut f = self.f.load(Ordering::Acquire);
 
@@ -38,6 +38,12 @@ impl GateInput {
         }
     }
 
+    /// Sets whether the gate is open while disconnected.
+    pub fn with_default(self, default: bool) -> Self {
+        self.default.store(default, Ordering::Relaxed);
+        self
+    }
+
     pub fn positive_edge(&self) -> bool {
         self.edge.load(Ordering::Relaxed) == Edge::Positive
     }
@@ -27,6 +27,14 @@ impl InputUi for PositiveInput {
         ValueKind::Float
     }
 
+    fn value(&self) -> Option<f32> {
+        Some(self.s.load(Ordering::Relaxed))
+    }
+
+    fn set_value(&self, value: f32) {
+        self.s.store(value.max(0.0), Ordering::Relaxed);
+    }
+
     fn show_disconnected(&self, ui: &mut eframe::egui::Ui, _verbose: bool) {
         let mut s = self.s.load(Ordering::Acquire);
         let s_old = s;
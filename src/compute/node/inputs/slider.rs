@@ -58,6 +58,20 @@ impl InputUi for SliderInput {
         ValueKind::Float
     }
 
+    fn value(&self) -> Option<f32> {
+        Some(self.s.load(Ordering::Relaxed))
+    }
+
+    fn set_value(&self, value: f32) {
+        self.s
+            .store(value.clamp(self.min, self.max), Ordering::Relaxed);
+    }
+
+    fn smoothed(&self) -> bool {
+        // Ramps would pass through fractions of whole number settings
+        !self.integral
+    }
+
     fn show_disconnected(&self, ui: &mut eframe::egui::Ui, _verbose: bool) {
         if !self.show_connected {
             self.show(ui);
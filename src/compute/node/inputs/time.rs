@@ -58,6 +58,14 @@ impl InputUi for TimeInput {
         ValueKind::Float
     }
 
+    fn value(&self) -> Option<f32> {
+        Some(self.samples.load(Ordering::Relaxed))
+    }
+
+    fn set_value(&self, value: f32) {
+        self.samples.store(value.max(0.0), Ordering::Relaxed);
+    }
+
     fn show_always(&self, ui: &mut egui::Ui, verbose: bool) {
         if verbose {
             let mut ty = self.in_ty.load(Ordering::Acquire);
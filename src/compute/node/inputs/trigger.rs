@@ -1,6 +1,7 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
 use atomic_float::AtomicF32;
+use midly::MidiMessage;
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -18,6 +19,7 @@ pub enum TriggerMode {
     Down,
     Change,
     Beat,
+    Note,
 }
 
 serde_atomic_enum!(AtomicTriggerMode);
@@ -30,6 +32,16 @@ pub struct TriggerInput {
     prev: AtomicF32,
     need_update: AtomicBool,
     force_trigger: AtomicBool,
+    #[serde(default = "default_velocity")]
+    velocity: AtomicF32,
+    #[serde(default)]
+    key: AtomicU8,
+    #[serde(default)]
+    held: AtomicBool,
+}
+
+fn default_velocity() -> AtomicF32 {
+    AtomicF32::new(1.0)
 }
 
 impl TriggerInput {
@@ -41,6 +53,9 @@ impl TriggerInput {
             prev: AtomicF32::new(0.0),
             need_update: AtomicBool::new(false),
             force_trigger: AtomicBool::new(false),
+            velocity: default_velocity(),
+            key: AtomicU8::new(0),
+            held: AtomicBool::new(false),
         }
     }
 
@@ -63,11 +78,61 @@ impl TriggerInput {
             }
             TriggerMode::Beat => self.beat.process(recv).is_some(),
             TriggerMode::Change => curr != prev,
+            TriggerMode::Note => return self.note(recv),
         };
         let force_trigger = self.force_trigger.swap(false, Ordering::Relaxed);
 
+        if do_trigger || force_trigger {
+            self.velocity.store(1.0, Ordering::Relaxed);
+        }
+
         do_trigger || force_trigger
     }
+
+    fn note(&self, recv: &Value) -> bool {
+        let force_trigger = self.force_trigger.swap(false, Ordering::Relaxed);
+        if force_trigger {
+            self.velocity.store(1.0, Ordering::Relaxed);
+        }
+
+        match recv.as_midi() {
+            Some((_, MidiMessage::NoteOn { key, vel })) if vel.as_int() > 0 => {
+                self.velocity
+                    .store(vel.as_int() as f32 / 127.0, Ordering::Relaxed);
+                self.key.store(key.as_int(), Ordering::Relaxed);
+                self.held.store(true, Ordering::Relaxed);
+                true
+            }
+            Some((_, MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. })) => {
+                if key.as_int() == self.key.load(Ordering::Relaxed) {
+                    self.held.store(false, Ordering::Relaxed);
+                }
+                force_trigger
+            }
+            _ => {
+                if recv.disconnected() {
+                    self.held.store(false, Ordering::Relaxed);
+                }
+                force_trigger
+            }
+        }
+    }
+
+    /// Strength of the last trigger in 0..1. Note mode reports the velocity
+    /// of the note that fired it, all other modes always trigger at 1.
+    pub fn velocity(&self) -> f32 {
+        self.velocity.load(Ordering::Relaxed)
+    }
+
+    /// Whether the note that fired the last trigger is still held. Always
+    /// true outside of Note mode, so instruments can use it to damp on
+    /// release without caring about the mode.
+    pub fn held(&self) -> bool {
+        match self.mode.load(Ordering::Relaxed) {
+            TriggerMode::Note => self.held.load(Ordering::Relaxed),
+            _ => true,
+        }
+    }
 }
 
 impl InputUi for TriggerInput {
@@ -76,6 +141,7 @@ impl InputUi for TriggerInput {
         match self.mode.load(Ordering::Relaxed) {
             Up | Down | Change => ValueKind::Float,
             Beat => ValueKind::Beat,
+            Note => ValueKind::Midi,
         }
     }
 
@@ -103,7 +169,7 @@ impl InputUi for TriggerInput {
                         self.beat.show_always(ui, verbose);
                     });
                 }
-                TriggerMode::Change => {
+                TriggerMode::Change | TriggerMode::Note => {
                     enum_combo_box(ui, &mut mode);
                 }
             };
@@ -13,11 +13,17 @@ use crate::compute::{
             positive::PositiveInput,
             trigger::{TriggerInput, TriggerMode},
         },
-        Input, Node, NodeEvent, NodeExt,
+        Input, InputData, Node, NodeEvent, NodeExt,
     },
     Value,
 };
 
+crate::ports! {
+    PLUCK = "pluck",
+    BOW = "bow",
+    FREQ = "freq",
+}
+
 /// This class implements a simple bowed string non-linear function,
 /// as described by Smith (1986).  The output is an instantaneous reflection
 /// coefficient value. by Perry R. Cook and Gary P. Scavone, 1995--2023.
@@ -123,8 +129,8 @@ impl Banded {
 
 #[typetag::serde]
 impl Node for Banded {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        let freq = self.freq.get_f32(&data[2]);
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let freq = self.freq.get_f32(&data[FREQ]);
         if freq != self.curr_freq {
             self.curr_freq = freq;
             Mode::set_frequency(self.modes.as_mut_slice(), freq);
@@ -138,14 +144,14 @@ impl Node for Banded {
                 .map(|Mode { delay, .. }| delay.last_out())
                 .sum::<f32>();
 
-        let bow_pressure = self.bow_pressure.get_f32(&data[1]);
+        let bow_pressure = self.bow_pressure.get_f32(&data[BOW]);
         let bow_en = bow_pressure > 0.0;
         let mut bow_input = bow_pressure - self.bow_vel;
         bow_input *= self.bow_table.compute(bow_input);
         bow_input /= self.modes.len() as f32;
 
-        if self.pluck.trigger(&data[0]) {
-            self.pluck(0.5)
+        if self.pluck.trigger(&data[PLUCK]) {
+            self.pluck(0.5 * self.pluck.velocity())
         };
 
         self.output = self.modes.iter_mut().fold(0.0, |acc, mode| {
@@ -154,11 +160,8 @@ impl Node for Banded {
                 filt_in += bow_input;
             }
 
-            mode.bandpass.feed(&[
-                Value::Float(filt_in),
-                Value::Disconnected,
-                Value::Disconnected,
-            ]);
+            mode.bandpass
+                .next(filt_in, &Value::Disconnected, &Value::Disconnected);
 
             let filt_out = mode.bandpass.read_f32();
             mode.delay.push(filt_out);
@@ -169,10 +172,30 @@ impl Node for Banded {
         Vec::default()
     }
 
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        // Mode filters are fed directly, so they have to be synced from here
+        for mode in &mut self.modes {
+            mode.bandpass.sync_config();
+        }
+
+        Vec::default()
+    }
+
+    fn panic(&mut self) {
+        for mode in &mut self.modes {
+            mode.delay.clear();
+        }
+        self.output = 0.0;
+    }
+
     fn read(&self, out: &mut [Value]) {
         out[0] = Value::Float(self.output);
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::stateful("pluck", &self.pluck),
@@ -1,17 +1,27 @@
 use std::{f32::consts::PI, sync::Arc};
 
 use rand::Rng;
+use rand_chacha::ChaCha12Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
+    deterministic,
     node::{
         all::{delay::RawDelay, one_zero::OneZero, pole_zero::RawPoleZero},
         inputs::{percentage::PercentageInput, real::RealInput},
-        Input, Node, NodeEvent, NodeExt,
+        Input, InputData, Node, NodeEvent, NodeExt,
     },
     Value,
 };
 
+crate::ports! {
+    PRESSURE = "pressure",
+    NOISE = "noise",
+    VIBRATO = "vibrato",
+    VENT = "vent",
+    TONEHOLE = "tonehole",
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct ReedTable {
     offset: f32,
@@ -46,6 +56,8 @@ pub struct BlowHole {
     scatter: f32,
     th_coeff: f32,
     rh_gain: f32,
+    #[serde(default = "deterministic::rng")]
+    rng: ChaCha12Rng,
 
     out: f32,
 }
@@ -103,6 +115,7 @@ impl BlowHole {
             scatter,
             th_coeff,
             rh_gain,
+            rng: deterministic::rng(),
 
             out: 0.0,
         };
@@ -119,7 +132,7 @@ impl BlowHole {
         }
         self.tonehole.feed(0.0);
         self.vent.feed(0.0);
-        self.filt.feed(&[Value::Float(0.0), Value::Disconnected]);
+        self.filt.next(0.0, &Value::Disconnected);
     }
 
     pub fn set_freq(&mut self, f: f32) {
@@ -152,11 +165,11 @@ impl BlowHole {
 
 #[typetag::serde]
 impl Node for BlowHole {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
         let pressure = {
-            let mut raw = self.pressure.get_f32(&data[0]);
-            let noise = rand::thread_rng().gen_range(-0.2..0.2); //self.noise.get_f32(&data[1]);
-            let vibrato = 0.0; //self.vibrato.get_f32(&data[2]);
+            let mut raw = self.pressure.get_f32(&data[PRESSURE]);
+            let noise = self.rng.gen_range(-0.2..0.2); //self.noise.get_f32(&data[NOISE]);
+            let vibrato = 0.0; //self.vibrato.get_f32(&data[VIBRATO]);
 
             raw += raw * noise;
             raw += raw * vibrato;
@@ -164,8 +177,8 @@ impl Node for BlowHole {
             raw
         };
 
-        self.set_vent(self.vent_in.get_f32(&data[3]));
-        self.set_tonehole(self.tonehole_in.get_f32(&data[4]));
+        self.set_vent(self.vent_in.get_f32(&data[VENT]));
+        self.set_tonehole(self.tonehole_in.get_f32(&data[TONEHOLE]));
 
         // Calculate the differential pressure = reflected - mouthpiece pressures
         let p_diff = self.delays[0].last_out() - pressure;
@@ -185,8 +198,7 @@ impl Node for BlowHole {
         let pth = self.tonehole.read();
         let temp = self.scatter * (pa2 + pb2 - 2.0 * pth);
 
-        self.filt
-            .feed(&[Value::Float(pa2 + temp), Value::Disconnected]);
+        self.filt.next(pa2 + temp, &Value::Disconnected);
         self.delays[2].push(self.filt.read_f32() * -0.95);
         self.delays[1].push(pb2 + temp);
         self.tonehole.feed(pa2 + pb2 - pth + temp);
@@ -194,10 +206,19 @@ impl Node for BlowHole {
         Vec::default()
     }
 
+    fn panic(&mut self) {
+        self.clear();
+        self.out = 0.0;
+    }
+
     fn read(&self, out: &mut [Value]) {
         out[0] = Value::Float(self.out);
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::stateful("pressure", &self.pressure),
@@ -207,4 +228,8 @@ impl Node for BlowHole {
             Input::stateful("tonehole", &self.tonehole_in),
         ]
     }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = deterministic::seeded(seed);
+    }
 }
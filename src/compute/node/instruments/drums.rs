@@ -0,0 +1,33 @@
+mod hat;
+mod kick;
+mod snare;
+
+pub use hat::hat;
+pub use kick::kick;
+pub use snare::snare;
+
+use serde::{Deserialize, Serialize};
+
+/// Exponential decay envelope, retriggered from full level.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Decay {
+    level: f32,
+}
+
+impl Decay {
+    fn new() -> Self {
+        Decay { level: 0.0 }
+    }
+
+    fn trigger(&mut self) {
+        self.level = 1.0;
+    }
+
+    // Decays by 60dB over `length` samples
+    fn step(&mut self, length: f32) -> f32 {
+        let out = self.level;
+        self.level *= 0.001f32.powf(1.0 / length.max(1.0));
+
+        out
+    }
+}
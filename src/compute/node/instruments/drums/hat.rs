@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{
+        inputs::{
+            freq::FreqInput,
+            time::TimeInput,
+            trigger::{TriggerInput, TriggerMode},
+        },
+        Input, InputData, Node, NodeEvent,
+    },
+    Value,
+};
+
+use super::Decay;
+
+crate::ports! {
+    TRIGGER = "trigger",
+    FREQ = "freq",
+    DECAY = "decay",
+}
+
+// Inharmonic square wave ratios, as found in classic analog drum machines
+static RATIOS: [f32; 6] = [2.0, 3.0, 4.16, 5.43, 6.79, 8.21];
+
+/// Bank of detuned square oscillators, high-passed and enveloped.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Hat {
+    trigger: Arc<TriggerInput>,
+    freq: Arc<FreqInput>,
+    decay: Arc<TimeInput>,
+    env: Decay,
+    phases: [f32; 6],
+    hp_hist: [f32; 2],
+    hp_out: [f32; 2],
+    out: f32,
+}
+
+impl Hat {
+    fn new() -> Self {
+        Hat {
+            trigger: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
+            freq: Arc::new(FreqInput::new(40.0)),
+            decay: Arc::new(TimeInput::from_ms(80.0)),
+            env: Decay::new(),
+            phases: [0.0; 6],
+            hp_hist: [0.0; 2],
+            hp_out: [0.0; 2],
+            out: 0.0,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Node for Hat {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if self.trigger.trigger(&data[TRIGGER]) {
+            self.env.trigger();
+        }
+
+        let freq = self.freq.get_f32(&data[FREQ]);
+        let decay = self.decay.get_samples(&data[DECAY]);
+
+        let mut metal = 0.0;
+        for (phase, ratio) in self.phases.iter_mut().zip(RATIOS) {
+            *phase = (*phase + freq * ratio / 44100.0) % 1.0;
+            metal += if *phase < 0.5 { 1.0 } else { -1.0 };
+        }
+        metal /= RATIOS.len() as f32;
+
+        // Two one-pole high-pass stages keep only the sizzle
+        let mut sig = metal;
+        for (hist, out) in self.hp_hist.iter_mut().zip(&mut self.hp_out) {
+            *out = 0.6 * (*out + sig - *hist);
+            *hist = sig;
+            sig = *out;
+        }
+
+        self.out = sig * self.env.step(decay);
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::stateful("trigger", &self.trigger),
+            Input::stateful("freq", &self.freq),
+            Input::stateful("decay", &self.decay),
+        ]
+    }
+}
+
+pub fn hat() -> Box<dyn Node> {
+    Box::new(Hat::new())
+}
@@ -0,0 +1,122 @@
+use std::{f32::consts::PI, sync::Arc};
+
+use rand::Rng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    deterministic,
+    node::{
+        inputs::{
+            freq::FreqInput,
+            percentage::PercentageInput,
+            time::TimeInput,
+            trigger::{TriggerInput, TriggerMode},
+        },
+        Input, InputData, Node, NodeEvent,
+    },
+    Value,
+};
+
+use super::Decay;
+
+crate::ports! {
+    TRIGGER = "trigger",
+    F_START = "f start",
+    F_END = "f end",
+    SWEEP = "sweep",
+    DECAY = "decay",
+    CLICK = "click",
+}
+
+/// Pitch-swept sine with a short noise click on top.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Kick {
+    trigger: Arc<TriggerInput>,
+    f_start: Arc<FreqInput>,
+    f_end: Arc<FreqInput>,
+    sweep: Arc<TimeInput>,
+    decay: Arc<TimeInput>,
+    click: Arc<PercentageInput>,
+    amp_env: Decay,
+    pitch_env: Decay,
+    click_env: Decay,
+    phase: f32,
+    rng: ChaCha12Rng,
+    out: f32,
+}
+
+impl Kick {
+    fn new() -> Self {
+        Kick {
+            trigger: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
+            f_start: Arc::new(FreqInput::new(180.0)),
+            f_end: Arc::new(FreqInput::new(45.0)),
+            sweep: Arc::new(TimeInput::from_ms(60.0)),
+            decay: Arc::new(TimeInput::from_ms(500.0)),
+            click: Arc::new(PercentageInput::new(30.0)),
+            amp_env: Decay::new(),
+            pitch_env: Decay::new(),
+            click_env: Decay::new(),
+            phase: 0.0,
+            rng: deterministic::rng(),
+            out: 0.0,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Node for Kick {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if self.trigger.trigger(&data[TRIGGER]) {
+            self.amp_env.trigger();
+            self.pitch_env.trigger();
+            self.click_env.trigger();
+            self.phase = 0.0;
+        }
+
+        let f_start = self.f_start.get_f32(&data[F_START]);
+        let f_end = self.f_end.get_f32(&data[F_END]);
+        let sweep = self.sweep.get_samples(&data[SWEEP]);
+        let decay = self.decay.get_samples(&data[DECAY]);
+        let click = self.click.get_f32(&data[CLICK]);
+
+        let freq = f_end + (f_start - f_end) * self.pitch_env.step(sweep);
+        self.phase = (self.phase + freq / 44100.0) % 1.0;
+
+        let body = (2.0 * PI * self.phase).sin() * self.amp_env.step(decay);
+        let noise: f32 = self.rng.gen_range(-1.0..=1.0);
+        let transient = noise * self.click_env.step(44.1 * 3.0) * click;
+
+        self.out = body + transient;
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::stateful("trigger", &self.trigger),
+            Input::stateful("f start", &self.f_start),
+            Input::stateful("f end", &self.f_end),
+            Input::stateful("sweep", &self.sweep),
+            Input::stateful("decay", &self.decay),
+            Input::stateful("click", &self.click),
+        ]
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = deterministic::seeded(seed);
+    }
+}
+
+pub fn kick() -> Box<dyn Node> {
+    Box::new(Kick::new())
+}
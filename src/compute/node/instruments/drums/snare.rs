@@ -0,0 +1,115 @@
+use std::{f32::consts::PI, sync::Arc};
+
+use rand::Rng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    deterministic,
+    node::{
+        inputs::{
+            freq::FreqInput,
+            percentage::PercentageInput,
+            time::TimeInput,
+            trigger::{TriggerInput, TriggerMode},
+        },
+        Input, InputData, Node, NodeEvent,
+    },
+    Value,
+};
+
+use super::Decay;
+
+crate::ports! {
+    TRIGGER = "trigger",
+    TONE = "tone",
+    DECAY = "decay",
+    SNAPPY = "snappy",
+}
+
+/// Short sine tone mixed with high-passed noise.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Snare {
+    trigger: Arc<TriggerInput>,
+    tone: Arc<FreqInput>,
+    decay: Arc<TimeInput>,
+    snappy: Arc<PercentageInput>,
+    tone_env: Decay,
+    noise_env: Decay,
+    phase: f32,
+    prev_noise: f32,
+    hp_out: f32,
+    rng: ChaCha12Rng,
+    out: f32,
+}
+
+impl Snare {
+    fn new() -> Self {
+        Snare {
+            trigger: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
+            tone: Arc::new(FreqInput::new(185.0)),
+            decay: Arc::new(TimeInput::from_ms(200.0)),
+            snappy: Arc::new(PercentageInput::new(60.0)),
+            tone_env: Decay::new(),
+            noise_env: Decay::new(),
+            phase: 0.0,
+            prev_noise: 0.0,
+            hp_out: 0.0,
+            rng: deterministic::rng(),
+            out: 0.0,
+        }
+    }
+}
+
+#[typetag::serde]
+impl Node for Snare {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if self.trigger.trigger(&data[TRIGGER]) {
+            self.tone_env.trigger();
+            self.noise_env.trigger();
+            self.phase = 0.0;
+        }
+
+        let tone = self.tone.get_f32(&data[TONE]);
+        let decay = self.decay.get_samples(&data[DECAY]);
+        let snappy = self.snappy.get_f32(&data[SNAPPY]).clamp(0.0, 1.0);
+
+        self.phase = (self.phase + tone / 44100.0) % 1.0;
+        let body = (2.0 * PI * self.phase).sin() * self.tone_env.step(decay * 0.5);
+
+        // One-pole high-pass takes the low end out of the noise
+        let noise: f32 = self.rng.gen_range(-1.0..=1.0);
+        self.hp_out = 0.9 * (self.hp_out + noise - self.prev_noise);
+        self.prev_noise = noise;
+        let rattle = self.hp_out * self.noise_env.step(decay);
+
+        self.out = body * (1.0 - snappy) + rattle * snappy;
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::stateful("trigger", &self.trigger),
+            Input::stateful("tone", &self.tone),
+            Input::stateful("decay", &self.decay),
+            Input::stateful("snappy", &self.snappy),
+        ]
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.rng = deterministic::seeded(seed);
+    }
+}
+
+pub fn snare() -> Box<dyn Node> {
+    Box::new(Snare::new())
+}
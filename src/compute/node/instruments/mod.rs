@@ -1,5 +1,6 @@
 mod banded;
 mod blow_hole;
+mod drums;
 mod twang;
 
 use banded::BandedPreset;
@@ -41,6 +42,21 @@ impl NodeList for Instruments {
                 "Uniform Bar".to_string(),
                 vec!["Instrument".to_string()],
             ),
+            (
+                drums::kick(),
+                "Kick".to_string(),
+                vec!["Instrument".to_string(), "Drum".to_string()],
+            ),
+            (
+                drums::snare(),
+                "Snare".to_string(),
+                vec!["Instrument".to_string(), "Drum".to_string()],
+            ),
+            (
+                drums::hat(),
+                "Hat".to_string(),
+                vec!["Instrument".to_string(), "Drum".to_string()],
+            ),
         ]
     }
 }
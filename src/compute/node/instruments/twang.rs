@@ -11,12 +11,23 @@ use serde::{Deserialize, Serialize};
 use crate::compute::{
     node::{
         all::delay::{RawDelay, ResizeStrategy},
-        inputs::{freq::FreqInput, percentage::PercentageInput},
-        Input, Node, NodeConfig, NodeEvent,
+        inputs::{
+            freq::FreqInput,
+            percentage::PercentageInput,
+            trigger::{TriggerInput, TriggerMode},
+        },
+        Input, InputData, Node, NodeConfig, NodeEvent,
     },
     Value, ValueKind,
 };
 
+crate::ports! {
+    SIG = "sig",
+    FREQ = "freq",
+    PLUCK_AT = "pluck at",
+    PLUCK = "pluck",
+}
+
 static RANDOM: [f32; 20] = [
     -0.974084759373488,
     -0.23180725390965073,
@@ -112,6 +123,8 @@ impl NodeConfig for TwangConfig {
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct Twang {
     config: Arc<TwangConfig>,
+    #[serde(default = "default_pluck")]
+    pluck_input: Arc<TriggerInput>,
     pluck_pos_input: Arc<PercentageInput>,
     freq_input: Arc<FreqInput>,
 
@@ -125,12 +138,17 @@ pub struct Twang {
     pluck_pos: f32,
 }
 
+fn default_pluck() -> Arc<TriggerInput> {
+    Arc::new(TriggerInput::new(TriggerMode::Up, 0.5))
+}
+
 impl Twang {
     pub fn new() -> Self {
         let mut this = Twang {
             config: Arc::new(TwangConfig {
                 pluck: AtomicBool::new(false),
             }),
+            pluck_input: default_pluck(),
             pluck_pos_input: Arc::new(PercentageInput::new(40.0)),
             freq_input: Arc::new(FreqInput::new(220.0)),
 
@@ -168,6 +186,12 @@ impl Twang {
         self.loop_filt.gain = gain;
     }
 
+    fn pluck(&mut self, amplitude: f32) {
+        for r in RANDOM.iter() {
+            self.tick(*r * amplitude);
+        }
+    }
+
     fn tick(&mut self, input: f32) {
         let filt_out = self.loop_filt.tick(self.delay_line.last_out());
         self.delay_line.push(input + filt_out);
@@ -182,34 +206,54 @@ impl Twang {
 
 #[typetag::serde]
 impl Node for Twang {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
         if self.config.pluck.fetch_and(false, Ordering::Relaxed) {
-            for r in RANDOM.iter() {
-                self.tick(*r);
-            }
+            self.pluck(1.0);
         }
 
-        let new_freq = self.freq_input.get_f32(&data[1]);
-        let new_pluck_pos = self.pluck_pos_input.get_f32(&data[2]);
+        if self.pluck_input.trigger(&data[PLUCK]) {
+            self.pluck(self.pluck_input.velocity());
+        }
+
+        // Damp the string once a note-triggered pluck is released
+        let loop_gain = if self.pluck_input.held() { 0.995 } else { 0.95 };
+        if loop_gain != self.loop_gain {
+            self.set_loop_gain(loop_gain);
+        }
+
+        let new_freq = self.freq_input.get_f32(&data[FREQ]);
+        let new_pluck_pos = self.pluck_pos_input.get_f32(&data[PLUCK_AT]);
         if new_freq != self.freq || new_pluck_pos != self.pluck_pos {
             self.pluck_pos = new_pluck_pos;
             self.set_frequency(new_freq);
         }
 
-        self.tick(data[0].as_float().unwrap_or_default());
+        self.tick(data[SIG].as_float().unwrap_or_default());
 
         Vec::default()
     }
 
+    fn panic(&mut self) {
+        self.delay_line.clear();
+        self.comb_delay.clear();
+        self.out = 0.0;
+    }
+
     fn read(&self, out: &mut [Value]) {
         out[0] = Value::Float(self.out);
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![
             Input::new("sig", ValueKind::Float),
             Input::stateful("freq", &self.freq_input),
             Input::stateful("pluck at", &self.pluck_pos_input),
+            // Added last, so wires of saved patches keep their inputs
+            Input::stateful("pluck", &self.pluck_input),
         ]
     }
 
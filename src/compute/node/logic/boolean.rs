@@ -0,0 +1,135 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute::{
+        node::{
+            inputs::gate::GateInput, ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
+        },
+        Value,
+    },
+    serde_atomic_enum,
+    util::enum_combo_box,
+};
+
+crate::ports! {
+    A = "a",
+    B = "b",
+}
+
+#[atomic_enum::atomic_enum]
+#[derive(PartialEq, Eq, derive_more::Display, strum::EnumIter)]
+pub enum Operation {
+    #[display(fmt = "AND")]
+    And,
+    #[display(fmt = "OR")]
+    Or,
+    #[display(fmt = "XOR")]
+    Xor,
+    #[display(fmt = "NOT")]
+    Not,
+}
+
+serde_atomic_enum!(AtomicOperation);
+
+impl Operation {
+    fn apply(self, a: bool, b: bool) -> bool {
+        match self {
+            Operation::And => a && b,
+            Operation::Or => a || b,
+            Operation::Xor => a != b,
+            Operation::Not => !a,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BooleanConfig {
+    ty: AtomicOperation,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for BooleanConfig {
+    fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn std::any::Any) {
+        let mut ty = self.ty.load(Ordering::Acquire);
+
+        let changed = enum_combo_box(ui, &mut ty);
+
+        self.ty.store(ty, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
+/// Boolean operation over gates, open at 1 and closed at 0.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Boolean {
+    conf: Arc<BooleanConfig>,
+    a: Arc<GateInput>,
+    b: Arc<GateInput>,
+    #[serde(skip)]
+    ty: Option<Operation>,
+    out: f32,
+}
+
+#[typetag::serde]
+impl Node for Boolean {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let ty = self.ty.unwrap_or(Operation::And);
+        let a = self.a.gate(&data[A]);
+        let b = ty != Operation::Not && self.b.gate(&data[B]);
+
+        self.out = if ty.apply(a, b) { 1.0 } else { 0.0 };
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.conf.dirty.take() {
+            return Default::default();
+        }
+
+        let ty = self.conf.ty.load(Ordering::Relaxed);
+        let was_not = self.ty == Some(Operation::Not);
+        self.ty = Some(ty);
+        if was_not == (ty == Operation::Not) {
+            return Default::default();
+        }
+
+        vec![NodeEvent::RecalcInputs(self.inputs())]
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.conf) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        if self.conf.ty.load(Ordering::Relaxed) == Operation::Not {
+            vec![Input::stateful("a", &self.a)]
+        } else {
+            vec![Input::stateful("a", &self.a), Input::stateful("b", &self.b)]
+        }
+    }
+}
+
+pub fn boolean() -> Box<dyn Node> {
+    Box::new(Boolean {
+        conf: Arc::new(BooleanConfig {
+            ty: AtomicOperation::new(Operation::And),
+            dirty: ConfigDirty::new(),
+        }),
+        a: Arc::new(GateInput::new(0.5)),
+        b: Arc::new(GateInput::new(0.5)),
+        ty: None,
+        out: 0.0,
+    })
+}
@@ -0,0 +1,160 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute::{
+        node::{
+            inputs::{positive::PositiveInput, real::RealInput},
+            ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
+        },
+        Value, ValueKind,
+    },
+    serde_atomic_enum,
+    util::enum_combo_box,
+};
+
+crate::ports! {
+    SIG = "sig",
+    THRESHOLD = "threshold",
+    LOW = "low",
+    HIGH = "high",
+    HYSTERESIS = "hysteresis",
+}
+
+#[atomic_enum::atomic_enum]
+#[derive(PartialEq, Eq, derive_more::Display, strum::EnumIter)]
+pub enum Comparison {
+    #[display(fmt = ">")]
+    Greater,
+    #[display(fmt = "<")]
+    Less,
+    Window,
+}
+
+serde_atomic_enum!(AtomicComparison);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ComparatorConfig {
+    ty: AtomicComparison,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for ComparatorConfig {
+    fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn std::any::Any) {
+        let mut ty = self.ty.load(Ordering::Acquire);
+
+        let changed = enum_combo_box(ui, &mut ty);
+
+        self.ty.store(ty, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
+/// Opens while the signal is past the threshold, or between the bounds of
+/// the window. Hysteresis keeps a noisy signal from flipping it near a
+/// threshold, it only flips once past by half of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Comparator {
+    conf: Arc<ComparatorConfig>,
+    threshold: Arc<RealInput>,
+    low: Arc<RealInput>,
+    high: Arc<RealInput>,
+    hysteresis: Arc<PositiveInput>,
+    #[serde(skip)]
+    ty: Option<Comparison>,
+    open: bool,
+}
+
+// Whether `x` is past `threshold`, keeping `open` within `margin` of it
+fn above(x: f32, threshold: f32, margin: f32, open: bool) -> bool {
+    if open {
+        x > threshold - margin
+    } else {
+        x > threshold + margin
+    }
+}
+
+#[typetag::serde]
+impl Node for Comparator {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sig = data[SIG].as_float().unwrap_or_default();
+        let margin = self.hysteresis.get_f32(&data[HYSTERESIS]) / 2.0;
+
+        self.open = match self.ty.unwrap_or(Comparison::Greater) {
+            Comparison::Greater => {
+                let threshold = self.threshold.get_f32(&data[THRESHOLD]);
+                above(sig, threshold, margin, self.open)
+            }
+            Comparison::Less => {
+                let threshold = self.threshold.get_f32(&data[THRESHOLD]);
+                above(-sig, -threshold, margin, self.open)
+            }
+            Comparison::Window => {
+                let low = self.low.get_f32(&data[LOW]);
+                let high = self.high.get_f32(&data[HIGH]);
+                above(sig, low, margin, self.open) && above(-sig, -high, margin, self.open)
+            }
+        };
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.conf.dirty.take() {
+            return Default::default();
+        }
+
+        let ty = self.conf.ty.load(Ordering::Relaxed);
+        if Some(ty) == self.ty {
+            return Default::default();
+        }
+        self.ty = Some(ty);
+
+        vec![NodeEvent::RecalcInputs(self.inputs())]
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(if self.open { 1.0 } else { 0.0 })
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.conf) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        let mut inputs = vec![Input::new("sig", ValueKind::Float)];
+        match self.conf.ty.load(Ordering::Relaxed) {
+            Comparison::Greater | Comparison::Less => {
+                inputs.push(Input::stateful("threshold", &self.threshold));
+            }
+            Comparison::Window => {
+                inputs.push(Input::stateful("low", &self.low));
+                inputs.push(Input::stateful("high", &self.high));
+            }
+        }
+        inputs.push(Input::stateful("hysteresis", &self.hysteresis));
+
+        inputs
+    }
+}
+
+pub fn comparator() -> Box<dyn Node> {
+    Box::new(Comparator {
+        conf: Arc::new(ComparatorConfig {
+            ty: AtomicComparison::new(Comparison::Greater),
+            dirty: ConfigDirty::new(),
+        }),
+        threshold: Arc::new(RealInput::new(0.0)),
+        low: Arc::new(RealInput::new(-0.5)),
+        high: Arc::new(RealInput::new(0.5)),
+        hysteresis: Arc::new(PositiveInput::new(0.0)),
+        ty: None,
+        open: false,
+    })
+}
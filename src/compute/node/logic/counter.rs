@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{
+        inputs::{
+            slider::SliderInput,
+            trigger::{TriggerInput, TriggerMode},
+        },
+        Input, InputData, Node, NodeEvent,
+    },
+    Output, Value, ValueKind,
+};
+
+crate::ports! {
+    TRIGGER = "trigger",
+    RESET = "reset",
+    STEPS = "steps",
+}
+
+/// Counts triggers from 0 up to one less than `steps`, then starts over.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Counter {
+    trigger: Arc<TriggerInput>,
+    reset: Arc<TriggerInput>,
+    steps: Arc<SliderInput>,
+    count: u32,
+    wrapped: bool,
+}
+
+#[typetag::serde]
+impl Node for Counter {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let steps = self.steps.as_f32(&data[STEPS]).round().max(1.0) as u32;
+        self.wrapped = false;
+
+        if self.reset.trigger(&data[RESET]) {
+            self.count = 0;
+        } else if self.trigger.trigger(&data[TRIGGER]) {
+            self.count += 1;
+            if self.count >= steps {
+                self.count = 0;
+                self.wrapped = true;
+            }
+        }
+        // Fewer steps than the count has reached start it over
+        self.count %= steps;
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.count as f32);
+        out[1] = Value::Float(if self.wrapped { 1.0 } else { 0.0 });
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::stateful("trigger", &self.trigger),
+            Input::stateful("reset", &self.reset),
+            Input::stateful("steps", &self.steps),
+        ]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![
+            Output::new("count", ValueKind::Float),
+            Output::new("wrap", ValueKind::Float),
+        ]
+    }
+}
+
+pub fn counter() -> Box<dyn Node> {
+    Box::new(Counter {
+        trigger: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
+        reset: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
+        steps: Arc::new(SliderInput::new(8.0, 1.0, 64.0).integral(true)),
+        count: 0,
+        wrapped: false,
+    })
+}
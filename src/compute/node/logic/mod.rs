@@ -0,0 +1,21 @@
+use super::{Node, NodeList};
+
+pub mod boolean;
+pub mod comparator;
+pub mod counter;
+
+pub struct Logic;
+
+impl NodeList for Logic {
+    fn all(&self) -> Vec<(Box<dyn Node>, String, Vec<String>)> {
+        vec![
+            (boolean::boolean(), "Boolean".into(), vec!["Logic".into()]),
+            (
+                comparator::comparator(),
+                "Comparator".into(),
+                vec!["Logic".into()],
+            ),
+            (counter::counter(), "Counter".into(), vec!["Logic".into()]),
+        ]
+    }
+}
@@ -0,0 +1,151 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use eframe::egui::{self, DragValue};
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    extern_midi::{self, Message},
+    node::{
+        inputs::{beat::BeatInput, gate::GateInput},
+        ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
+    },
+    Value,
+};
+
+crate::ports! {
+    BEAT = "beat",
+    RUN = "run",
+}
+
+// MIDI clock ticks per quarter note
+const PPQN: usize = 24;
+// Length of the pulse on every quarter note, 10 ms
+const PULSE_LEN: usize = 441;
+
+const TIMING_CLOCK: Message = Message::status(0xF8);
+const START: Message = Message::status(0xFA);
+const STOP: Message = Message::status(0xFC);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ClockOutConfig {
+    // 1-based, like the port names
+    port: AtomicUsize,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for ClockOutConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mut port = self.port.load(Ordering::Acquire);
+
+        ui.horizontal(|ui| {
+            ui.label("Port");
+            let changed = ui.add(DragValue::new(&mut port).range(1..=16)).changed();
+
+            self.port.store(port, Ordering::Release);
+            self.dirty.mark_if(changed);
+        });
+    }
+}
+
+/// Sends MIDI clock following its beat input to one of the JACK MIDI outputs
+/// and outputs a short pulse on every quarter note.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ClockOut {
+    config: Arc<ClockOutConfig>,
+    beat: Arc<BeatInput>,
+    run: Arc<GateInput>,
+    // Samples between ticks of the last beat
+    tick_len: f32,
+    // Samples since the last beat and ticks sent since
+    since_beat: usize,
+    ticks: usize,
+    out: f32,
+    #[serde(skip)]
+    port: usize,
+}
+
+#[typetag::serde]
+impl Node for ClockOut {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let running = self.run.gate(&data[RUN]);
+        if self.run.positive_edge() {
+            extern_midi::push(self.port.saturating_sub(1), START);
+        } else if self.run.negative_edge() {
+            extern_midi::push(self.port.saturating_sub(1), STOP);
+        }
+
+        // Every beat restarts the ticks, so the clock never drifts away
+        if let Some(beat) = self.beat.process(&data[BEAT]) {
+            self.tick_len = beat.period_secs * 44100.0 / PPQN as f32;
+            self.since_beat = 0;
+            self.ticks = 0;
+        }
+
+        let due = self.ticks < PPQN && self.since_beat as f32 >= self.ticks as f32 * self.tick_len;
+        if self.tick_len > 0.0 && due {
+            if running {
+                extern_midi::push(self.port.saturating_sub(1), TIMING_CLOCK);
+            }
+            self.ticks += 1;
+        }
+
+        self.out = if running && self.tick_len > 0.0 && self.since_beat < PULSE_LEN {
+            1.0
+        } else {
+            0.0
+        };
+        self.since_beat += 1;
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out);
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.port = self.config.port.load(Ordering::Relaxed);
+        }
+
+        Default::default()
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::stateful("beat", &self.beat),
+            Input::stateful("run", &self.run),
+        ]
+    }
+}
+
+pub fn clock_out() -> Box<dyn Node> {
+    Box::new(ClockOut {
+        config: Arc::new(ClockOutConfig {
+            port: AtomicUsize::new(1),
+            dirty: ConfigDirty::new(),
+        }),
+        beat: Arc::new(BeatInput::new(false)),
+        run: Arc::new(GateInput::new(0.5).with_default(true)),
+        tick_len: 0.0,
+        since_beat: 0,
+        ticks: 0,
+        out: 0.0,
+        port: 1,
+    })
+}
@@ -0,0 +1,343 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use eframe::egui::{self, DragValue};
+use midly::MidiMessage;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute::{
+        node::{
+            inputs::midi::MidiInput, ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
+        },
+        Value,
+    },
+    util::load_sample,
+};
+
+crate::ports! {
+    MIDI = "midi",
+}
+
+const PADS: usize = 16;
+// Voices sounding at once, the oldest one is cut to make room
+const VOICES: usize = 32;
+// Choke groups besides 0, which doesn't choke
+const CHOKE_GROUPS: u8 = 8;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Pad {
+    key: u8,
+    path: Option<PathBuf>,
+    // In dB
+    gain: f32,
+    // In semitones
+    pitch: f32,
+    choke: u8,
+}
+
+impl Pad {
+    // Laid out from General MIDI kick up, a semitone apart
+    fn new(k: usize) -> Self {
+        Pad {
+            key: 36 + k as u8,
+            path: None,
+            gain: 0.0,
+            pitch: 0.0,
+            choke: 0,
+        }
+    }
+
+    fn name(&self) -> String {
+        self.path
+            .as_ref()
+            .and_then(|path| path.file_stem())
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Empty".into())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DrumSamplerConfig {
+    #[serde(with = "crate::util::serde_mutex")]
+    pads: Mutex<Vec<Pad>>,
+    #[serde(skip)]
+    selected: AtomicUsize,
+    // Pads the last loading failed on, with the reason
+    #[serde(skip)]
+    errors: Mutex<Vec<(usize, String)>>,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl DrumSamplerConfig {
+    fn new() -> Self {
+        DrumSamplerConfig {
+            pads: Mutex::new((0..PADS).map(Pad::new).collect()),
+            selected: AtomicUsize::new(0),
+            errors: Mutex::new(Vec::new()),
+            dirty: ConfigDirty::new(),
+        }
+    }
+}
+
+impl NodeConfig for DrumSamplerConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mut pads = self.pads.lock().unwrap();
+        let mut selected = self.selected.load(Ordering::Acquire).min(PADS - 1);
+        let mut changed = false;
+
+        // Files dropped on the window land on the pad under the pointer
+        let dropped = ui.ctx().input(|i| {
+            i.raw
+                .dropped_files
+                .first()
+                .and_then(|file| file.path.clone())
+        });
+        let errors = self.errors.lock().unwrap();
+
+        egui::Grid::new(ui.next_auto_id()).show(ui, |ui| {
+            for (k, pad) in pads.iter_mut().enumerate() {
+                let mut text = egui::RichText::new(format!("{}\n{}", pad.key, pad.name()));
+                if errors.iter().any(|(idx, _)| *idx == k) {
+                    text = text.color(ui.visuals().error_fg_color);
+                }
+                let button = egui::Button::new(text)
+                    .min_size(egui::vec2(64.0, 40.0))
+                    .selected(k == selected);
+                let response = ui.add(button);
+
+                if response.clicked() {
+                    selected = k;
+                }
+                if let Some(path) = &dropped {
+                    if ui.rect_contains_pointer(response.rect) {
+                        pad.path = Some(path.clone());
+                        selected = k;
+                        changed = true;
+                    }
+                }
+
+                if k % 4 == 3 {
+                    ui.end_row();
+                }
+            }
+        });
+
+        let pad = &mut pads[selected];
+        ui.horizontal(|ui| {
+            ui.label(pad.name()).on_hover_text(
+                pad.path
+                    .as_deref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default(),
+            );
+            if ui.button("Open").clicked() {
+                let picked = rfd::FileDialog::new()
+                    .add_filter("WAV", &["wav"])
+                    .pick_file();
+                if let Some(picked) = picked {
+                    pad.path = Some(picked);
+                    changed = true;
+                }
+            }
+            if ui.button("Clear").clicked() {
+                pad.path = None;
+                changed = true;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Key");
+            changed |= ui
+                .add(DragValue::new(&mut pad.key).range(0..=127))
+                .changed();
+            ui.label("Gain");
+            changed |= ui
+                .add(
+                    DragValue::new(&mut pad.gain)
+                        .range(-48.0..=12.0)
+                        .speed(0.1)
+                        .suffix(" dB"),
+                )
+                .changed();
+        });
+        ui.horizontal(|ui| {
+            ui.label("Pitch");
+            changed |= ui
+                .add(
+                    DragValue::new(&mut pad.pitch)
+                        .range(-24.0..=24.0)
+                        .speed(0.1)
+                        .suffix(" st"),
+                )
+                .changed();
+            ui.label("Choke");
+            changed |= ui
+                .add(DragValue::new(&mut pad.choke).range(0..=CHOKE_GROUPS))
+                .on_hover_text("Pads of the same group cut each other off, 0 never does")
+                .changed();
+        });
+
+        if let Some((_, error)) = errors.iter().find(|(idx, _)| *idx == selected) {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        self.selected.store(selected, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Voice {
+    pad: usize,
+    sample: Arc<Vec<f32>>,
+    pos: f32,
+    rate: f32,
+    gain: f32,
+}
+
+/// Plays a sample per pad on the notes mapped to it, one shot and ignoring
+/// note offs, so a whole kit fits in a single node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DrumSampler {
+    config: Arc<DrumSamplerConfig>,
+    midi_in: Arc<MidiInput>,
+    #[serde(skip)]
+    pads: Vec<Pad>,
+    // Loaded samples by path, along with the rate they play at
+    #[serde(skip)]
+    samples: HashMap<PathBuf, (Arc<Vec<f32>>, f32)>,
+    #[serde(skip)]
+    voices: Vec<Voice>,
+    #[serde(skip)]
+    out: f32,
+}
+
+impl DrumSampler {
+    fn trigger(&mut self, key: u8, vel: u8) {
+        for (idx, pad) in self.pads.iter().enumerate() {
+            if pad.key != key {
+                continue;
+            }
+            let loaded = pad.path.as_ref().and_then(|path| self.samples.get(path));
+            let Some((sample, rate)) = loaded else {
+                continue;
+            };
+
+            if pad.choke != 0 {
+                let pads = &self.pads;
+                self.voices
+                    .retain(|voice| pads[voice.pad].choke != pad.choke);
+            }
+            if self.voices.len() >= VOICES {
+                self.voices.remove(0);
+            }
+
+            self.voices.push(Voice {
+                pad: idx,
+                sample: Arc::clone(sample),
+                pos: 0.0,
+                rate: rate * 2f32.powf(pad.pitch / 12.0),
+                gain: 10f32.powf(pad.gain / 20.0) * vel as f32 / 127.0,
+            });
+        }
+    }
+}
+
+#[typetag::serde]
+impl Node for DrumSampler {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if let Some((_, MidiMessage::NoteOn { key, vel })) = self.midi_in.pop_msg(&data[MIDI]) {
+            self.trigger(key.as_int(), vel.as_int());
+        }
+
+        let mut out = 0.0;
+        self.voices.retain_mut(|voice| {
+            // Linear interpolation between the samples around the position
+            let idx = voice.pos as usize;
+            let Some(&a) = voice.sample.get(idx) else {
+                return false;
+            };
+            let b = voice.sample.get(idx + 1).copied().unwrap_or_default();
+            let t = voice.pos.fract();
+            out += (a + (b - a) * t) * voice.gain;
+            voice.pos += voice.rate;
+
+            true
+        });
+        self.out = out;
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out);
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
+        self.pads = self.config.pads.lock().unwrap().clone();
+
+        let mut errors = Vec::new();
+        self.samples
+            .retain(|path, _| self.pads.iter().any(|pad| pad.path.as_ref() == Some(path)));
+        for (idx, pad) in self.pads.iter().enumerate() {
+            let Some(path) = &pad.path else {
+                continue;
+            };
+            if self.samples.contains_key(path) {
+                continue;
+            }
+
+            match load_sample(path) {
+                Ok((sample, rate)) => {
+                    self.samples
+                        .insert(path.clone(), (Arc::new(sample), rate as f32 / 44100.0));
+                }
+                Err(e) => errors.push((idx, format!("Failed to load {}: {e}", path.display()))),
+            }
+        }
+        *self.config.errors.lock().unwrap() = errors;
+
+        // Voices of pads since emptied or remapped play out
+        Default::default()
+    }
+
+    fn panic(&mut self) {
+        self.voices.clear();
+        self.out = 0.0;
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::stateful("midi", &self.midi_in)]
+    }
+}
+
+pub fn drum_sampler() -> Box<dyn Node> {
+    Box::new(DrumSampler {
+        config: Arc::new(DrumSamplerConfig::new()),
+        midi_in: Arc::new(MidiInput::new()),
+        pads: Vec::new(),
+        samples: HashMap::new(),
+        voices: Vec::new(),
+        out: 0.0,
+    })
+}
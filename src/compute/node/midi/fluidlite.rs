@@ -1,29 +1,191 @@
-use std::{collections::VecDeque, fmt::Debug, sync::Arc};
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex,
+    },
+};
 
+use eframe::egui;
 use fluidlite as fl;
 use midly::MidiMessage;
 use serde::{Deserialize, Serialize};
 
 use crate::compute::{
-    node::{inputs::midi::MidiInput, Input, Node, NodeEvent},
-    Value,
+    node::{inputs::midi::MidiInput, ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
+    tuning, Value,
 };
 
-struct MyFluidlite(fl::Synth);
+crate::ports! {
+    MIDI = "midi",
+}
+
+// Used by patches saved before the soundfont could be picked
+const DEFAULT_FONT: &str = "./sf_/GuitarA.sf2";
+
+#[derive(Clone, Debug, PartialEq)]
+struct Preset {
+    bank: u32,
+    program: u32,
+    name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct FluidliteConfig {
+    #[serde(with = "crate::util::serde_mutex")]
+    path: Mutex<PathBuf>,
+    bank: AtomicU32,
+    program: AtomicU32,
+    #[serde(skip)]
+    presets: Mutex<Vec<Preset>>,
+    #[serde(skip)]
+    error: Mutex<Option<String>>,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl Default for FluidliteConfig {
+    fn default() -> Self {
+        FluidliteConfig {
+            path: Mutex::new(PathBuf::from(DEFAULT_FONT)),
+            bank: AtomicU32::new(0),
+            program: AtomicU32::new(0),
+            presets: Mutex::new(Vec::new()),
+            error: Mutex::new(None),
+            dirty: ConfigDirty::new(),
+        }
+    }
+}
+
+impl NodeConfig for FluidliteConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mut changed = false;
+
+        ui.horizontal(|ui| {
+            let path = self.path.lock().unwrap().clone();
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            ui.label(name).on_hover_text(path.display().to_string());
+
+            if ui.button("Open").clicked() {
+                let picked = rfd::FileDialog::new()
+                    .add_filter("SoundFont", &["sf2", "sf3"])
+                    .pick_file();
+                if let Some(picked) = picked {
+                    *self.path.lock().unwrap() = picked;
+                    changed = true;
+                }
+            }
+        });
+
+        let mut bank = self.bank.load(Ordering::Acquire);
+        let mut program = self.program.load(Ordering::Acquire);
+        let presets = self.presets.lock().unwrap();
+        let selected = presets
+            .iter()
+            .find(|preset| preset.bank == bank && preset.program == program)
+            .map(|preset| format!("{bank}:{program} {}", preset.name))
+            .unwrap_or(format!("{bank}:{program}"));
+
+        egui::ComboBox::from_id_salt("fluidlite-preset")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for preset in presets.iter() {
+                    let text = format!("{}:{} {}", preset.bank, preset.program, preset.name);
+                    let current = preset.bank == bank && preset.program == program;
+                    if ui.selectable_label(current, text).clicked() {
+                        bank = preset.bank;
+                        program = preset.program;
+                        changed = true;
+                    }
+                }
+            });
+        drop(presets);
+
+        if let Some(error) = &*self.error.lock().unwrap() {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        self.bank.store(bank, Ordering::Release);
+        self.program.store(program, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
+struct MyFluidlite {
+    synth: fl::Synth,
+    font: Option<(PathBuf, u32)>,
+}
+
+impl MyFluidlite {
+    fn load(&mut self, path: &Path) -> Result<(), fl::Error> {
+        let id = self.synth.sfload(path, true)?;
+        if let Some((_, old)) = self.font.replace((path.to_owned(), id)) {
+            self.synth.sfunload(old, true).ok();
+        }
+
+        Ok(())
+    }
+
+    fn presets(&self) -> Vec<Preset> {
+        let Some(font) = self
+            .font
+            .as_ref()
+            .and_then(|(_, id)| self.synth.get_sfont_by_id(*id))
+        else {
+            return Vec::new();
+        };
+
+        let mut presets = Vec::new();
+        // Bank 128 holds the percussion kits
+        for bank in 0..=128 {
+            for program in 0..128 {
+                if let Some(preset) = font.get_preset(bank, program) {
+                    presets.push(Preset {
+                        bank,
+                        program,
+                        name: preset.get_name().unwrap_or_default().to_string(),
+                    });
+                }
+            }
+        }
+
+        presets
+    }
+
+    fn select(&self, bank: u32, program: u32) {
+        let Some((_, id)) = self.font else {
+            return;
+        };
+
+        for channel in 0..16 {
+            self.synth.program_select(channel, id, bank, program).ok();
+        }
+    }
+}
 
 impl Default for MyFluidlite {
     fn default() -> Self {
         let settings = fl::Settings::new().unwrap();
         let synth = fl::Synth::new(settings).unwrap();
-        synth.sfload("./sf_/GuitarA.sf2", true).unwrap();
-        MyFluidlite(synth)
+        MyFluidlite { synth, font: None }
     }
 }
 
-// Fake, only creates a new instance
+// Fake, only creates a new instance with the same soundfont
 impl Clone for MyFluidlite {
     fn clone(&self) -> Self {
-        MyFluidlite::default()
+        let mut synth = MyFluidlite::default();
+        if let Some((path, _)) = &self.font {
+            synth.load(path).ok();
+        }
+
+        synth
     }
 }
 
@@ -35,9 +197,15 @@ impl Debug for MyFluidlite {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Fluidlite {
+    #[serde(default)]
+    config: Arc<FluidliteConfig>,
     midi_in: Arc<MidiInput>,
     #[serde(skip)]
     synth: MyFluidlite,
+    // Keys actually sent to the synth, so transposition changes can't leave
+    // notes hanging
+    #[serde(skip)]
+    sounding: HashMap<(u8, u8), u8>,
     out: f32,
     buf: VecDeque<f32>,
 }
@@ -45,40 +213,72 @@ pub struct Fluidlite {
 impl Fluidlite {
     pub fn new() -> Self {
         Fluidlite {
+            config: Default::default(),
             midi_in: Arc::new(MidiInput::new()),
             synth: MyFluidlite::default(),
+            sounding: HashMap::new(),
             out: 0.0,
             buf: VecDeque::new(),
         }
     }
 }
 
+impl Fluidlite {
+    fn note_off(&mut self, channel: u8, key: u8) {
+        let sent = self.sounding.remove(&(channel, key)).unwrap_or(key);
+        self.synth.synth.note_off(channel as u32, sent as _).ok();
+    }
+}
+
 #[typetag::serde]
 impl Node for Fluidlite {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        match self.midi_in.pop_msg(&data[0]) {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        match self.midi_in.pop_msg(&data[MIDI]) {
             Some((channel, msg)) => match msg {
                 MidiMessage::NoteOn { key, vel } => {
                     let vel = vel.as_int() as u32;
-                    let key = key.as_int() as u32;
                     if vel > 0 {
-                        self.synth.0.note_on(channel as u32, key, vel).ok();
+                        let sent = tuning::transpose_key(key.as_int());
+                        self.sounding.insert((channel, key.as_int()), sent);
+                        self.synth
+                            .synth
+                            .note_on(channel as u32, sent as _, vel)
+                            .ok();
                     } else {
-                        self.synth.0.note_off(channel as u32, key).ok();
+                        self.note_off(channel, key.as_int());
                     }
                 }
                 MidiMessage::NoteOff { key, .. } => {
-                    self.synth
-                        .0
-                        .note_off(channel as u32, key.as_int() as _)
-                        .ok();
+                    self.note_off(channel, key.as_int());
                 }
                 MidiMessage::Controller { controller, value } => {
                     self.synth
-                        .0
+                        .synth
                         .cc(channel as _, controller.as_int() as _, value.as_int() as _)
                         .ok();
                 }
+                MidiMessage::PitchBend { bend } => {
+                    self.synth
+                        .synth
+                        .pitch_bend(channel as _, bend.0.as_int() as _)
+                        .ok();
+                }
+                MidiMessage::ProgramChange { program } => {
+                    self.synth
+                        .synth
+                        .program_change(channel as _, program.as_int() as _)
+                        .ok();
+                    // Remembered, so the patch reopens with the last program
+                    self.config
+                        .program
+                        .store(program.as_int() as _, Ordering::Relaxed);
+                }
+                MidiMessage::ChannelAftertouch { vel } => {
+                    self.synth
+                        .synth
+                        .channel_pressure(channel as _, vel.as_int() as _)
+                        .ok();
+                }
                 _ => {}
             },
             _ => {}
@@ -86,7 +286,7 @@ impl Node for Fluidlite {
 
         if self.buf.is_empty() {
             let mut buf = [0.0; 441];
-            self.synth.0.write(&mut buf[..]).unwrap();
+            self.synth.synth.write(&mut buf[..]).unwrap();
             // las sample is always 0
             self.buf.extend(&buf[0..440]);
         }
@@ -96,10 +296,56 @@ impl Node for Fluidlite {
         Default::default()
     }
 
+    fn panic(&mut self) {
+        for channel in 0..16 {
+            // All Sound Off, cutting release tails too
+            self.synth.synth.cc(channel, 120, 0).ok();
+        }
+        self.sounding.clear();
+        self.buf.clear();
+        self.out = 0.0;
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
+        }
+
+        let path = self.config.path.lock().unwrap().clone();
+        let loaded = self.synth.font.as_ref().map(|(path, _)| path.as_path());
+        if loaded != Some(path.as_path()) {
+            match self.synth.load(&path) {
+                Ok(()) => {
+                    *self.config.presets.lock().unwrap() = self.synth.presets();
+                    *self.config.error.lock().unwrap() = None;
+                }
+                Err(e) => {
+                    *self.config.error.lock().unwrap() =
+                        Some(format!("Failed to load {}: {e}", path.display()));
+                }
+            }
+        }
+
+        self.synth.select(
+            self.config.bank.load(Ordering::Relaxed),
+            self.config.program.load(Ordering::Relaxed),
+        );
+
+        Default::default()
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
     fn read(&self, out: &mut [Value]) {
         out[0] = Value::Float(self.out)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         vec![Input::stateful("midi", &self.midi_in)]
     }
@@ -0,0 +1,111 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use eframe::egui::{self, DragValue};
+use midly::live::LiveEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    extern_midi::{self, Message},
+    node::{inputs::midi::MidiInput, ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent},
+    Output,
+};
+
+crate::ports! {
+    MIDI = "midi",
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct MidiOutConfig {
+    // 1-based, like the port names
+    port: AtomicUsize,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl NodeConfig for MidiOutConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mut port = self.port.load(Ordering::Acquire);
+
+        ui.horizontal(|ui| {
+            ui.label("Port");
+            let changed = ui.add(DragValue::new(&mut port).range(1..=16)).changed();
+
+            self.port.store(port, Ordering::Release);
+            self.dirty.mark_if(changed);
+        });
+    }
+}
+
+/// Sends its input to one of the JACK MIDI outputs from the audio settings.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MidiOut {
+    config: Arc<MidiOutConfig>,
+    midi: Arc<MidiInput>,
+    #[serde(skip)]
+    port: usize,
+}
+
+#[typetag::serde]
+impl Node for MidiOut {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if let Some((channel, message)) = self.midi.pop_msg(&data[MIDI]) {
+            let ev = LiveEvent::Midi {
+                channel: channel.into(),
+                message,
+            };
+
+            // Channel messages take at most three bytes
+            let mut bytes = [0; 3];
+            let mut rest = &mut bytes[..];
+            ev.write_std(&mut rest).ok();
+            let len = 3 - rest.len();
+
+            if let Some(message) = Message::new(&bytes[..len]) {
+                extern_midi::push(self.port.saturating_sub(1), message);
+            }
+        }
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.port = self.config.port.load(Ordering::Relaxed);
+        }
+
+        Default::default()
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::stateful("midi", &self.midi)]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        Vec::new()
+    }
+}
+
+pub fn midi_out() -> Box<dyn Node> {
+    Box::new(MidiOut {
+        config: Arc::new(MidiOutConfig {
+            port: AtomicUsize::new(1),
+            dirty: ConfigDirty::new(),
+        }),
+        midi: Arc::new(MidiInput::new()),
+        port: 1,
+    })
+}
@@ -1,7 +1,11 @@
 use super::NodeList;
 
+pub mod clock_out;
+pub mod drum_sampler;
 pub mod fluidlite;
+pub mod midi_out;
 pub mod one_note;
+pub mod recorder;
 pub mod source;
 
 pub struct Midi;
@@ -9,6 +13,16 @@ pub struct Midi;
 impl NodeList for Midi {
     fn all(&self) -> Vec<(Box<dyn super::Node>, String, Vec<String>)> {
         vec![
+            (
+                clock_out::clock_out(),
+                "Clock Out".into(),
+                vec!["Midi".into(), "Output".into()],
+            ),
+            (
+                drum_sampler::drum_sampler(),
+                "Drum Sampler".into(),
+                vec!["Midi".into(), "Drum".into()],
+            ),
             (
                 fluidlite::fluidlite(),
                 "Fluidlite Synth".into(),
@@ -20,6 +34,16 @@ impl NodeList for Midi {
                 vec!["Midi".into()],
             ),
             (source::midi_in(), "Midi In".into(), vec!["Midi".into()]),
+            (
+                recorder::recorder(),
+                "Midi Recorder".into(),
+                vec!["Midi".into()],
+            ),
+            (
+                midi_out::midi_out(),
+                "Midi Out".into(),
+                vec!["Midi".into(), "Output".into()],
+            ),
         ]
     }
 }
@@ -1,22 +1,136 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    any::Any,
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
+use atomic_float::AtomicF32;
+use eframe::egui::DragValue;
 use midly::MidiMessage;
 use serde::{Deserialize, Serialize};
 
-use crate::compute::{
-    node::{inputs::midi::MidiInput, Input, Node, NodeEvent},
-    Output, Value, ValueKind,
+use crate::{
+    compute::{
+        node::{
+            inputs::{midi::MidiInput, time::TimeInput},
+            ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
+        },
+        tuning, Output, Value, ValueKind,
+    },
+    serde_atomic_enum,
+    util::enum_combo_box,
 };
 
+crate::ports! {
+    MIDI = "midi",
+    GLIDE = "glide",
+}
+
+#[atomic_enum::atomic_enum]
+#[derive(PartialEq, Eq, derive_more::Display, strum::EnumIter)]
+pub enum GlideMode {
+    /// Every glide takes the glide time
+    #[display(fmt = "Constant time")]
+    Time,
+    /// The glide time is spent on every octave
+    #[display(fmt = "Constant rate")]
+    Rate,
+}
+
+serde_atomic_enum!(AtomicGlideMode);
+
+impl Default for GlideMode {
+    fn default() -> Self {
+        GlideMode::Time
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OneNoteConfig {
+    bend_range: AtomicF32,
+    #[serde(default = "default_glide_mode")]
+    glide_mode: AtomicGlideMode,
+    #[serde(default)]
+    legato: AtomicBool,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+fn default_glide_mode() -> AtomicGlideMode {
+    AtomicGlideMode::new(GlideMode::Time)
+}
+
+impl Default for OneNoteConfig {
+    fn default() -> Self {
+        OneNoteConfig {
+            bend_range: AtomicF32::new(2.0),
+            glide_mode: default_glide_mode(),
+            legato: AtomicBool::new(false),
+            dirty: ConfigDirty::new(),
+        }
+    }
+}
+
+impl NodeConfig for OneNoteConfig {
+    fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn Any) {
+        let mut bend_range = self.bend_range.load(Ordering::Acquire);
+
+        ui.horizontal(|ui| {
+            ui.label("Bend range");
+            let changed = ui
+                .add(
+                    DragValue::new(&mut bend_range)
+                        .range(0.0..=48.0)
+                        .speed(0.1)
+                        .suffix(" st"),
+                )
+                .changed();
+
+            self.bend_range.store(bend_range, Ordering::Release);
+            self.dirty.mark_if(changed);
+        });
+
+        let mut glide_mode = self.glide_mode.load(Ordering::Acquire);
+        let mut legato = self.legato.load(Ordering::Acquire);
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Glide");
+            changed |= enum_combo_box(ui, &mut glide_mode);
+        });
+        changed |= ui
+            .checkbox(&mut legato, "Legato only")
+            .on_hover_text("Glide only to notes played while another one is held")
+            .changed();
+
+        self.glide_mode.store(glide_mode, Ordering::Release);
+        self.legato.store(legato, Ordering::Release);
+        self.dirty.mark_if(changed);
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct OneNoteState {
     key: u8,
     vel: u8,
+    // Normalized to -1..1
+    #[serde(default)]
+    bend: f32,
+    // Normalized to 0..1
+    #[serde(default)]
+    pressure: f32,
 }
 
 impl OneNoteState {
     fn new() -> Self {
-        OneNoteState { key: 0, vel: 0 }
+        OneNoteState {
+            key: 0,
+            vel: 0,
+            bend: 0.0,
+            pressure: 0.0,
+        }
     }
 
     fn update(&mut self, message: &MidiMessage) {
@@ -28,6 +142,7 @@ impl OneNoteState {
                 } else {
                     self.key = key.as_int();
                     self.vel = vel.as_int();
+                    self.pressure = 0.0;
                 }
             }
             MidiMessage::NoteOff { key, .. } => {
@@ -35,44 +150,158 @@ impl OneNoteState {
                     self.vel = 0;
                 }
             }
+            MidiMessage::PitchBend { bend } => {
+                self.bend = bend.as_f32();
+            }
+            MidiMessage::ChannelAftertouch { vel } => {
+                self.pressure = vel.as_int() as f32 / 127.0;
+            }
+            MidiMessage::Aftertouch { key, vel } => {
+                if key == &self.key {
+                    self.pressure = vel.as_int() as f32 / 127.0;
+                }
+            }
             _ => {}
         }
     }
+
+    fn bend_semitones(&self, bend_range: f32) -> f32 {
+        self.bend * bend_range
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OneNote {
+    #[serde(default)]
+    config: Arc<OneNoteConfig>,
     midi_in: Arc<MidiInput>,
     state: OneNoteState,
+    #[serde(default = "default_glide")]
+    glide: Arc<TimeInput>,
+    #[serde(skip)]
+    bend_range: f32,
+    #[serde(skip)]
+    glide_mode: GlideMode,
+    #[serde(skip)]
+    legato: bool,
+    // Key the output is at while gliding, and the semitones it moves per sample
+    #[serde(skip)]
+    pitch: Option<f32>,
+    #[serde(skip)]
+    rate: f32,
+    // Patches saved before the glide input get it on the first sync
+    #[serde(skip)]
+    synced: bool,
+}
+
+fn default_glide() -> Arc<TimeInput> {
+    Arc::new(TimeInput::new(0.0))
 }
 
 impl OneNote {
     pub fn new() -> Self {
         OneNote {
+            config: Arc::new(OneNoteConfig::default()),
             midi_in: Arc::new(MidiInput::new()),
             state: OneNoteState::new(),
+            glide: default_glide(),
+            bend_range: 2.0,
+            glide_mode: GlideMode::Time,
+            legato: false,
+            pitch: None,
+            rate: 0.0,
+            synced: false,
+        }
+    }
+
+    fn note_on(&mut self, legato: bool, glide_samples: f32) {
+        let key = self.state.key as f32;
+        let from = match self.pitch {
+            Some(from) if glide_samples >= 1.0 && (legato || !self.legato) => from,
+            _ => {
+                self.pitch = Some(key);
+                return;
+            }
+        };
+
+        self.rate = match self.glide_mode {
+            GlideMode::Time => (key - from).abs() / glide_samples,
+            GlideMode::Rate => 12.0 / glide_samples,
+        };
+    }
+
+    fn glide_step(&mut self) {
+        let key = self.state.key as f32;
+        if let Some(pitch) = &mut self.pitch {
+            let diff = key - *pitch;
+            *pitch += diff.clamp(-self.rate, self.rate);
         }
     }
 }
 
 #[typetag::serde]
 impl Node for OneNote {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
-        if let Some((_, msg)) = self.midi_in.pop_msg(&data[0]) {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let glide_samples = self.glide.get_samples(&data[GLIDE]);
+        if let Some((_, msg)) = self.midi_in.pop_msg(&data[MIDI]) {
+            let held = self.state.vel > 0;
             self.state.update(&msg);
+            if let MidiMessage::NoteOn { vel, .. } = msg {
+                if vel.as_int() > 0 {
+                    self.note_on(held, glide_samples);
+                }
+            }
+        }
+        self.glide_step();
+
+        Default::default()
+    }
+
+    fn panic(&mut self) {
+        self.state.vel = 0;
+        self.state.pressure = 0.0;
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if self.config.dirty.take() {
+            self.bend_range = self.config.bend_range.load(Ordering::Relaxed);
+            self.glide_mode = self.config.glide_mode.load(Ordering::Relaxed);
+            self.legato = self.config.legato.load(Ordering::Relaxed);
+        }
+
+        if !self.synced {
+            self.synced = true;
+            return vec![NodeEvent::RecalcInputs(self.inputs())];
         }
 
         Default::default()
     }
 
     fn read(&self, out: &mut [Value]) {
+        let bend = self.state.bend_semitones(self.bend_range);
+        let key = self.pitch.unwrap_or(self.state.key as f32);
+        let note = key + bend;
+
         out[0] = Value::Float(self.state.key as _);
-        out[1] = Value::Float(440.0 * 2f32.powf((self.state.key as f32 - 69.0) / 12.0));
+        out[1] = Value::Float(tuning::key_to_freq(note));
         out[2] = Value::Float(self.state.vel as f32 / 127.0);
+        out[3] = Value::Float(bend);
+        out[4] = Value::Float(self.state.pressure);
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
     }
 
     fn inputs(&self) -> Vec<Input> {
-        vec![Input::stateful("midi", &self.midi_in)]
+        vec![
+            Input::stateful("midi", &self.midi_in),
+            Input::stateful("glide", &self.glide),
+        ]
     }
 
     fn output(&self) -> Vec<Output> {
@@ -80,6 +309,8 @@ impl Node for OneNote {
             Output::new("key", ValueKind::Float),
             Output::new("freq", ValueKind::Float),
             Output::new("vel", ValueKind::Float),
+            Output::new("bend", ValueKind::Float),
+            Output::new("aftertouch", ValueKind::Float),
         ]
     }
 }
@@ -0,0 +1,442 @@
+use std::{
+    any::Any,
+    collections::VecDeque,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use eframe::egui;
+use midly::{
+    live::LiveEvent,
+    num::{u15, u24, u28, u4},
+    Format, Header, MetaMessage, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute::{
+        node::{
+            inputs::midi::MidiInput, ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent,
+        },
+        Output, Value, ValueKind,
+    },
+    notify, serde_atomic_enum,
+};
+
+crate::ports! {
+    MIDI = "midi",
+}
+
+// Exported files run at 120 beats per minute
+const TICKS_PER_BEAT: u16 = 480;
+const TEMPO: u32 = 500000;
+
+#[atomic_enum::atomic_enum]
+#[derive(PartialEq, Eq)]
+enum Mode {
+    Stopped,
+    Recording,
+    Playing,
+}
+
+serde_atomic_enum!(AtomicMode);
+
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::Stopped
+    }
+}
+
+/// Recorded messages, saved with the patch.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+struct Clip {
+    // Raw messages with the sample they came on
+    events: Vec<(u64, Vec<u8>)>,
+    // Samples until the clip loops
+    length: u64,
+}
+
+impl Clip {
+    fn push(&mut self, t: u64, channel: u8, message: MidiMessage) {
+        let mut bytes = Vec::new();
+        let ev = LiveEvent::Midi {
+            channel: channel.into(),
+            message,
+        };
+        if ev.write_std(&mut bytes).is_ok() {
+            self.events.push((t, bytes));
+        }
+    }
+
+    fn message(bytes: &[u8]) -> Option<(u8, MidiMessage)> {
+        match LiveEvent::parse(bytes).ok()? {
+            LiveEvent::Midi { channel, message } => Some((channel.as_int(), message)),
+            _ => None,
+        }
+    }
+
+    // Notes with their key, start and end, for drawing
+    fn notes(&self) -> Vec<(u8, u64, u64)> {
+        let mut notes = Vec::new();
+        let mut held: Vec<(u8, u8, u64)> = Vec::new();
+        for (t, bytes) in &self.events {
+            let (on, channel, key) = match Clip::message(bytes) {
+                Some((channel, MidiMessage::NoteOn { key, vel })) => {
+                    (vel.as_int() > 0, channel, key.as_int())
+                }
+                Some((channel, MidiMessage::NoteOff { key, .. })) => (false, channel, key.as_int()),
+                _ => continue,
+            };
+
+            if let Some(k) = held.iter().position(|(c, n, _)| (*c, *n) == (channel, key)) {
+                let (_, _, start) = held.swap_remove(k);
+                notes.push((key, start, *t));
+            }
+            if on {
+                held.push((channel, key, *t));
+            }
+        }
+        notes.extend(
+            held.into_iter()
+                .map(|(_, key, start)| (key, start, self.length)),
+        );
+
+        notes
+    }
+
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        let ticks_per_sample = TICKS_PER_BEAT as f64 * 1000000.0 / TEMPO as f64 / 44100.0;
+
+        let mut track = vec![TrackEvent {
+            delta: u28::from_int_lossy(0),
+            kind: TrackEventKind::Meta(MetaMessage::Tempo(u24::from_int_lossy(TEMPO))),
+        }];
+        let mut last = 0;
+        for (t, bytes) in &self.events {
+            let Some((channel, message)) = Clip::message(bytes) else {
+                continue;
+            };
+
+            let tick = (*t as f64 * ticks_per_sample).round() as u32;
+            track.push(TrackEvent {
+                delta: u28::from_int_lossy(tick - last),
+                kind: TrackEventKind::Midi {
+                    channel: u4::from_int_lossy(channel),
+                    message,
+                },
+            });
+            last = tick;
+        }
+        let end = (self.length as f64 * ticks_per_sample).round() as u32;
+        track.push(TrackEvent {
+            delta: u28::from_int_lossy(end.saturating_sub(last)),
+            kind: TrackEventKind::Meta(MetaMessage::EndOfTrack),
+        });
+
+        let smf = Smf {
+            header: Header::new(
+                Format::SingleTrack,
+                Timing::Metrical(u15::from_int_lossy(TICKS_PER_BEAT)),
+            ),
+            tracks: vec![track],
+        };
+        smf.save(path)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RecorderConfig {
+    mode: AtomicMode,
+    #[serde(with = "crate::util::serde_mutex")]
+    clip: Mutex<Clip>,
+    // Sample recorded or played, for the playhead
+    #[serde(skip)]
+    position: AtomicU64,
+    #[serde(skip)]
+    dirty: ConfigDirty,
+}
+
+impl RecorderConfig {
+    fn show_roll(&self, ui: &mut egui::Ui, clip: &Clip) {
+        let size = egui::vec2(ui.available_width().max(200.0), 64.0);
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+        let notes = clip.notes();
+        let length = notes
+            .iter()
+            .map(|(_, _, end)| *end)
+            .chain([clip.length, self.position.load(Ordering::Relaxed)])
+            .max()
+            .unwrap_or_default()
+            .max(1) as f32;
+        let low = notes.iter().map(|(key, _, _)| *key).min().unwrap_or(60);
+        let high = notes
+            .iter()
+            .map(|(key, _, _)| *key)
+            .max()
+            .unwrap_or(72)
+            .max(low + 12);
+        let row = rect.height() / (high - low + 1) as f32;
+        let x = |t: u64| rect.left() + t as f32 / length * rect.width();
+
+        for (key, start, end) in notes {
+            let top = rect.bottom() - (key - low + 1) as f32 * row;
+            let note = egui::Rect::from_min_max(
+                egui::pos2(x(start), top),
+                egui::pos2(x(end).max(x(start) + 1.0), top + row.max(1.0)),
+            );
+            painter.rect_filled(note, 0.0, visuals.selection.bg_fill);
+        }
+
+        let playhead = x(self.position.load(Ordering::Relaxed));
+        painter.vline(playhead, rect.y_range(), visuals.widgets.active.fg_stroke);
+    }
+}
+
+impl NodeConfig for RecorderConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        let mode = self.mode.load(Ordering::Acquire);
+        let mut new_mode = mode;
+
+        ui.horizontal(|ui| {
+            if ui
+                .selectable_label(mode == Mode::Recording, "⏺ Record")
+                .clicked()
+            {
+                new_mode = Mode::Recording;
+            }
+            if ui
+                .selectable_label(mode == Mode::Playing, "🔁 Loop")
+                .clicked()
+            {
+                new_mode = Mode::Playing;
+            }
+            if ui.button("⏹").clicked() {
+                new_mode = Mode::Stopped;
+            }
+        });
+
+        let mut clip = self.clip.lock().unwrap();
+        ui.label(format!(
+            "{} events, {:.1} s",
+            clip.events.len(),
+            clip.length as f32 / 44100.0
+        ));
+        self.show_roll(ui, &clip);
+
+        let (clear, export) = ui
+            .add_enabled_ui(mode != Mode::Recording, |ui| {
+                ui.horizontal(|ui| (ui.button("Clear").clicked(), ui.button("Export").clicked()))
+                    .inner
+            })
+            .inner;
+        if clear {
+            *clip = Clip::default();
+            self.dirty.mark();
+        }
+
+        // The runtime can't take the clip while the dialog is open
+        let exported = export.then(|| clip.clone());
+        drop(clip);
+        if let Some(clip) = exported {
+            let picked = rfd::FileDialog::new()
+                .add_filter("MIDI", &["mid"])
+                .save_file();
+            if let Some(path) = picked {
+                if let Err(e) = clip.save(&path) {
+                    notify::error(&format!("Failed to save {}", path.display()), e);
+                }
+            }
+        }
+
+        if new_mode != mode {
+            self.mode.store(new_mode, Ordering::Release);
+            self.dirty.mark();
+        }
+    }
+}
+
+/// Records the MIDI passing through it and loops the recording back,
+/// played along with its input.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Recorder {
+    config: Arc<RecorderConfig>,
+    midi: Arc<MidiInput>,
+    #[serde(skip)]
+    mode: Mode,
+    #[serde(skip)]
+    clip: Clip,
+    #[serde(skip)]
+    t: u64,
+    #[serde(skip)]
+    cursor: usize,
+    // Notes of the recording sounding, released on looping and stopping
+    #[serde(skip)]
+    sounding: Vec<(u8, u8)>,
+    #[serde(skip)]
+    queue: VecDeque<(u8, MidiMessage)>,
+    #[serde(skip)]
+    out: Value,
+}
+
+impl Recorder {
+    fn release(&mut self) {
+        for (channel, key) in self.sounding.drain(..) {
+            self.queue.push_back((
+                channel,
+                MidiMessage::NoteOff {
+                    key: key.into(),
+                    vel: 0.into(),
+                },
+            ));
+        }
+    }
+
+    fn play(&mut self, channel: u8, message: MidiMessage) {
+        match message {
+            MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => {
+                self.sounding.push((channel, key.as_int()));
+            }
+            MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => {
+                self.sounding
+                    .retain(|sounding| *sounding != (channel, key.as_int()));
+            }
+            _ => {}
+        }
+        self.queue.push_back((channel, message));
+    }
+}
+
+#[typetag::serde]
+impl Node for Recorder {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        if let Some((channel, message)) = self.midi.pop_msg(&data[MIDI]) {
+            if self.mode == Mode::Recording {
+                self.clip.push(self.t, channel, message);
+            }
+            self.queue.push_back((channel, message));
+        }
+
+        match self.mode {
+            Mode::Recording => self.t += 1,
+            Mode::Playing if self.clip.length > 0 => {
+                while let Some((at, bytes)) = self.clip.events.get(self.cursor) {
+                    if *at > self.t {
+                        break;
+                    }
+                    if let Some((channel, message)) = Clip::message(bytes) {
+                        self.play(channel, message);
+                    }
+                    self.cursor += 1;
+                }
+
+                self.t += 1;
+                if self.t >= self.clip.length {
+                    self.t = 0;
+                    self.cursor = 0;
+                    self.release();
+                }
+            }
+            _ => {}
+        }
+
+        self.out = self
+            .queue
+            .pop_front()
+            .map(|(channel, message)| Value::Midi { channel, message })
+            .unwrap_or(Value::None);
+
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        self.config.position.store(self.t, Ordering::Relaxed);
+
+        // Held by the editor while it's drawn, the changes wait for the next
+        // block then
+        let Ok(mut clip) = self.config.clip.try_lock() else {
+            return Default::default();
+        };
+
+        if self.config.dirty.take() {
+            let mode = self.config.mode.load(Ordering::Relaxed);
+            if mode != self.mode {
+                if self.mode == Mode::Recording {
+                    self.clip.length = self.t;
+                    *clip = self.clip.clone();
+                }
+                if mode == Mode::Recording {
+                    self.clip = Clip::default();
+                }
+
+                self.release();
+                self.t = 0;
+                self.cursor = 0;
+                self.mode = mode;
+            }
+
+            if mode != Mode::Recording {
+                self.clip = clip.clone();
+            }
+        }
+
+        // The editor sees the recording grow
+        if self.mode == Mode::Recording {
+            let recorded = clip.events.len().min(self.clip.events.len());
+            clip.events.truncate(recorded);
+            clip.events.extend_from_slice(&self.clip.events[recorded..]);
+            clip.length = self.t;
+        }
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = self.out.clone();
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn panic(&mut self) {
+        self.release();
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::stateful("midi", &self.midi)]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![Output::new("midi", ValueKind::Midi)]
+    }
+}
+
+pub fn recorder() -> Box<dyn Node> {
+    Box::new(Recorder {
+        config: Arc::new(RecorderConfig {
+            mode: AtomicMode::new(Mode::Stopped),
+            clip: Mutex::new(Clip::default()),
+            position: AtomicU64::new(0),
+            dirty: ConfigDirty::new(),
+        }),
+        midi: Arc::new(MidiInput::new()),
+        mode: Mode::Stopped,
+        clip: Clip::default(),
+        t: 0,
+        cursor: 0,
+        sounding: Vec::new(),
+        queue: VecDeque::new(),
+        out: Value::None,
+    })
+}
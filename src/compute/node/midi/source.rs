@@ -1,7 +1,8 @@
 use std::{
     any::Any,
     fmt::Debug,
-    sync::{Arc, Mutex},
+    sync::{atomic::Ordering, Arc, Mutex},
+    time::Instant,
 };
 
 use anyhow::Result;
@@ -13,22 +14,31 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     compute::{
-        node::{Node, NodeConfig, NodeEvent},
+        node::{InputData, Node, NodeConfig, NodeEvent},
         Output, Value, ValueKind,
     },
-    graph::SynthCtx,
     util,
 };
 
-use self::null::NullSourceNew;
+use self::{
+    jack::JackSourceNew,
+    keyboard::KeyboardSourceNew,
+    null::NullSourceNew,
+    smf::{SmfSourceNew, Timeline},
+};
 
 pub mod jack;
+pub mod keyboard;
 mod null;
 pub mod smf;
 
 pub trait MidiSource: Debug + Send {
     fn try_next(&mut self) -> Option<(u8, MidiMessage)>;
     fn reset(&mut self);
+    /// Transport of sources playing a recording.
+    fn timeline(&self) -> Option<Arc<Timeline>> {
+        None
+    }
 }
 
 #[typetag::serde]
@@ -37,6 +47,41 @@ pub trait MidiSourceNew: Debug + DynClone + Send + Sync {
     fn name(&self) -> String;
 }
 
+/// Passed to every node config shown, with the MIDI sources to pick from.
+#[derive(Serialize, Deserialize)]
+pub struct SynthCtx {
+    pub midi_smf: Vec<SmfSourceNew>,
+    pub midi_jack: Vec<JackSourceNew>,
+    /// Named buses the runtime's Send nodes feed.
+    #[serde(skip)]
+    pub bus_names: Vec<String>,
+    #[serde(skip)]
+    #[serde(default = "Instant::now")]
+    last_updated_jack: Instant,
+}
+
+impl Default for SynthCtx {
+    fn default() -> Self {
+        SynthCtx {
+            midi_smf: Default::default(),
+            midi_jack: Default::default(),
+            bus_names: Default::default(),
+            last_updated_jack: Instant::now(),
+        }
+    }
+}
+
+impl SynthCtx {
+    pub fn update_jack(&mut self) {
+        if self.last_updated_jack.elapsed().as_secs_f32() < 2.0 {
+            return;
+        }
+
+        self.last_updated_jack = Instant::now();
+        self.midi_jack = JackSourceNew::all();
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct RecoverableMidiSource {
     new: Box<dyn MidiSourceNew>,
@@ -77,6 +122,7 @@ impl Clone for RecoverableMidiSource {
 enum SourceKind {
     File,
     Jack,
+    Keyboard,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -85,6 +131,9 @@ struct Inner {
     replace_new: Option<Box<dyn MidiSourceNew>>,
     replacing: bool,
     source_kind: SourceKind,
+    // Transport of the playing source, handed over by the runtime
+    #[serde(skip)]
+    timeline: Option<Arc<Timeline>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -100,11 +149,93 @@ impl MidiInConf {
                 replace_new: None,
                 replacing: false,
                 source_kind: SourceKind::File,
+                timeline: None,
             }),
         }
     }
 }
 
+fn show_timeline(ui: &mut egui::Ui, timeline: &Timeline) {
+    let position = timeline.position();
+    let mut playing = timeline.playing.load(Ordering::Relaxed);
+
+    ui.horizontal(|ui| {
+        if ui.button(if playing { "⏸" } else { "▶" }).clicked() {
+            playing = !playing;
+            timeline.playing.store(playing, Ordering::Relaxed);
+        }
+        if ui.button("⏹").clicked() {
+            timeline.stop();
+        }
+
+        let time = format!("{}:{:04.1}", (position / 60.0) as u64, position % 60.0);
+        match timeline.tempo.bar_beat(position) {
+            Some((bar, beat)) => ui.label(format!("{bar}:{beat}  {time}")),
+            None => ui.label(time),
+        };
+    });
+
+    let mut seek = position;
+    if ui
+        .add(egui::Slider::new(&mut seek, 0.0..=timeline.length).show_value(false))
+        .changed()
+    {
+        timeline.seek(seek);
+    }
+
+    ui.horizontal(|ui| {
+        let mut speed = timeline.speed.load(Ordering::Relaxed);
+        ui.label("Speed");
+        if ui
+            .add(
+                egui::DragValue::new(&mut speed)
+                    .range(0.1..=4.0)
+                    .speed(0.01)
+                    .suffix("×"),
+            )
+            .changed()
+        {
+            timeline.speed.store(speed, Ordering::Relaxed);
+        }
+    });
+
+    ui.horizontal(|ui| {
+        let mut looping = timeline.looping.load(Ordering::Relaxed);
+        if ui.checkbox(&mut looping, "Loop").changed() {
+            timeline.looping.store(looping, Ordering::Relaxed);
+        }
+        for (k, bound) in [&timeline.loop_start, &timeline.loop_end]
+            .into_iter()
+            .enumerate()
+        {
+            if k == 1 {
+                ui.label("to");
+            }
+            let mut secs = bound.load(Ordering::Relaxed);
+            if ui
+                .add(
+                    egui::DragValue::new(&mut secs)
+                        .range(0.0..=timeline.length)
+                        .speed(0.1)
+                        .suffix(" s"),
+                )
+                .changed()
+            {
+                bound.store(secs, Ordering::Relaxed);
+            }
+        }
+    });
+
+    ui.collapsing("Tracks", |ui| {
+        for (name, muted) in timeline.tracks.iter().zip(&timeline.muted) {
+            let mut heard = !muted.load(Ordering::Relaxed);
+            if ui.checkbox(&mut heard, name).changed() {
+                muted.store(!heard, Ordering::Relaxed);
+            }
+        }
+    });
+}
+
 impl NodeConfig for MidiInConf {
     fn show(&self, ui: &mut egui::Ui, data: &dyn Any) {
         let mut inner = self.inner.lock().unwrap();
@@ -117,11 +248,16 @@ impl NodeConfig for MidiInConf {
             inner.replacing = !inner.replacing;
         }
 
+        if let Some(timeline) = &inner.timeline {
+            show_timeline(ui, timeline);
+        }
+
         if inner.replacing {
             egui::Window::new("Choose Midi Source").show(ui.ctx(), |ui| {
                 ui.horizontal(|ui| {
                     ui.selectable_value(&mut inner.source_kind, SourceKind::File, "File");
                     ui.selectable_value(&mut inner.source_kind, SourceKind::Jack, "Jack");
+                    ui.selectable_value(&mut inner.source_kind, SourceKind::Keyboard, "Keyboard");
                 });
                 ui.separator();
 
@@ -148,6 +284,16 @@ impl NodeConfig for MidiInConf {
                             }
                         }
                     }
+                    SourceKind::Keyboard => {
+                        let new = KeyboardSourceNew;
+                        if ui
+                            .add(egui::Label::new(new.name()).sense(egui::Sense::click()))
+                            .clicked()
+                        {
+                            inner.replace_new = Some(Box::new(new));
+                            inner.replacing = false;
+                        }
+                    }
                 });
             });
         }
@@ -163,14 +309,7 @@ pub struct MidiIn {
 
 #[typetag::serde]
 impl Node for MidiIn {
-    fn feed(&mut self, _data: &[Value]) -> Vec<NodeEvent> {
-        if let Ok(mut conf) = self.conf.inner.try_lock() {
-            if let Some(new) = conf.replace_new.take() {
-                self.source.new = new;
-                self.source.source = None;
-            }
-        }
-
+    fn feed(&mut self, _data: &InputData) -> Vec<NodeEvent> {
         self.out = self
             .source
             .source()
@@ -181,6 +320,18 @@ impl Node for MidiIn {
         Default::default()
     }
 
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if let Ok(mut conf) = self.conf.inner.try_lock() {
+            if let Some(new) = conf.replace_new.take() {
+                self.source.new = new;
+                self.source.source = None;
+            }
+            conf.timeline = self.source.source.as_ref().and_then(|src| src.timeline());
+        }
+
+        Default::default()
+    }
+
     fn read(&self, out: &mut [Value]) {
         out[0] = self.out.clone()
     }
@@ -9,19 +9,54 @@ use std::{
 
 use super::{MidiSource, MidiSourceNew};
 
+// Messages due further ahead than this mean the runtime restarted or stalled
+const RESYNC_FRAMES: i64 = 44100;
+
 #[derive(Debug)]
 pub struct JackSource {
     // the type is hard to spell and it only needs to be kept alive.
     _client: Box<dyn Any + Send + Sync>,
-    midi_rx: Receiver<(u8, MidiMessage)>,
+    // Messages with the JACK frame they were received on
+    midi_rx: Receiver<(u64, u8, MidiMessage)>,
+    pending: Option<(u64, u8, MidiMessage)>,
+    // Samples pulled so far and their distance from JACK frames. Messages
+    // of one JACK period arrive together, the offset spreads them back out
+    // to their frames within it.
+    samples: u64,
+    offset: Option<i64>,
 }
 
 impl MidiSource for JackSource {
     fn try_next(&mut self) -> Option<(u8, MidiMessage)> {
-        self.midi_rx.try_recv().ok()
+        let now = self.samples as i64;
+        self.samples += 1;
+
+        if self.pending.is_none() {
+            self.pending = self.midi_rx.try_recv().ok();
+        }
+        let (frame, channel, message) = self.pending?;
+
+        let offset = match self.offset {
+            Some(offset) if frame as i64 + offset - now <= RESYNC_FRAMES => offset,
+            _ => now - frame as i64,
+        };
+        let due = frame as i64 + offset;
+        if due > now {
+            self.offset = Some(offset);
+            return None;
+        }
+
+        // Late messages delay the following ones by as much, which keeps
+        // their spacing
+        self.offset = Some(offset + now - due);
+        self.pending = None;
+
+        Some((channel, message))
     }
 
-    fn reset(&mut self) {}
+    fn reset(&mut self) {
+        self.offset = None;
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,12 +104,14 @@ impl MidiSourceNew for JackSourceNew {
         let (midi_tx, midi_rx) = channel();
         let mut arena = Arena::new();
         let process_cb = move |_: &jack::Client, ps: &jack::ProcessScope| {
+            let period_start = ps.last_frame_time() as u64;
             for msg in midi_in.iter(ps) {
                 if let Ok(live_ev) = LiveEvent::parse(msg.bytes) {
                     let track_ev = live_ev.as_track_event(&mut arena);
 
                     if let TrackEventKind::Midi { channel, message } = track_ev {
-                        midi_tx.send((channel.as_int(), message)).ok();
+                        let frame = period_start + msg.time as u64;
+                        midi_tx.send((frame, channel.as_int(), message)).ok();
                     }
                 }
             }
@@ -92,6 +129,9 @@ impl MidiSourceNew for JackSourceNew {
         Ok(Box::new(JackSource {
             _client: Box::new(async_client),
             midi_rx,
+            pending: None,
+            samples: 0,
+            offset: None,
         }))
     }
 
@@ -0,0 +1,52 @@
+use std::sync::{
+    mpsc::{channel, Receiver, Sender},
+    Mutex,
+};
+
+use anyhow::Result;
+use midly::MidiMessage;
+use serde::{Deserialize, Serialize};
+
+use super::{MidiSource, MidiSourceNew};
+
+// Every source playing the keyboard gets its own copy of the messages
+static LISTENERS: Mutex<Vec<Sender<MidiMessage>>> = Mutex::new(Vec::new());
+
+/// Sends a message played on the on-screen keyboard to every source
+/// listening to it.
+pub fn push(message: MidiMessage) {
+    LISTENERS
+        .lock()
+        .unwrap()
+        .retain(|tx| tx.send(message).is_ok());
+}
+
+#[derive(Debug)]
+struct KeyboardSource {
+    rx: Receiver<MidiMessage>,
+}
+
+impl MidiSource for KeyboardSource {
+    fn try_next(&mut self) -> Option<(u8, MidiMessage)> {
+        self.rx.try_recv().ok().map(|message| (0, message))
+    }
+
+    fn reset(&mut self) {}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyboardSourceNew;
+
+#[typetag::serde]
+impl MidiSourceNew for KeyboardSourceNew {
+    fn new_src(&self) -> Result<Box<dyn MidiSource>> {
+        let (tx, rx) = channel();
+        LISTENERS.lock().unwrap().push(tx);
+
+        Ok(Box::new(KeyboardSource { rx }))
+    }
+
+    fn name(&self) -> String {
+        "Virtual Keyboard".into()
+    }
+}
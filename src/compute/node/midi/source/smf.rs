@@ -3,111 +3,321 @@ use std::{
     ffi::OsStr,
     fmt::Debug,
     path::Path,
-    time::{Duration, Instant},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
 };
 
 use anyhow::Result;
 
-use midly::{MetaMessage, MidiMessage, Smf, Timing, TrackEventKind};
+use atomic_float::{AtomicF32, AtomicF64};
+use midly::{num::u7, MetaMessage, MidiMessage, Smf, Timing, TrackEventKind};
 use serde::{Deserialize, Serialize};
 
-use crate::compute::node::all::source::MidiSourceNew;
+use crate::compute::{deterministic, node::all::source::MidiSourceNew};
 
 use super::MidiSource;
 
-#[derive(Clone, Copy, Debug)]
-struct Beat {
-    us: u32,
+// Tempo of files that don't set one, in microseconds per beat
+const DEFAULT_TEMPO: u32 = 500000;
+
+/// Seconds of every tick of a file, following its tempo changes.
+#[derive(Clone, Debug)]
+pub struct TempoMap {
+    // Ticks per beat of metrical files, timecode ones have no beats
+    ticks_per_beat: Option<u16>,
+    // Tick of each tempo change, its time and the seconds of every tick after
+    changes: Vec<(u64, f64, f64)>,
+    // Beats in a bar, from the first time signature
+    bar: f64,
 }
 
-impl Beat {
-    fn us(&self) -> u32 {
-        self.us
-    }
+impl TempoMap {
+    fn new(smf: &Smf) -> Self {
+        let ticks_per_beat = match smf.header.timing {
+            Timing::Metrical(ticks) => Some(ticks.as_int().max(1)),
+            Timing::Timecode(..) => None,
+        };
+
+        let mut tempos = Vec::new();
+        let mut bar = None;
+        for track in &smf.tracks {
+            let mut tick = 0;
+            for ev in track {
+                tick += ev.delta.as_int() as u64;
+                match ev.kind {
+                    TrackEventKind::Meta(MetaMessage::Tempo(us)) => {
+                        tempos.push((tick, us.as_int()))
+                    }
+                    TrackEventKind::Meta(MetaMessage::TimeSignature(num, den, ..)) => {
+                        // The denominator is a power of two, in quarters
+                        bar.get_or_insert(num as f64 * 4.0 / 2f64.powi(den as i32));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        tempos.sort_by_key(|(tick, _)| *tick);
+
+        let tick_secs = |us: u32| match smf.header.timing {
+            Timing::Metrical(ticks) => us as f64 / 1000000.0 / ticks.as_int().max(1) as f64,
+            Timing::Timecode(fps, subframe) => 1.0 / fps.as_f32() as f64 / subframe as f64,
+        };
+
+        let mut changes = vec![(0, 0.0, tick_secs(DEFAULT_TEMPO))];
+        if ticks_per_beat.is_some() {
+            for (tick, us) in tempos {
+                let (last_tick, last_secs, last_tick_secs) = *changes.last().unwrap();
+                let secs = last_secs + (tick - last_tick) as f64 * last_tick_secs;
+                if tick == last_tick {
+                    changes.pop();
+                }
+                changes.push((tick, secs, tick_secs(us)));
+            }
+        }
 
-    fn update(&mut self, message: &MetaMessage) {
-        if let MetaMessage::Tempo(us) = message {
-            self.us = us.as_int();
+        TempoMap {
+            ticks_per_beat,
+            changes,
+            bar: bar.unwrap_or(4.0),
         }
     }
+
+    fn secs(&self, tick: u64) -> f64 {
+        let k = self.changes.partition_point(|(at, _, _)| *at <= tick) - 1;
+        let (at, secs, tick_secs) = self.changes[k];
+        secs + (tick - at) as f64 * tick_secs
+    }
+
+    fn ticks(&self, secs: f64) -> f64 {
+        let k = self
+            .changes
+            .partition_point(|(_, at, _)| *at <= secs)
+            .max(1)
+            - 1;
+        let (tick, at, tick_secs) = self.changes[k];
+        tick as f64 + (secs - at) / tick_secs
+    }
+
+    /// Bar and beat at `secs`, both counted from 1, for files measured in
+    /// beats.
+    pub fn bar_beat(&self, secs: f64) -> Option<(u64, u64)> {
+        let beats = self.ticks(secs.max(0.0)) / self.ticks_per_beat? as f64;
+        let bar = (beats / self.bar).floor();
+        let beat = (beats - bar * self.bar).floor();
+
+        Some((bar as u64 + 1, beat as u64 + 1))
+    }
+}
+
+/// Controls of a file source shared with the editor, and its position.
+#[derive(Debug)]
+pub struct Timeline {
+    pub playing: AtomicBool,
+    pub speed: AtomicF32,
+    pub looping: AtomicBool,
+    pub loop_start: AtomicF64,
+    pub loop_end: AtomicF64,
+    pub muted: Vec<AtomicBool>,
+    // Position requested by the editor, negative if none
+    seek: AtomicF64,
+    position: AtomicF64,
+    /// Seconds until the last event
+    pub length: f64,
+    pub tracks: Vec<String>,
+    pub tempo: TempoMap,
 }
 
-impl Default for Beat {
-    fn default() -> Self {
-        Beat { us: 666667 }
+impl Timeline {
+    /// Seconds played so far.
+    pub fn position(&self) -> f64 {
+        self.position.load(Ordering::Relaxed)
+    }
+
+    pub fn seek(&self, secs: f64) {
+        self.seek.store(secs.max(0.0), Ordering::Relaxed);
+    }
+
+    /// Pauses and returns to the start.
+    pub fn stop(&self) {
+        self.playing.store(false, Ordering::Relaxed);
+        self.seek(0.0);
     }
 }
 
+#[derive(Clone, Copy, Debug)]
+struct Event {
+    secs: f64,
+    track: usize,
+    channel: u8,
+    message: MidiMessage,
+}
+
 #[derive(Clone, Debug)]
 pub struct SmfSource {
-    smf: Smf<'static>,
-    cursors: Vec<usize>,
-    last_ev_tick: Vec<u32>,
-    t0: Instant,
-    tick: Duration,
+    // Events of all the tracks in order
+    events: Vec<Event>,
+    cursor: usize,
+    timeline: Arc<Timeline>,
+    // Position in seconds of the file
+    secs: f64,
+    last: Instant,
+    // Notes sounding with their channel, released on seeking and pausing
+    sounding: Vec<(u8, u8)>,
+    // Releases and events due on the same sample, handed out one at a time
     queue: VecDeque<(u8, MidiMessage)>,
 }
 
 impl SmfSource {
     fn new(bytes: &[u8]) -> Result<Self> {
-        let smf = Smf::parse(bytes)?.to_static();
+        let smf = Smf::parse(bytes)?;
+        let tempo = TempoMap::new(&smf);
 
-        let mut beat = Beat::default();
-        for ev in &smf.tracks[0] {
-            if let TrackEventKind::Meta(message) = &ev.kind {
-                beat.update(message);
+        let mut events = Vec::new();
+        let mut tracks = Vec::new();
+        for (k, track) in smf.tracks.iter().enumerate() {
+            let mut name = format!("Track {}", k + 1);
+            let mut tick = 0;
+            for ev in track {
+                tick += ev.delta.as_int() as u64;
+                match ev.kind {
+                    TrackEventKind::Midi { channel, message } => events.push(Event {
+                        secs: tempo.secs(tick),
+                        track: k,
+                        channel: channel.as_int(),
+                        message,
+                    }),
+                    TrackEventKind::Meta(MetaMessage::TrackName(bytes)) => {
+                        name = String::from_utf8_lossy(bytes).into_owned();
+                    }
+                    _ => {}
+                }
             }
+            tracks.push(name);
         }
+        // Stable, so events of a track on the same tick keep their order
+        events.sort_by(|a, b| a.secs.total_cmp(&b.secs));
 
-        let tick = Duration::from_secs_f64(match smf.header.timing {
-            Timing::Metrical(ticks_per_beat) => {
-                beat.us() as f64 / 1000000.0 / ticks_per_beat.as_int() as f64
-            }
-            Timing::Timecode(fps, subframe) => 1f64 / fps.as_f32() as f64 / subframe as f64,
-        });
-
-        let cursors = std::iter::repeat(0).take(smf.tracks.len()).collect();
-        let last_ev_tick = std::iter::repeat(0).take(smf.tracks.len()).collect();
+        let length = events.last().map(|ev| ev.secs).unwrap_or_default();
+        let timeline = Timeline {
+            playing: AtomicBool::new(true),
+            speed: AtomicF32::new(1.0),
+            looping: AtomicBool::new(false),
+            loop_start: AtomicF64::new(0.0),
+            loop_end: AtomicF64::new(length),
+            muted: tracks.iter().map(|_| AtomicBool::new(false)).collect(),
+            seek: AtomicF64::new(-1.0),
+            position: AtomicF64::new(0.0),
+            length,
+            tracks,
+            tempo,
+        };
 
         Ok(SmfSource {
-            smf,
-            cursors,
-            last_ev_tick,
-            t0: Instant::now(),
-            tick,
+            events,
+            cursor: 0,
+            timeline: Arc::new(timeline),
+            secs: 0.0,
+            last: Instant::now(),
+            sounding: Vec::new(),
             queue: VecDeque::new(),
         })
     }
+
+    fn release(&mut self) {
+        for (channel, key) in self.sounding.drain(..) {
+            self.queue.push_back((
+                channel,
+                MidiMessage::NoteOff {
+                    key: u7::from_int_lossy(key),
+                    vel: u7::from_int_lossy(0),
+                },
+            ));
+        }
+    }
+
+    fn jump(&mut self, secs: f64) {
+        self.release();
+        self.secs = secs;
+        self.cursor = self.events.partition_point(|ev| ev.secs < secs);
+    }
+
+    fn play(&mut self, ev: Event) {
+        let (on, key) = match ev.message {
+            MidiMessage::NoteOn { key, vel } => (vel.as_int() > 0, key.as_int()),
+            MidiMessage::NoteOff { key, .. } => (false, key.as_int()),
+            _ => {
+                if !self.timeline.muted[ev.track].load(Ordering::Relaxed) {
+                    self.queue.push_back((ev.channel, ev.message));
+                }
+                return;
+            }
+        };
+
+        // Releases of muted tracks still go out, so nothing hangs
+        let sounding = self.sounding.iter().position(|n| *n == (ev.channel, key));
+        if on && !self.timeline.muted[ev.track].load(Ordering::Relaxed) {
+            if sounding.is_none() {
+                self.sounding.push((ev.channel, key));
+            }
+            self.queue.push_back((ev.channel, ev.message));
+        } else if let (false, Some(k)) = (on, sounding) {
+            self.sounding.swap_remove(k);
+            self.queue.push_back((ev.channel, ev.message));
+        }
+    }
 }
 
 impl MidiSource for SmfSource {
     fn try_next(&mut self) -> Option<(u8, MidiMessage)> {
-        let t = Instant::now() - self.t0;
-        let tick_f = t.as_secs_f64() / self.tick.as_secs_f64();
-        let tick_n = tick_f.round() as u32;
+        // Called once per sample
+        let dt = if deterministic::enabled() {
+            1.0 / 44100.0
+        } else {
+            let now = Instant::now();
+            let dt = (now - self.last).as_secs_f64();
+            self.last = now;
+            dt
+        };
 
-        for (k, track) in self.smf.tracks.iter().enumerate() {
-            let Some(next_ev) = track.get(self.cursors[k]) else {
-                continue;
-            };
+        let seek = self.timeline.seek.swap(-1.0, Ordering::Relaxed);
+        if seek >= 0.0 {
+            self.jump(seek);
+        }
 
-            let target_tick = self.last_ev_tick[k] + next_ev.delta.as_int();
+        if self.timeline.playing.load(Ordering::Relaxed) {
+            self.secs += dt * self.timeline.speed.load(Ordering::Relaxed) as f64;
 
-            if tick_n >= target_tick {
-                if let TrackEventKind::Midi { channel, message } = next_ev.kind {
-                    self.queue.push_back((channel.as_int(), message));
-                }
+            let loop_end = self.timeline.loop_end.load(Ordering::Relaxed);
+            if self.timeline.looping.load(Ordering::Relaxed) && self.secs >= loop_end {
+                let loop_start = self.timeline.loop_start.load(Ordering::Relaxed);
+                self.jump(loop_start.min(loop_end));
+            }
 
-                self.last_ev_tick[k] = target_tick;
-                self.cursors[k] += 1;
+            while let Some(ev) = self.events.get(self.cursor).copied() {
+                if ev.secs > self.secs {
+                    break;
+                }
+                self.play(ev);
+                self.cursor += 1;
             }
+        } else {
+            self.release();
         }
+        self.timeline.position.store(self.secs, Ordering::Relaxed);
 
         self.queue.pop_front()
     }
 
     fn reset(&mut self) {
-        self.t0 = Instant::now();
+        self.jump(0.0);
+        self.last = Instant::now();
+    }
+
+    fn timeline(&self) -> Option<Arc<Timeline>> {
+        Some(Arc::clone(&self.timeline))
     }
 }
 
@@ -0,0 +1,111 @@
+//! Placeholder for nodes whose type doesn't exist in this build, so patches
+//! using them still load and save back unchanged.
+
+use std::{any::Any, cell::RefCell, sync::Arc};
+
+use eframe::egui;
+use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Node, NodeConfig};
+use crate::compute::{Output, ValueKind};
+
+// Outputs reported by the placeholder, enough for connections made to any
+// output of the original node to stay in range
+const OUTPUTS: usize = 16;
+
+thread_local! {
+    // Type name of the node being loaded as a placeholder, handed over from
+    // the tag to the fields
+    static PENDING: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Makes the next `MissingNode` deserialized on this thread stand in for
+/// `name`.
+pub(super) fn expect(name: String) {
+    PENDING.with(|pending| *pending.borrow_mut() = Some(name));
+}
+
+#[derive(Debug)]
+struct MissingConfig {
+    name: String,
+}
+
+impl NodeConfig for MissingConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!("Unknown node type {}", self.name),
+        );
+        ui.label("Kept as saved, produces no output");
+    }
+
+    fn show_short(&self, ui: &mut egui::Ui, data: &dyn Any) {
+        self.show(ui, data);
+    }
+}
+
+/// Node of an unknown type, holding its saved fields.
+#[derive(Clone, Debug)]
+pub struct MissingNode {
+    name: String,
+    fields: serde_json::Map<String, serde_json::Value>,
+    config: Arc<MissingConfig>,
+}
+
+impl<'de> Deserialize<'de> for MissingNode {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let fields = serde_json::Map::deserialize(d)?;
+        let name = PENDING
+            .with(|pending| pending.borrow_mut().take())
+            .unwrap_or_default();
+        crate::notify::report(format!(
+            "Node type {name} doesn't exist in this build, loaded as a placeholder"
+        ));
+
+        Ok(MissingNode {
+            config: Arc::new(MissingConfig { name: name.clone() }),
+            name,
+            fields,
+        })
+    }
+}
+
+impl Serialize for MissingNode {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        self.fields.serialize(s)
+    }
+}
+
+#[typetag::serde]
+impl Node for MissingNode {
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn output(&self) -> Vec<Output> {
+        (0..OUTPUTS)
+            .map(|k| Output::new(format!("out{k}"), ValueKind::Float))
+            .collect()
+    }
+
+    fn as_missing(&self) -> Option<&MissingNode> {
+        Some(self)
+    }
+}
+
+/// Serializes a node, writing placeholders back under their original type.
+// The signature is dictated by `serialize_with`
+#[allow(clippy::borrowed_box)]
+pub fn serialize<S: Serializer>(node: &Box<dyn Node>, s: S) -> Result<S::Ok, S::Error> {
+    let Some(missing) = node.as_missing() else {
+        return node.serialize(s);
+    };
+
+    let mut map = s.serialize_map(Some(missing.fields.len() + 1))?;
+    map.serialize_entry("__ty", &missing.name)?;
+    for (key, value) in &missing.fields {
+        map.serialize_entry(key, value)?;
+    }
+
+    map.end()
+}
@@ -1,23 +1,73 @@
 use eframe::egui;
-use std::{any::Any, fmt::Debug, sync::Arc};
+use std::{
+    any::Any,
+    fmt::Debug,
+    ops::Index,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
 
 use dyn_clone::DynClone;
 
-use super::{Output, Value, ValueKind};
+use super::{bus::Buses, Output, Value, ValueKind};
 
+pub mod aliases;
+pub mod auto_gain;
 pub mod basic;
 pub mod effects;
 pub mod filters;
 pub mod inputs;
 pub mod instruments;
+pub mod logic;
 pub mod midi;
+pub mod missing;
 pub mod noise;
+pub mod pack;
+pub mod stereo;
 
 pub trait NodeConfig {
     fn show(&self, ui: &mut egui::Ui, data: &dyn Any);
     fn show_short(&self, _ui: &mut egui::Ui, _data: &dyn Any) {}
 }
 
+/// Change marker shared between a node config and its node.
+///
+/// The editor marks it whenever the config is modified and the node consumes
+/// it in [`Node::sync_config`], which the runtime calls once per block instead
+/// of the node polling its config on every sample.
+#[derive(Debug)]
+pub struct ConfigDirty(AtomicBool);
+
+impl ConfigDirty {
+    pub fn new() -> Self {
+        ConfigDirty(AtomicBool::new(true))
+    }
+
+    pub fn mark(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    pub fn mark_if(&self, changed: bool) {
+        if changed {
+            self.mark();
+        }
+    }
+
+    pub fn take(&self) -> bool {
+        self.0.swap(false, Ordering::AcqRel)
+    }
+}
+
+// Freshly created and deserialized configs start dirty, so nodes pick up
+// their state on the first block.
+impl Default for ConfigDirty {
+    fn default() -> Self {
+        ConfigDirty::new()
+    }
+}
+
 pub trait InputUi: Send + Sync {
     fn value_kind(&self) -> ValueKind;
     fn needs_deep_update(&self) -> bool {
@@ -28,6 +78,17 @@ pub trait InputUi: Send + Sync {
     }
     fn show_always(&self, _ui: &mut egui::Ui, _verbose: bool) {}
     fn show_disconnected(&self, _ui: &mut egui::Ui, _verbose: bool) {}
+
+    /// Value used while disconnected, for inputs edited as a single number.
+    fn value(&self) -> Option<f32> {
+        None
+    }
+    fn set_value(&self, _value: f32) {}
+    /// Whether the runtime ramps to edits of [`InputUi::value`] instead of
+    /// jumping to them.
+    fn smoothed(&self) -> bool {
+        self.value().is_some()
+    }
 }
 
 pub struct Input {
@@ -56,6 +117,13 @@ impl Input {
             default_value: Some(Arc::clone(default_value) as Arc<dyn InputUi>),
         }
     }
+
+    /// The same input carrying another kind, like an array of the values
+    /// its widget edits.
+    pub fn with_kind(mut self, kind: ValueKind) -> Self {
+        self.kind = kind;
+        self
+    }
 }
 
 impl Debug for Input {
@@ -70,19 +138,114 @@ impl Debug for Input {
     }
 }
 
+static DISCONNECTED: Value = Value::Disconnected;
+
+/// Input a node reads, by its position among the names the node lists in
+/// [`Node::ports`]. Declared with [`ports!`](crate::ports).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Port(pub usize);
+
+/// Declares the inputs the nodes of a module read as [`Port`] constants, and
+/// `PORTS`, the list of their names to return from [`Node::ports`].
+#[macro_export]
+macro_rules! ports {
+    ($($port:ident = $name:literal),* $(,)?) => {
+        const PORTS: &[&str] = &[$($name),*];
+        $crate::ports!(@index 0; $($port)*);
+    };
+    (@index $k:expr;) => {};
+    (@index $k:expr; $port:ident $($rest:ident)*) => {
+        const $port: $crate::compute::node::Port = $crate::compute::node::Port($k);
+        $crate::ports!(@index $k + 1; $($rest)*);
+    };
+}
+
+/// Position of every port of `node` among the declared inputs called
+/// `names`, `None` for ports the node doesn't declare at the moment.
+pub fn resolve_ports(node: &dyn Node, names: &[String]) -> Vec<Option<usize>> {
+    node.ports()
+        .iter()
+        .map(|port| names.iter().position(|name| name == port))
+        .collect()
+}
+
+/// Values received by a node in [`Node::feed`].
+///
+/// Inputs are read through [`Port`]s, resolved to positions whenever the
+/// inputs of the node change, so nodes with a changing set of inputs don't
+/// have to track their positions and feeding them doesn't compare names.
+/// [`InputData::get`] is for optional inputs.
+#[derive(Clone, Copy, Debug)]
+pub struct InputData<'a> {
+    ports: &'a [Option<usize>],
+    values: &'a [Value],
+    bus: &'a Buses,
+}
+
+impl<'a> InputData<'a> {
+    pub fn new(ports: &'a [Option<usize>], values: &'a [Value], bus: &'a Buses) -> Self {
+        InputData { ports, values, bus }
+    }
+
+    /// Buses of the runtime feeding the node.
+    pub fn bus(&self) -> &'a Buses {
+        self.bus
+    }
+
+    /// Value of `port`, `None` if the node doesn't declare it.
+    pub fn get(&self, port: Port) -> Option<&'a Value> {
+        let idx = self.ports.get(port.0).copied().flatten()?;
+        self.values.get(idx)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'a, Value> {
+        self.values.iter()
+    }
+}
+
+impl Index<Port> for InputData<'_> {
+    type Output = Value;
+
+    fn index(&self, port: Port) -> &Value {
+        self.get(port).unwrap_or(&DISCONNECTED)
+    }
+}
+
+// Positional access, for nodes with a variable number of uniform inputs.
+impl Index<usize> for InputData<'_> {
+    type Output = Value;
+
+    fn index(&self, idx: usize) -> &Value {
+        self.values.get(idx).unwrap_or(&DISCONNECTED)
+    }
+}
+
 #[derive(Debug)]
 pub enum NodeEvent {
     RecalcInputs(Vec<Input>),
+    RecalcOutputs(Vec<Output>),
 }
 
 #[typetag::serde(tag = "__ty")]
 pub trait Node: DynClone + Debug + Send {
-    fn feed(&mut self, _data: &[Value]) -> Vec<NodeEvent> {
+    fn feed(&mut self, _data: &InputData) -> Vec<NodeEvent> {
         Default::default()
     }
 
     fn read(&self, _out: &mut [Value]) {}
 
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        Default::default()
+    }
+
     fn config(&self) -> Option<Arc<dyn NodeConfig>> {
         None
     }
@@ -91,9 +254,27 @@ pub trait Node: DynClone + Debug + Send {
         Vec::default()
     }
 
+    /// Names of the inputs read through [`Port`]s, declared or not.
+    fn ports(&self) -> &'static [&'static str] {
+        &[]
+    }
+
     fn output(&self) -> Vec<Output> {
         vec![Output::new("", ValueKind::Float)]
     }
+
+    /// Drops ringing state, like delay lines and sounding notes, for the
+    /// panic button.
+    fn panic(&mut self) {}
+
+    /// Restarts random generators from `seed`, so renders don't depend on
+    /// how long the patch played before it was saved.
+    fn reseed(&mut self, _seed: u64) {}
+
+    /// Placeholder standing in for a node of an unknown type.
+    fn as_missing(&self) -> Option<&missing::MissingNode> {
+        None
+    }
 }
 
 pub trait NodeExt {
@@ -108,15 +289,65 @@ impl<T: Node> NodeExt for T {
     }
 }
 
-pub trait NodeList {
+pub trait NodeList: Send + Sync {
     fn all(&self) -> Vec<(Box<dyn Node>, String, Vec<String>)>;
 }
 
+impl NodeList for Arc<dyn NodeList> {
+    fn all(&self) -> Vec<(Box<dyn Node>, String, Vec<String>)> {
+        (**self).all()
+    }
+}
+
+static REGISTERED: Mutex<Vec<Arc<dyn NodeList>>> = Mutex::new(Vec::new());
+
+/// Adds a list of nodes to the node finder of every patch loaded after.
+/// Crates shipping their own nodes call it through
+/// `modal_lib::register_node_list`, before the editor starts.
+///
+/// Nodes are saved through `typetag`, tagged with the name of their type.
+/// An external crate depends on the same major versions of `typetag` and
+/// `serde` as this one and puts `#[typetag::serde]` on each of its
+/// `impl Node` blocks, which registers the type when the crate is linked
+/// in. Type names have to be unique across all crates, a patch can't tell
+/// two nodes of the same name apart. Nodes of a list registered too late
+/// still load from patches, they just can't be placed from the finder.
+pub fn register_node_list(list: Box<dyn NodeList>) {
+    REGISTERED.lock().unwrap().push(Arc::from(list));
+}
+
+/// Node lists shipped with the editor, then registered ones.
+pub fn node_lists() -> Vec<Box<dyn NodeList>> {
+    use all::*;
+
+    let mut lists: Vec<Box<dyn NodeList>> = vec![
+        Box::new(Basic),
+        Box::new(Effects),
+        Box::new(Filters),
+        Box::new(Instruments),
+        Box::new(Logic),
+        Box::new(Midi),
+        Box::new(Noise),
+        Box::new(Stereo),
+    ];
+    lists.extend(
+        REGISTERED
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|list| Box::new(Arc::clone(list)) as Box<dyn NodeList>),
+    );
+
+    lists
+}
+
 pub mod all {
     pub use super::basic::*;
     pub use super::effects::*;
     pub use super::filters::*;
     pub use super::instruments::*;
+    pub use super::logic::*;
     pub use super::midi::*;
     pub use super::noise::*;
+    pub use super::stereo::*;
 }
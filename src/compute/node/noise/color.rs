@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+/// Spectral shaping of white noise into the usual noise colors.
+///
+/// All filters run on every sample, so switching colors doesn't start from
+/// a cold filter state. Outputs are scaled to roughly -1..1.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ColorFilters {
+    pink: [f32; 7],
+    brown: f32,
+    prev_pink: f32,
+    pink_out: f32,
+    blue_out: f32,
+}
+
+impl ColorFilters {
+    pub fn feed(&mut self, white: f32) {
+        // Paul Kellet's refined -3dB/octave filter
+        let b = &mut self.pink;
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.1538520;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+        let pink = b.iter().sum::<f32>() + white * 0.5362;
+        b[6] = white * 0.115926;
+
+        self.pink_out = pink * 0.11;
+
+        // Leaky integrator, -6dB/octave
+        self.brown = (self.brown + 0.02 * white) / 1.02;
+
+        // Differentiated pink, +3dB/octave
+        self.blue_out = self.pink_out - self.prev_pink;
+        self.prev_pink = self.pink_out;
+    }
+
+    pub fn pink(&self) -> f32 {
+        self.pink_out.clamp(-1.0, 1.0)
+    }
+
+    pub fn brown(&self) -> f32 {
+        (self.brown * 3.5).clamp(-1.0, 1.0)
+    }
+
+    pub fn blue(&self) -> f32 {
+        (self.blue_out * 2.0).clamp(-1.0, 1.0)
+    }
+}
@@ -1,32 +1,47 @@
+mod color;
+
 use std::{
     any::Any,
     sync::{atomic::Ordering, Arc},
 };
 
-use rand::{Rng, SeedableRng};
+use rand::Rng;
 use rand_chacha::ChaCha12Rng;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    compute::Value,
+    compute::{deterministic, Value},
     serde_atomic_enum,
     util::{enum_combo_box, perlin::Perlin1D},
 };
 
+use color::ColorFilters;
+
 use super::{
     inputs::{
         freq::FreqInput,
         real::RealInput,
         trigger::{TriggerInput, TriggerMode},
     },
-    Input, Node, NodeConfig, NodeEvent, NodeList,
+    ConfigDirty, Input, InputData, Node, NodeConfig, NodeEvent, NodeList,
 };
 
+crate::ports! {
+    LATCH = "latch",
+    RESET = "reset",
+    MIN = "min",
+    MAX = "max",
+    F = "f",
+}
+
 #[atomic_enum::atomic_enum]
 #[derive(Serialize, Deserialize, PartialEq, derive_more::Display, strum::EnumIter)]
 enum NoiseType {
     Uniform,
     Perlin,
+    Pink,
+    Brown,
+    Blue,
 }
 
 serde_atomic_enum!(AtomicNoiseType);
@@ -36,6 +51,8 @@ impl Eq for NoiseType {}
 #[derive(Debug, Serialize, Deserialize)]
 struct NoiseGenConfig {
     ty: AtomicNoiseType,
+    #[serde(skip)]
+    dirty: ConfigDirty,
 }
 
 impl NoiseGenConfig {
@@ -48,9 +65,10 @@ impl NodeConfig for NoiseGenConfig {
     fn show(&self, ui: &mut eframe::egui::Ui, _data: &dyn Any) {
         let mut ty = self.ty.load(Ordering::Acquire);
 
-        enum_combo_box(ui, &mut ty);
+        let changed = enum_combo_box(ui, &mut ty);
 
         self.ty.store(ty, Ordering::Release);
+        self.dirty.mark_if(changed);
     }
 }
 
@@ -65,41 +83,46 @@ pub struct NoiseGen {
 
     ty: NoiseType,
     perlin_noise: Perlin1D,
+    #[serde(default)]
+    colors: ColorFilters,
     out: f32,
     t: u64,
 
+    // Reset restarts the generator from it
+    #[serde(default = "deterministic::seed")]
+    seed: u64,
     rng: ChaCha12Rng,
 }
 
 #[typetag::serde]
 impl Node for NoiseGen {
-    fn feed(&mut self, data: &[Value]) -> Vec<NodeEvent> {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
         // Latch continuously if disconnected
-        let latch = if data[0].disconnected() {
+        let latch = if data[LATCH].disconnected() {
             true
         } else {
-            self.latch.trigger(&data[0])
+            self.latch.trigger(&data[LATCH])
         };
 
-        let reset = self.reset.trigger(&data[1]);
+        let reset = self.reset.trigger(&data[RESET]);
         if reset {
-            self.rng = ChaCha12Rng::from_seed([0xFE; 32]);
+            self.rng = deterministic::seeded(self.seed);
             self.t = 0;
         }
 
-        let min = self.min.get_f32(&data[2]);
-        let max = self.max.get_f32(&data[3]);
-        let ty = self.config.noise_type();
+        let min = self.min.get_f32(&data[MIN]);
+        let max = self.max.get_f32(&data[MAX]);
 
-        let emit = ty != self.ty;
-        self.ty = ty;
+        let white = self.rng.gen_range(-1.0..=1.0);
+        self.colors.feed(white);
 
-        let m1_to_p1 = match ty {
-            NoiseType::Uniform => self.rng.gen_range(-1.0..=1.0),
+        let m1_to_p1 = match self.ty {
+            NoiseType::Uniform => white,
+            NoiseType::Pink => self.colors.pink(),
+            NoiseType::Brown => self.colors.brown(),
+            NoiseType::Blue => self.colors.blue(),
             NoiseType::Perlin => {
-                let frequency = self
-                    .frequency_input
-                    .get_f32(data.get(4).unwrap_or(&Value::None));
+                let frequency = self.frequency_input.get_f32(&data[F]);
                 self.t += 1;
                 let perlin_arg = self.t as f32 / 44100.0 * frequency;
 
@@ -113,11 +136,22 @@ impl Node for NoiseGen {
             self.out = z_to_p1 * (max - min) + min;
         }
 
-        if emit {
-            vec![NodeEvent::RecalcInputs(self.inputs())]
-        } else {
-            Default::default()
+        Default::default()
+    }
+
+    fn sync_config(&mut self) -> Vec<NodeEvent> {
+        if !self.config.dirty.take() {
+            return Default::default();
         }
+
+        let ty = self.config.noise_type();
+        if ty == self.ty {
+            return Default::default();
+        }
+
+        self.ty = ty;
+
+        vec![NodeEvent::RecalcInputs(self.inputs())]
     }
 
     fn read(&self, out: &mut [Value]) {
@@ -127,6 +161,10 @@ impl Node for NoiseGen {
         Some(Arc::clone(&self.config) as Arc<_>)
     }
 
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
     fn inputs(&self) -> Vec<Input> {
         let mut ins = vec![
             Input::stateful("latch", &self.latch),
@@ -142,12 +180,19 @@ impl Node for NoiseGen {
 
         ins
     }
+
+    fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = deterministic::seeded(seed);
+    }
 }
 
 fn noise_gen() -> Box<dyn Node> {
+    let seed = deterministic::seed();
     Box::new(NoiseGen {
         config: Arc::new(NoiseGenConfig {
             ty: AtomicNoiseType::new(NoiseType::Uniform),
+            dirty: ConfigDirty::new(),
         }),
         latch: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
         reset: Arc::new(TriggerInput::new(TriggerMode::Up, 0.5)),
@@ -156,10 +201,12 @@ fn noise_gen() -> Box<dyn Node> {
         frequency_input: Arc::new(FreqInput::new(440.0)),
         ty: NoiseType::Uniform,
         perlin_noise: Perlin1D::new(),
+        colors: ColorFilters::default(),
         out: 0.0,
         t: 0,
 
-        rng: ChaCha12Rng::from_seed([0xFE; 32]),
+        seed,
+        rng: deterministic::seeded(seed),
     })
 }
 
@@ -0,0 +1,454 @@
+//! Nodes loaded from native libraries at startup, so community nodes can be
+//! installed without rebuilding the editor.
+//!
+//! A node pack is a `cdylib` in the `nodes` folder next to the settings,
+//! exporting a C function `modal_node_pack` that returns a pointer to a
+//! [`RawPack`] valid for as long as the library is loaded:
+//!
+//! ```c
+//! typedef struct {
+//!     const char *name;                 // in the node finder
+//!     const char *category;
+//!     uint32_t inputs;
+//!     const char *const *input_names;
+//!     uint32_t outputs;
+//!     const char *const *output_names;
+//!     void *(*create)(void);
+//!     void (*destroy)(void *node);
+//!     // Once per sample, `in` holds a value per input and `out` one per output
+//!     void (*process)(void *node, const float *in, float *out);
+//!     // Silences the node like a new one, on the audio thread like `process`
+//!     void (*reset)(void *node);
+//! } ModalNode;
+//!
+//! typedef struct {
+//!     uint32_t abi;                     // 2
+//!     uint32_t len;
+//!     const ModalNode *nodes;
+//! } ModalNodePack;
+//!
+//! const ModalNodePack *modal_node_pack(void);
+//! ```
+//!
+//! Every function is required and input names must be unique within a node,
+//! a pack breaking either fails to load. Nodes are created and destroyed
+//! away from the audio thread where possible, and processed on another, but
+//! only one thread uses a node at a time.
+
+use std::{
+    any::Any,
+    ffi::{c_char, c_void, CStr},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{anyhow, bail, Result};
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use super::{Input, InputData, Node, NodeConfig, NodeEvent, NodeList};
+use crate::compute::{Output, Value, ValueKind};
+
+/// Version of the layout below, bumped on every change to it.
+pub const ABI: u32 = 2;
+
+// Category every pack node is listed under, besides its own
+const CATEGORY: &str = "Packs";
+
+// Both are only ever made by packs
+#[repr(C)]
+pub struct RawNode {
+    name: *const c_char,
+    category: *const c_char,
+    inputs: u32,
+    input_names: *const *const c_char,
+    outputs: u32,
+    output_names: *const *const c_char,
+    // Null in a broken pack, checked on load
+    create: Option<extern "C" fn() -> *mut c_void>,
+    destroy: Option<extern "C" fn(*mut c_void)>,
+    process: Option<extern "C" fn(*mut c_void, *const f32, *mut f32)>,
+    reset: Option<extern "C" fn(*mut c_void)>,
+}
+
+#[repr(C)]
+pub struct RawPack {
+    abi: u32,
+    len: u32,
+    nodes: *const RawNode,
+}
+
+struct Kind {
+    name: String,
+    category: String,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    create: extern "C" fn() -> *mut c_void,
+    destroy: extern "C" fn(*mut c_void),
+    process: extern "C" fn(*mut c_void, *const f32, *mut f32),
+    reset: extern "C" fn(*mut c_void),
+}
+
+struct Pack {
+    name: String,
+    kinds: Vec<Kind>,
+    // Functions of the kinds live in the library, it's never unloaded
+    _lib: libloading::Library,
+}
+
+impl std::fmt::Debug for Pack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pack").field("name", &self.name).finish()
+    }
+}
+
+static PACKS: Mutex<Vec<Arc<Pack>>> = Mutex::new(Vec::new());
+
+unsafe fn string(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        String::new()
+    } else {
+        CStr::from_ptr(ptr).to_string_lossy().into_owned()
+    }
+}
+
+unsafe fn strings(ptr: *const *const c_char, len: u32) -> Vec<String> {
+    if ptr.is_null() {
+        return vec![String::new(); len as usize];
+    }
+    std::slice::from_raw_parts(ptr, len as usize)
+        .iter()
+        .map(|name| string(*name))
+        .collect()
+}
+
+fn load(path: &Path) -> Result<Pack> {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .ok_or(anyhow!("No file name"))?;
+
+    // Safety: packs are native code trusted like the editor itself, and are
+    // required to follow the layout of this module
+    unsafe {
+        let lib = libloading::Library::new(path)?;
+        let entry: libloading::Symbol<extern "C" fn() -> *const RawPack> =
+            lib.get(b"modal_node_pack\0")?;
+        let Some(raw) = entry().as_ref() else {
+            bail!("modal_node_pack returned no pack");
+        };
+        if raw.abi != ABI {
+            bail!(
+                "Pack is for version {} of the interface, not {ABI}",
+                raw.abi
+            );
+        }
+
+        let nodes: &[RawNode] = if raw.nodes.is_null() {
+            &[]
+        } else {
+            std::slice::from_raw_parts(raw.nodes, raw.len as usize)
+        };
+        let kinds = nodes
+            .iter()
+            .map(|node| {
+                let name = string(node.name);
+                let missing = |function| anyhow!("{name} has no {function} function");
+                Ok(Kind {
+                    category: string(node.category),
+                    inputs: strings(node.input_names, node.inputs),
+                    outputs: strings(node.output_names, node.outputs),
+                    create: node.create.ok_or_else(|| missing("create"))?,
+                    destroy: node.destroy.ok_or_else(|| missing("destroy"))?,
+                    process: node.process.ok_or_else(|| missing("process"))?,
+                    reset: node.reset.ok_or_else(|| missing("reset"))?,
+                    name,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Connections follow inputs by name, a repeated one would shadow the
+        // others
+        for kind in &kinds {
+            for (k, input) in kind.inputs.iter().enumerate() {
+                if kind.inputs[..k].contains(input) {
+                    bail!("{} has two inputs called {input:?}", kind.name);
+                }
+            }
+        }
+
+        Ok(Pack {
+            name,
+            kinds,
+            _lib: lib,
+        })
+    }
+}
+
+/// Loads every library in `dir` as a node pack, reporting the ones that
+/// fail. Only patches loaded afterwards find the nodes of the packs.
+pub fn load_dir(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut loaded = Vec::new();
+    for path in entries.filter_map(|entry| Some(entry.ok()?.path())) {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        if !matches!(extension, Some("so" | "dylib" | "dll")) {
+            continue;
+        }
+
+        match load(&path) {
+            Ok(pack) => loaded.push(Arc::new(pack)),
+            Err(e) => crate::notify::error(&format!("Failed to load {}", path.display()), e),
+        }
+    }
+
+    if !loaded.is_empty() {
+        let mut packs = PACKS.lock().unwrap();
+        if packs.is_empty() {
+            super::register_node_list(Box::new(Packs));
+        }
+        packs.extend(loaded);
+    }
+}
+
+// Kind of a node, resolved when the node is made so the audio thread never
+// looks it up
+type Loaded = (Arc<Pack>, usize);
+
+fn find(pack: &str, kind: &str) -> Option<Loaded> {
+    let packs = PACKS.lock().unwrap();
+    let pack = packs.iter().find(|loaded| loaded.name == pack)?;
+    let kind = pack.kinds.iter().position(|loaded| loaded.name == kind)?;
+
+    Some((Arc::clone(pack), kind))
+}
+
+struct Instance {
+    pack: Arc<Pack>,
+    kind: usize,
+    state: *mut c_void,
+}
+
+// Safety: packs allow their nodes to move between threads, and the runtime
+// uses a node from one thread at a time
+unsafe impl Send for Instance {}
+
+impl Instance {
+    fn new((pack, kind): &Loaded) -> Self {
+        let state = (pack.kinds[*kind].create)();
+        Instance {
+            pack: Arc::clone(pack),
+            kind: *kind,
+            state,
+        }
+    }
+
+    fn process(&mut self, ins: &[f32], outs: &mut [f32]) {
+        (self.pack.kinds[self.kind].process)(self.state, ins.as_ptr(), outs.as_mut_ptr());
+    }
+
+    fn reset(&mut self) {
+        (self.pack.kinds[self.kind].reset)(self.state);
+    }
+}
+
+impl Drop for Instance {
+    fn drop(&mut self) {
+        (self.pack.kinds[self.kind].destroy)(self.state);
+    }
+}
+
+impl std::fmt::Debug for Instance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Instance")
+            .field("pack", &self.pack.name)
+            .field("kind", &self.pack.kinds[self.kind].name)
+            .finish()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PackConfig {
+    pack: String,
+    kind: String,
+}
+
+impl NodeConfig for PackConfig {
+    fn show(&self, ui: &mut egui::Ui, _data: &dyn Any) {
+        if find(&self.pack, &self.kind).is_some() {
+            ui.weak(format!("From pack {}", self.pack));
+        } else {
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                format!("Pack {} isn't installed", self.pack),
+            );
+            ui.label("Kept as saved, produces no output");
+        }
+    }
+}
+
+/// Node of a pack, saved by the names of the pack and the node so that it
+/// loads back once the pack is installed.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(from = "SavedPackNode")]
+pub struct PackNode {
+    config: Arc<PackConfig>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    #[serde(skip)]
+    loaded: Option<Loaded>,
+    // Made along with the node, templates never get one
+    #[serde(skip)]
+    instance: Option<Instance>,
+    #[serde(skip)]
+    ins: Vec<f32>,
+    #[serde(skip)]
+    outs: Vec<f32>,
+}
+
+#[derive(Deserialize)]
+struct SavedPackNode {
+    config: Arc<PackConfig>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+}
+
+impl From<SavedPackNode> for PackNode {
+    fn from(saved: SavedPackNode) -> Self {
+        // A pack changed since the patch was saved could read or write past
+        // the values, it stays silent instead
+        let loaded = find(&saved.config.pack, &saved.config.kind).filter(|(pack, kind)| {
+            let loaded = &pack.kinds[*kind];
+            loaded.inputs.len() == saved.inputs.len() && loaded.outputs.len() == saved.outputs.len()
+        });
+
+        let mut node = PackNode::with_names(saved.config, saved.inputs, saved.outputs, loaded);
+        node.instance = node.loaded.as_ref().map(Instance::new);
+
+        node
+    }
+}
+
+impl PackNode {
+    fn with_names(
+        config: Arc<PackConfig>,
+        inputs: Vec<String>,
+        outputs: Vec<String>,
+        loaded: Option<Loaded>,
+    ) -> Self {
+        PackNode {
+            config,
+            ins: vec![0.0; inputs.len()],
+            outs: vec![0.0; outputs.len()],
+            inputs,
+            outputs,
+            loaded,
+            instance: None,
+        }
+    }
+
+    fn new(pack: &Arc<Pack>, kind: usize) -> Self {
+        let config = PackConfig {
+            pack: pack.name.clone(),
+            kind: pack.kinds[kind].name.clone(),
+        };
+
+        PackNode::with_names(
+            Arc::new(config),
+            pack.kinds[kind].inputs.clone(),
+            pack.kinds[kind].outputs.clone(),
+            Some((Arc::clone(pack), kind)),
+        )
+    }
+}
+
+// Copies start over with state of their own
+impl Clone for PackNode {
+    fn clone(&self) -> Self {
+        let mut node = PackNode::with_names(
+            Arc::clone(&self.config),
+            self.inputs.clone(),
+            self.outputs.clone(),
+            self.loaded.clone(),
+        );
+        node.instance = node.loaded.as_ref().map(Instance::new);
+
+        node
+    }
+}
+
+#[typetag::serde]
+impl Node for PackNode {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        // Inputs are declared in the order of the pack
+        for (k, value) in self.ins.iter_mut().enumerate() {
+            *value = data[k].as_float().unwrap_or_default();
+        }
+
+        if let Some(instance) = &mut self.instance {
+            instance.process(&self.ins, &mut self.outs);
+        }
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        for (out, value) in out.iter_mut().zip(&self.outs) {
+            *out = Value::Float(*value);
+        }
+    }
+
+    fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        Some(Arc::clone(&self.config) as Arc<_>)
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        self.inputs
+            .iter()
+            .map(|name| Input::new(name, ValueKind::Float))
+            .collect()
+    }
+
+    fn output(&self) -> Vec<Output> {
+        self.outputs
+            .iter()
+            .map(|name| Output::new(name, ValueKind::Float))
+            .collect()
+    }
+
+    fn panic(&mut self) {
+        if let Some(instance) = &mut self.instance {
+            instance.reset();
+        }
+        self.outs.fill(0.0);
+    }
+}
+
+/// Nodes of every loaded pack.
+pub struct Packs;
+
+impl NodeList for Packs {
+    fn all(&self) -> Vec<(Box<dyn Node>, String, Vec<String>)> {
+        PACKS
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|pack| {
+                pack.kinds.iter().enumerate().map(|(k, kind)| {
+                    let mut categories = vec![CATEGORY.to_string()];
+                    if !kind.category.is_empty() {
+                        categories.push(kind.category.clone());
+                    }
+                    (
+                        Box::new(PackNode::new(pack, k)) as Box<dyn Node>,
+                        kind.name.clone(),
+                        categories,
+                    )
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,41 @@
+//! Stereo signals are two channel arrays, left then right. Mono signals
+//! read as the same value on both sides.
+
+use super::{Node, NodeList};
+use crate::compute::Value;
+
+pub mod pan;
+pub mod utility;
+pub mod width;
+
+fn left_right(value: &Value) -> (f32, f32) {
+    let side = |k| value.channel(k).as_float().unwrap_or_default();
+
+    (side(0), side(1))
+}
+
+pub struct Stereo;
+
+impl NodeList for Stereo {
+    fn all(&self) -> Vec<(Box<dyn Node>, String, Vec<String>)> {
+        vec![
+            (pan::pan(), "Pan".into(), vec!["Stereo".into()]),
+            (width::width(), "Width".into(), vec!["Stereo".into()]),
+            (
+                utility::mono_to_stereo(),
+                "Mono to Stereo".into(),
+                vec!["Stereo".into()],
+            ),
+            (
+                utility::stereo_to_mono(),
+                "Stereo to Mono".into(),
+                vec!["Stereo".into()],
+            ),
+            (
+                utility::channel_swap(),
+                "Channel Swap".into(),
+                vec!["Stereo".into()],
+            ),
+        ]
+    }
+}
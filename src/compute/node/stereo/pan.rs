@@ -0,0 +1,60 @@
+use std::{f32::consts::FRAC_PI_4, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{inputs::slider::SliderInput, Input, InputData, Node, NodeEvent},
+    Output, Value, ValueKind,
+};
+
+crate::ports! {
+    SIG = "sig",
+    PAN = "pan",
+}
+
+/// Places a mono signal between left (-1) and right (1) at constant power,
+/// so it's as loud in the middle as at either side.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Pan {
+    pan: Arc<SliderInput>,
+    out: [f32; 2],
+}
+
+#[typetag::serde]
+impl Node for Pan {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let sig = data[SIG].as_float().unwrap_or_default();
+        let pan = self.pan.as_f32(&data[PAN]).clamp(-1.0, 1.0);
+
+        let (right, left) = ((pan + 1.0) * FRAC_PI_4).sin_cos();
+        self.out = [sig * left, sig * right];
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::FloatArray(self.out.to_vec())
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::new("sig", ValueKind::Float),
+            Input::stateful("pan", &self.pan),
+        ]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![Output::new("", ValueKind::FloatArray)]
+    }
+}
+
+pub fn pan() -> Box<dyn Node> {
+    Box::new(Pan {
+        pan: Arc::new(SliderInput::new(0.0, -1.0, 1.0)),
+        out: [0.0; 2],
+    })
+}
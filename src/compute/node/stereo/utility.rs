@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{Input, InputData, Node, NodeEvent},
+    Output, Value, ValueKind,
+};
+
+use super::left_right;
+
+crate::ports! {
+    SIG = "sig",
+}
+
+/// The same signal on both sides.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MonoToStereo {
+    out: f32,
+}
+
+#[typetag::serde]
+impl Node for MonoToStereo {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        self.out = data[SIG].as_float().unwrap_or_default();
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::FloatArray(vec![self.out; 2])
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::new("sig", ValueKind::Float)]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![Output::new("", ValueKind::FloatArray)]
+    }
+}
+
+/// Average of the two sides.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StereoToMono {
+    out: f32,
+}
+
+#[typetag::serde]
+impl Node for StereoToMono {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let (left, right) = left_right(&data[SIG]);
+        self.out = (left + right) / 2.0;
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::Float(self.out)
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::new("sig", ValueKind::FloatArray)]
+    }
+}
+
+/// Left and right the other way around.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChannelSwap {
+    out: [f32; 2],
+}
+
+#[typetag::serde]
+impl Node for ChannelSwap {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let (left, right) = left_right(&data[SIG]);
+        self.out = [right, left];
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::FloatArray(self.out.to_vec())
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![Input::new("sig", ValueKind::FloatArray)]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![Output::new("", ValueKind::FloatArray)]
+    }
+}
+
+pub fn mono_to_stereo() -> Box<dyn Node> {
+    Box::new(MonoToStereo { out: 0.0 })
+}
+
+pub fn stereo_to_mono() -> Box<dyn Node> {
+    Box::new(StereoToMono { out: 0.0 })
+}
+
+pub fn channel_swap() -> Box<dyn Node> {
+    Box::new(ChannelSwap { out: [0.0; 2] })
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::compute::{
+    node::{inputs::slider::SliderInput, Input, InputData, Node, NodeEvent},
+    Output, Value, ValueKind,
+};
+
+use super::left_right;
+
+crate::ports! {
+    SIG = "sig",
+    WIDTH = "width",
+}
+
+/// Scales the side of a stereo signal against its mid, 0 folds it to mono
+/// and 2 doubles the difference between the sides.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Width {
+    width: Arc<SliderInput>,
+    out: [f32; 2],
+}
+
+#[typetag::serde]
+impl Node for Width {
+    fn feed(&mut self, data: &InputData) -> Vec<NodeEvent> {
+        let (left, right) = left_right(&data[SIG]);
+        let width = self.width.as_f32(&data[WIDTH]);
+
+        let mid = (left + right) / 2.0;
+        let side = (left - right) / 2.0 * width;
+        self.out = [mid + side, mid - side];
+
+        Default::default()
+    }
+
+    fn read(&self, out: &mut [Value]) {
+        out[0] = Value::FloatArray(self.out.to_vec())
+    }
+
+    fn ports(&self) -> &'static [&'static str] {
+        PORTS
+    }
+
+    fn inputs(&self) -> Vec<Input> {
+        vec![
+            Input::new("sig", ValueKind::FloatArray),
+            Input::stateful("width", &self.width),
+        ]
+    }
+
+    fn output(&self) -> Vec<Output> {
+        vec![Output::new("", ValueKind::FloatArray)]
+    }
+}
+
+pub fn width() -> Box<dyn Node> {
+    Box::new(Width {
+        width: Arc::new(SliderInput::new(1.0, 0.0, 2.0)),
+        out: [0.0; 2],
+    })
+}
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use super::Value;
+
+// Magnitude infinities are clamped to
+const LIMIT: f32 = 1.0;
+
+/// What the runtime does with NaN and infinite values nodes output, before
+/// they reach the nodes fed from them. A single filter blowing up would
+/// otherwise poison everything downstream of it for good.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Quarantine {
+    /// Passed on unchecked
+    #[default]
+    Off,
+    /// Replaced with 0
+    Zero,
+    /// Infinities clamped to ±1, NaN replaced with 0
+    Clamp,
+}
+
+impl Quarantine {
+    pub const ALL: [Quarantine; 3] = [Quarantine::Off, Quarantine::Zero, Quarantine::Clamp];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Quarantine::Off => "Off",
+            Quarantine::Zero => "Zero",
+            Quarantine::Clamp => "Clamp",
+        }
+    }
+
+    fn fix(self, s: &mut f32) {
+        if s.is_finite() {
+            return;
+        }
+
+        *s = match self {
+            Quarantine::Clamp if s.is_infinite() => LIMIT.copysign(*s),
+            _ => 0.0,
+        };
+    }
+
+    /// Makes `value` finite, unless off.
+    pub fn apply(self, value: &mut Value) {
+        if self == Quarantine::Off {
+            return;
+        }
+
+        match value {
+            Value::Float(s) => self.fix(s),
+            Value::FloatArray(s) => s.iter_mut().for_each(|s| self.fix(s)),
+            _ => {}
+        }
+    }
+}
+
+/// Whether `value` holds a NaN or an infinity.
+pub fn non_finite(value: &Value) -> bool {
+    match value {
+        Value::Float(s) => !s.is_finite(),
+        Value::FloatArray(s) => s.iter().any(|s| !s.is_finite()),
+        _ => false,
+    }
+}
@@ -0,0 +1,121 @@
+//! Fixed size queues between the runtime thread and the callbacks of audio
+//! backends, neither of which may block or allocate.
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+    OnceLock,
+};
+
+/// Queue between one producer thread and one consumer thread, of entries
+/// `W` words wide. Entries pushed while it's full are dropped.
+pub struct Ring<const W: usize> {
+    slots: Box<[[AtomicU64; W]]>,
+    // Entries pushed and popped so far, the slot of entry n is n % len
+    pushed: AtomicUsize,
+    popped: AtomicUsize,
+}
+
+impl<const W: usize> Ring<W> {
+    pub fn new(capacity: usize) -> Self {
+        Ring {
+            slots: (0..capacity.max(1))
+                .map(|_| std::array::from_fn(|_| AtomicU64::new(0)))
+                .collect(),
+            pushed: AtomicUsize::new(0),
+            popped: AtomicUsize::new(0),
+        }
+    }
+
+    /// Appends an entry, returns `false` if there was no room for it. Only
+    /// called by the producer.
+    pub fn push(&self, entry: [u64; W]) -> bool {
+        let pushed = self.pushed.load(Ordering::Relaxed);
+        let popped = self.popped.load(Ordering::Acquire);
+        if pushed.wrapping_sub(popped) >= self.slots.len() {
+            return false;
+        }
+
+        let slot = &self.slots[pushed % self.slots.len()];
+        for (word, value) in slot.iter().zip(entry) {
+            word.store(value, Ordering::Relaxed);
+        }
+        self.pushed.store(pushed.wrapping_add(1), Ordering::Release);
+
+        true
+    }
+
+    /// Oldest entry, left in the queue. Only called by the consumer.
+    pub fn peek(&self) -> Option<[u64; W]> {
+        let popped = self.popped.load(Ordering::Relaxed);
+        if popped == self.pushed.load(Ordering::Acquire) {
+            return None;
+        }
+
+        let slot = &self.slots[popped % self.slots.len()];
+        Some(std::array::from_fn(|k| slot[k].load(Ordering::Relaxed)))
+    }
+
+    /// Removes the oldest entry. Only called by the consumer.
+    pub fn pop(&self) -> Option<[u64; W]> {
+        let entry = self.peek()?;
+        let popped = self.popped.load(Ordering::Relaxed);
+        self.popped.store(popped.wrapping_add(1), Ordering::Release);
+
+        Some(entry)
+    }
+
+    /// Drops every queued entry. Only called by the consumer.
+    pub fn clear(&self) {
+        let pushed = self.pushed.load(Ordering::Acquire);
+        self.popped.store(pushed, Ordering::Release);
+    }
+}
+
+/// Ring fed by the runtime only while a reader outside of it is open, so
+/// nothing piles up with nobody listening. Meant for statics, the ring is
+/// allocated when first opened.
+pub struct Channel<const W: usize> {
+    capacity: usize,
+    ring: OnceLock<Ring<W>>,
+    open: AtomicBool,
+}
+
+impl<const W: usize> Channel<W> {
+    pub const fn new(capacity: usize) -> Self {
+        Channel {
+            capacity,
+            ring: OnceLock::new(),
+            open: AtomicBool::new(false),
+        }
+    }
+
+    /// Starts queueing, dropping whatever an earlier reader left. Called by
+    /// the reader before it starts reading, never from a real time thread.
+    pub fn open(&self) {
+        let ring = self.ring.get_or_init(|| Ring::new(self.capacity));
+        ring.clear();
+        self.open.store(true, Ordering::Release);
+    }
+
+    /// Stops queueing, once the reader is gone.
+    pub fn close(&self) {
+        self.open.store(false, Ordering::Release);
+    }
+
+    /// Queues an entry if the channel is open, returns whether it was.
+    pub fn push(&self, entry: [u64; W]) -> bool {
+        match self.reader() {
+            Some(ring) => ring.push(entry),
+            None => false,
+        }
+    }
+
+    /// Ring to read from, `None` while closed.
+    pub fn reader(&self) -> Option<&Ring<W>> {
+        if self.open.load(Ordering::Acquire) {
+            self.ring.get()
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::{fmt::Debug, sync::Arc};
+
+use super::{
+    node::{Input, InputUi},
+    Value,
+};
+
+/// Time constant of the ramps unless set otherwise.
+pub const DEFAULT_MS: f32 = 5.0;
+
+// Distance to the edited value, relative to it, at which a ramp ends
+const SETTLED: f32 = 1e-4;
+
+/// Part of the distance to the edited value covered every sample, for a
+/// time constant of `ms`.
+pub fn coefficient(ms: f32) -> f32 {
+    if ms > 0.0 {
+        1.0 - (-1000.0 / (ms * 44100.0)).exp()
+    } else {
+        1.0
+    }
+}
+
+#[derive(Clone)]
+struct Ramp {
+    ui: Arc<dyn InputUi>,
+    // Value last fed, `None` until the input is first fed disconnected
+    at: Option<f32>,
+}
+
+/// Ramps of the disconnected inputs of a node towards the values edited in
+/// the editor, so edits don't jump from one sample to the next.
+#[derive(Clone, Default)]
+pub struct Smoothing {
+    ramps: Vec<Option<Ramp>>,
+}
+
+impl Smoothing {
+    pub fn new(inputs: &[Input]) -> Self {
+        let ramps = inputs
+            .iter()
+            .map(|input| {
+                let ui = input.default_value.as_ref().filter(|ui| ui.smoothed())?;
+                Some(Ramp {
+                    ui: Arc::clone(ui),
+                    at: None,
+                })
+            })
+            .collect();
+
+        Smoothing { ramps }
+    }
+
+    /// Value of disconnected input `port`, [`Value::Disconnected`] unless
+    /// it's still ramping, so settled inputs read their own value.
+    pub fn next(&mut self, port: usize, coefficient: f32) -> Value {
+        let Some(Some(ramp)) = self.ramps.get_mut(port) else {
+            return Value::Disconnected;
+        };
+        let Some(target) = ramp.ui.value() else {
+            return Value::Disconnected;
+        };
+
+        let at = ramp.at.unwrap_or(target);
+        let next = at + (target - at) * coefficient;
+        if (target - next).abs() <= SETTLED * target.abs().max(1.0) {
+            ramp.at = Some(target);
+            return Value::Disconnected;
+        }
+
+        ramp.at = Some(next);
+        Value::Float(next)
+    }
+
+    /// Forgets where a connected input was, it starts at the edited value
+    /// once disconnected again.
+    pub fn connected(&mut self, port: usize) {
+        if let Some(Some(ramp)) = self.ramps.get_mut(port) {
+            ramp.at = None;
+        }
+    }
+}
+
+impl Debug for Smoothing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Smoothing")
+            .field(
+                "ramps",
+                &self
+                    .ramps
+                    .iter()
+                    .map(|ramp| ramp.as_ref().and_then(|ramp| ramp.at))
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
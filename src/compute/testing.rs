@@ -0,0 +1,251 @@
+//! Drives single nodes with synthetic input, without the editor or an audio
+//! device, for the tests of the filter and envelope nodes.
+
+use std::f32::consts::TAU;
+
+use midly::MidiMessage;
+
+use super::{
+    bus::Buses,
+    node::{all, resolve_ports, InputData, Node, NodeEvent},
+    Value,
+};
+
+/// Synthetic input sequence, sampled once per step.
+#[derive(Clone, Debug)]
+pub enum Signal {
+    /// 1.0 on the first sample, 0.0 afterwards
+    Impulse,
+    /// 0.0 before `at`, 1.0 from then on
+    Step {
+        at: usize,
+    },
+    /// 1.0 from `on` until `off`
+    Pulse {
+        on: usize,
+        off: usize,
+    },
+    Sine {
+        freq: f32,
+        amp: f32,
+    },
+    Constant(f32),
+    /// Messages sent on the given samples, `Value::None` in between
+    Midi(Vec<(usize, u8, MidiMessage)>),
+    Disconnected,
+}
+
+impl Signal {
+    pub fn at(&self, t: usize) -> Value {
+        match self {
+            Signal::Impulse => Value::Float(if t == 0 { 1.0 } else { 0.0 }),
+            Signal::Step { at } => Value::Float(if t >= *at { 1.0 } else { 0.0 }),
+            Signal::Pulse { on, off } => {
+                Value::Float(if (*on..*off).contains(&t) { 1.0 } else { 0.0 })
+            }
+            Signal::Sine { freq, amp } => {
+                Value::Float(amp * (TAU * freq * t as f32 / 44100.0).sin())
+            }
+            Signal::Constant(value) => Value::Float(*value),
+            Signal::Midi(script) => script
+                .iter()
+                .find(|(at, _, _)| *at == t)
+                .map(|(_, channel, message)| Value::Midi {
+                    channel: *channel,
+                    message: *message,
+                })
+                .unwrap_or(Value::None),
+            Signal::Disconnected => Value::Disconnected,
+        }
+    }
+}
+
+/// Single node fed the way the runtime feeds it.
+///
+/// Configs are synced before the first sample and every 512 samples after,
+/// and the input layout follows `RecalcInputs`. Outputs are read right after
+/// each feed, the runtime sees them one step later.
+pub struct Harness {
+    node: Box<dyn Node>,
+    names: Vec<String>,
+    ports: Vec<Option<usize>>,
+    buses: Buses,
+    t: usize,
+}
+
+impl Harness {
+    pub fn new(node: Box<dyn Node>) -> Self {
+        let names: Vec<_> = node.inputs().into_iter().map(|input| input.name).collect();
+        let ports = resolve_ports(&*node, &names);
+
+        Harness {
+            node,
+            names,
+            ports,
+            buses: Buses::default(),
+            t: 0,
+        }
+    }
+
+    pub fn node(&self) -> &dyn Node {
+        &*self.node
+    }
+
+    fn handle(&mut self, events: Vec<NodeEvent>) {
+        for event in events {
+            match event {
+                NodeEvent::RecalcInputs(inputs) => {
+                    self.names = inputs.into_iter().map(|input| input.name).collect();
+                    self.ports = resolve_ports(&*self.node, &self.names);
+                }
+                // Outputs are read by their count, taken from the node
+                NodeEvent::RecalcOutputs(_) => {}
+            }
+        }
+    }
+
+    /// Feeds one sample, inputs missing from `signals` are disconnected.
+    pub fn step(&mut self, signals: &[(&str, &Signal)]) -> Vec<Value> {
+        if self.t % 512 == 0 {
+            let events = self.node.sync_config();
+            self.handle(events);
+        }
+
+        let values: Vec<_> = self
+            .names
+            .iter()
+            .map(|name| {
+                signals
+                    .iter()
+                    .find(|(signal_name, _)| signal_name == name)
+                    .map(|(_, signal)| signal.at(self.t))
+                    .unwrap_or(Value::Disconnected)
+            })
+            .collect();
+
+        let events = self
+            .node
+            .feed(&InputData::new(&self.ports, &values, &self.buses));
+        self.handle(events);
+        self.buses.step();
+        self.t += 1;
+
+        let mut out = vec![Value::None; self.node.output().len()];
+        self.node.read(&mut out);
+
+        out
+    }
+
+    /// Runs `len` samples, returns the first output as floats.
+    pub fn run(&mut self, signals: &[(&str, &Signal)], len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|_| {
+                self.step(signals)
+                    .first()
+                    .and_then(Value::as_float)
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+}
+
+pub fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+
+    (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt()
+}
+
+/// Amplitude of `freq` in `samples`, by a single bin of the DFT.
+pub fn magnitude(samples: &[f32], freq: f32) -> f32 {
+    let (re, im) = samples
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(re, im), (t, x)| {
+            let phase = TAU * freq * t as f32 / 44100.0;
+            (re + x * phase.cos(), im - x * phase.sin())
+        });
+
+    2.0 * (re * re + im * im).sqrt() / samples.len().max(1) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_within(what: &str, value: f32, range: std::ops::RangeInclusive<f32>) {
+        assert!(
+            range.contains(&value),
+            "{what} is {value}, expected {range:?}"
+        );
+    }
+
+    // Low-pass at 440 Hz passes 100 Hz and attenuates 5 kHz
+    #[test]
+    fn biquad_response() {
+        let response = |freq| {
+            let mut harness = Harness::new(all::biquad::biquad());
+            let sig = Signal::Sine { freq, amp: 1.0 };
+            let samples = harness.run(&[("sig", &sig)], 44100);
+
+            // Past the transient
+            magnitude(&samples[22050..], freq)
+        };
+
+        assert_within("gain at 100 Hz", response(100.0), 0.9..=1.2);
+        assert_within("gain at 5 kHz", response(5000.0), 0.0..=0.02);
+    }
+
+    // Impulse response sums to the unit gain at DC and dies out
+    #[test]
+    fn biquad_impulse() {
+        let mut harness = Harness::new(all::biquad::biquad());
+        let samples = harness.run(&[("sig", &Signal::Impulse)], 44100);
+
+        assert_within("gain at DC", samples.iter().sum(), 0.99..=1.01);
+        assert_within(
+            "tail of the impulse response",
+            rms(&samples[22050..]),
+            0.0..=1e-6,
+        );
+    }
+
+    // Default envelope: 50 ms attack, 50 ms decay to 70%, 500 ms release
+    #[test]
+    fn adsr() {
+        let mut harness = Harness::new(all::adsr::adsr());
+        let gate = Signal::Pulse { on: 0, off: 22050 };
+        let signal = Signal::Constant(1.0);
+        let samples = harness.run(&[("gate", &gate), ("signal", &signal)], 66150);
+
+        assert_within("level half way through attack", samples[1102], 0.45..=0.55);
+        assert_within(
+            "peak",
+            samples.iter().copied().fold(0.0, f32::max),
+            0.99..=1.0,
+        );
+        assert_within("sustain level", samples[11025], 0.69..=0.71);
+        assert_within("level half way through release", samples[33075], 0.3..=0.4);
+        assert_within("level after release", samples[66000], 0.0..=1e-6);
+    }
+
+    // Retriggered over and over with no length, the curve must stay finite
+    #[test]
+    fn curve_without_length() {
+        let mut harness = Harness::new(all::curve::curve());
+        let trigger = Signal::Impulse;
+        let length = Signal::Constant(0.0);
+        let repeat = Signal::Constant(1.0);
+        let samples = harness.run(
+            &[
+                ("trigger", &trigger),
+                ("length", &length),
+                ("repeat", &repeat),
+            ],
+            1024,
+        );
+
+        assert!(samples.iter().all(|sample| sample.is_finite()));
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use atomic_float::{AtomicF32, AtomicF64};
+
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+static ROLLING: AtomicBool = AtomicBool::new(false);
+static BPM: AtomicF32 = AtomicF32::new(120.0);
+static BEATS: AtomicF64 = AtomicF64::new(0.0);
+static SERIAL: AtomicU64 = AtomicU64::new(0);
+
+/// Latest transport position reported by an external timebase master.
+#[derive(Clone, Copy, Debug)]
+pub struct Snapshot {
+    pub rolling: bool,
+    pub bpm: f32,
+    /// Beats since the start of the song
+    pub beats: f64,
+    /// Changes with every report, readers extrapolate in between
+    pub serial: u64,
+}
+
+pub fn publish(rolling: bool, bpm: f32, beats: f64) {
+    ROLLING.store(rolling, Ordering::Relaxed);
+    BPM.store(bpm, Ordering::Relaxed);
+    BEATS.store(beats, Ordering::Relaxed);
+    SERIAL.fetch_add(1, Ordering::Release);
+    ACTIVE.store(true, Ordering::Release);
+}
+
+pub fn clear() {
+    ACTIVE.store(false, Ordering::Release);
+}
+
+pub fn snapshot() -> Option<Snapshot> {
+    if !ACTIVE.load(Ordering::Acquire) {
+        return None;
+    }
+
+    Some(Snapshot {
+        serial: SERIAL.load(Ordering::Acquire),
+        rolling: ROLLING.load(Ordering::Relaxed),
+        bpm: BPM.load(Ordering::Relaxed),
+        beats: BEATS.load(Ordering::Relaxed),
+    })
+}
+
+/// Follows the transport sample by sample between reports.
+#[derive(Clone, Debug, Default)]
+pub struct Follower {
+    serial: u64,
+    base: f64,
+    since: u64,
+    last: f64,
+    // Index of the last beat reported by `step`
+    emitted: Option<i64>,
+}
+
+impl Follower {
+    /// Advances by one sample, returns the current position and whether a
+    /// beat starts on this sample.
+    pub fn step(&mut self, snapshot: &Snapshot) -> (f64, bool) {
+        if snapshot.serial != self.serial {
+            self.serial = snapshot.serial;
+            self.base = snapshot.beats;
+            self.since = 0;
+        } else if snapshot.rolling {
+            self.since += 1;
+        }
+
+        let beats = if snapshot.rolling {
+            self.base + self.since as f64 * snapshot.bpm as f64 / 60.0 / 44100.0
+        } else {
+            self.base
+        };
+        let idx = beats.floor() as i64;
+
+        // Small steps back are reports correcting the extrapolation, larger
+        // ones are the transport being moved
+        let relocated = beats < self.last - 0.25;
+        if self.emitted.is_none() || relocated {
+            self.emitted = Some(if beats.fract() < 1e-6 { idx - 1 } else { idx });
+        }
+        self.last = beats;
+
+        let beat = snapshot.rolling && Some(idx) > self.emitted;
+        if beat {
+            self.emitted = Some(idx);
+        }
+
+        (beats, beat)
+    }
+}
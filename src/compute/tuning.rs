@@ -0,0 +1,55 @@
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use atomic_float::AtomicF32;
+use serde::{Deserialize, Serialize};
+
+static A4: AtomicF32 = AtomicF32::new(440.0);
+static TRANSPOSE: AtomicI32 = AtomicI32::new(0);
+
+/// Patch-wide reference pitch and transposition, used by every
+/// key-to-frequency conversion.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tuning {
+    pub a4: f32,
+    pub transpose: i32,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Tuning {
+            a4: 440.0,
+            transpose: 0,
+        }
+    }
+}
+
+impl Tuning {
+    pub const A4_RANGE: std::ops::RangeInclusive<f32> = 432.0..=446.0;
+
+    pub fn current() -> Self {
+        Tuning {
+            a4: A4.load(Ordering::Relaxed),
+            transpose: TRANSPOSE.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Makes this tuning the one used by the runtime.
+    pub fn apply(&self) {
+        A4.store(self.a4, Ordering::Relaxed);
+        TRANSPOSE.store(self.transpose, Ordering::Relaxed);
+    }
+
+    pub fn key_to_freq(&self, key: f32) -> f32 {
+        self.a4 * 2f32.powf((key + self.transpose as f32 - 69.0) / 12.0)
+    }
+}
+
+/// Frequency of a (possibly fractional) midi key under the current tuning.
+pub fn key_to_freq(key: f32) -> f32 {
+    Tuning::current().key_to_freq(key)
+}
+
+/// Midi key shifted by the current transposition, clamped to the valid range.
+pub fn transpose_key(key: u8) -> u8 {
+    (key as i32 + TRANSPOSE.load(Ordering::Relaxed)).clamp(0, 127) as u8
+}
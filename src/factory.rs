@@ -0,0 +1,545 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use eframe::egui;
+use egui_graph_edit::NodeTemplateTrait;
+
+use crate::{
+    compute::{OutputPort, Runtime},
+    graph::{AllSynthNodeTemplates, PatchState, SynthEditorState, SynthGraphState},
+};
+
+/// Node of a factory patch.
+struct Part {
+    /// Name of the node in the node finder
+    template: &'static str,
+    /// Title shown instead of the template name
+    label: Option<&'static str>,
+    pos: (f32, f32),
+    /// Values of disconnected inputs, by name
+    values: &'static [(&'static str, f32)],
+}
+
+/// Example patch shipped with the editor, built from the node templates so
+/// it never goes stale as nodes change.
+pub struct Patch {
+    pub name: &'static str,
+    pub description: &'static str,
+    parts: &'static [Part],
+    /// Connections from an output of a part to an input of another, by index
+    /// into `parts` and port name
+    wires: &'static [(usize, &'static str, usize, &'static str)],
+    playback: (usize, &'static str),
+    /// Sticky note explaining the patch, shown above it
+    note: &'static str,
+}
+
+pub const PATCHES: &[Patch] = &[
+    Patch {
+        name: "Subtractive synth",
+        description: "Sawtooth through a resonant low-pass, played from MIDI",
+        parts: &[
+            Part {
+                template: "Midi In",
+                label: None,
+                pos: (0.0, 0.0),
+                values: &[],
+            },
+            Part {
+                template: "One Note Instrument",
+                label: None,
+                pos: (220.0, 0.0),
+                values: &[],
+            },
+            Part {
+                template: "Oscillator",
+                label: None,
+                pos: (440.0, 0.0),
+                values: &[("shape", 0.5)],
+            },
+            Part {
+                template: "BiQuad Filter",
+                label: None,
+                pos: (660.0, 0.0),
+                values: &[("f0", 1200.0), ("Q", 2.0)],
+            },
+            Part {
+                template: "Adsr",
+                label: None,
+                pos: (880.0, 0.0),
+                values: &[],
+            },
+        ],
+        wires: &[
+            (0, "", 1, "midi"),
+            (1, "freq", 2, "f"),
+            (2, "", 3, "sig"),
+            (1, "vel", 4, "gate"),
+            (3, "", 4, "signal"),
+        ],
+        playback: (4, ""),
+        note: "Pick a MIDI source in Midi In, or play the virtual keyboard. \
+               f0 and Q of the filter shape the tone.",
+    },
+    Patch {
+        name: "FM bell",
+        description: "Carrier phase modulated at an inharmonic ratio",
+        parts: &[
+            Part {
+                template: "Midi In",
+                label: None,
+                pos: (0.0, 0.0),
+                values: &[],
+            },
+            Part {
+                template: "One Note Instrument",
+                label: None,
+                pos: (220.0, 0.0),
+                values: &[],
+            },
+            Part {
+                template: "Gain",
+                label: Some("Ratio"),
+                pos: (440.0, 220.0),
+                values: &[("sig 1", 3.5)],
+            },
+            Part {
+                template: "Oscillator",
+                label: Some("Modulator"),
+                pos: (660.0, 220.0),
+                values: &[],
+            },
+            Part {
+                template: "Gain",
+                label: Some("Index"),
+                pos: (880.0, 220.0),
+                values: &[("sig 1", 0.4)],
+            },
+            Part {
+                template: "Oscillator",
+                label: Some("Carrier"),
+                pos: (1100.0, 0.0),
+                values: &[],
+            },
+            Part {
+                template: "Adsr",
+                label: None,
+                pos: (1320.0, 0.0),
+                values: &[],
+            },
+        ],
+        wires: &[
+            (0, "", 1, "midi"),
+            (1, "freq", 2, "sig 0"),
+            (2, "", 3, "f"),
+            (3, "", 4, "sig 0"),
+            (1, "freq", 5, "f"),
+            (4, "", 5, "pm"),
+            (1, "vel", 6, "gate"),
+            (5, "", 6, "signal"),
+        ],
+        playback: (6, ""),
+        note: "Pick a MIDI source in Midi In. Ratio sets the modulator against \
+               the played pitch, Index how much it bends the carrier.",
+    },
+    Patch {
+        name: "Drum kit",
+        description: "Kick, snare and hats on a 120 BPM groove",
+        parts: &[
+            Part {
+                template: "BPM",
+                label: None,
+                pos: (0.0, 0.0),
+                values: &[("BPM", 120.0)],
+            },
+            Part {
+                template: "On Beat",
+                label: None,
+                pos: (220.0, 0.0),
+                values: &[],
+            },
+            Part {
+                template: "Kick",
+                label: None,
+                pos: (440.0, 0.0),
+                values: &[],
+            },
+            Part {
+                template: "BPM",
+                label: Some("Half time"),
+                pos: (0.0, 220.0),
+                values: &[("BPM", 60.0)],
+            },
+            Part {
+                template: "On Beat",
+                label: None,
+                pos: (220.0, 220.0),
+                values: &[],
+            },
+            Part {
+                template: "Delay",
+                label: Some("Backbeat"),
+                pos: (440.0, 220.0),
+                // Half a second, the second beat of every two
+                values: &[("time", 22050.0)],
+            },
+            Part {
+                template: "Snare",
+                label: None,
+                pos: (660.0, 220.0),
+                values: &[],
+            },
+            Part {
+                template: "BPM",
+                label: Some("Eighths"),
+                pos: (0.0, 440.0),
+                values: &[("BPM", 240.0)],
+            },
+            Part {
+                template: "On Beat",
+                label: None,
+                pos: (220.0, 440.0),
+                values: &[],
+            },
+            Part {
+                template: "Hat",
+                label: None,
+                pos: (440.0, 440.0),
+                values: &[],
+            },
+            Part {
+                template: "Mix",
+                label: None,
+                pos: (880.0, 0.0),
+                values: &[],
+            },
+            Part {
+                template: "Mix",
+                label: None,
+                pos: (1100.0, 220.0),
+                values: &[("sig 1", 0.5)],
+            },
+        ],
+        wires: &[
+            (0, "", 1, "beat"),
+            (1, "", 2, "trigger"),
+            (3, "", 4, "beat"),
+            (4, "", 5, "sig"),
+            (5, "", 6, "trigger"),
+            (7, "", 8, "beat"),
+            (8, "", 9, "trigger"),
+            (2, "", 10, "sig 0"),
+            (6, "", 10, "sig 1"),
+            (10, "", 11, "sig 0"),
+            (9, "", 11, "sig 1"),
+        ],
+        playback: (11, ""),
+        note: "Each drum has a clock of its own. The delay moves the snare to \
+               the backbeat.",
+    },
+    Patch {
+        name: "Dub delay",
+        description: "Audio input through a dark, long feedback delay and reverb",
+        parts: &[
+            Part {
+                template: "Audio In",
+                label: None,
+                pos: (0.0, 0.0),
+                values: &[],
+            },
+            Part {
+                template: "Delay",
+                label: None,
+                pos: (220.0, 0.0),
+                // Dotted eighth at 120 BPM
+                values: &[("time", 16538.0), ("feedback", 65.0)],
+            },
+            Part {
+                template: "BiQuad Filter",
+                label: None,
+                pos: (440.0, 0.0),
+                values: &[("f0", 900.0)],
+            },
+            Part {
+                template: "Reverb",
+                label: None,
+                pos: (660.0, 0.0),
+                values: &[("dry/wet", 0.3)],
+            },
+            Part {
+                template: "Mix 2",
+                label: Some("Dry/wet"),
+                pos: (880.0, 0.0),
+                values: &[("mix2", 0.5)],
+            },
+        ],
+        wires: &[
+            (0, "", 1, "sig"),
+            (1, "", 2, "sig"),
+            (2, "", 3, "sig"),
+            (0, "", 4, "sig 0"),
+            (3, "", 4, "sig 1"),
+        ],
+        playback: (4, ""),
+        note: "Plays the audio input, enable one in the audio settings. \
+               Feedback of the delay sets how long the echoes ring.",
+    },
+    Patch {
+        name: "Sidechain ducking",
+        description: "Kick ducking a sustained saw through a compressor key",
+        parts: &[
+            Part {
+                template: "BPM",
+                label: None,
+                pos: (0.0, 0.0),
+                values: &[("BPM", 120.0)],
+            },
+            Part {
+                template: "On Beat",
+                label: None,
+                pos: (220.0, 0.0),
+                values: &[],
+            },
+            Part {
+                template: "Kick",
+                label: None,
+                pos: (440.0, 0.0),
+                values: &[],
+            },
+            Part {
+                template: "Oscillator",
+                label: Some("Pad"),
+                pos: (440.0, 220.0),
+                values: &[("f", 110.0), ("shape", 0.5)],
+            },
+            Part {
+                template: "Compressor",
+                label: None,
+                pos: (660.0, 220.0),
+                // 200 ms release, the pad swells back between kicks
+                values: &[("threshold", -40.0), ("ratio", 10.0), ("release", 8820.0)],
+            },
+            Part {
+                template: "Mix",
+                label: None,
+                pos: (880.0, 0.0),
+                values: &[],
+            },
+        ],
+        wires: &[
+            (0, "", 1, "beat"),
+            (1, "", 2, "trigger"),
+            (3, "", 4, "sig"),
+            (2, "", 4, "key"),
+            (2, "", 5, "sig 0"),
+            (4, "", 5, "sig 1"),
+        ],
+        playback: (5, ""),
+        note: "The kick drives the key of the compressor, turning the pad \
+               down on every beat. Any signal can be the key, like Audio In \
+               on the JACK sidechain channel.",
+    },
+];
+
+/// Constant of 0.25 played on the Main Out, rendered by the tests.
+#[cfg(test)]
+pub const CONSTANT: Patch = Patch {
+    name: "Constant",
+    description: "",
+    parts: &[
+        Part {
+            template: "Constant",
+            label: None,
+            pos: (0.0, 0.0),
+            values: &[("value", 0.25)],
+        },
+        Part {
+            template: "Main Out",
+            label: None,
+            pos: (220.0, 0.0),
+            values: &[],
+        },
+    ],
+    wires: &[(0, "", 1, "sig")],
+    playback: (0, ""),
+    note: "",
+};
+
+/// Uniform noise between -1 and 1 played on the Main Out, rendered by the
+/// tests.
+#[cfg(test)]
+pub const NOISE: Patch = Patch {
+    name: "Noise",
+    description: "",
+    parts: &[
+        Part {
+            template: "Noise",
+            label: None,
+            pos: (0.0, 0.0),
+            values: &[("min", -1.0), ("max", 1.0)],
+        },
+        Part {
+            template: "Main Out",
+            label: None,
+            pos: (220.0, 0.0),
+            values: &[],
+        },
+    ],
+    wires: &[(0, "", 1, "sig")],
+    playback: (0, ""),
+    note: "",
+};
+
+/// Saw through a filter, an envelope gated twice a second and a reverb,
+/// rendered by the tests.
+#[cfg(test)]
+pub const TONE: Patch = Patch {
+    name: "Tone",
+    description: "",
+    parts: &[
+        Part {
+            template: "Oscillator",
+            label: Some("Gate"),
+            pos: (0.0, 220.0),
+            values: &[("f", 2.0)],
+        },
+        Part {
+            template: "Oscillator",
+            label: None,
+            pos: (0.0, 0.0),
+            values: &[("f", 110.0), ("shape", 0.5)],
+        },
+        Part {
+            template: "BiQuad Filter",
+            label: None,
+            pos: (220.0, 0.0),
+            values: &[("f0", 1200.0), ("Q", 2.0)],
+        },
+        Part {
+            template: "Adsr",
+            label: None,
+            pos: (440.0, 0.0),
+            values: &[],
+        },
+        Part {
+            template: "Reverb",
+            label: None,
+            pos: (660.0, 0.0),
+            values: &[("dry/wet", 0.3)],
+        },
+    ],
+    wires: &[
+        (0, "", 3, "gate"),
+        (1, "", 2, "sig"),
+        (2, "", 3, "signal"),
+        (3, "", 4, "sig"),
+    ],
+    playback: (4, ""),
+    note: "",
+};
+
+/// Editor and runtime state of `patch`, in the form a saved patch loads in.
+pub fn build(patch: &Patch, templates: &AllSynthNodeTemplates) -> Result<PatchState> {
+    let mut editor = SynthEditorState::default();
+    let mut user_state = SynthGraphState::default();
+
+    let mut ids = Vec::new();
+    for part in patch.parts {
+        // Templates are fresh nodes every time, parts can't share any. Nodes
+        // hidden from the finder are still built
+        let template = templates
+            .unarranged()
+            .into_iter()
+            .find(|template| template.node_finder_label(&mut user_state) == part.template)
+            .ok_or(anyhow!("No node named {}", part.template))?;
+
+        let label = match part.label {
+            Some(label) => label.to_string(),
+            None => template.node_graph_label(&mut user_state),
+        };
+        let node_id = editor.graph.add_node(
+            label,
+            template.user_data(&mut user_state),
+            |graph, node_id| template.build_node(graph, &mut user_state, node_id),
+        );
+        editor
+            .node_positions
+            .insert(node_id, egui::pos2(part.pos.0, part.pos.1));
+        editor.node_order.push(node_id);
+
+        let inputs = &user_state.node_ui_inputs[&node_id];
+        for (name, value) in part.values {
+            let input = inputs
+                .get(*name)
+                .ok_or(anyhow!("{} has no input {name}", part.template))?;
+            input.set_value(*value);
+        }
+
+        ids.push(node_id);
+    }
+
+    let output = |editor: &SynthEditorState, part: usize, name: &str| {
+        let node = &editor.graph.nodes[ids[part]];
+        node.outputs
+            .iter()
+            .position(|(output, _)| output == name)
+            .map(|port| (port, node.outputs[port].1))
+            .ok_or(anyhow!(
+                "{} has no output {name:?}",
+                patch.parts[part].template
+            ))
+    };
+
+    let mut wired = Vec::new();
+    for (src, out_name, dst, in_name) in patch.wires {
+        let (out_port, output_id) = output(&editor, *src, out_name)?;
+        let node = &editor.graph.nodes[ids[*dst]];
+        let (in_port, input_id) = node
+            .inputs
+            .iter()
+            .position(|(input, _)| input == in_name)
+            .map(|port| (port, node.inputs[port].1))
+            .ok_or(anyhow!(
+                "{} has no input {in_name}",
+                patch.parts[*dst].template
+            ))?;
+
+        editor.graph.add_connection(output_id, input_id);
+        wired.push((ids[*src], out_port, ids[*dst], in_port));
+    }
+
+    let (part, name) = patch.playback;
+    user_state.rt_playback = Some((ids[part], output(&editor, part, name)?.0));
+
+    let mut rt = Runtime::new();
+    let mut indices = HashMap::new();
+    let mut inputs = HashMap::new();
+    for node_id in &ids {
+        let node = user_state.nodes.remove(node_id).unwrap();
+        let unconnected: Vec<Option<OutputPort>> = vec![None; node.inputs().len()];
+        inputs.insert(*node_id, unconnected.clone());
+        indices.insert(*node_id, rt.insert(unconnected, node));
+    }
+    for (src, out_port, dst, in_port) in wired {
+        inputs.get_mut(&dst).unwrap()[in_port] = Some(OutputPort::new(indices[&src], out_port));
+    }
+    for (node_id, inputs) in inputs {
+        rt.set_all_inputs(indices[&node_id], inputs);
+    }
+
+    let right = patch
+        .parts
+        .iter()
+        .map(|part| part.pos.0)
+        .fold(0.0, f32::max);
+    user_state
+        .notes
+        .add_text(egui::pos2(right / 2.0, -160.0), patch.name, patch.note);
+
+    let mapping = indices
+        .into_iter()
+        .map(|(node_id, index)| (node_id, index.to_bits()))
+        .collect();
+
+    Ok(((rt, mapping), editor, user_state))
+}
@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+
+use eframe::egui::{self, Pos2};
+use egui_graph_edit::{
+    AnyParameterId, InputId, NodeId, NodeTemplateIter, NodeTemplateTrait, OutputId,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commands::fuzzy,
+    graph::{
+        AllSynthNodeTemplates, SynthDataType, SynthEditorState, SynthGraphState, SynthNodeTemplate,
+    },
+};
+
+const FAVORITES: &str = "Favorites";
+const RECENT: &str = "Recent";
+// Nodes listed under Recent
+const RECENT_LEN: usize = 8;
+
+fn icon(category: &str) -> &'static str {
+    match category {
+        FAVORITES => "⭐",
+        RECENT => "🕘",
+        "Source" => "〰",
+        "Channels" => "☰",
+        "Control" => "🎛",
+        "Effect" => "✨",
+        "Envelope" => "📈",
+        "Filter" => "🔉",
+        "Instrument" => "🎻",
+        "Drum" => "🥁",
+        "Math" => "➗",
+        "Midi" => "🎹",
+        "Noise" => "🌫",
+        "Output" => "🔊",
+        "Routing" => "📡",
+        _ => "▪",
+    }
+}
+
+/// Pinned and often used nodes, saved with the app settings.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct FinderHistory {
+    favorites: Vec<String>,
+    uses: HashMap<String, usize>,
+}
+
+#[derive(Default)]
+enum Step {
+    #[default]
+    Browse,
+    // Template adjusted before it's created
+    Configure(SynthNodeTemplate),
+}
+
+/// Node placed by the dialog.
+pub struct Placed {
+    pub node_id: NodeId,
+    /// Connection to the port the dialog was opened from
+    pub connection: Option<(OutputId, InputId)>,
+}
+
+/// Dialog placing new nodes, opened in place of the editor's own node finder.
+#[derive(Default)]
+pub struct NodeFinder {
+    pub history: FinderHistory,
+    // Screen position the dialog was opened at
+    open_at: Option<Pos2>,
+    query: String,
+    category: Option<String>,
+    step: Step,
+    // Fresh templates, built once per opening
+    templates: Vec<SynthNodeTemplate>,
+    // Port a connection was dragged from, only nodes fitting it are listed
+    from: Option<(AnyParameterId, SynthDataType)>,
+}
+
+impl NodeFinder {
+    pub fn open(&mut self, pos: Pos2) {
+        self.open_at = Some(pos);
+        self.query.clear();
+        self.category = None;
+        self.step = Step::Browse;
+        self.templates.clear();
+        self.from = None;
+    }
+
+    /// Opens the dialog for a connection dropped on the empty canvas, the
+    /// new node gets connected to `port`.
+    pub fn open_from(&mut self, pos: Pos2, port: AnyParameterId, data_type: SynthDataType) {
+        self.open(pos);
+        self.from = Some((port, data_type));
+    }
+
+    /// Shows the dialog, returns the node once created.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        editor: &mut SynthEditorState,
+        user_state: &mut SynthGraphState,
+        all_nodes: &AllSynthNodeTemplates,
+        editor_rect: egui::Rect,
+    ) -> Option<Placed> {
+        let pos = self.open_at?;
+
+        let mut open = true;
+        let mut chosen = None;
+        egui::Window::new("Add node")
+            .id(egui::Id::new("node-finder"))
+            .open(&mut open)
+            .fixed_pos(pos)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.set_width(320.0);
+                chosen = match std::mem::take(&mut self.step) {
+                    Step::Browse => {
+                        if self.templates.is_empty() {
+                            self.templates = all_nodes.all_kinds();
+                            if let Some((port, data_type)) = self.from {
+                                self.templates.retain(|template| match port {
+                                    AnyParameterId::Output(_) => template.accepts(data_type),
+                                    AnyParameterId::Input(_) => template.produces(data_type),
+                                });
+                            }
+                        }
+                        self.browse(ui)
+                    }
+                    Step::Configure(template) => self.configure(ui, template, user_state),
+                };
+            });
+
+        if ctx.input(|state| state.key_pressed(egui::Key::Escape)) {
+            open = false;
+        }
+        if !open {
+            self.open_at = None;
+        }
+
+        let template = chosen?;
+        self.open_at = None;
+        let name = template.node_finder_label(user_state).into_owned();
+        *self.history.uses.entry(name).or_default() += 1;
+
+        let node_id = editor.graph.add_node(
+            template.node_graph_label(user_state),
+            template.user_data(user_state),
+            |graph, node_id| template.build_node(graph, user_state, node_id),
+        );
+        editor.node_positions.insert(
+            node_id,
+            pos - editor_rect.min.to_vec2() - editor.pan_zoom.pan,
+        );
+        editor.node_order.push(node_id);
+
+        let graph = &editor.graph;
+        let node = &graph.nodes[node_id];
+        let connection = self.from.take().and_then(|(port, data_type)| match port {
+            AnyParameterId::Output(output) => node
+                .inputs
+                .iter()
+                .find(|(_, input)| graph.get_input(*input).typ == data_type)
+                .map(|(_, input)| (output, *input)),
+            AnyParameterId::Input(input) => node
+                .outputs
+                .iter()
+                .find(|(_, output)| graph.get_output(*output).typ == data_type)
+                .map(|(_, output)| (*output, input)),
+        });
+
+        Some(Placed {
+            node_id,
+            connection,
+        })
+    }
+
+    fn browse(&mut self, ui: &mut egui::Ui) -> Option<SynthNodeTemplate> {
+        let search = ui.add(egui::TextEdit::singleline(&mut self.query).hint_text("Search"));
+        search.request_focus();
+        ui.separator();
+
+        let templates = &self.templates;
+        let names: Vec<String> = templates.iter().map(|t| t.name().to_string()).collect();
+
+        let query = self.query.to_lowercase();
+        let listed: Vec<usize> = if !query.is_empty() {
+            // Most used matches first
+            let mut matching: Vec<usize> = (0..templates.len())
+                .filter(|k| fuzzy(&names[*k].to_lowercase(), &query))
+                .collect();
+            matching.sort_by_key(|k| std::cmp::Reverse(self.uses(&names[*k])));
+            matching
+        } else if let Some(category) = &self.category {
+            match category.as_str() {
+                FAVORITES => (0..templates.len())
+                    .filter(|k| self.history.favorites.contains(&names[*k]))
+                    .collect(),
+                RECENT => {
+                    let mut used: Vec<usize> = (0..templates.len())
+                        .filter(|k| self.uses(&names[*k]) > 0)
+                        .collect();
+                    used.sort_by_key(|k| std::cmp::Reverse(self.uses(&names[*k])));
+                    used.truncate(RECENT_LEN);
+                    used
+                }
+                _ => (0..templates.len())
+                    .filter(|k| templates[*k].categories().contains(category))
+                    .collect(),
+            }
+        } else {
+            let templates = std::mem::take(&mut self.templates);
+            self.show_categories(ui, &templates);
+            self.templates = templates;
+            return None;
+        };
+
+        if self.query.is_empty() && ui.button("⬅ Categories").clicked() {
+            self.category = None;
+        }
+
+        let mut chosen = None;
+        let mut configure = None;
+        let enter = ui.input(|state| state.key_pressed(egui::Key::Enter));
+        egui::ScrollArea::vertical()
+            .max_height(360.0)
+            .show(ui, |ui| {
+                for (row, k) in listed.iter().enumerate() {
+                    let name = &names[*k];
+                    ui.horizontal(|ui| {
+                        let pinned = self.history.favorites.contains(name);
+                        if ui
+                            .selectable_label(pinned, "⭐")
+                            .on_hover_text("Pin to favorites")
+                            .clicked()
+                        {
+                            if pinned {
+                                self.history.favorites.retain(|fav| fav != name);
+                            } else {
+                                self.history.favorites.push(name.clone());
+                            }
+                        }
+
+                        // Enter takes the first match of a search
+                        let first = row == 0 && enter && !self.query.is_empty();
+                        if ui.button(name).clicked() || first {
+                            chosen = Some(*k);
+                        }
+                        if templates[*k].config().is_some()
+                            && ui
+                                .small_button("⚙")
+                                .on_hover_text("Set up before placing")
+                                .clicked()
+                        {
+                            configure = Some(*k);
+                        }
+                    });
+                }
+            });
+
+        if let Some(k) = configure {
+            self.step = Step::Configure(self.templates.swap_remove(k));
+            return None;
+        }
+
+        chosen.map(|k| self.templates.swap_remove(k))
+    }
+
+    fn show_categories(&mut self, ui: &mut egui::Ui, templates: &[SynthNodeTemplate]) {
+        let mut categories: Vec<String> = templates
+            .iter()
+            .flat_map(|template| template.categories().iter().cloned())
+            .collect();
+        categories.sort();
+        categories.dedup();
+        categories.insert(0, RECENT.to_string());
+        categories.insert(0, FAVORITES.to_string());
+
+        egui::Grid::new("finder-categories")
+            .num_columns(3)
+            .spacing([6.0, 6.0])
+            .show(ui, |ui| {
+                for (k, category) in categories.into_iter().enumerate() {
+                    let text = format!("{}\n{category}", icon(&category));
+                    let button = egui::Button::new(text).min_size(egui::vec2(100.0, 48.0));
+                    if ui.add(button).clicked() {
+                        self.category = Some(category);
+                    }
+                    if k % 3 == 2 {
+                        ui.end_row();
+                    }
+                }
+            });
+    }
+
+    fn configure(
+        &mut self,
+        ui: &mut egui::Ui,
+        template: SynthNodeTemplate,
+        user_state: &mut SynthGraphState,
+    ) -> Option<SynthNodeTemplate> {
+        ui.heading(template.name());
+        if let Some(config) = template.config() {
+            config.show(ui, &mut user_state.ctx);
+        }
+        ui.separator();
+
+        let (back, create) = ui
+            .horizontal(|ui| (ui.button("Back").clicked(), ui.button("Create").clicked()))
+            .inner;
+        if create {
+            return Some(template);
+        }
+        if back {
+            self.templates.push(template);
+        } else {
+            self.step = Step::Configure(template);
+        }
+
+        None
+    }
+
+    fn uses(&self, name: &str) -> usize {
+        self.history.uses.get(name).copied().unwrap_or_default()
+    }
+}
@@ -0,0 +1,416 @@
+use std::{collections::HashSet, sync::Arc};
+
+use eframe::egui;
+use egui_graph_edit::{InputId, NodeId, NodeResponse, NodeTemplateTrait, OutputId};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute::node::{
+        all::sampler::{sampler_with, SamplerConfig},
+        inputs::trigger::TriggerMode,
+    },
+    graph::{
+        SynthDataType, SynthEditorState, SynthGraph, SynthGraphState, SynthNodeData,
+        SynthNodeResponse, SynthNodeTemplate,
+    },
+    model::GraphModel,
+    remote::RuntimeRemote,
+};
+
+/// Nodes swapped for a Sampler playing a render of their output. They stay
+/// in the patch bypassed, so they cost nothing until unfrozen.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Frozen {
+    pub sampler: NodeId,
+    /// Node whose output was rendered, and the port of it
+    pub root: (NodeId, usize),
+    /// The root and the nodes feeding only it, bypassed while frozen
+    pub nodes: Vec<NodeId>,
+}
+
+// Freeze waiting for its Sampler to reach the runtime and to record
+#[derive(Debug)]
+struct Pending {
+    sampler: NodeId,
+    config: Arc<SamplerConfig>,
+    root: (NodeId, usize),
+    trigger: Option<OutputId>,
+    armed: bool,
+}
+
+/// Window freezing the selected node and listing the frozen ones.
+#[derive(Debug)]
+pub struct Freezer {
+    pub open: bool,
+    /// Length of the render, from the trigger on
+    secs: f32,
+    /// Output starting the render and, after it, the playback of the
+    /// Sampler. Without one the render starts right away and loops
+    trigger: Option<(NodeId, usize)>,
+    pending: Option<Pending>,
+}
+
+impl Default for Freezer {
+    fn default() -> Self {
+        Freezer {
+            open: false,
+            secs: 4.0,
+            trigger: None,
+            pending: None,
+        }
+    }
+}
+
+// Output of `node_id` at `port`
+fn output_id(graph: &SynthGraph, (node_id, port): (NodeId, usize)) -> Option<OutputId> {
+    graph
+        .nodes
+        .get(node_id)?
+        .outputs
+        .get(port)
+        .map(|(_, id)| *id)
+}
+
+// Input of `node_id` called `name`
+fn input_id(graph: &SynthGraph, node_id: NodeId, name: &str) -> Option<InputId> {
+    graph
+        .nodes
+        .get(node_id)?
+        .inputs
+        .iter()
+        .find(|(input, _)| input == name)
+        .map(|(_, id)| *id)
+}
+
+/// `root` and every node whose connections all lead into the frozen nodes.
+fn subgraph(graph: &SynthGraph, root: NodeId) -> Vec<NodeId> {
+    let mut nodes = HashSet::from([root]);
+    loop {
+        let feeding: Vec<NodeId> = graph
+            .nodes
+            .iter()
+            .map(|(node_id, _)| node_id)
+            .filter(|node_id| !nodes.contains(node_id))
+            .filter(|node_id| {
+                let mut consumers = graph
+                    .iter_connections()
+                    .filter(|(_, output)| graph.get_output(*output).node == *node_id)
+                    .map(|(input, _)| graph.get_input(input).node)
+                    .peekable();
+                consumers.peek().is_some() && consumers.all(|dst| nodes.contains(&dst))
+            })
+            .collect();
+        if feeding.is_empty() {
+            break;
+        }
+        nodes.extend(feeding);
+    }
+
+    nodes.into_iter().collect()
+}
+
+impl Freezer {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        editor: &mut SynthEditorState,
+        user_state: &mut SynthGraphState,
+        remote: &mut RuntimeRemote,
+        responses: &mut Vec<NodeResponse<SynthNodeResponse, SynthNodeData>>,
+    ) {
+        self.update(&mut editor.graph, user_state, remote);
+
+        // Samplers deleted by hand take their freeze along
+        let (kept, gone): (Vec<_>, Vec<_>) = std::mem::take(&mut user_state.frozen)
+            .into_iter()
+            .partition(|frozen| editor.graph.nodes.contains_key(frozen.sampler));
+        user_state.frozen = kept;
+        for frozen in gone {
+            set_bypass(&editor.graph, &frozen.nodes, false);
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Freeze")
+            .open(&mut open)
+            .default_width(280.0)
+            .show(ctx, |ui| {
+                self.show_freeze(ui, editor, user_state, responses);
+
+                ui.separator();
+                let mut thawed = None;
+                for (k, frozen) in user_state.frozen.iter().enumerate() {
+                    let label = editor
+                        .graph
+                        .nodes
+                        .get(frozen.root.0)
+                        .map(|node| node.label.as_str())
+                        .unwrap_or("?");
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{label}, {} nodes", frozen.nodes.len()));
+                        if ui.button("Unfreeze").clicked() {
+                            thawed = Some(k);
+                        }
+                    });
+                }
+                if user_state.frozen.is_empty() {
+                    ui.weak("Nothing is frozen");
+                }
+
+                if let Some(k) = thawed {
+                    let frozen = user_state.frozen.remove(k);
+                    unfreeze(&frozen, &mut editor.graph, user_state, remote);
+                    responses.push(NodeResponse::DeleteNodeUi(frozen.sampler));
+                }
+            });
+        self.open = open;
+    }
+
+    fn show_freeze(
+        &mut self,
+        ui: &mut egui::Ui,
+        editor: &mut SynthEditorState,
+        user_state: &mut SynthGraphState,
+        responses: &mut Vec<NodeResponse<SynthNodeResponse, SynthNodeData>>,
+    ) {
+        if let Some(pending) = &self.pending {
+            let text = if pending.armed && !pending.config.busy() {
+                "Swapping in the render"
+            } else if pending.trigger.is_some() {
+                "Rendering from the next trigger"
+            } else {
+                "Rendering"
+            };
+            ui.label(text);
+            ui.ctx().request_repaint();
+            return;
+        }
+
+        let graph = &editor.graph;
+        let root = match editor.selected_nodes.as_slice() {
+            [node_id] => graph.nodes.get(*node_id).and_then(|node| {
+                let port = node
+                    .outputs
+                    .iter()
+                    .position(|(_, id)| graph.get_output(*id).typ == SynthDataType::Float)?;
+                Some((*node_id, port, node.label.clone()))
+            }),
+            _ => None,
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Length");
+            ui.add(
+                egui::DragValue::new(&mut self.secs)
+                    .range(0.1..=60.0)
+                    .speed(0.1)
+                    .suffix(" s"),
+            );
+        });
+
+        self.trigger = self
+            .trigger
+            .filter(|trigger| output_id(graph, *trigger).is_some());
+        let trigger_name = |(node_id, port): (NodeId, usize)| {
+            let node = &graph.nodes[node_id];
+            format!("{} - {}", node.label, node.outputs[port].0)
+        };
+        ui.horizontal(|ui| {
+            ui.label("Trigger");
+            egui::ComboBox::from_id_salt("freeze-trigger")
+                .selected_text(match self.trigger {
+                    Some(trigger) => trigger_name(trigger),
+                    None => "None, loop".into(),
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.trigger, None, "None, loop");
+                    for (node_id, node) in &graph.nodes {
+                        if root.as_ref().is_some_and(|(root, ..)| *root == node_id) {
+                            continue;
+                        }
+                        for port in 0..node.outputs.len() {
+                            let trigger = Some((node_id, port));
+                            ui.selectable_value(
+                                &mut self.trigger,
+                                trigger,
+                                trigger_name((node_id, port)),
+                            );
+                        }
+                    }
+                });
+        });
+
+        let Some((root, port, label)) = root else {
+            ui.weak("Select a node with a signal output to freeze");
+            return;
+        };
+        if ui
+            .button(format!("Freeze {label}"))
+            .on_hover_text("Render it and the nodes feeding only it to a Sampler")
+            .clicked()
+        {
+            let sampler = self.place_sampler(editor, user_state, (root, port));
+            responses.push(NodeResponse::CreatedNode(sampler));
+        }
+    }
+
+    // Adds a Sampler under the root, recording once the runtime has it
+    fn place_sampler(
+        &mut self,
+        editor: &mut SynthEditorState,
+        user_state: &mut SynthGraphState,
+        root: (NodeId, usize),
+    ) -> NodeId {
+        let trigger = self
+            .trigger
+            .and_then(|trigger| output_id(&editor.graph, trigger));
+        let mode = match trigger.map(|trigger| editor.graph.get_output(trigger).typ) {
+            Some(SynthDataType::Midi) => TriggerMode::Note,
+            Some(SynthDataType::Beat) => TriggerMode::Beat,
+            _ => TriggerMode::Up,
+        };
+        let config = Arc::new(SamplerConfig::new(trigger.is_none(), self.secs));
+        let template = SynthNodeTemplate::new(
+            sampler_with(Arc::clone(&config), mode),
+            "Sampler",
+            Vec::new(),
+        );
+
+        let label = format!("{} (frozen)", editor.graph.nodes[root.0].label);
+        let sampler =
+            editor
+                .graph
+                .add_node(label, template.user_data(user_state), |graph, node_id| {
+                    template.build_node(graph, user_state, node_id)
+                });
+        let pos = editor
+            .node_positions
+            .get(root.0)
+            .copied()
+            .unwrap_or_default();
+        editor
+            .node_positions
+            .insert(sampler, pos + egui::vec2(0.0, 220.0));
+        editor.node_order.push(sampler);
+
+        self.pending = Some(Pending {
+            sampler,
+            config,
+            root,
+            trigger,
+            armed: false,
+        });
+
+        sampler
+    }
+
+    // Arms the pending Sampler once the runtime has it, then swaps it in
+    // once it recorded
+    fn update(
+        &mut self,
+        graph: &mut SynthGraph,
+        user_state: &mut SynthGraphState,
+        remote: &mut RuntimeRemote,
+    ) {
+        let Some(pending) = &mut self.pending else {
+            return;
+        };
+
+        let (Some(root_out), Some(rec), Some(trigger_in)) = (
+            output_id(graph, pending.root),
+            input_id(graph, pending.sampler, "rec"),
+            input_id(graph, pending.sampler, "trigger"),
+        ) else {
+            // Root or Sampler removed midway
+            self.pending = None;
+            return;
+        };
+
+        if !pending.armed {
+            if remote.id_to_index(pending.sampler).is_none() {
+                return;
+            }
+
+            let mut model = GraphModel::new(graph, user_state, remote);
+            model.connect(root_out, rec);
+            if let Some(trigger) = pending.trigger {
+                model.connect(trigger, trigger_in);
+            }
+            pending.config.arm();
+            pending.armed = true;
+            return;
+        }
+        if pending.config.busy() {
+            return;
+        }
+
+        let Some((_, sampler_out)) = graph.nodes[pending.sampler].outputs.first().cloned() else {
+            return;
+        };
+        let consumers: Vec<_> = graph
+            .iter_connections()
+            .filter(|(input, output)| *output == root_out && *input != rec)
+            .map(|(input, _)| input)
+            .collect();
+
+        let mut model = GraphModel::new(graph, user_state, remote);
+        model.disconnect(rec);
+        for input in consumers {
+            model.connect(sampler_out, input);
+        }
+        if user_state.rt_playback == Some(pending.root) {
+            GraphModel::new(graph, user_state, remote).handle(NodeResponse::User(
+                SynthNodeResponse::SetRtPlayback(pending.sampler, 0),
+            ));
+        }
+
+        let nodes = subgraph(graph, pending.root.0);
+        set_bypass(graph, &nodes, true);
+        user_state.frozen.push(Frozen {
+            sampler: pending.sampler,
+            root: pending.root,
+            nodes,
+        });
+        self.pending = None;
+    }
+}
+
+fn set_bypass(graph: &SynthGraph, nodes: &[NodeId], bypass: bool) {
+    for node_id in nodes {
+        if let Some(node) = graph.nodes.get(*node_id) {
+            node.user_data.ui.borrow_mut().bypass = bypass;
+        }
+    }
+}
+
+// Moves the connections of the Sampler back to the root and wakes the
+// frozen nodes, the Sampler is left to be deleted
+fn unfreeze(
+    frozen: &Frozen,
+    graph: &mut SynthGraph,
+    user_state: &mut SynthGraphState,
+    remote: &mut RuntimeRemote,
+) {
+    set_bypass(graph, &frozen.nodes, false);
+
+    let (Some(root_out), Some(sampler_out)) = (
+        output_id(graph, frozen.root),
+        output_id(graph, (frozen.sampler, 0)),
+    ) else {
+        return;
+    };
+    let consumers: Vec<_> = graph
+        .iter_connections()
+        .filter(|(_, output)| *output == sampler_out)
+        .map(|(input, _)| input)
+        .collect();
+
+    let mut model = GraphModel::new(graph, user_state, remote);
+    for input in consumers {
+        model.connect(root_out, input);
+    }
+    if user_state.rt_playback == Some((frozen.sampler, 0)) {
+        GraphModel::new(graph, user_state, remote).handle(NodeResponse::User(
+            SynthNodeResponse::SetRtPlayback(frozen.root.0, frozen.root.1),
+        ));
+    }
+}
@@ -3,7 +3,6 @@ use std::{
     cell::RefCell,
     collections::HashMap,
     sync::{Arc, Weak},
-    time::Instant,
 };
 
 use egui_graph_edit::{
@@ -17,13 +16,18 @@ use serde::{Deserialize, Serialize};
 use crate::{
     compute::{
         self,
-        node::{
-            all::source::{jack::JackSourceNew, smf::SmfSourceNew},
-            InputUi, Node, NodeConfig, NodeList,
-        },
-        ValueKind,
+        node::{all::source::SynthCtx, InputUi, Node, NodeConfig, NodeList},
+        tuning::Tuning,
+        Runtime, ValueKind,
     },
-    scope::Scope,
+    freeze::Frozen,
+    library::Library,
+    links::LinkGroups,
+    notes::Notes,
+    scope::{Meter, PortChoice, Scope},
+    sink::Master,
+    snapshots::Snapshots,
+    tour,
     util::{self, toggle_button},
 };
 
@@ -31,12 +35,54 @@ use crate::{
 pub struct OutputState {
     show_scope: bool,
     pub scope: Option<Scope>,
+    #[serde(default)]
+    show_meter: bool,
+    #[serde(skip)]
+    pub meter: Option<Meter>,
+}
+
+impl OutputState {
+    /// Whether the output is being recorded for its scope or meter.
+    pub fn recorded(&self) -> bool {
+        self.show_scope || self.show_meter
+    }
+}
+
+/// Editor-only state of a node, saved together with the patch.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeUiState {
+    pub verbose: bool,
+    pub out_states: HashMap<String, OutputState>,
+    /// Whether only this node's branch is heard on the played output
+    #[serde(default)]
+    pub solo: bool,
+    /// Whether the node passes its first input through instead of running
+    #[serde(default)]
+    pub bypass: bool,
+}
+
+impl Default for NodeUiState {
+    fn default() -> Self {
+        NodeUiState {
+            verbose: true,
+            out_states: HashMap::new(),
+            solo: false,
+            bypass: false,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SynthNodeData {
-    pub out_states: RefCell<HashMap<String, OutputState>>,
-    verbose: RefCell<bool>,
+    // Flattened to keep the layout of patches saved before it was split out
+    #[serde(flatten)]
+    pub ui: RefCell<NodeUiState>,
+}
+
+impl SynthNodeData {
+    fn verbose(&self) -> bool {
+        self.ui.borrow().verbose
+    }
 }
 
 impl NodeDataTrait for SynthNodeData {
@@ -48,19 +94,48 @@ impl NodeDataTrait for SynthNodeData {
     fn top_bar_ui(
         &self,
         ui: &mut egui::Ui,
-        _node_id: NodeId,
+        node_id: NodeId,
         _graph: &egui_graph_edit::Graph<Self, Self::DataType, Self::ValueType>,
-        _user_state: &mut Self::UserState,
+        user_state: &mut Self::UserState,
     ) -> Vec<egui_graph_edit::NodeResponse<Self::Response, Self>>
     where
         Self::Response: UserResponseTrait,
     {
+        if ui.add(toggle_button("Full", self.verbose())).clicked() {
+            let mut state = self.ui.borrow_mut();
+            state.verbose = !state.verbose;
+        }
+
+        let solo = self.ui.borrow().solo;
+        if ui
+            .add(toggle_button("Solo", solo))
+            .on_hover_text("Mute the other branches reaching the played output")
+            .clicked()
+        {
+            self.ui.borrow_mut().solo = !solo;
+        }
+
+        let bypass = self.ui.borrow().bypass;
         if ui
-            .add(toggle_button("Full", *self.verbose.borrow()))
+            .add(toggle_button("Bypass", bypass))
+            .on_hover_text("Pass the first input straight through (B)")
             .clicked()
         {
-            let mut state = self.verbose.borrow_mut();
-            *state = !*state;
+            self.ui.borrow_mut().bypass = !bypass;
+        }
+
+        if let Some(samples) = user_state.quarantined.get(&node_id).copied() {
+            let badge = egui::RichText::new("⚠ NaN").color(ui.visuals().warn_fg_color);
+            if ui
+                .add(egui::Button::new(badge).frame(false))
+                .on_hover_text(format!(
+                    "Output NaN or infinity in {samples} samples, fixed by the NaN guard. \
+                     Panic resets most nodes, click to dismiss"
+                ))
+                .clicked()
+            {
+                user_state.quarantined.remove(&node_id);
+            }
         }
 
         Default::default()
@@ -76,7 +151,7 @@ impl NodeDataTrait for SynthNodeData {
     where
         Self::Response: UserResponseTrait,
     {
-        if !*self.verbose.borrow() {
+        if !self.verbose() {
             if let Some(config) = user_state
                 .node_configs
                 .get(&node_id)
@@ -112,28 +187,39 @@ impl NodeDataTrait for SynthNodeData {
     {
         let mut responses = vec![];
 
-        let mut states_guard = self.out_states.borrow_mut();
+        let mut ui_state = self.ui.borrow_mut();
 
-        let state = states_guard.entry(param_name.to_string()).or_default();
+        let state = ui_state
+            .out_states
+            .entry(param_name.to_string())
+            .or_default();
 
         let port = graph.get_port(node_id, param_name).unwrap();
         let is_playing = user_state.rt_playback == Some((node_id, port));
 
         let scope_btn = util::toggle_button("👁Scope", state.show_scope);
+        let meter_btn = util::toggle_button("📶Meter", state.show_meter);
         let play_btn = util::toggle_button("👂Play", is_playing);
 
         let resp = ui.horizontal(|ui| {
             ui.with_layout(egui::Layout::right_to_left(Align::RIGHT), |ui| {
                 ui.label(param_name);
-                (ui.add(scope_btn), ui.add(play_btn))
+                (ui.add(scope_btn), ui.add(meter_btn), ui.add(play_btn))
             })
         });
 
+        tour::mark(ui.ctx(), tour::Target::Scope, resp.inner.inner.0.rect);
+        tour::mark(ui.ctx(), tour::Target::Play, resp.inner.inner.2.rect);
+
+        let was_recorded = state.recorded();
         if resp.inner.inner.0.clicked() {
             state.show_scope = !state.show_scope;
         }
-
         if resp.inner.inner.1.clicked() {
+            state.show_meter = !state.show_meter;
+        }
+
+        if resp.inner.inner.2.clicked() {
             if !is_playing {
                 responses.push(NodeResponse::User(SynthNodeResponse::SetRtPlayback(
                     node_id, port,
@@ -143,20 +229,37 @@ impl NodeDataTrait for SynthNodeData {
             }
         }
 
+        // Scopes and meters of a port share its recording
+        match (was_recorded, state.recorded()) {
+            (false, true) => responses.push(NodeResponse::User(SynthNodeResponse::StartRecording(
+                node_id, port,
+            ))),
+            (true, false) => responses.push(NodeResponse::User(SynthNodeResponse::StopRecording(
+                node_id, port,
+            ))),
+            _ => {}
+        }
+
         if state.show_scope && state.scope.is_none() {
             state.scope = Some(Scope::new());
-            responses.push(NodeResponse::User(SynthNodeResponse::StartRecording(
-                node_id, port,
-            )));
-        } else if !state.show_scope && state.scope.is_some() {
+        } else if !state.show_scope {
             state.scope = None;
-            responses.push(NodeResponse::User(SynthNodeResponse::StopRecording(
-                node_id, port,
-            )));
+        }
+        if state.show_meter && state.meter.is_none() {
+            state.meter = Some(Meter::default());
+        } else if !state.show_meter {
+            state.meter = None;
         }
 
+        if let Some(meter) = &mut state.meter {
+            meter.show(ui);
+        }
         if let Some(scope) = &mut state.scope {
-            scope.show(ui);
+            let mut ports = output_choices(graph);
+            ports.retain(|((id, name), _)| (*id, name.as_str()) != (node_id, param_name));
+
+            // Scopes keep plot and combo box state in egui memory
+            ui.push_id((node_id, param_name), |ui| scope.show(ui, &ports));
         }
 
         responses
@@ -179,6 +282,7 @@ pub enum SynthDataType {
     Float,
     Midi,
     Beat,
+    FloatArray,
 }
 
 impl SynthDataType {
@@ -187,6 +291,7 @@ impl SynthDataType {
             compute::ValueKind::Float => SynthDataType::Float,
             compute::ValueKind::Midi => SynthDataType::Midi,
             compute::ValueKind::Beat => SynthDataType::Beat,
+            compute::ValueKind::FloatArray => SynthDataType::FloatArray,
             _ => unimplemented!("compute kind {ty:?} isn't supported as a graph connection type"),
         }
     }
@@ -198,6 +303,7 @@ impl DataTypeTrait<SynthGraphState> for SynthDataType {
             SynthDataType::Float => egui::Color32::LIGHT_BLUE,
             SynthDataType::Midi => egui::Color32::LIGHT_GREEN,
             SynthDataType::Beat => egui::Color32::LIGHT_RED,
+            SynthDataType::FloatArray => egui::Color32::GOLD,
         }
     }
 
@@ -206,6 +312,7 @@ impl DataTypeTrait<SynthGraphState> for SynthDataType {
             SynthDataType::Float => Cow::Borrowed("signal"),
             SynthDataType::Midi => Cow::Borrowed("MIDI"),
             SynthDataType::Beat => Cow::Borrowed("Beat"),
+            SynthDataType::FloatArray => Cow::Borrowed("channels"),
         }
     }
 }
@@ -219,6 +326,7 @@ impl SynthValueType {
             compute::Value::Float(_) => SynthDataType::Float,
             compute::Value::Midi { .. } => SynthDataType::Midi,
             compute::Value::Beat(_) => SynthDataType::Beat,
+            compute::Value::FloatArray(_) => SynthDataType::FloatArray,
             _ => unimplemented!(),
         }
     }
@@ -228,6 +336,7 @@ impl SynthValueType {
             SynthDataType::Float => compute::Value::Float(0.0),
             SynthDataType::Midi => compute::Value::None,
             SynthDataType::Beat => compute::Value::None,
+            SynthDataType::FloatArray => compute::Value::FloatArray(Vec::new()),
         })
     }
 }
@@ -260,8 +369,8 @@ impl WidgetValueTrait for SynthValueType {
             ui.horizontal(|ui| {
                 if let Some(input) = ui_inputs.get(param_name) {
                     input.show_name(ui, param_name);
-                    input.show_always(ui, *node_data.verbose.borrow());
-                    input.show_disconnected(ui, *node_data.verbose.borrow());
+                    input.show_always(ui, node_data.verbose());
+                    input.show_disconnected(ui, node_data.verbose());
 
                     if input.needs_deep_update() {
                         resp.push(SynthNodeResponse::UpdateInputType(
@@ -294,7 +403,7 @@ impl WidgetValueTrait for SynthValueType {
             ui.horizontal(|ui| {
                 ui.label(param_name);
                 if let Some(input) = ui_inputs.get(param_name) {
-                    input.show_always(ui, *node_data.verbose.borrow());
+                    input.show_always(ui, node_data.verbose());
 
                     if input.needs_deep_update() {
                         resp.push(SynthNodeResponse::UpdateInputType(
@@ -313,6 +422,7 @@ impl WidgetValueTrait for SynthValueType {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SynthNodeTemplate {
+    #[serde(deserialize_with = "compute::node::aliases::deserialize")]
     template: Box<dyn Node>,
     name: String,
     categories: Vec<String>,
@@ -328,6 +438,47 @@ impl Clone for SynthNodeTemplate {
     }
 }
 
+impl SynthNodeTemplate {
+    /// Template of a node built by the editor itself, rather than picked
+    /// from a node list.
+    pub fn new(template: Box<dyn Node>, name: &str, categories: Vec<String>) -> Self {
+        SynthNodeTemplate {
+            template,
+            name: name.to_string(),
+            categories,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+
+    /// Configuration of the node to be created, shared with it once built.
+    pub fn config(&self) -> Option<Arc<dyn NodeConfig>> {
+        self.template.config()
+    }
+
+    /// Whether the node has an input of `data_type`.
+    pub fn accepts(&self, data_type: SynthDataType) -> bool {
+        self.template
+            .inputs()
+            .iter()
+            .any(|input| SynthDataType::from_value_kind(input.kind) == data_type)
+    }
+
+    /// Whether the node has an output of `data_type`.
+    pub fn produces(&self, data_type: SynthDataType) -> bool {
+        self.template
+            .output()
+            .iter()
+            .any(|out| SynthDataType::from_value_kind(out.kind) == data_type)
+    }
+}
+
 impl NodeTemplateTrait for SynthNodeTemplate {
     type NodeData = SynthNodeData;
     type DataType = SynthDataType;
@@ -345,8 +496,7 @@ impl NodeTemplateTrait for SynthNodeTemplate {
 
     fn user_data(&self, _user_state: &mut Self::UserState) -> Self::NodeData {
         SynthNodeData {
-            out_states: RefCell::new(Default::default()),
-            verbose: RefCell::new(true),
+            ui: RefCell::new(NodeUiState::default()),
         }
     }
 
@@ -401,18 +551,20 @@ impl NodeTemplateTrait for SynthNodeTemplate {
 
 pub struct AllSynthNodeTemplates {
     lists: Vec<Box<dyn NodeList>>,
+    /// Arrangement the templates are listed in
+    pub library: Library,
 }
 
 impl AllSynthNodeTemplates {
     pub fn new(lists: Vec<Box<dyn NodeList>>) -> Self {
-        AllSynthNodeTemplates { lists }
+        AllSynthNodeTemplates {
+            lists,
+            library: Library::default(),
+        }
     }
-}
-
-impl NodeTemplateIter for &AllSynthNodeTemplates {
-    type Item = SynthNodeTemplate;
 
-    fn all_kinds(&self) -> Vec<Self::Item> {
+    /// Every template with the categories it comes with, hidden ones too.
+    pub fn unarranged(&self) -> Vec<SynthNodeTemplate> {
         let mut all = Vec::new();
         for list in &self.lists {
             all.extend(list.all().into_iter().map(|(template, name, categories)| {
@@ -428,6 +580,22 @@ impl NodeTemplateIter for &AllSynthNodeTemplates {
     }
 }
 
+impl NodeTemplateIter for &AllSynthNodeTemplates {
+    type Item = SynthNodeTemplate;
+
+    fn all_kinds(&self) -> Vec<Self::Item> {
+        let mut all = self.unarranged();
+        all.retain(|template| !self.library.hidden.contains(&template.name));
+        for template in &mut all {
+            template.categories = self
+                .library
+                .categories(&template.name, &template.categories);
+        }
+
+        all
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum SynthNodeResponse {
     SetRtPlayback(NodeId, usize),
@@ -439,46 +607,35 @@ pub enum SynthNodeResponse {
 
 impl UserResponseTrait for SynthNodeResponse {}
 
-#[derive(Serialize, Deserialize)]
-pub struct SynthCtx {
-    pub midi_smf: Vec<SmfSourceNew>,
-    pub midi_jack: Vec<JackSourceNew>,
-    #[serde(skip)]
-    #[serde(default = "Instant::now")]
-    last_updated_jack: Instant,
-}
-
-impl Default for SynthCtx {
-    fn default() -> Self {
-        SynthCtx {
-            midi_smf: Default::default(),
-            midi_jack: Default::default(),
-            last_updated_jack: Instant::now(),
-        }
-    }
-}
-
-impl SynthCtx {
-    pub fn update_jack(&mut self) {
-        if self.last_updated_jack.elapsed().as_secs_f32() < 2.0 {
-            return;
-        }
-
-        self.last_updated_jack = Instant::now();
-        self.midi_jack = JackSourceNew::all();
-    }
-}
-
 #[derive(Default, Serialize, Deserialize)]
 pub struct SynthGraphState {
     pub rt_playback: Option<(NodeId, usize)>,
     pub ctx: SynthCtx,
+    #[serde(default)]
+    pub tuning: Tuning,
+    #[serde(default)]
+    pub bypass_safety: bool,
+    #[serde(default)]
+    pub master: Master,
+    #[serde(default)]
+    pub links: LinkGroups,
+    #[serde(default)]
+    pub snapshots: Snapshots,
+    #[serde(default)]
+    pub notes: Notes,
+    #[serde(default)]
+    pub snap_to_grid: bool,
+    #[serde(default)]
+    pub frozen: Vec<Frozen>,
 
     // node_ui_inputs and node_configs need to be initialized separately
     #[serde(skip)]
     pub node_ui_inputs: HashMap<NodeId, HashMap<String, Arc<dyn InputUi>>>,
     #[serde(skip)]
     pub node_configs: HashMap<NodeId, Weak<dyn NodeConfig>>,
+    /// Samples nodes output NaN or infinity in, as fixed by the runtime
+    #[serde(skip)]
+    pub quarantined: HashMap<NodeId, usize>,
 
     // this only stores intermediate values, can be skipped during serde
     #[serde(skip)]
@@ -486,6 +643,19 @@ pub struct SynthGraphState {
 }
 
 pub type SynthGraph = Graph<SynthNodeData, SynthDataType, SynthValueType>;
+
+/// Every output of the graph, for scopes plotted against another port.
+pub fn output_choices(graph: &SynthGraph) -> Vec<PortChoice> {
+    graph
+        .nodes
+        .iter()
+        .flat_map(|(node_id, node)| {
+            node.outputs
+                .iter()
+                .map(move |(name, _)| ((node_id, name.clone()), format!("{} - {name}", node.label)))
+        })
+        .collect()
+}
 pub type SynthEditorState = GraphEditorState<
     SynthNodeData,
     SynthDataType,
@@ -494,6 +664,13 @@ pub type SynthEditorState = GraphEditorState<
     SynthGraphState,
 >;
 
+/// Everything a patch file holds, the runtime with its node ids first.
+pub type PatchState = (
+    (Runtime, Vec<(NodeId, u64)>),
+    SynthEditorState,
+    SynthGraphState,
+);
+
 pub trait SynthGraphExt {
     fn get_port(&self, node_id: NodeId, param_name: &str) -> Option<usize>;
 }
@@ -0,0 +1,265 @@
+use std::collections::{HashMap, HashSet};
+
+use eframe::egui;
+use egui_graph_edit::NodeId;
+
+use crate::{
+    graph::{AllSynthNodeTemplates, SynthEditorState, SynthGraph, SynthGraphState},
+    minimap,
+    remote::RuntimeRemote,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Warning {
+    Unused,
+    Feedback,
+    NonFinite(usize),
+}
+
+impl Warning {
+    fn text(self) -> String {
+        match self {
+            Warning::Unused => "Output goes nowhere".into(),
+            Warning::Feedback => "In a feedback loop".into(),
+            Warning::NonFinite(samples) => format!("NaN or infinity in {samples} samples"),
+        }
+    }
+
+    fn hover(self) -> &'static str {
+        match self {
+            Warning::Unused => "None of its outputs is connected or played",
+            Warning::Feedback => "Its output reaches it back a sample late",
+            Warning::NonFinite(_) => "Over the last profile, poisoning every node fed from it",
+        }
+    }
+}
+
+// Everything listed about a node
+struct Row {
+    node_id: NodeId,
+    label: String,
+    category: String,
+    // Connected inputs and how many there are
+    inputs: (usize, usize),
+    // Connections leaving the node
+    outputs: usize,
+    // Share of real time the node took, while profiled
+    load: Option<f32>,
+    warnings: Vec<Warning>,
+}
+
+// Nodes fed by each node
+fn consumers(graph: &SynthGraph) -> HashMap<NodeId, HashSet<NodeId>> {
+    let mut consumers: HashMap<NodeId, HashSet<NodeId>> = HashMap::new();
+    for (input, output) in graph.iter_connections() {
+        consumers
+            .entry(graph.get_output(output).node)
+            .or_default()
+            .insert(graph.get_input(input).node);
+    }
+
+    consumers
+}
+
+// Whether the output of `node_id` makes its way back to it
+fn in_feedback(consumers: &HashMap<NodeId, HashSet<NodeId>>, node_id: NodeId) -> bool {
+    let mut seen = HashSet::new();
+    let mut stack = vec![node_id];
+    while let Some(current) = stack.pop() {
+        for next in consumers.get(&current).into_iter().flatten() {
+            if *next == node_id {
+                return true;
+            }
+            if seen.insert(*next) {
+                stack.push(*next);
+            }
+        }
+    }
+
+    false
+}
+
+/// Window listing every node with its connections, its cost and what may
+/// be wrong with it. The runtime is profiled while it's open.
+#[derive(Debug, Default)]
+pub struct Inspector {
+    pub open: bool,
+    warnings_only: bool,
+    busiest_first: bool,
+    // Categories by node name, gathered once the window first opens
+    categories: HashMap<String, String>,
+}
+
+impl Inspector {
+    fn rows(
+        &self,
+        graph: &SynthGraph,
+        user_state: &SynthGraphState,
+        remote: &RuntimeRemote,
+    ) -> Vec<Row> {
+        let consumers = consumers(graph);
+        let profiles: HashMap<NodeId, _> = remote
+            .profile()
+            .map(|profile| {
+                profile
+                    .nodes
+                    .iter()
+                    .filter_map(|(idx, node)| Some((remote.index_to_id(*idx)?, *node)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        graph
+            .nodes
+            .iter()
+            .map(|(node_id, node)| {
+                let connected = node
+                    .inputs
+                    .iter()
+                    .filter(|(_, input)| graph.connection(*input).is_some())
+                    .count();
+                let outputs = graph
+                    .iter_connections()
+                    .filter(|(_, output)| graph.get_output(*output).node == node_id)
+                    .count();
+                let profile = profiles.get(&node_id);
+
+                let mut warnings = Vec::new();
+                let played = user_state.rt_playback.map(|(id, _)| id) == Some(node_id);
+                if !node.outputs.is_empty() && outputs == 0 && !played {
+                    warnings.push(Warning::Unused);
+                }
+                if in_feedback(&consumers, node_id) {
+                    warnings.push(Warning::Feedback);
+                }
+                if let Some(profile) = profile.filter(|profile| profile.non_finite > 0) {
+                    warnings.push(Warning::NonFinite(profile.non_finite));
+                }
+
+                Row {
+                    node_id,
+                    label: node.label.clone(),
+                    category: self
+                        .categories
+                        .get(&node.label)
+                        .cloned()
+                        .unwrap_or_default(),
+                    inputs: (connected, node.inputs.len()),
+                    outputs,
+                    load: profile
+                        .zip(remote.profile())
+                        .map(|(node, all)| all.load(node.busy)),
+                    warnings,
+                }
+            })
+            .collect()
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        editor: &mut SynthEditorState,
+        user_state: &SynthGraphState,
+        all_nodes: &AllSynthNodeTemplates,
+        remote: &mut RuntimeRemote,
+        editor_rect: Option<egui::Rect>,
+    ) {
+        remote.set_profiling(self.open);
+        if !self.open {
+            return;
+        }
+
+        if self.categories.is_empty() {
+            self.categories = all_nodes
+                .unarranged()
+                .into_iter()
+                .map(|template| {
+                    (
+                        template.name().to_string(),
+                        template.categories().join(" / "),
+                    )
+                })
+                .collect();
+        }
+
+        let mut rows = self.rows(&editor.graph, user_state, remote);
+        let total_load: f32 = rows.iter().filter_map(|row| row.load).sum();
+        let warned = rows.iter().filter(|row| !row.warnings.is_empty()).count();
+        if self.busiest_first {
+            rows.sort_by(|a, b| b.load.unwrap_or(0.0).total_cmp(&a.load.unwrap_or(0.0)));
+        }
+        if self.warnings_only {
+            rows.retain(|row| !row.warnings.is_empty());
+        }
+
+        let mut focused = None;
+        let mut open = self.open;
+        egui::Window::new("Inspector")
+            .open(&mut open)
+            .default_size([560.0, 420.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} nodes, {} connections, {warned} with warnings",
+                    editor.graph.nodes.len(),
+                    editor.graph.iter_connections().count(),
+                ));
+                ui.horizontal(|ui| {
+                    match remote.profile() {
+                        Some(_) => ui.label(format!("CPU: {:.1}%", total_load * 100.0)),
+                        None => ui.weak("Profiling"),
+                    }
+                    .on_hover_text("Share of real time the nodes take to run");
+                    ui.checkbox(&mut self.busiest_first, "Busiest first");
+                    ui.checkbox(&mut self.warnings_only, "Warnings only");
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("inspector")
+                        .num_columns(6)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Node");
+                            ui.strong("Category");
+                            ui.strong("In");
+                            ui.strong("Out");
+                            ui.strong("CPU");
+                            ui.strong("Warnings");
+                            ui.end_row();
+
+                            for row in &rows {
+                                if ui
+                                    .link(&row.label)
+                                    .on_hover_text("Show in the editor")
+                                    .clicked()
+                                {
+                                    focused = Some(row.node_id);
+                                }
+                                ui.label(&row.category);
+                                ui.label(format!("{}/{}", row.inputs.0, row.inputs.1));
+                                ui.label(row.outputs.to_string());
+                                match row.load {
+                                    Some(load) => ui.label(format!("{:.2}%", load * 100.0)),
+                                    None => ui.weak("-"),
+                                };
+                                ui.horizontal(|ui| {
+                                    for warning in &row.warnings {
+                                        ui.colored_label(
+                                            ui.visuals().warn_fg_color,
+                                            warning.text(),
+                                        )
+                                        .on_hover_text(warning.hover());
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        self.open = open;
+
+        if let (Some(node_id), Some(editor_rect)) = (focused, editor_rect) {
+            minimap::focus(editor, editor_rect, node_id);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::any::Any;
+
+use anyhow::Result;
+use jack::{AudioIn, AudioOut, ClientOptions, MidiOut, Port, RawMidi, TransportState};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio::CaptureConverter,
+    compute::{bus, extern_in, extern_midi, extern_out, transport},
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JackSettings {
+    pub enabled: bool,
+    pub audio_ins: usize,
+    pub audio_outs: usize,
+    /// Ports playing the aux buses, in order
+    pub aux_outs: usize,
+    /// Input port read by `Audio In` on the sidechain channel, left out of
+    /// the connections to the system ports
+    pub sidechain_in: bool,
+    pub midi_outs: usize,
+    /// Connects the ports to `system:capture_*` and `system:playback_*`
+    pub auto_connect: bool,
+}
+
+impl Default for JackSettings {
+    fn default() -> Self {
+        JackSettings {
+            enabled: false,
+            audio_ins: 2,
+            audio_outs: 2,
+            aux_outs: 0,
+            sidechain_in: false,
+            midi_outs: 1,
+            auto_connect: true,
+        }
+    }
+}
+
+/// Keeps the JACK client and its ports alive.
+pub struct JackIo {
+    // the type is hard to spell and it only needs to be kept alive.
+    _client: Box<dyn Any + Send + Sync>,
+    // Output channels and MIDI ports opened for reading
+    channels: Vec<usize>,
+    midi_outs: usize,
+}
+
+impl JackIo {
+    pub fn start(settings: &JackSettings) -> Result<Self> {
+        let (client, _status) = jack::Client::new("modal", ClientOptions::NO_START_SERVER)?;
+
+        let sample_rate = client.sample_rate() as u32;
+        if sample_rate != 44100 {
+            println!("JACK runs at {sample_rate} Hz, outputs will be played at the wrong speed");
+        }
+
+        // JACK input k is external input channel k, after the capture device
+        let mut ins = Vec::new();
+        for k in 1..=settings.audio_ins {
+            let port = client.register_port(&format!("in_{k}"), AudioIn::default())?;
+            let converter = CaptureConverter::new(k, 1, sample_rate);
+            ins.push((port, converter));
+        }
+
+        let mut outs: Vec<Port<AudioOut>> = Vec::new();
+        for k in 1..=settings.audio_outs.min(extern_out::CHANNELS - bus::AUX) {
+            outs.push(client.register_port(&format!("out_{k}"), AudioOut::default())?);
+        }
+
+        let mut aux_outs: Vec<Port<AudioOut>> = Vec::new();
+        for k in 1..=settings.aux_outs.min(bus::AUX) {
+            aux_outs.push(client.register_port(&format!("aux_{k}"), AudioOut::default())?);
+        }
+
+        let mut midi_outs: Vec<Port<MidiOut>> = Vec::new();
+        for k in 1..=settings.midi_outs.min(extern_midi::PORTS) {
+            midi_outs.push(client.register_port(&format!("midi_out_{k}"), MidiOut::default())?);
+        }
+
+        let in_names: Vec<_> = ins
+            .iter()
+            .filter_map(|(port, _)| port.name().ok())
+            .collect();
+        let out_names: Vec<_> = outs.iter().filter_map(|port| port.name().ok()).collect();
+
+        if settings.sidechain_in {
+            let port = client.register_port("sidechain_in", AudioIn::default())?;
+            let converter = CaptureConverter::new(extern_in::SIDECHAIN, 1, sample_rate);
+            ins.push((port, converter));
+        }
+
+        let channels: Vec<_> = (0..outs.len())
+            .chain((1..=aux_outs.len()).map(bus::aux_channel))
+            .collect();
+        let opened = midi_outs.len();
+        let process_cb = move |client: &jack::Client, ps: &jack::ProcessScope| {
+            publish_transport(client);
+
+            for (port, converter) in &mut ins {
+                converter.convert(port.as_slice(ps));
+            }
+
+            for (channel, port) in outs.iter_mut().enumerate() {
+                extern_out::pull(channel, port.as_mut_slice(ps));
+            }
+
+            for (k, port) in aux_outs.iter_mut().enumerate() {
+                extern_out::pull(bus::aux_channel(k + 1), port.as_mut_slice(ps));
+            }
+
+            for (channel, port) in midi_outs.iter_mut().enumerate() {
+                let mut writer = port.writer(ps);
+                extern_midi::pull(channel, ps.n_frames() as u64, |time, bytes| {
+                    writer.write(&RawMidi { time, bytes }).ok();
+                });
+            }
+
+            jack::Control::Continue
+        };
+
+        let async_client =
+            client.activate_async((), jack::ClosureProcessHandler::new(process_cb))?;
+
+        // Nodes only queue what's sent to ports being read
+        for &channel in &channels {
+            extern_out::open(channel);
+        }
+        for channel in 0..opened {
+            extern_midi::open(channel);
+        }
+
+        if settings.auto_connect {
+            let client = async_client.as_client();
+            for (k, name) in in_names.iter().enumerate() {
+                let system = format!("system:capture_{}", k + 1);
+                if let Err(e) = client.connect_ports_by_name(&system, name) {
+                    println!("Failed to connect {system} to {name}: {e}");
+                }
+            }
+            for (k, name) in out_names.iter().enumerate() {
+                let system = format!("system:playback_{}", k + 1);
+                if let Err(e) = client.connect_ports_by_name(name, &system) {
+                    println!("Failed to connect {name} to {system}: {e}");
+                }
+            }
+        }
+
+        Ok(JackIo {
+            _client: Box::new(async_client),
+            channels,
+            midi_outs: opened,
+        })
+    }
+}
+
+impl Drop for JackIo {
+    fn drop(&mut self) {
+        transport::clear();
+        for &channel in &self.channels {
+            extern_out::close(channel);
+        }
+        for channel in 0..self.midi_outs {
+            extern_midi::close(channel);
+        }
+    }
+}
+
+fn publish_transport(client: &jack::Client) {
+    let Ok(status) = client.transport().query() else {
+        transport::clear();
+        return;
+    };
+
+    // Tempo is only known when some client is the timebase master
+    let Some(bbt) = status.pos.bbt() else {
+        transport::clear();
+        return;
+    };
+
+    let beats = (bbt.bar as f64 - 1.0) * bbt.sig_num as f64
+        + (bbt.beat as f64 - 1.0)
+        + bbt.tick as f64 / bbt.ticks_per_beat;
+    let rolling = status.state == TransportState::Rolling;
+
+    transport::publish(rolling, bbt.bpm as f32, beats);
+}
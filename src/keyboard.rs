@@ -0,0 +1,243 @@
+use eframe::egui::{self, Key, Modifiers};
+use midly::{num::u7, MidiMessage};
+
+use crate::compute::node::all::source::keyboard;
+
+const OCTAVES: u8 = 3;
+// Computer keys from C up, in the layout of a piano's white and black keys
+const QWERTY: [Key; 17] = [
+    Key::A,
+    Key::W,
+    Key::S,
+    Key::E,
+    Key::D,
+    Key::F,
+    Key::T,
+    Key::G,
+    Key::Y,
+    Key::H,
+    Key::U,
+    Key::J,
+    Key::K,
+    Key::O,
+    Key::L,
+    Key::P,
+    Key::Semicolon,
+];
+// Semitones of the white keys within an octave
+const WHITE: [u8; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+fn is_black(note: u8) -> bool {
+    matches!(note % 12, 1 | 3 | 6 | 8 | 10)
+}
+
+/// Piano played with the mouse or the computer keyboard, heard through Midi
+/// In nodes set to the virtual keyboard.
+#[derive(Debug)]
+pub struct VirtualKeyboard {
+    pub open: bool,
+    /// Whether the letter keys play notes
+    pub qwerty: bool,
+    // Octave of the lowest key, C4 being middle C
+    octave: u8,
+    velocity: u8,
+    // Notes held by the letter keys and by the pointer
+    keys: Vec<(Key, u8)>,
+    pointer: Option<u8>,
+}
+
+impl Default for VirtualKeyboard {
+    fn default() -> Self {
+        VirtualKeyboard {
+            open: false,
+            qwerty: false,
+            octave: 4,
+            velocity: 100,
+            keys: Vec::new(),
+            pointer: None,
+        }
+    }
+}
+
+impl VirtualKeyboard {
+    fn lowest(&self) -> u8 {
+        (self.octave + 1) * 12
+    }
+
+    fn note_on(&self, note: u8) {
+        keyboard::push(MidiMessage::NoteOn {
+            key: u7::from_int_lossy(note),
+            vel: u7::from_int_lossy(self.velocity),
+        });
+    }
+
+    fn note_off(&self, note: u8) {
+        keyboard::push(MidiMessage::NoteOff {
+            key: u7::from_int_lossy(note),
+            vel: u7::from_int_lossy(0),
+        });
+    }
+
+    /// Plays the letter keys, taking them before the shortcuts do. Z and X
+    /// shift the octave, C and V the velocity.
+    pub fn handle_keys(&mut self, ctx: &egui::Context) {
+        if !self.open || !self.qwerty || ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let events = ctx.input_mut(|state| {
+            let events: Vec<_> = state
+                .events
+                .iter()
+                .filter_map(|event| match event {
+                    egui::Event::Key {
+                        key,
+                        pressed,
+                        repeat: false,
+                        modifiers,
+                        ..
+                    } if modifiers.is_none() => Some((*key, *pressed)),
+                    _ => None,
+                })
+                .collect();
+
+            for key in QWERTY.iter().chain(&[Key::Z, Key::X, Key::C, Key::V]) {
+                state.consume_key(Modifiers::NONE, *key);
+            }
+
+            events
+        });
+
+        for (key, pressed) in events {
+            match (key, pressed) {
+                (Key::Z, true) => self.octave = self.octave.saturating_sub(1),
+                (Key::X, true) => self.octave = (self.octave + 1).min(8),
+                (Key::C, true) => self.velocity = self.velocity.saturating_sub(16).max(1),
+                (Key::V, true) => self.velocity = (self.velocity + 16).min(127),
+                (key, true) => {
+                    let Some(k) = QWERTY.iter().position(|qwerty| *qwerty == key) else {
+                        continue;
+                    };
+                    let note = self.lowest() + k as u8;
+                    if note < 128 && !self.keys.iter().any(|(held, _)| *held == key) {
+                        self.note_on(note);
+                        self.keys.push((key, note));
+                    }
+                }
+                (key, false) => {
+                    if let Some(k) = self.keys.iter().position(|(held, _)| *held == key) {
+                        let (_, note) = self.keys.swap_remove(k);
+                        self.note_off(note);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Shows the keyboard along the bottom of the window.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            self.release_all();
+            return;
+        }
+
+        egui::TopBottomPanel::bottom("virtual-keyboard").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Octave");
+                ui.add(egui::DragValue::new(&mut self.octave).range(0..=8));
+                ui.label("Velocity");
+                ui.add(egui::DragValue::new(&mut self.velocity).range(1..=127));
+                ui.checkbox(&mut self.qwerty, "Computer keyboard")
+                    .on_hover_text("A to ; play the notes, Z/X shift the octave, C/V the velocity");
+            });
+
+            self.show_keys(ui);
+        });
+    }
+
+    fn show_keys(&mut self, ui: &mut egui::Ui) {
+        let whites = OCTAVES as usize * WHITE.len() + 1;
+        let size = egui::vec2(ui.available_width(), 72.0);
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+        let white_width = rect.width() / whites as f32;
+        let lowest = self.lowest();
+
+        // White keys first, black ones are drawn and hit on top of them
+        let mut keys = Vec::new();
+        for k in 0..whites {
+            let note = lowest + 12 * (k / WHITE.len()) as u8 + WHITE[k % WHITE.len()];
+            let left = rect.left() + k as f32 * white_width;
+            let key = egui::Rect::from_min_size(
+                egui::pos2(left, rect.top()),
+                egui::vec2(white_width, rect.height()),
+            );
+            keys.push((note, key));
+        }
+        for k in 0..whites - 1 {
+            let note = lowest + 12 * (k / WHITE.len()) as u8 + WHITE[k % WHITE.len()] + 1;
+            if !is_black(note) {
+                continue;
+            }
+            let center = rect.left() + (k + 1) as f32 * white_width;
+            let key = egui::Rect::from_center_size(
+                egui::pos2(center, rect.top() + rect.height() * 0.3),
+                egui::vec2(white_width * 0.6, rect.height() * 0.6),
+            );
+            keys.push((note, key));
+        }
+
+        let hovered = response.interact_pointer_pos().and_then(|pos| {
+            keys.iter()
+                .rev()
+                .find(|(_, key)| key.contains(pos))
+                .map(|(note, _)| *note)
+        });
+        let pressed = hovered.filter(|note| {
+            *note < 128 && (response.is_pointer_button_down_on() || response.dragged())
+        });
+        if pressed != self.pointer {
+            if let Some(note) = self.pointer {
+                self.note_off(note);
+            }
+            if let Some(note) = pressed {
+                self.note_on(note);
+            }
+            self.pointer = pressed;
+        }
+
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        for (note, key) in &keys {
+            let held =
+                self.pointer == Some(*note) || self.keys.iter().any(|(_, held)| held == note);
+            let fill = match (held, is_black(*note)) {
+                (true, _) => visuals.selection.bg_fill,
+                (false, true) => egui::Color32::from_gray(30),
+                (false, false) => egui::Color32::from_gray(235),
+            };
+            painter.rect(key.shrink(0.5), 2.0, fill, visuals.window_stroke);
+            if note % 12 == 0 && !is_black(*note) {
+                painter.text(
+                    key.center_bottom() - egui::vec2(0.0, 4.0),
+                    egui::Align2::CENTER_BOTTOM,
+                    format!("C{}", note / 12 - 1),
+                    egui::FontId::proportional(10.0),
+                    egui::Color32::from_gray(90),
+                );
+            }
+        }
+    }
+
+    // Notes shouldn't hang when the keyboard is closed while playing
+    fn release_all(&mut self) {
+        let held: Vec<u8> = self
+            .keys
+            .drain(..)
+            .map(|(_, note)| note)
+            .chain(self.pointer.take())
+            .collect();
+        for note in held {
+            self.note_off(note);
+        }
+    }
+}
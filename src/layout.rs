@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use eframe::egui::{self, Pos2, Vec2};
+use egui_graph_edit::NodeId;
+
+use crate::graph::{SynthEditorState, SynthGraph};
+
+/// Room given to a node where its size matters, only its corner is known.
+pub const NODE_SIZE: Vec2 = Vec2::new(220.0, 160.0);
+// Spacing of the columns and rows of an arranged patch
+const COLUMN: f32 = 260.0;
+const ROW: f32 = 180.0;
+// Size of the grid nodes snap to
+const GRID: f32 = 20.0;
+
+// Nodes among `nodes` feeding `node_id`
+fn feeding(graph: &SynthGraph, node_id: NodeId, nodes: &[NodeId]) -> Vec<NodeId> {
+    let Some(node) = graph.nodes.get(node_id) else {
+        return Vec::new();
+    };
+
+    node.inputs
+        .iter()
+        .filter_map(|(_, input_id)| graph.try_get_output(graph.connection(*input_id)?))
+        .map(|output| output.node)
+        .filter(|src| *src != node_id && nodes.contains(src))
+        .collect()
+}
+
+// Selected nodes, or all of them when nothing is selected
+fn targets(editor: &SynthEditorState) -> Vec<NodeId> {
+    if editor.selected_nodes.is_empty() {
+        editor.graph.nodes.keys().collect()
+    } else {
+        editor.selected_nodes.clone()
+    }
+}
+
+fn positions(editor: &SynthEditorState, nodes: &[NodeId]) -> Vec<(NodeId, Pos2)> {
+    nodes
+        .iter()
+        .filter_map(|node_id| Some((*node_id, *editor.node_positions.get(*node_id)?)))
+        .collect()
+}
+
+/// Arranges the nodes left to right by signal flow, in columns by their
+/// distance from the sources, ordered within a column by the rows of the
+/// nodes feeding them.
+pub fn arrange(editor: &mut SynthEditorState) {
+    let nodes = targets(editor);
+    let placed = positions(editor, &nodes);
+    let Some(origin) = placed.iter().map(|(_, pos)| *pos).reduce(|a, b| a.min(b)) else {
+        return;
+    };
+
+    let inputs: HashMap<NodeId, Vec<NodeId>> = nodes
+        .iter()
+        .map(|node_id| (*node_id, feeding(&editor.graph, *node_id, &nodes)))
+        .collect();
+
+    // Longest path from a source, feedback loops stop growing once every
+    // node got a column further than all nodes it could be behind
+    let mut column: HashMap<NodeId, usize> = nodes.iter().map(|node_id| (*node_id, 0)).collect();
+    for _ in 0..nodes.len() {
+        let mut changed = false;
+        for node_id in &nodes {
+            let deepest = inputs[node_id]
+                .iter()
+                .map(|src| column[src] + 1)
+                .max()
+                .unwrap_or(0)
+                .min(nodes.len() - 1);
+            if deepest > column[node_id] {
+                column.insert(*node_id, deepest);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let columns = column.values().copied().max().unwrap_or(0) + 1;
+    let mut row: HashMap<NodeId, f32> = HashMap::new();
+    for k in 0..columns {
+        // Keeping the previous vertical order breaks ties of the barycenters
+        let mut members: Vec<_> = placed
+            .iter()
+            .filter(|(node_id, _)| column[node_id] == k)
+            .map(|(node_id, pos)| {
+                let rows: Vec<f32> = inputs[node_id]
+                    .iter()
+                    .filter_map(|src| row.get(src).copied())
+                    .collect();
+                let center = if rows.is_empty() {
+                    f32::INFINITY
+                } else {
+                    rows.iter().sum::<f32>() / rows.len() as f32
+                };
+                (*node_id, center, pos.y)
+            })
+            .collect();
+        members.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.2.total_cmp(&b.2)));
+
+        for (r, (node_id, _, _)) in members.into_iter().enumerate() {
+            row.insert(node_id, r as f32);
+            editor.node_positions[node_id] = origin + egui::vec2(k as f32 * COLUMN, r as f32 * ROW);
+        }
+    }
+}
+
+/// Moves the nodes to the leftmost one of them.
+pub fn align_left(editor: &mut SynthEditorState) {
+    let placed = positions(editor, &targets(editor));
+    let Some(x) = placed.iter().map(|(_, pos)| pos.x).reduce(f32::min) else {
+        return;
+    };
+
+    for (node_id, _) in placed {
+        editor.node_positions[node_id].x = x;
+    }
+}
+
+/// Moves the nodes to the topmost one of them.
+pub fn align_top(editor: &mut SynthEditorState) {
+    let placed = positions(editor, &targets(editor));
+    let Some(y) = placed.iter().map(|(_, pos)| pos.y).reduce(f32::min) else {
+        return;
+    };
+
+    for (node_id, _) in placed {
+        editor.node_positions[node_id].y = y;
+    }
+}
+
+/// Spaces the nodes evenly between the outermost ones, along x when
+/// `horizontal` and along y otherwise.
+pub fn distribute(editor: &mut SynthEditorState, horizontal: bool) {
+    let axis = |pos: &Pos2| if horizontal { pos.x } else { pos.y };
+
+    let mut placed = positions(editor, &targets(editor));
+    if placed.len() < 3 {
+        return;
+    }
+    placed.sort_by(|a, b| axis(&a.1).total_cmp(&axis(&b.1)));
+
+    let first = axis(&placed[0].1);
+    let step = (axis(&placed[placed.len() - 1].1) - first) / (placed.len() - 1) as f32;
+    for (k, (node_id, _)) in placed.into_iter().enumerate() {
+        let pos = &mut editor.node_positions[node_id];
+        if horizontal {
+            pos.x = first + k as f32 * step;
+        } else {
+            pos.y = first + k as f32 * step;
+        }
+    }
+}
+
+/// Rounds the positions of all nodes to the grid.
+pub fn snap(editor: &mut SynthEditorState) {
+    for (_, pos) in editor.node_positions.iter_mut() {
+        *pos = ((pos.to_vec2() / GRID).round() * GRID).to_pos2();
+    }
+}
@@ -0,0 +1,15 @@
+//! The node runtime of Modal and every node shipped with it, without the
+//! editor around them. Crates with nodes of their own depend on it to
+//! implement [`Node`](compute::node::Node) and hand their list to
+//! [`register_node_list`], see its documentation for how their nodes get
+//! saved in patches.
+
+pub mod compute;
+pub mod notify;
+pub mod util;
+pub mod wave;
+
+// Nodes reach each other through `crate::node`
+use compute::node;
+
+pub use node::{register_node_list, NodeList};
@@ -0,0 +1,188 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::graph::AllSynthNodeTemplates;
+
+/// Arrangement of the node library by the user, over the categories nodes
+/// come with. Kept in the settings.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Library {
+    /// Nodes left out of the node finder, by name
+    pub hidden: BTreeSet<String>,
+    /// Categories replacing the ones a node comes with, by node name
+    pub categories: BTreeMap<String, Vec<String>>,
+    /// New names of categories, by the name they come with
+    pub renamed: BTreeMap<String, String>,
+}
+
+impl Library {
+    /// Categories `name` is listed under, `shipped` being its own.
+    pub fn categories(&self, name: &str, shipped: &[String]) -> Vec<String> {
+        let categories = self
+            .categories
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or(shipped);
+
+        let mut arranged: Vec<String> = Vec::new();
+        for category in categories {
+            let category = self.renamed.get(category).unwrap_or(category);
+            if !arranged.contains(category) {
+                arranged.push(category.clone());
+            }
+        }
+        arranged
+    }
+}
+
+// Categories as typed, comma separated
+fn parse(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|category| !category.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Window rearranging the node library.
+#[derive(Debug, Default)]
+pub struct LibraryDialog {
+    pub open: bool,
+    filter: String,
+    // Nodes with the categories they come with, gathered on opening
+    nodes: Vec<(String, Vec<String>)>,
+    // Text of the edits in progress, by node and by category
+    editing: HashMap<String, String>,
+    renaming: HashMap<String, String>,
+}
+
+impl LibraryDialog {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        library: &mut Library,
+        templates: &AllSynthNodeTemplates,
+    ) {
+        if !self.open {
+            self.nodes.clear();
+            self.editing.clear();
+            self.renaming.clear();
+            return;
+        }
+        if self.nodes.is_empty() {
+            self.nodes = templates
+                .unarranged()
+                .iter()
+                .map(|template| (template.name().to_string(), template.categories().to_vec()))
+                .collect();
+            self.nodes.sort();
+        }
+
+        let mut open = self.open;
+        egui::Window::new("Node library")
+            .open(&mut open)
+            .default_height(480.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.filter).hint_text("Filter"));
+                    if ui
+                        .button("Reset")
+                        .on_hover_text("Back to the categories nodes come with")
+                        .clicked()
+                    {
+                        *library = Library::default();
+                        self.editing.clear();
+                        self.renaming.clear();
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.collapsing("Categories", |ui| self.show_renames(ui, library));
+                    ui.collapsing("Nodes", |ui| self.show_nodes(ui, library));
+                });
+            });
+
+        self.open = open;
+    }
+
+    fn show_renames(&mut self, ui: &mut egui::Ui, library: &mut Library) {
+        let mut shipped: Vec<&String> = self
+            .nodes
+            .iter()
+            .flat_map(|(_, categories)| categories)
+            .collect();
+        shipped.sort();
+        shipped.dedup();
+
+        egui::Grid::new("library-categories")
+            .num_columns(2)
+            .show(ui, |ui| {
+                for category in shipped {
+                    ui.label(category);
+                    let text = self.renaming.entry(category.clone()).or_insert_with(|| {
+                        library.renamed.get(category).unwrap_or(category).clone()
+                    });
+                    let response = ui.add(egui::TextEdit::singleline(text).desired_width(160.0));
+                    if response.lost_focus() {
+                        let name = text.trim();
+                        if name.is_empty() || name == category {
+                            library.renamed.remove(category);
+                        } else {
+                            library.renamed.insert(category.clone(), name.to_string());
+                        }
+                        self.renaming.remove(category);
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn show_nodes(&mut self, ui: &mut egui::Ui, library: &mut Library) {
+        let filter = self.filter.to_lowercase();
+
+        egui::Grid::new("library-nodes")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                for (name, shipped) in &self.nodes {
+                    if !name.to_lowercase().contains(&filter) {
+                        continue;
+                    }
+
+                    let mut shown = !library.hidden.contains(name);
+                    if ui
+                        .checkbox(&mut shown, name)
+                        .on_hover_text("Listed in the node finder")
+                        .changed()
+                    {
+                        if shown {
+                            library.hidden.remove(name);
+                        } else {
+                            library.hidden.insert(name.clone());
+                        }
+                    }
+
+                    let text = self.editing.entry(name.clone()).or_insert_with(|| {
+                        library.categories.get(name).unwrap_or(shipped).join(", ")
+                    });
+                    let response = ui
+                        .add(egui::TextEdit::singleline(text).desired_width(200.0))
+                        .on_hover_text("Categories, separated by commas");
+                    if response.lost_focus() {
+                        let categories = parse(text);
+                        if categories == *shipped {
+                            library.categories.remove(name);
+                        } else {
+                            library.categories.insert(name.clone(), categories);
+                        }
+                        self.editing.remove(name);
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+}
@@ -0,0 +1,265 @@
+use std::{collections::HashMap, sync::Arc};
+
+use eframe::egui;
+use egui_graph_edit::NodeId;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compute::node::InputUi,
+    graph::{SynthGraph, SynthGraphState},
+};
+
+type UiInputs = HashMap<NodeId, HashMap<String, Arc<dyn InputUi>>>;
+
+/// Input following the value of its group as `value * scale + offset`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinkMember {
+    pub node: NodeId,
+    pub input: String,
+    pub scale: f32,
+    pub offset: f32,
+    // Value last seen on the input, a different one means it was edited
+    #[serde(skip)]
+    last: Option<f32>,
+}
+
+impl LinkMember {
+    fn input<'a>(&self, inputs: &'a UiInputs) -> Option<&'a Arc<dyn InputUi>> {
+        inputs.get(&self.node)?.get(&self.input)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LinkGroup {
+    pub name: String,
+    pub value: f32,
+    pub members: Vec<LinkMember>,
+}
+
+impl LinkGroup {
+    fn apply(&mut self, inputs: &UiInputs) {
+        for member in &mut self.members {
+            if let Some(input) = member.input(inputs) {
+                input.set_value(self.value * member.scale + member.offset);
+                member.last = input.value();
+            }
+        }
+    }
+}
+
+/// Named groups of inputs changed together, saved with the patch.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LinkGroups {
+    pub groups: Vec<LinkGroup>,
+}
+
+impl LinkGroups {
+    /// Propagates an edit of any member to the rest of its group.
+    pub fn sync(&mut self, inputs: &UiInputs) {
+        for group in &mut self.groups {
+            // Members of deleted nodes or inputs
+            group
+                .members
+                .retain(|member| member.input(inputs).is_some());
+
+            let mut edited = None;
+            for member in &mut group.members {
+                let Some(value) = member.input(inputs).and_then(|input| input.value()) else {
+                    continue;
+                };
+
+                match member.last {
+                    Some(last) if last != value => {
+                        edited.get_or_insert((value - member.offset) / member.scale);
+                    }
+                    Some(_) => {}
+                    // First look after a load
+                    None => member.last = Some(value),
+                }
+            }
+
+            if let Some(value) = edited.filter(|value| value.is_finite()) {
+                group.value = value;
+                group.apply(inputs);
+            }
+        }
+    }
+}
+
+/// Window for creating link groups and picking their members.
+#[derive(Debug, Default)]
+pub struct LinkEditor {
+    pub open: bool,
+    // Node and input picked for the next member of each group
+    picked: HashMap<usize, (Option<NodeId>, Option<String>)>,
+}
+
+impl LinkEditor {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        graph: &SynthGraph,
+        user_state: &mut SynthGraphState,
+    ) {
+        let mut open = self.open;
+        let SynthGraphState {
+            links,
+            node_ui_inputs,
+            ..
+        } = user_state;
+
+        egui::Window::new("Link Groups")
+            .open(&mut open)
+            .default_size([420.0, 360.0])
+            .show(ctx, |ui| {
+                if ui.button("New group").clicked() {
+                    links.groups.push(LinkGroup {
+                        name: format!("Group {}", links.groups.len() + 1),
+                        value: 0.0,
+                        members: Vec::new(),
+                    });
+                }
+
+                let mut remove_group = None;
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (k, group) in links.groups.iter_mut().enumerate() {
+                        ui.push_id(k, |ui| {
+                            ui.separator();
+                            if self.show_group(ui, k, group, graph, node_ui_inputs) {
+                                remove_group = Some(k);
+                            }
+                        });
+                    }
+                });
+
+                if let Some(k) = remove_group {
+                    links.groups.remove(k);
+                    self.picked.clear();
+                }
+            });
+
+        self.open = open;
+    }
+
+    // Returns whether the group should be removed
+    fn show_group(
+        &mut self,
+        ui: &mut egui::Ui,
+        k: usize,
+        group: &mut LinkGroup,
+        graph: &SynthGraph,
+        inputs: &UiInputs,
+    ) -> bool {
+        let mut changed = false;
+        let mut remove = false;
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut group.name);
+            changed |= ui
+                .add(egui::DragValue::new(&mut group.value).speed(0.01))
+                .changed();
+            remove = ui.button("Remove").clicked();
+        });
+
+        let mut remove_member = None;
+        egui::Grid::new("members").num_columns(5).show(ui, |ui| {
+            for (m, member) in group.members.iter_mut().enumerate() {
+                let label = graph
+                    .nodes
+                    .get(member.node)
+                    .map(|node| node.label.as_str())
+                    .unwrap_or("?");
+                ui.label(label);
+                ui.label(&member.input);
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut member.scale)
+                            .speed(0.01)
+                            .prefix("×"),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut member.offset)
+                            .speed(0.01)
+                            .prefix("+"),
+                    )
+                    .changed();
+                if ui.small_button("🗙").clicked() {
+                    remove_member = Some(m);
+                }
+                ui.end_row();
+            }
+        });
+
+        if let Some(m) = remove_member {
+            group.members.remove(m);
+        }
+
+        let (node, input) = self.picked.entry(k).or_default();
+        ui.horizontal(|ui| {
+            let node_text = node
+                .and_then(|id| graph.nodes.get(id))
+                .map(|node| node.label.clone())
+                .unwrap_or("Node".into());
+            egui::ComboBox::from_id_salt("node")
+                .selected_text(node_text)
+                .show_ui(ui, |ui| {
+                    for (node_id, node_data) in &graph.nodes {
+                        let has_values = inputs
+                            .get(&node_id)
+                            .is_some_and(|ins| ins.values().any(|i| i.value().is_some()));
+                        if has_values {
+                            ui.selectable_value(node, Some(node_id), &node_data.label);
+                        }
+                    }
+                });
+
+            let names: Vec<_> = node
+                .and_then(|id| inputs.get(&id))
+                .map(|ins| {
+                    ins.iter()
+                        .filter(|(_, input)| input.value().is_some())
+                        .map(|(name, _)| name.clone())
+                        .collect()
+                })
+                .unwrap_or_default();
+            egui::ComboBox::from_id_salt("input")
+                .selected_text(input.as_deref().unwrap_or("Input"))
+                .show_ui(ui, |ui| {
+                    for name in names {
+                        ui.selectable_value(input, Some(name.clone()), name);
+                    }
+                });
+
+            if ui.button("Add").clicked() {
+                if let (Some(node_id), Some(name)) = (*node, input.take()) {
+                    let value = inputs
+                        .get(&node_id)
+                        .and_then(|ins| ins.get(&name))
+                        .and_then(|input| input.value());
+
+                    // The first member decides the value of the group
+                    if let (true, Some(value)) = (group.members.is_empty(), value) {
+                        group.value = value;
+                    }
+
+                    group.members.push(LinkMember {
+                        node: node_id,
+                        input: name,
+                        scale: 1.0,
+                        offset: 0.0,
+                        last: None,
+                    });
+                    changed = true;
+                }
+            }
+        });
+
+        if changed {
+            group.apply(inputs);
+        }
+
+        remove
+    }
+}
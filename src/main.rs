@@ -1,33 +1,74 @@
-mod compute;
+mod audio;
+mod autoconnect;
+mod capture;
+mod commands;
+mod factory;
+mod finder;
+mod freeze;
 mod graph;
+mod inspector;
+mod jack_io;
+mod keyboard;
+mod layout;
+mod library;
+mod links;
+mod minimap;
+mod model;
+mod mutate;
+mod notes;
+mod perform;
 mod remote;
+mod render;
 mod scope;
-
-mod util;
-mod wave;
+mod settings;
+mod sink;
+mod snapshots;
+mod solo;
+mod table;
+mod tour;
 
 use std::{collections::HashMap, fs::File, sync::Arc, time::Instant};
 
 use eframe::egui::{self, Vec2};
-use egui_graph_edit::{InputParamKind, NodeId, NodeResponse};
-
-use compute::{
-    node::{
-        self,
-        all::source::{smf::SmfSourceNew, MidiSourceNew},
-        Input, NodeEvent,
-    },
-    OutputPort,
+use egui_graph_edit::{AnyParameterId, InputId, NodeId, NodeResponse, OutputId};
+
+use modal_lib::{compute, notify, util};
+
+use compute::node::{
+    self,
+    all::source::{smf::SmfSourceNew, MidiSourceNew},
+    NodeEvent,
 };
-use graph::{OutputState, SynthDataType};
-use rfd::FileDialog;
+use graph::OutputState;
+use scope::Meter;
 
 use crate::{
-    compute::Runtime,
-    graph::{SynthEditorState, SynthGraphExt, SynthGraphState},
+    commands::Command,
+    compute::{tuning::Tuning, Runtime, Value},
+    graph::{SynthEditorState, SynthGraphExt, SynthGraphState, SynthNodeData, SynthNodeResponse},
+    model::GraphModel,
+    sink::SinkId,
 };
 
 fn main() {
+    // Before anything deserializes patches, which may use their nodes
+    if let Some(dir) = eframe::storage_dir("Modal") {
+        node::pack::load_dir(&dir.join("nodes"));
+    }
+
+    // Offline renders, used to catch DSP regressions
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(result) = render::run_cli(&args) {
+        match result {
+            Ok(true) => return,
+            Ok(false) => std::process::exit(1),
+            Err(e) => {
+                println!("Render failed: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+
     let options = eframe::NativeOptions {
         window_builder: Some(Box::new(|viewport| {
             viewport.with_inner_size(Vec2::new(1600.0, 1200.0))
@@ -48,7 +89,36 @@ struct SynthApp {
     user_state: graph::SynthGraphState,
     all_nodes: graph::AllSynthNodeTemplates,
     remote: remote::RuntimeRemote,
+    last_clip: Option<Instant>,
+    table: table::ParamTable,
+    links: links::LinkEditor,
+    snapshots: snapshots::SnapshotEditor,
+    mutator: mutate::Mutator,
+    freezer: freeze::Freezer,
+    inspector: inspector::Inspector,
+    audio: audio::AudioDialog,
+    settings: settings::Settings,
+    settings_dialog: settings::SettingsDialog,
+    library: library::LibraryDialog,
+    capture: capture::Capture,
+    keyboard: keyboard::VirtualKeyboard,
+    auto_connect: autoconnect::AutoConnect,
+    perform: perform::Performance,
     prev_frame: Instant,
+    notifications: notify::Notifications,
+    muted: Vec<(NodeId, usize)>,
+    bypassed: Vec<NodeId>,
+    // Ports recorded only because an XY scope is plotted against them
+    xy_partners: Vec<(NodeId, usize)>,
+    // Screen area of the graph editor, where new notes are placed from
+    editor_rect: Option<egui::Rect>,
+    commands: commands::Commands,
+    finder: finder::NodeFinder,
+    // Responses handed to the editor on its next frame, from commands
+    pending_responses: Vec<NodeResponse<SynthNodeResponse, SynthNodeData>>,
+    // Connection to a node placed from a dropped connection, made once the
+    // runtime has the node
+    quick_connection: Option<(OutputId, InputId)>,
 }
 
 impl SynthApp {
@@ -58,16 +128,14 @@ impl SynthApp {
             SynthEditorState,
             SynthGraphState,
         )>,
-    ) -> Self {
-        pub use node::all::*;
-
+    ) -> anyhow::Result<Self> {
         if let Some(((rt, mapping), editor, mut user_state)) = state {
             for (idx, node) in rt.nodes() {
-                let node_id = mapping
-                    .iter()
-                    .find(|(_, bits)| *bits == idx.to_bits())
-                    .unwrap()
-                    .0;
+                let Some((node_id, _)) = mapping.iter().find(|(_, bits)| *bits == idx.to_bits())
+                else {
+                    anyhow::bail!("node {idx:?} of the runtime is missing from the editor");
+                };
+                let node_id = *node_id;
                 if let Some(config) = node.config() {
                     user_state
                         .node_configs
@@ -86,42 +154,87 @@ impl SynthApp {
             let mut remote = remote::RuntimeRemote::with_rt_and_mapping(rt, mapping);
 
             for (node_id, node) in &editor.graph.nodes {
-                for (param_name, _out_state) in node.user_data.out_states.borrow().iter() {
-                    remote.record(node_id, editor.graph.get_port(node_id, param_name).unwrap());
+                for (param_name, out_state) in node.user_data.ui.borrow().out_states.iter() {
+                    if let (true, Ok(port)) = (
+                        out_state.recorded(),
+                        editor.graph.get_port(node_id, param_name),
+                    ) {
+                        remote.record(node_id, port);
+                    }
                 }
             }
 
             remote.play(user_state.rt_playback);
+            remote.output_guard().set_bypass(user_state.bypass_safety);
+            remote.set_sink_gain(SinkId::PLAYBACK, user_state.master.gain());
+            user_state.tuning.apply();
 
-            SynthApp {
+            Ok(SynthApp {
                 state: editor,
                 user_state,
-                all_nodes: graph::AllSynthNodeTemplates::new(vec![
-                    Box::new(Basic),
-                    Box::new(Effects),
-                    Box::new(Filters),
-                    Box::new(Instruments),
-                    Box::new(Midi),
-                    Box::new(Noise),
-                ]),
+                all_nodes: graph::AllSynthNodeTemplates::new(node::node_lists()),
                 remote,
+                last_clip: None,
+                table: Default::default(),
+                links: Default::default(),
+                snapshots: Default::default(),
+                mutator: Default::default(),
+                freezer: Default::default(),
+                inspector: Default::default(),
+                audio: Default::default(),
+                settings: Default::default(),
+                settings_dialog: Default::default(),
+                library: Default::default(),
+                capture: Default::default(),
+                keyboard: Default::default(),
+                auto_connect: Default::default(),
+                perform: Default::default(),
                 prev_frame: Instant::now(),
-            }
+                notifications: Default::default(),
+                muted: Vec::new(),
+                bypassed: Vec::new(),
+                xy_partners: Vec::new(),
+                editor_rect: None,
+                commands: Default::default(),
+                finder: Default::default(),
+                pending_responses: Vec::new(),
+                quick_connection: None,
+            })
         } else {
-            SynthApp {
+            let user_state: graph::SynthGraphState = Default::default();
+            user_state.tuning.apply();
+
+            Ok(SynthApp {
                 state: Default::default(),
-                user_state: Default::default(),
-                all_nodes: graph::AllSynthNodeTemplates::new(vec![
-                    Box::new(Basic),
-                    Box::new(Effects),
-                    Box::new(Filters),
-                    Box::new(Instruments),
-                    Box::new(Midi),
-                    Box::new(Noise),
-                ]),
+                user_state,
+                all_nodes: graph::AllSynthNodeTemplates::new(node::node_lists()),
                 remote: Default::default(),
+                last_clip: None,
+                table: Default::default(),
+                links: Default::default(),
+                snapshots: Default::default(),
+                mutator: Default::default(),
+                freezer: Default::default(),
+                inspector: Default::default(),
+                audio: Default::default(),
+                settings: Default::default(),
+                settings_dialog: Default::default(),
+                library: Default::default(),
+                capture: Default::default(),
+                keyboard: Default::default(),
+                auto_connect: Default::default(),
+                perform: Default::default(),
                 prev_frame: Instant::now(),
-            }
+                notifications: Default::default(),
+                muted: Vec::new(),
+                bypassed: Vec::new(),
+                xy_partners: Vec::new(),
+                editor_rect: None,
+                commands: Default::default(),
+                finder: Default::default(),
+                pending_responses: Vec::new(),
+                quick_connection: None,
+            })
         }
     }
 
@@ -136,102 +249,484 @@ impl SynthApp {
         )> = cc
             .storage
             .and_then(|storage| eframe::get_value(storage, "synth-app"));
+        let connect_history: Option<autoconnect::ConnectHistory> = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, "connect-history"));
+        let finder_history: Option<finder::FinderHistory> = cc
+            .storage
+            .and_then(|storage| eframe::get_value(storage, "node-finder"));
+
+        let mut app = Self::new(state).unwrap_or_else(|e| {
+            notify::error("Failed to restore the last session", e);
+            Self::new(None).expect("an empty patch always loads")
+        });
+        app.auto_connect.history = connect_history.unwrap_or_default();
+        app.finder.history = finder_history.unwrap_or_default();
 
-        Self::new(state)
+        // Zoom shortcuts go through the keymap, so they can be rebound
+        cc.egui_ctx
+            .options_mut(|options| options.zoom_with_keyboard = false);
+
+        let settings = match settings::Settings::load() {
+            Some(settings) => {
+                settings.apply_ui(&cc.egui_ctx);
+                settings
+            }
+            // Sessions before the settings file kept some of them with eframe
+            None => settings::Settings {
+                audio: cc
+                    .storage
+                    .and_then(|storage| eframe::get_value(storage, "audio-settings"))
+                    .unwrap_or_default(),
+                keymap: cc
+                    .storage
+                    .and_then(|storage| eframe::get_value(storage, "keymap"))
+                    .unwrap_or_default(),
+                // Only new users get the tour on their own
+                tour: match cc
+                    .storage
+                    .and_then(|storage| storage.get_string("synth-app"))
+                {
+                    Some(_) => tour::TourStep::Done,
+                    None => Default::default(),
+                },
+                ..Default::default()
+            },
+        };
+        app.audio.settings = settings.audio.clone();
+        app.commands.keymap = settings.keymap.clone();
+        app.settings = settings;
+        if app.audio.settings != audio::AudioSettings::default() {
+            app.apply_audio();
+        }
+
+        app
     }
 }
 
 impl SynthApp {
-    fn recalc_inputs(&mut self, node_id: NodeId, inputs: Vec<Input>) {
-        let curr_inputs = self.state.graph.nodes.get(node_id).unwrap().inputs.clone();
-        let input_names: Vec<_> = inputs.iter().map(|input| input.name.clone()).collect();
-
-        // remove inputs that exist but aren't in `inputs` arg
-        for (name, in_id) in &curr_inputs {
-            if !input_names.contains(name) {
-                self.state.graph.remove_input_param(*in_id);
+    fn apply_audio(&mut self) {
+        match self.remote.set_audio(&self.audio.settings) {
+            Ok(()) => self.audio.set_error(None),
+            Err(e) => {
+                notify::error("Failed to open audio output", &e);
+                self.audio.set_error(Some(e.to_string()));
             }
         }
+    }
+
+    fn save_patch(&mut self) {
+        let chosen_path = self
+            .settings
+            .file_dialog()
+            .add_filter("json", &["json"])
+            .save_file();
+
+        let Some(path) = chosen_path else { return };
+
+        let state = self.serializable_state();
+        let saved = File::create(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|file| Ok(serde_json::to_writer(file, &state)?));
+        if let Err(e) = saved {
+            notify::error(&format!("Failed to save {}", path.display()), e);
+        }
+    }
 
-        // create inputs that don't exist but are in `inputs` arg
-        let ui_inputs = self.user_state.node_ui_inputs.get_mut(&node_id).unwrap();
-        for input in inputs {
-            if !curr_inputs.iter().any(|(name, _)| name == &input.name) {
-                let data_type = graph::SynthDataType::from_value_kind(input.kind);
+    fn load_patch(&mut self) {
+        let chosen_path = self
+            .settings
+            .file_dialog()
+            .add_filter("json", &["json"])
+            .pick_file();
 
-                self.state.graph.add_input_param(
-                    node_id,
-                    input.name.clone(),
-                    data_type,
-                    graph::SynthValueType::default_with_type(data_type),
-                    InputParamKind::ConnectionOrConstant,
-                    true,
-                );
+        let Some(path) = chosen_path else { return };
+
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                notify::error(&format!("Failed to open {}", path.display()), e);
+                return;
             }
+        };
 
-            if let Some(default_value) = input.default_value {
-                ui_inputs.insert(input.name, default_value);
+        let state = match serde_json::from_reader::<_, graph::PatchState>(file) {
+            Ok(state) => state,
+            Err(e) => {
+                notify::error(&format!("Failed to load {}", path.display()), e);
+                return;
             }
+        };
+
+        let loaded = match Self::new(Some(state)) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                notify::error(&format!("Failed to load {}", path.display()), e);
+                return;
+            }
+        };
+
+        self.replace(loaded);
+    }
+
+    fn load_factory(&mut self, patch: &factory::Patch) {
+        let loaded =
+            factory::build(patch, &self.all_nodes).and_then(|state| Self::new(Some(state)));
+        match loaded {
+            Ok(loaded) => self.replace(loaded),
+            Err(e) => notify::error(&format!("Failed to load {}", patch.name), e),
         }
+    }
 
+    // Swaps in a loaded patch, keeping what belongs to the editor
+    fn replace(&mut self, loaded: Self) {
+        let audio = std::mem::take(&mut self.audio);
+        let settings = std::mem::take(&mut self.settings);
+        let settings_dialog = std::mem::take(&mut self.settings_dialog);
+        let library = std::mem::take(&mut self.library);
+        let history = std::mem::take(&mut self.auto_connect.history);
+        let commands = std::mem::take(&mut self.commands);
+        let finder = std::mem::take(&mut self.finder);
+        let _ = std::mem::replace(self, loaded);
+        self.audio = audio;
+        self.settings = settings;
+        self.settings_dialog = settings_dialog;
+        self.library = library;
+        self.auto_connect.history = history;
+        self.commands = commands;
+        self.finder = finder;
+        if self.audio.settings != audio::AudioSettings::default() {
+            self.apply_audio();
+        }
+    }
+
+    // Top left corner of the visible graph, in graph coordinates
+    fn note_pos(&self) -> egui::Pos2 {
+        egui::pos2(48.0, 48.0) - self.state.pan_zoom.pan
+    }
+
+    fn selected_ui(&self) -> impl Iterator<Item = &std::cell::RefCell<graph::NodeUiState>> {
         self.state
-            .graph
-            .nodes
-            .get_mut(node_id)
-            .unwrap()
-            .inputs
-            .sort_by_key(|(name, _id)| {
-                input_names
-                    .iter()
-                    .enumerate()
-                    .find(|(_, source_name)| *source_name == name)
-                    .unwrap()
-                    .0
-            });
+            .selected_nodes
+            .iter()
+            .filter_map(|node_id| self.state.graph.nodes.get(*node_id))
+            .map(|node| &node.user_data.ui)
+    }
 
-        // recalculate runtime inputs
-        let mut rt_inputs = Vec::new();
-        for in_id in self.state.graph.nodes.get(node_id).unwrap().input_ids() {
-            let src = self
-                .state
-                .graph
-                .connection(in_id)
-                .map(|out| (self.state.graph.get_output(out), out))
-                .map(|(out_params, out)| (out_params.node, out))
-                .and_then(|(node_id, out)| {
-                    self.remote
-                        .id_to_index(node_id)
-                        .map(|idx| (idx, node_id, out))
-                });
+    fn run(&mut self, ctx: &egui::Context, command: Command) {
+        match command {
+            Command::Palette => self.commands.open_palette(),
+            Command::AddNode => {
+                let pos = self
+                    .editor_rect
+                    .map(|rect| rect.center())
+                    .unwrap_or_default();
+                self.finder.open(pos);
+            }
+            Command::Save => self.save_patch(),
+            Command::Load => self.load_patch(),
+            Command::OpenMidi => self.load_midi(),
+            Command::DeleteSelection => {
+                self.pending_responses.extend(
+                    self.state
+                        .selected_nodes
+                        .iter()
+                        .copied()
+                        .map(NodeResponse::DeleteNodeUi),
+                );
+            }
+            Command::BypassSelection => {
+                for ui in self.selected_ui() {
+                    let mut ui = ui.borrow_mut();
+                    ui.bypass = !ui.bypass;
+                }
+            }
+            Command::SoloSelection => {
+                for ui in self.selected_ui() {
+                    let mut ui = ui.borrow_mut();
+                    ui.solo = !ui.solo;
+                }
+            }
+            Command::MutateSelection => {
+                self.mutator
+                    .mutate(&self.user_state, &self.state.selected_nodes);
+                self.mutator.open = true;
+            }
+            Command::Freeze => self.freezer.open = !self.freezer.open,
+            Command::Arrange => layout::arrange(&mut self.state),
+            Command::AlignLeft => layout::align_left(&mut self.state),
+            Command::AlignTop => layout::align_top(&mut self.state),
+            Command::DistributeHorizontally => layout::distribute(&mut self.state, true),
+            Command::DistributeVertically => layout::distribute(&mut self.state, false),
+            Command::FitAll => {
+                if let Some(editor_rect) = self.editor_rect {
+                    minimap::fit_all(&mut self.state, editor_rect);
+                }
+            }
+            Command::FitSelection => {
+                if let Some(editor_rect) = self.editor_rect {
+                    minimap::fit_selection(&mut self.state, editor_rect);
+                }
+            }
+            Command::StickyNote => {
+                let pos = self.note_pos();
+                self.user_state.notes.add_sticky(pos);
+            }
+            Command::Frame => {
+                let pos = self.note_pos();
+                self.user_state.notes.add_frame(&self.state, pos);
+            }
+            Command::Perform => self.perform.enabled = !self.perform.enabled,
+            Command::Table => self.table.open = !self.table.open,
+            Command::Inspector => self.inspector.open = !self.inspector.open,
+            Command::Links => self.links.open = !self.links.open,
+            Command::Snapshots => self.snapshots.open = !self.snapshots.open,
+            Command::Audio => self.audio.open = !self.audio.open,
+            Command::Settings => self.settings_dialog.open = !self.settings_dialog.open,
+            Command::ZoomIn => self.settings.zoom(ctx, 1),
+            Command::ZoomOut => self.settings.zoom(ctx, -1),
+            Command::ResetZoom => self.settings.reset_zoom(ctx),
+            Command::Keyboard => self.keyboard.open = !self.keyboard.open,
+            Command::Shortcuts => self.commands.shortcuts_open = !self.commands.shortcuts_open,
+            Command::Record => self.capture.toggle(&self.state.graph, &mut self.remote),
+            Command::Mute => {
+                let master = &mut self.user_state.master;
+                master.mute = !master.mute;
+                self.remote.set_sink_gain(SinkId::PLAYBACK, master.gain());
+            }
+            Command::Panic => self.remote.panic(),
+        }
+    }
 
-            let src = src.map(|(idx, node_id, out_id)| {
-                let port = self
-                    .state
-                    .graph
-                    .nodes
-                    .get(node_id)
-                    .unwrap()
-                    .outputs
-                    .iter()
-                    .enumerate()
-                    .find(|(_i, (_name, id))| out_id == *id)
-                    .unwrap()
-                    .0;
-
-                OutputPort::new(idx, port)
-            });
+    // Hands XY scopes the samples of their partners and keeps the partners
+    // recorded while they're plotted against
+    fn feed_xy_partners(&mut self, recorded: &HashMap<(NodeId, String), (Vec<Value>, usize)>) {
+        let graph = &self.state.graph;
+        let mut partners = Vec::new();
+        for (_, node) in &graph.nodes {
+            for state in node.user_data.ui.borrow_mut().out_states.values_mut() {
+                let Some(scope) = &mut state.scope else {
+                    continue;
+                };
+                let Some(partner) = scope.partner().cloned() else {
+                    continue;
+                };
 
-            rt_inputs.push(src);
+                if let Some((samples, step)) = recorded.get(&partner) {
+                    scope.feed_partner(samples, *step);
+                }
+                if let Ok(port) = graph.get_port(partner.0, &partner.1) {
+                    partners.push((partner.0, port));
+                }
+            }
+        }
+
+        for (node_id, port) in &partners {
+            let known = self.remote.id_to_index(*node_id).is_some();
+            if known && !self.remote.recording(*node_id, *port) {
+                self.remote.record(*node_id, *port);
+            }
+        }
+
+        for (node_id, port) in std::mem::replace(&mut self.xy_partners, partners.clone()) {
+            if partners.contains(&(node_id, port)) {
+                continue;
+            }
+
+            // Scopes and meters of the port itself still need it
+            let Some(node) = graph.nodes.get(node_id) else {
+                continue;
+            };
+            let own = node
+                .outputs
+                .get(port)
+                .and_then(|(name, _)| {
+                    node.user_data
+                        .ui
+                        .borrow()
+                        .out_states
+                        .get(name)
+                        .map(OutputState::recorded)
+                })
+                .unwrap_or(false);
+            if !own && self.remote.recording(node_id, port) {
+                self.remote.stop_recording(node_id, port);
+            }
+        }
+    }
+
+    // Recordings send as much detail as their scopes can show, the partners
+    // of XY scopes every sample
+    fn set_record_decimation(&mut self) {
+        let graph = &self.state.graph;
+        for (node_id, node) in &graph.nodes {
+            for (name, state) in node.user_data.ui.borrow().out_states.iter() {
+                let step = match (&state.scope, &state.meter) {
+                    (Some(scope), _) => scope.step(),
+                    (None, Some(_)) => Meter::STEP,
+                    (None, None) => continue,
+                };
+                let Ok(port) = graph.get_port(node_id, name) else {
+                    continue;
+                };
+
+                let step = if self.xy_partners.contains(&(node_id, port)) {
+                    1
+                } else {
+                    step
+                };
+                self.remote.set_record_decimation(node_id, port, step);
+            }
+        }
+    }
+
+    fn show_editor(&mut self, ctx: &egui::Context) {
+        let prepend_responses = std::mem::take(&mut self.pending_responses);
+
+        // Node new ones are offered to be connected to
+        let selected = match self.state.selected_nodes.as_slice() {
+            [id] => Some(*id),
+            _ => None,
+        };
+        let dragging = self.state.connection_in_progress;
+
+        let (editor_rect, graph_response) = egui::CentralPanel::default()
+            .show(ctx, |ui| {
+                // Notes go first, so the nodes are drawn over them
+                let editor_rect = ui.max_rect();
+                let origin = editor_rect.min + self.state.pan_zoom.pan;
+                self.user_state.notes.paint(ui.painter(), origin);
+
+                let response = self.state.draw_graph_editor(
+                    ui,
+                    &self.all_nodes,
+                    &mut self.user_state,
+                    prepend_responses,
+                );
+
+                (editor_rect, response)
+            })
+            .inner;
+        let origin = editor_rect.min + self.state.pan_zoom.pan;
+        self.user_state.notes.show(ctx, &mut self.state, origin);
+        // Dragged nodes move freely and land on the grid once released
+        if self.user_state.snap_to_grid && !ctx.input(|state| state.pointer.any_down()) {
+            layout::snap(&mut self.state);
+        }
+        self.editor_rect = Some(editor_rect);
+        tour::mark(ctx, tour::Target::Canvas, editor_rect);
+
+        minimap::show(ctx, &mut self.state, editor_rect);
+
+        // Right clicks open this dialog instead of the editor's own finder
+        if self.state.node_finder.take().is_some() {
+            let pos = ctx.input(|state| state.pointer.interact_pos());
+            self.finder.open(pos.unwrap_or(editor_rect.center()));
+        }
+        // A connection dropped away from the ports offers nodes to continue it
+        if let Some((_, port)) = dragging {
+            let connected = graph_response
+                .node_responses
+                .iter()
+                .any(|response| matches!(response, NodeResponse::ConnectEventEnded { .. }));
+            let graph = &self.state.graph;
+            let data_type = match port {
+                AnyParameterId::Output(output) => graph.try_get_output(output).map(|p| p.typ),
+                AnyParameterId::Input(input) => graph.try_get_input(input).map(|p| p.typ),
+            };
+            let pos = ctx.input(|state| state.pointer.interact_pos());
+            if let (None, false, Some(data_type), Some(pos)) =
+                (self.state.connection_in_progress, connected, data_type, pos)
+            {
+                self.finder.open_from(pos, port, data_type);
+            }
+        }
+        if let Some(placed) = self.finder.show(
+            ctx,
+            &mut self.state,
+            &mut self.user_state,
+            &self.all_nodes,
+            editor_rect,
+        ) {
+            self.pending_responses
+                .push(NodeResponse::CreatedNode(placed.node_id));
+            self.quick_connection = placed.connection;
+        }
+        for node_response in &graph_response.node_responses {
+            match node_response {
+                NodeResponse::CreatedNode(id) => {
+                    self.auto_connect
+                        .node_created(&self.state.graph, selected, *id);
+                }
+                NodeResponse::ConnectEventEnded { output, input } => {
+                    self.auto_connect
+                        .history
+                        .learn(&self.state.graph, *output, *input);
+                }
+                _ => {}
+            }
+        }
+
+        let mut model = GraphModel::new(
+            &mut self.state.graph,
+            &mut self.user_state,
+            &mut self.remote,
+        );
+        for node_response in graph_response.node_responses {
+            model.handle(node_response);
+        }
+
+        if let Some((output, input)) = self.quick_connection {
+            let graph = &self.state.graph;
+            // Whether the runtime has both ends, None once one got removed
+            let known = graph
+                .try_get_output(output)
+                .zip(graph.try_get_input(input))
+                .map(|(output, input)| [output.node, input.node])
+                .map(|nodes| {
+                    nodes
+                        .iter()
+                        .all(|id| self.remote.id_to_index(*id).is_some())
+                });
+            match known {
+                Some(true) => {
+                    self.state.graph.add_connection(output, input);
+                    self.auto_connect
+                        .history
+                        .learn(&self.state.graph, output, input);
+                    GraphModel::new(
+                        &mut self.state.graph,
+                        &mut self.user_state,
+                        &mut self.remote,
+                    )
+                    .handle(NodeResponse::ConnectEventEnded { output, input });
+                    self.quick_connection = None;
+                }
+                Some(false) => {}
+                None => self.quick_connection = None,
+            }
+        }
+
+        if let Some((output, input)) = self.auto_connect.show(ctx, &self.state.graph) {
+            self.state.graph.add_connection(output, input);
+            self.auto_connect
+                .history
+                .learn(&self.state.graph, output, input);
+            GraphModel::new(
+                &mut self.state.graph,
+                &mut self.user_state,
+                &mut self.remote,
+            )
+            .handle(NodeResponse::ConnectEventEnded { output, input });
         }
-        self.remote.set_inputs(node_id, rt_inputs);
     }
 
     fn load_midi(&mut self) {
-        if let Some(path) = rfd::FileDialog::new().pick_file() {
+        if let Some(path) = self.settings.file_dialog().pick_file() {
             let new = match SmfSourceNew::new(&path) {
                 Ok(new) => new,
                 Err(e) => {
-                    println!("{}", e);
+                    notify::error(&format!("Failed to open {}", path.display()), e);
                     return;
                 }
             };
@@ -253,198 +748,307 @@ impl SynthApp {
 impl eframe::App for SynthApp {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         eframe::set_value(storage, "synth-app", &self.serializable_state());
+        eframe::set_value(storage, "connect-history", &self.auto_connect.history);
+        eframe::set_value(storage, "node-finder", &self.finder.history);
+
+        self.settings.audio = self.audio.settings.clone();
+        self.settings.keymap = self.commands.keymap.clone();
+        if let Err(e) = self.settings.save() {
+            notify::error("Failed to save the settings", e);
+        }
         println!("state saved");
     }
 
+    fn auto_save_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f32(self.settings.autosave_secs.max(1.0))
+    }
+
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.capture.stop(&mut self.remote);
         self.remote.shutdown();
+        self.capture.wait();
     }
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Both can also be changed from the top bar and by zoom shortcuts
+        self.settings.theme = ctx.options(|options| options.theme_preference);
+        self.settings.ui_scale = ctx.zoom_factor();
+
+        self.keyboard.handle_keys(ctx);
+        for command in self.commands.show(ctx, self.perform.enabled) {
+            self.run(ctx, command);
+        }
+
         egui::TopBottomPanel::top("top").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 egui::widgets::global_theme_preference_switch(ui);
 
-                egui::menu::menu_button(ui, "File", |ui| {
-                    if ui.button("Save").clicked() {
-                        let chosen_path =
-                            FileDialog::new().add_filter("json", &["json"]).save_file();
+                if !self.perform.enabled {
+                    let file = egui::menu::menu_button(ui, "File", |ui| {
+                        if ui.button("Save").clicked() {
+                            self.save_patch();
+                        }
+                        if ui.button("Load").clicked() {
+                            self.load_patch();
+                        }
+                        ui.menu_button("Factory", |ui| {
+                            for patch in factory::PATCHES {
+                                if ui
+                                    .button(patch.name)
+                                    .on_hover_text(patch.description)
+                                    .clicked()
+                                {
+                                    self.load_factory(patch);
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    });
+                    tour::mark(ctx, tour::Target::File, file.response.rect);
 
-                        let Some(path) = chosen_path else { return };
+                    if ui.button("Open Midi").clicked() {
+                        self.load_midi();
+                    }
 
-                        let state = self.serializable_state();
-                        match File::create(&path) {
-                            Ok(file) => serde_json::to_writer(file, &state).unwrap(),
-                            Err(e) => println!("Failed to open file {}: {}", path.display(), e),
+                    ui.menu_button("Layout", |ui| {
+                        ui.label("Selected nodes, or all without a selection");
+                        if ui
+                            .button("Arrange")
+                            .on_hover_text("Left to right by signal flow")
+                            .clicked()
+                        {
+                            layout::arrange(&mut self.state);
+                            ui.close_menu();
                         }
-                    }
+                        if ui.button("Align left").clicked() {
+                            layout::align_left(&mut self.state);
+                            ui.close_menu();
+                        }
+                        if ui.button("Align top").clicked() {
+                            layout::align_top(&mut self.state);
+                            ui.close_menu();
+                        }
+                        if ui.button("Distribute horizontally").clicked() {
+                            layout::distribute(&mut self.state, true);
+                            ui.close_menu();
+                        }
+                        if ui.button("Distribute vertically").clicked() {
+                            layout::distribute(&mut self.state, false);
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        if let Some(editor_rect) = self.editor_rect {
+                            if ui.button("Fit all (F)").clicked() {
+                                minimap::fit_all(&mut self.state, editor_rect);
+                                ui.close_menu();
+                            }
+                            if ui.button("Fit selection (Shift+F)").clicked() {
+                                minimap::fit_selection(&mut self.state, editor_rect);
+                                ui.close_menu();
+                            }
+                        }
+                        ui.checkbox(&mut self.user_state.snap_to_grid, "Snap to grid");
+                    });
 
-                    if ui.button("Load").clicked() {
-                        let chosen_path =
-                            FileDialog::new().add_filter("json", &["json"]).pick_file();
+                    ui.menu_button("Notes", |ui| {
+                        let pos = self.note_pos();
+                        if ui.button("Sticky note").clicked() {
+                            self.user_state.notes.add_sticky(pos);
+                            ui.close_menu();
+                        }
+                        if ui
+                            .button("Frame")
+                            .on_hover_text("Around the selected nodes, moving with them")
+                            .clicked()
+                        {
+                            self.user_state.notes.add_frame(&self.state, pos);
+                            ui.close_menu();
+                        }
+                    });
 
-                        let Some(path) = chosen_path else { return };
+                    ui.toggle_value(&mut self.table.open, "Table");
+                    ui.toggle_value(&mut self.inspector.open, "Inspect");
+                    ui.toggle_value(&mut self.links.open, "Links");
+                    ui.toggle_value(&mut self.snapshots.open, "A/B");
+                    ui.toggle_value(&mut self.mutator.open, "Mutate");
+                    ui.toggle_value(&mut self.freezer.open, "Freeze");
+                    ui.toggle_value(&mut self.audio.open, "Audio");
+                    ui.toggle_value(&mut self.commands.shortcuts_open, "Keys");
+                    ui.toggle_value(&mut self.settings_dialog.open, "Settings");
+                }
 
-                        let file = match File::open(&path) {
-                            Ok(file) => file,
-                            Err(e) => {
-                                println!("Failed to open file {}: {}", path.display(), e);
-                                return;
-                            }
-                        };
-
-                        let state = match serde_json::from_reader::<
-                            _,
-                            (
-                                (Runtime, Vec<(NodeId, u64)>),
-                                SynthEditorState,
-                                SynthGraphState,
-                            ),
-                        >(file)
-                        {
-                            Ok(state) => state,
-                            Err(e) => {
-                                println!("Failed to deserialize state {}: {}", path.display(), e);
-                                return;
-                            }
-                        };
+                ui.toggle_value(&mut self.perform.enabled, "Perform")
+                    .on_hover_text("Lock the patch, only parameters can be changed");
+                ui.toggle_value(&mut self.keyboard.open, "🎹")
+                    .on_hover_text("Virtual keyboard, played through Midi In nodes");
 
-                        let _ = std::mem::replace(self, Self::new(Some(state)));
-                    }
-                });
+                ui.separator();
+
+                let tuning = &mut self.user_state.tuning;
+                let a4_changed = ui
+                    .add(
+                        egui::DragValue::new(&mut tuning.a4)
+                            .range(Tuning::A4_RANGE)
+                            .speed(0.1)
+                            .prefix("A4: ")
+                            .suffix(" Hz"),
+                    )
+                    .changed();
+                let transpose_changed = ui
+                    .add(
+                        egui::DragValue::new(&mut tuning.transpose)
+                            .range(-24..=24)
+                            .prefix("transpose: ")
+                            .suffix(" st"),
+                    )
+                    .changed();
+                if a4_changed || transpose_changed {
+                    tuning.apply();
+                }
+
+                ui.separator();
+
+                let mut safety = !self.user_state.bypass_safety;
+                if ui
+                    .checkbox(&mut safety, "Limiter")
+                    .on_hover_text("DC blocker and soft limiter on playback")
+                    .changed()
+                {
+                    self.user_state.bypass_safety = !safety;
+                    self.remote.output_guard().set_bypass(!safety);
+                }
+
+                if self.remote.output_guard().take_clipped() {
+                    self.last_clip = Some(Instant::now());
+                }
+                let clipping = self
+                    .last_clip
+                    .is_some_and(|last| last.elapsed().as_secs_f32() < 1.0);
+                let clip_color = if clipping {
+                    egui::Color32::RED
+                } else {
+                    ui.visuals().weak_text_color()
+                };
+                ui.label(egui::RichText::new("CLIP").color(clip_color));
+
+                ui.separator();
 
-                if ui.button("Open Midi").clicked() {
-                    self.load_midi();
+                let master = &mut self.user_state.master;
+                let before = *master;
+                ui.add(
+                    egui::Slider::new(&mut master.volume, 0.0..=2.0)
+                        .fixed_decimals(2)
+                        .text("Master"),
+                );
+                ui.toggle_value(&mut master.mute, "Mute");
+                if *master != before {
+                    self.remote.set_sink_gain(SinkId::PLAYBACK, master.gain());
+                }
+                if ui
+                    .button("Panic")
+                    .on_hover_text("Release all notes and clear delays and reverbs")
+                    .clicked()
+                {
+                    self.remote.panic();
                 }
 
+                ui.separator();
+
+                self.capture.show(ui, &self.state.graph, &mut self.remote);
+
+                ui.separator();
+
                 let fps = 1.0 / self.prev_frame.elapsed().as_secs_f32();
                 self.prev_frame = Instant::now();
                 ui.label(format!("fps: {fps:.2}"));
             });
         });
 
-        let mut prepend_responses = Vec::new();
+        self.table.show(ctx, &self.state.graph, &self.user_state);
+        self.inspector.show(
+            ctx,
+            &mut self.state,
+            &self.user_state,
+            &self.all_nodes,
+            &mut self.remote,
+            self.editor_rect,
+        );
+        self.links
+            .show(ctx, &self.state.graph, &mut self.user_state);
+        self.keyboard.show(ctx);
+        self.snapshots.show(ctx, &mut self.user_state);
+        self.mutator.show(
+            ctx,
+            &self.state.graph,
+            &self.user_state,
+            &self.state.selected_nodes,
+        );
+        self.settings_dialog.show(
+            ctx,
+            &mut self.settings,
+            &mut self.audio.open,
+            &mut self.commands.shortcuts_open,
+            &mut self.library.open,
+        );
+        self.library
+            .show(ctx, &mut self.settings.library, &self.all_nodes);
+        if self.all_nodes.library != self.settings.library {
+            self.all_nodes.library = self.settings.library.clone();
+        }
+        self.audio.output_rate = Some(self.remote.output_rate());
+        if let Some(settings) = self.audio.show(ctx) {
+            self.audio.settings = settings;
+            self.apply_audio();
+        }
 
-        if ctx.input(|state| state.key_pressed(egui::Key::Delete)) {
-            prepend_responses.extend(
-                self.state
-                    .selected_nodes
-                    .iter()
-                    .copied()
-                    .map(NodeResponse::DeleteNodeUi),
+        if self.perform.enabled {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                self.perform
+                    .show(ui, &self.state.graph, &mut self.user_state)
+            });
+        } else {
+            self.show_editor(ctx);
+            self.freezer.show(
+                ctx,
+                &mut self.state,
+                &mut self.user_state,
+                &mut self.remote,
+                &mut self.pending_responses,
             );
         }
+        self.user_state.links.sync(&self.user_state.node_ui_inputs);
 
-        let graph_response = egui::CentralPanel::default()
-            .show(ctx, |ui| {
-                self.state.draw_graph_editor(
-                    ui,
-                    &self.all_nodes,
-                    &mut self.user_state,
-                    prepend_responses,
-                )
-            })
-            .inner;
-        for node_response in graph_response.node_responses {
-            match node_response {
-                NodeResponse::CreatedNode(id) => {
-                    println!("create node {id:?}");
-                    let node = self.user_state.nodes.remove(&id).unwrap();
-                    self.remote.insert(id, node);
-                }
-                NodeResponse::DeleteNodeFull { node_id, .. } => {
-                    println!("remove node {node_id:?}");
-                    self.remote.remove(node_id);
-                }
-                NodeResponse::DisconnectEvent { input, .. } => {
-                    let Some(in_param) = self.state.graph.try_get_input(input) else {
-                        continue;
-                    };
-                    let in_node_id = in_param.node;
-                    let in_node = self.state.graph.nodes.get(in_node_id).unwrap();
-                    let in_idx = in_node
-                        .input_ids()
-                        .enumerate()
-                        .find(|(_i, id)| id == &in_param.id)
-                        .unwrap()
-                        .0;
-
-                    println!("disconnect from {in_node_id:?}:{in_idx:?}");
-                    self.remote.disconnect(in_node_id, in_idx);
-                }
-                NodeResponse::ConnectEventEnded { output, input } => {
-                    let out_node_id = self.state.graph.get_output(output).node;
-
-                    let in_param = self.state.graph.get_input(input);
-                    let in_node_id = in_param.node;
-                    let in_node = self.state.graph.nodes.get(in_node_id).unwrap();
-                    let in_idx = in_node
-                        .input_ids()
-                        .enumerate()
-                        .find(|(_i, id)| id == &in_param.id)
-                        .unwrap()
-                        .0;
-
-                    let out_node = self.state.graph.nodes.get(out_node_id).unwrap();
-                    let out_port = out_node
-                        .output_ids()
-                        .enumerate()
-                        .find(|(_i, id)| output == *id)
-                        .unwrap()
-                        .0;
-
-                    println!("connect {out_node_id:?}:{out_port} to {in_node_id:?}:{in_idx}");
-                    self.remote
-                        .connect(out_node_id, out_port, in_node_id, in_idx);
-                }
-                NodeResponse::User(graph::SynthNodeResponse::SetRtPlayback(id, port)) => {
-                    println!("set real-time playback {id:?}:{port}");
-                    self.user_state.rt_playback = Some((id, port));
-                    self.remote.play(Some((id, port)));
-                }
-                NodeResponse::User(graph::SynthNodeResponse::ClearRtPlayback) => {
-                    println!("disable real-time playback");
-                    self.user_state.rt_playback = None;
-                    self.remote.play(None);
-                }
-                NodeResponse::User(graph::SynthNodeResponse::StartRecording(node, port)) => {
-                    println!("record {node:?}:{port}");
-                    self.remote.record(node, port);
-                }
-                NodeResponse::User(graph::SynthNodeResponse::StopRecording(node, port)) => {
-                    println!("record {node:?}:{port}");
-                    self.remote.stop_recording(node, port);
-                }
-                NodeResponse::User(graph::SynthNodeResponse::UpdateInputType(
-                    node,
-                    param_name,
-                    new_kind,
-                )) => {
-                    let input_id = self
-                        .state
-                        .graph
-                        .nodes
-                        .get_mut(node)
-                        .unwrap()
-                        .inputs
-                        .iter()
-                        .find(|(input_name, _input_id)| *input_name == param_name)
-                        .unwrap()
-                        .1;
-
-                    self.state.graph.update_input_param(
-                        input_id,
-                        None,
-                        Some(SynthDataType::from_value_kind(new_kind)),
-                        None,
-                        None,
-                        None,
-                    );
-                }
-                _ => {}
-            }
+        let outputs: Vec<_> = self
+            .state
+            .graph
+            .nodes
+            .iter()
+            .filter(|(_, node)| node.label == "Main Out")
+            .map(|(node_id, _)| node_id)
+            .chain(self.user_state.rt_playback.map(|(node_id, _)| node_id))
+            .collect();
+        let muted = solo::muted_inputs(&self.state.graph, &outputs);
+        if muted != self.muted {
+            self.remote.set_muted(&muted);
+            self.muted = muted;
         }
 
+        let mut bypassed: Vec<_> = self
+            .state
+            .graph
+            .nodes
+            .iter()
+            .filter(|(_, node)| node.user_data.ui.borrow().bypass)
+            .map(|(node_id, _)| node_id)
+            .collect();
+        bypassed.sort();
+        if bypassed != self.bypassed {
+            self.remote.set_bypassed(&bypassed);
+            self.bypassed = bypassed;
+        }
+        self.notifications.show(ctx);
+        tour::show(ctx, &mut self.settings.tour, self.perform.enabled);
+
         for (idx, evs) in self.remote.events() {
             let Some(node_id) = self.remote.index_to_id(idx) else {
                 continue;
@@ -453,13 +1057,36 @@ impl eframe::App for SynthApp {
             for ev in evs {
                 match ev {
                     NodeEvent::RecalcInputs(inputs) => {
-                        self.recalc_inputs(node_id, inputs);
+                        GraphModel::new(
+                            &mut self.state.graph,
+                            &mut self.user_state,
+                            &mut self.remote,
+                        )
+                        .recalc_inputs(node_id, inputs);
+                    }
+                    NodeEvent::RecalcOutputs(outputs) => {
+                        GraphModel::new(
+                            &mut self.state.graph,
+                            &mut self.user_state,
+                            &mut self.remote,
+                        )
+                        .recalc_outputs(node_id, outputs);
                     }
                 }
             }
         }
 
-        for (out_port, samples) in self.remote.recordings() {
+        for (idx, samples) in self.remote.quarantined() {
+            if let Some(node_id) = self.remote.index_to_id(idx) {
+                *self.user_state.quarantined.entry(node_id).or_default() += samples;
+            }
+        }
+        if let Some(names) = self.remote.bus_names() {
+            self.user_state.ctx.bus_names = names;
+        }
+
+        let mut recorded = HashMap::new();
+        for (out_port, samples, step) in self.remote.recordings() {
             let Some(node_id) = self.remote.index_to_id(out_port.node) else {
                 continue;
             };
@@ -472,13 +1099,21 @@ impl eframe::App for SynthApp {
                 continue;
             };
 
-            if let Some(OutputState {
-                scope: Some(scope), ..
-            }) = node.user_data.out_states.borrow_mut().get_mut(name)
+            if let Some(OutputState { scope, meter, .. }) =
+                node.user_data.ui.borrow_mut().out_states.get_mut(name)
             {
-                scope.feed(samples.clone());
+                if let Some(meter) = meter {
+                    meter.feed(&samples, step);
+                }
+                if let Some(scope) = scope {
+                    scope.feed(samples.clone(), step);
+                }
             }
+
+            recorded.insert((node_id, name.clone()), (samples, step));
         }
+        self.feed_xy_partners(&recorded);
+        self.set_record_decimation();
 
         self.user_state.ctx.update_jack();
 
@@ -0,0 +1,94 @@
+use eframe::egui::{self, Color32, Pos2, Rect, Vec2};
+use egui_graph_edit::NodeId;
+
+use crate::{graph::SynthEditorState, layout::NODE_SIZE};
+
+const SIZE: Vec2 = Vec2::new(200.0, 140.0);
+
+// Area covered by the nodes, in graph coordinates
+fn bounds(editor: &SynthEditorState, nodes: impl Iterator<Item = NodeId>) -> Option<Rect> {
+    nodes
+        .filter_map(|node_id| editor.node_positions.get(node_id))
+        .map(|pos| Rect::from_min_size(*pos, NODE_SIZE))
+        .reduce(|a, b| a.union(b))
+}
+
+// Part of the graph visible in `editor_rect`, in graph coordinates
+fn viewport(editor: &SynthEditorState, editor_rect: Rect) -> Rect {
+    Rect::from_min_size((-editor.pan_zoom.pan).to_pos2(), editor_rect.size())
+}
+
+fn center_on(editor: &mut SynthEditorState, editor_rect: Rect, center: Pos2) {
+    editor.pan_zoom.pan = editor_rect.size() / 2.0 - center.to_vec2();
+}
+
+/// Centers the view on every node.
+pub fn fit_all(editor: &mut SynthEditorState, editor_rect: Rect) {
+    if let Some(rect) = bounds(editor, editor.graph.nodes.keys()) {
+        center_on(editor, editor_rect, rect.center());
+    }
+}
+
+/// Centers the view on the selected nodes.
+pub fn fit_selection(editor: &mut SynthEditorState, editor_rect: Rect) {
+    let selected = editor.selected_nodes.clone();
+    if let Some(rect) = bounds(editor, selected.into_iter()) {
+        center_on(editor, editor_rect, rect.center());
+    }
+}
+
+/// Selects the node and centers the view on it.
+pub fn focus(editor: &mut SynthEditorState, editor_rect: Rect, node_id: NodeId) {
+    if let Some(pos) = editor.node_positions.get(node_id).copied() {
+        let center = Rect::from_min_size(pos, NODE_SIZE).center();
+        editor.selected_nodes = vec![node_id];
+        center_on(editor, editor_rect, center);
+    }
+}
+
+/// Overview of the whole graph in the corner of the editor. Clicking or
+/// dragging in it moves the view there.
+pub fn show(ctx: &egui::Context, editor: &mut SynthEditorState, editor_rect: Rect) {
+    let view = viewport(editor, editor_rect);
+    let Some(nodes) = bounds(editor, editor.graph.nodes.keys()) else {
+        return;
+    };
+    let world = nodes.union(view).expand(40.0);
+    let scale = (SIZE.x / world.width()).min(SIZE.y / world.height());
+
+    egui::Area::new(egui::Id::new("minimap"))
+        .fixed_pos(editor_rect.left_bottom() + Vec2::new(8.0, -SIZE.y - 8.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            let (rect, response) = ui.allocate_exact_size(SIZE, egui::Sense::click_and_drag());
+            let to_map = |pos: Pos2| rect.min + (pos - world.min) * scale;
+            let map_rect = |r: Rect| Rect::from_min_max(to_map(r.min), to_map(r.max));
+
+            let painter = ui.painter_at(rect);
+            let visuals = ui.visuals();
+            painter.rect_filled(rect, 4.0, visuals.extreme_bg_color.gamma_multiply(0.9));
+            painter.rect_stroke(rect, 4.0, visuals.window_stroke);
+
+            for (node_id, pos) in editor.node_positions.iter() {
+                let color = if editor.selected_nodes.contains(&node_id) {
+                    visuals.selection.bg_fill
+                } else {
+                    visuals.widgets.inactive.bg_fill
+                };
+                painter.rect_filled(map_rect(Rect::from_min_size(*pos, NODE_SIZE)), 1.0, color);
+            }
+            painter.rect_stroke(
+                map_rect(view),
+                0.0,
+                egui::Stroke::new(1.0, Color32::from_rgb(0xe0, 0xa0, 0x40)),
+            );
+
+            if let Some(pointer) = response
+                .interact_pointer_pos()
+                .filter(|_| response.clicked() || response.dragged())
+            {
+                let center = world.min + (pointer - rect.min) / scale;
+                center_on(editor, editor_rect, center);
+            }
+        });
+}
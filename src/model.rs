@@ -0,0 +1,588 @@
+use egui_graph_edit::{InputId, InputParamKind, NodeId, NodeResponse, OutputId};
+use thunderdome::Index;
+
+use crate::{
+    compute::{
+        node::{Input, Node},
+        Output, OutputPort,
+    },
+    graph::{
+        SynthDataType, SynthGraph, SynthGraphState, SynthNodeData, SynthNodeResponse,
+        SynthValueType,
+    },
+};
+
+/// Runtime operations the editor needs, implemented by the runtime remote.
+pub trait RuntimeLink {
+    fn insert(&mut self, id: NodeId, node: Box<dyn Node>);
+    fn remove(&mut self, id: NodeId);
+    fn set_inputs(&mut self, dst: NodeId, inputs: Vec<Option<OutputPort>>);
+    fn connect(&mut self, src: NodeId, src_port: usize, dst: NodeId, dst_port: usize);
+    fn disconnect(&mut self, dst: NodeId, port: usize);
+    fn play(&mut self, id: Option<(NodeId, usize)>);
+    fn record(&mut self, id: NodeId, port: usize);
+    fn stop_recording(&mut self, id: NodeId, port: usize);
+    fn id_to_index(&self, id: NodeId) -> Option<Index>;
+}
+
+/// Keeps the editor graph and the runtime in agreement while the editor
+/// reports changes. Holds no egui state, so it can be driven without a
+/// window and any [`RuntimeLink`].
+pub struct GraphModel<'a, R> {
+    graph: &'a mut SynthGraph,
+    user_state: &'a mut SynthGraphState,
+    runtime: &'a mut R,
+    // Connections severed so far, the editor reports them before the
+    // removal of their node
+    disconnected: Vec<(InputId, OutputId)>,
+}
+
+impl<'a, R: RuntimeLink> GraphModel<'a, R> {
+    pub fn new(
+        graph: &'a mut SynthGraph,
+        user_state: &'a mut SynthGraphState,
+        runtime: &'a mut R,
+    ) -> Self {
+        GraphModel {
+            graph,
+            user_state,
+            runtime,
+            disconnected: Vec::new(),
+        }
+    }
+
+    pub fn handle(&mut self, response: NodeResponse<SynthNodeResponse, SynthNodeData>) {
+        match response {
+            NodeResponse::CreatedNode(id) => {
+                println!("create node {id:?}");
+                let node = self.user_state.nodes.remove(&id).unwrap();
+                self.runtime.insert(id, node);
+            }
+            NodeResponse::DeleteNodeFull { node_id, node } => {
+                println!("remove node {node_id:?}");
+                self.runtime.remove(node_id);
+
+                if self.user_state.rt_playback.map(|(id, _)| id) == Some(node_id) {
+                    let upstream = self.upstream_output(&node);
+                    println!("move real-time playback to {upstream:?}");
+                    self.user_state.rt_playback = upstream;
+                    self.runtime.play(upstream);
+                }
+            }
+            NodeResponse::DisconnectEvent { input, output } => {
+                self.disconnected.push((input, output));
+
+                let Some(in_param) = self.graph.try_get_input(input) else {
+                    return;
+                };
+                let in_node_id = in_param.node;
+                let in_node = self.graph.nodes.get(in_node_id).unwrap();
+                let in_idx = in_node
+                    .input_ids()
+                    .enumerate()
+                    .find(|(_i, id)| id == &in_param.id)
+                    .unwrap()
+                    .0;
+
+                println!("disconnect from {in_node_id:?}:{in_idx:?}");
+                self.runtime.disconnect(in_node_id, in_idx);
+            }
+            NodeResponse::ConnectEventEnded { output, input } => {
+                let out_node_id = self.graph.get_output(output).node;
+
+                let in_param = self.graph.get_input(input);
+                let in_node_id = in_param.node;
+                let in_node = self.graph.nodes.get(in_node_id).unwrap();
+                let in_idx = in_node
+                    .input_ids()
+                    .enumerate()
+                    .find(|(_i, id)| id == &in_param.id)
+                    .unwrap()
+                    .0;
+
+                let out_node = self.graph.nodes.get(out_node_id).unwrap();
+                let out_port = out_node
+                    .output_ids()
+                    .enumerate()
+                    .find(|(_i, id)| output == *id)
+                    .unwrap()
+                    .0;
+
+                println!("connect {out_node_id:?}:{out_port} to {in_node_id:?}:{in_idx}");
+                self.runtime
+                    .connect(out_node_id, out_port, in_node_id, in_idx);
+            }
+            NodeResponse::User(SynthNodeResponse::SetRtPlayback(id, port)) => {
+                println!("set real-time playback {id:?}:{port}");
+                self.user_state.rt_playback = Some((id, port));
+                self.runtime.play(Some((id, port)));
+            }
+            NodeResponse::User(SynthNodeResponse::ClearRtPlayback) => {
+                println!("disable real-time playback");
+                self.user_state.rt_playback = None;
+                self.runtime.play(None);
+            }
+            NodeResponse::User(SynthNodeResponse::StartRecording(node, port)) => {
+                println!("record {node:?}:{port}");
+                self.runtime.record(node, port);
+            }
+            NodeResponse::User(SynthNodeResponse::StopRecording(node, port)) => {
+                println!("record {node:?}:{port}");
+                self.runtime.stop_recording(node, port);
+            }
+            NodeResponse::User(SynthNodeResponse::UpdateInputType(node, param_name, new_kind)) => {
+                let input_id = self
+                    .graph
+                    .nodes
+                    .get_mut(node)
+                    .unwrap()
+                    .inputs
+                    .iter()
+                    .find(|(input_name, _input_id)| *input_name == param_name)
+                    .unwrap()
+                    .1;
+
+                self.graph.update_input_param(
+                    input_id,
+                    None,
+                    Some(SynthDataType::from_value_kind(new_kind)),
+                    None,
+                    None,
+                    None,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Connects `output` to `input` in the graph and the runtime, replacing
+    /// the connection `input` had.
+    pub fn connect(&mut self, output: OutputId, input: InputId) {
+        self.graph.remove_connection(input);
+        self.graph.add_connection(output, input);
+        self.handle(NodeResponse::ConnectEventEnded { output, input });
+    }
+
+    /// Removes the connection of `input` from the graph and the runtime.
+    pub fn disconnect(&mut self, input: InputId) {
+        if let Some(output) = self.graph.remove_connection(input) {
+            self.handle(NodeResponse::DisconnectEvent { input, output });
+        }
+    }
+
+    /// First float output which was connected to one of the inputs of a
+    /// removed node, if it still exists.
+    fn upstream_output(
+        &self,
+        node: &egui_graph_edit::Node<SynthNodeData>,
+    ) -> Option<(NodeId, usize)> {
+        node.inputs.iter().find_map(|(_name, input_id)| {
+            let (_, output_id) = self.disconnected.iter().find(|(id, _)| id == input_id)?;
+            let output = self.graph.try_get_output(*output_id)?;
+            if output.typ != SynthDataType::Float {
+                return None;
+            }
+
+            let port = self
+                .graph
+                .nodes
+                .get(output.node)?
+                .output_ids()
+                .position(|id| id == *output_id)?;
+
+            Some((output.node, port))
+        })
+    }
+
+    pub fn recalc_inputs(&mut self, node_id: NodeId, inputs: Vec<Input>) {
+        let curr_inputs = self.graph.nodes.get(node_id).unwrap().inputs.clone();
+        let input_names: Vec<_> = inputs.iter().map(|input| input.name.clone()).collect();
+
+        // remove inputs that exist but aren't in `inputs` arg, or changed
+        // their kind
+        let mut kept = Vec::new();
+        for (name, in_id) in &curr_inputs {
+            let typ = self.graph.get_input(*in_id).typ;
+            if inputs.iter().any(|input| {
+                input.name == *name && SynthDataType::from_value_kind(input.kind) == typ
+            }) {
+                kept.push(name.clone());
+            } else {
+                self.graph.remove_input_param(*in_id);
+            }
+        }
+
+        // create inputs that don't exist but are in `inputs` arg
+        let ui_inputs = self.user_state.node_ui_inputs.get_mut(&node_id).unwrap();
+        for input in inputs {
+            if !kept.contains(&input.name) {
+                let data_type = SynthDataType::from_value_kind(input.kind);
+
+                self.graph.add_input_param(
+                    node_id,
+                    input.name.clone(),
+                    data_type,
+                    SynthValueType::default_with_type(data_type),
+                    InputParamKind::ConnectionOrConstant,
+                    true,
+                );
+            }
+
+            if let Some(default_value) = input.default_value {
+                ui_inputs.insert(input.name, default_value);
+            }
+        }
+
+        self.graph
+            .nodes
+            .get_mut(node_id)
+            .unwrap()
+            .inputs
+            .sort_by_key(|(name, _id)| {
+                input_names
+                    .iter()
+                    .enumerate()
+                    .find(|(_, source_name)| *source_name == name)
+                    .unwrap()
+                    .0
+            });
+
+        self.sync_inputs(node_id);
+    }
+
+    /// Same as [`Self::recalc_inputs`] for the outputs, the ones recreated
+    /// for a new kind lose their connections.
+    pub fn recalc_outputs(&mut self, node_id: NodeId, outputs: Vec<Output>) {
+        let curr_outputs = self.graph.nodes.get(node_id).unwrap().outputs.clone();
+
+        let mut kept = Vec::new();
+        for (name, out_id) in &curr_outputs {
+            let typ = self.graph.get_output(*out_id).typ;
+            if outputs
+                .iter()
+                .any(|out| out.name == *name && SynthDataType::from_value_kind(out.kind) == typ)
+            {
+                kept.push(name.clone());
+                continue;
+            }
+
+            let severed: Vec<_> = self
+                .graph
+                .iter_connections()
+                .filter(|(_, output)| output == out_id)
+                .collect();
+            for (input, output) in severed {
+                self.handle(NodeResponse::DisconnectEvent { input, output });
+            }
+            self.graph.remove_output_param(*out_id);
+        }
+
+        for out in &outputs {
+            if !kept.contains(&out.name) {
+                let data_type = SynthDataType::from_value_kind(out.kind);
+                self.graph
+                    .add_output_param(node_id, out.name.clone(), data_type);
+            }
+        }
+
+        self.graph
+            .nodes
+            .get_mut(node_id)
+            .unwrap()
+            .outputs
+            .sort_by_key(|(name, _id)| outputs.iter().position(|out| out.name == *name).unwrap());
+
+        // Ports of the kept outputs may have moved
+        let mut downstream: Vec<NodeId> = self
+            .graph
+            .iter_connections()
+            .filter(|(_, output)| self.graph.get_output(*output).node == node_id)
+            .map(|(input, _)| self.graph.get_input(input).node)
+            .collect();
+        downstream.sort();
+        downstream.dedup();
+        for dst in downstream {
+            self.sync_inputs(dst);
+        }
+    }
+
+    // Sends the connections of all inputs of `node_id` to the runtime
+    fn sync_inputs(&mut self, node_id: NodeId) {
+        let mut rt_inputs = Vec::new();
+        for in_id in self.graph.nodes.get(node_id).unwrap().input_ids() {
+            let src = self
+                .graph
+                .connection(in_id)
+                .map(|out| (self.graph.get_output(out), out))
+                .map(|(out_params, out)| (out_params.node, out))
+                .and_then(|(node_id, out)| {
+                    self.runtime
+                        .id_to_index(node_id)
+                        .map(|idx| (idx, node_id, out))
+                });
+
+            let src = src.map(|(idx, node_id, out_id)| {
+                let port = self
+                    .graph
+                    .nodes
+                    .get(node_id)
+                    .unwrap()
+                    .outputs
+                    .iter()
+                    .enumerate()
+                    .find(|(_i, (_name, id))| out_id == *id)
+                    .unwrap()
+                    .0;
+
+                OutputPort::new(idx, port)
+            });
+
+            rt_inputs.push(src);
+        }
+        self.runtime.set_inputs(node_id, rt_inputs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use egui_graph_edit::NodeTemplateTrait;
+    use thunderdome::Arena;
+
+    use super::*;
+    use crate::{
+        compute::{node, ValueKind},
+        graph::{AllSynthNodeTemplates, SynthEditorState},
+    };
+
+    // Runtime keeping just the connections of each node, by input name
+    #[derive(Default)]
+    struct MockRuntime {
+        indices: Arena<NodeId>,
+        ids: HashMap<NodeId, Index>,
+        names: HashMap<NodeId, Vec<String>>,
+        inputs: HashMap<NodeId, Vec<Option<OutputPort>>>,
+        played: Option<(NodeId, usize)>,
+    }
+
+    impl MockRuntime {
+        // Node and port `dst` is fed from, by its own input name
+        fn source(&self, dst: NodeId, name: &str) -> Option<(NodeId, usize)> {
+            let port = self.names[&dst].iter().position(|n| n == name)?;
+            let src = self.inputs[&dst].get(port).copied().flatten()?;
+
+            Some((self.indices[src.node], src.port))
+        }
+    }
+
+    impl RuntimeLink for MockRuntime {
+        fn insert(&mut self, id: NodeId, node: Box<dyn Node>) {
+            let inputs = node.inputs();
+            self.ids.insert(id, self.indices.insert(id));
+            self.inputs.insert(id, vec![None; inputs.len()]);
+            self.names
+                .insert(id, inputs.into_iter().map(|input| input.name).collect());
+        }
+
+        fn remove(&mut self, id: NodeId) {
+            let idx = self.ids.remove(&id).unwrap();
+            self.indices.remove(idx);
+            self.inputs.remove(&id);
+            self.names.remove(&id);
+        }
+
+        fn set_inputs(&mut self, dst: NodeId, inputs: Vec<Option<OutputPort>>) {
+            self.inputs.insert(dst, inputs);
+        }
+
+        fn connect(&mut self, src: NodeId, src_port: usize, dst: NodeId, dst_port: usize) {
+            let src = OutputPort::new(self.ids[&src], src_port);
+            self.inputs.get_mut(&dst).unwrap()[dst_port] = Some(src);
+        }
+
+        fn disconnect(&mut self, dst: NodeId, port: usize) {
+            self.inputs.get_mut(&dst).unwrap()[port] = None;
+        }
+
+        fn play(&mut self, id: Option<(NodeId, usize)>) {
+            self.played = id;
+        }
+
+        fn record(&mut self, _id: NodeId, _port: usize) {}
+
+        fn stop_recording(&mut self, _id: NodeId, _port: usize) {}
+
+        fn id_to_index(&self, id: NodeId) -> Option<Index> {
+            self.ids.get(&id).copied()
+        }
+    }
+
+    struct Fixture {
+        templates: AllSynthNodeTemplates,
+        editor: SynthEditorState,
+        user_state: SynthGraphState,
+        runtime: MockRuntime,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            Fixture {
+                templates: AllSynthNodeTemplates::new(node::node_lists()),
+                editor: SynthEditorState::default(),
+                user_state: SynthGraphState::default(),
+                runtime: MockRuntime::default(),
+            }
+        }
+
+        fn model(&mut self) -> GraphModel<'_, MockRuntime> {
+            GraphModel::new(
+                &mut self.editor.graph,
+                &mut self.user_state,
+                &mut self.runtime,
+            )
+        }
+
+        // Adds the node named `name` in the node finder, like the editor
+        fn add(&mut self, name: &str) -> NodeId {
+            let user_state = &mut self.user_state;
+            let template = self
+                .templates
+                .unarranged()
+                .into_iter()
+                .find(|template| template.node_finder_label(user_state) == name)
+                .unwrap();
+            let label = template.node_graph_label(user_state);
+            let user_data = template.user_data(user_state);
+            let node_id = self
+                .editor
+                .graph
+                .add_node(label, user_data, |graph, node_id| {
+                    template.build_node(graph, user_state, node_id)
+                });
+
+            self.model().handle(NodeResponse::CreatedNode(node_id));
+
+            node_id
+        }
+
+        fn input(&self, node_id: NodeId, name: &str) -> InputId {
+            self.editor.graph.nodes[node_id].get_input(name).unwrap()
+        }
+
+        fn output(&self, node_id: NodeId, name: &str) -> OutputId {
+            self.editor.graph.nodes[node_id].get_output(name).unwrap()
+        }
+
+        // Removes a node, reporting it the way the editor does
+        fn delete(&mut self, node_id: NodeId) {
+            let (node, severed) = self.editor.graph.remove_node(node_id);
+            let mut model = self.model();
+            for (input, output) in severed {
+                model.handle(NodeResponse::DisconnectEvent { input, output });
+            }
+            model.handle(NodeResponse::DeleteNodeFull { node_id, node });
+        }
+    }
+
+    #[test]
+    fn connect_maps_ports() {
+        let mut fixture = Fixture::new();
+        let constant = fixture.add("Constant");
+        let adsr = fixture.add("Adsr");
+
+        let output = fixture.output(constant, "");
+        let input = fixture.input(adsr, "signal");
+        fixture.model().connect(output, input);
+
+        assert_eq!(fixture.runtime.source(adsr, "signal"), Some((constant, 0)));
+        assert_eq!(fixture.runtime.source(adsr, "gate"), None);
+    }
+
+    #[test]
+    fn disconnect_clears_port() {
+        let mut fixture = Fixture::new();
+        let constant = fixture.add("Constant");
+        let adsr = fixture.add("Adsr");
+
+        let output = fixture.output(constant, "");
+        let gate = fixture.input(adsr, "gate");
+        let signal = fixture.input(adsr, "signal");
+        fixture.model().connect(output, gate);
+        fixture.model().connect(output, signal);
+        fixture.model().disconnect(gate);
+
+        assert_eq!(fixture.runtime.source(adsr, "gate"), None);
+        assert_eq!(fixture.runtime.source(adsr, "signal"), Some((constant, 0)));
+    }
+
+    #[test]
+    fn delete_moves_playback_upstream() {
+        let mut fixture = Fixture::new();
+        let constant = fixture.add("Constant");
+        let gain = fixture.add("Gain");
+
+        let output = fixture.output(constant, "");
+        let input = fixture.input(gain, "sig 0");
+        fixture.model().connect(output, input);
+        fixture
+            .model()
+            .handle(NodeResponse::User(SynthNodeResponse::SetRtPlayback(
+                gain, 0,
+            )));
+        fixture.delete(gain);
+
+        assert_eq!(fixture.user_state.rt_playback, Some((constant, 0)));
+        assert_eq!(fixture.runtime.played, Some((constant, 0)));
+        assert!(fixture.runtime.id_to_index(gain).is_none());
+    }
+
+    #[test]
+    fn delete_without_upstream_stops_playback() {
+        let mut fixture = Fixture::new();
+        let gain = fixture.add("Gain");
+
+        fixture
+            .model()
+            .handle(NodeResponse::User(SynthNodeResponse::SetRtPlayback(
+                gain, 0,
+            )));
+        fixture.delete(gain);
+
+        assert_eq!(fixture.user_state.rt_playback, None);
+        assert_eq!(fixture.runtime.played, None);
+    }
+
+    #[test]
+    fn recalc_inputs_keeps_connections() {
+        let mut fixture = Fixture::new();
+        let constant = fixture.add("Constant");
+        let adsr = fixture.add("Adsr");
+
+        let output = fixture.output(constant, "");
+        let input = fixture.input(adsr, "signal");
+        fixture.model().connect(output, input);
+
+        // Moves the connected input first, drops the other and adds one
+        let inputs = vec![
+            Input::new("signal", ValueKind::Float),
+            Input::new("extra", ValueKind::Float),
+        ];
+        fixture.model().recalc_inputs(adsr, inputs);
+
+        let names: Vec<_> = fixture.editor.graph.nodes[adsr]
+            .inputs
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["signal", "extra"]);
+        assert_eq!(fixture.editor.graph.connection(input), Some(output));
+        assert_eq!(
+            fixture.runtime.inputs[&adsr],
+            [
+                Some(OutputPort::new(fixture.runtime.ids[&constant], 0)),
+                None
+            ]
+        );
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+
+use eframe::egui;
+use egui_graph_edit::NodeId;
+
+use crate::graph::{SynthGraph, SynthGraphState};
+
+// Mutations undone at most
+const HISTORY: usize = 32;
+
+/// Window randomizing the inputs of the selected nodes.
+#[derive(Debug)]
+pub struct Mutator {
+    pub open: bool,
+    /// Largest change of a value, in percent of it
+    amount: f32,
+    // Inputs left alone, by node and name
+    locked: HashSet<(NodeId, String)>,
+    // Values before each mutation, latest last
+    history: Vec<Vec<(NodeId, String, f32)>>,
+}
+
+impl Default for Mutator {
+    fn default() -> Self {
+        Mutator {
+            open: false,
+            amount: 20.0,
+            locked: HashSet::new(),
+            history: Vec::new(),
+        }
+    }
+}
+
+impl Mutator {
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        graph: &SynthGraph,
+        user_state: &SynthGraphState,
+        selected: &[NodeId],
+    ) {
+        let mut open = self.open;
+
+        egui::Window::new("Mutate")
+            .open(&mut open)
+            .default_width(280.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Amount");
+                    ui.add(
+                        egui::DragValue::new(&mut self.amount)
+                            .range(0.0..=100.0)
+                            .suffix(" %"),
+                    );
+                });
+
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        for node_id in selected {
+                            let Some(inputs) = user_state.node_ui_inputs.get(node_id) else {
+                                continue;
+                            };
+                            let mut names: Vec<_> = inputs
+                                .iter()
+                                .filter(|(_, input)| input.value().is_some())
+                                .map(|(name, _)| name.clone())
+                                .collect();
+                            if names.is_empty() {
+                                continue;
+                            }
+                            names.sort();
+
+                            let label = graph
+                                .nodes
+                                .get(*node_id)
+                                .map(|node| node.label.as_str())
+                                .unwrap_or("?");
+                            ui.label(label);
+                            ui.indent(node_id, |ui| {
+                                for name in names {
+                                    let key = (*node_id, name);
+                                    let mut locked = self.locked.contains(&key);
+                                    if ui.checkbox(&mut locked, format!("🔒 {}", key.1)).changed()
+                                    {
+                                        if locked {
+                                            self.locked.insert(key);
+                                        } else {
+                                            self.locked.remove(&key);
+                                        }
+                                    }
+                                }
+                            });
+                        }
+                    });
+
+                if selected.is_empty() {
+                    ui.weak("Select the nodes to mutate");
+                }
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!selected.is_empty(), egui::Button::new("Mutate"))
+                        .clicked()
+                    {
+                        self.mutate(user_state, selected);
+                    }
+                    if ui
+                        .add_enabled(!self.history.is_empty(), egui::Button::new("Undo"))
+                        .clicked()
+                    {
+                        self.undo(user_state);
+                    }
+                });
+            });
+
+        self.open = open;
+    }
+
+    /// Moves every unlocked input of the selected nodes by a random part of
+    /// the amount, inputs that aren't continuous are rounded.
+    pub fn mutate(&mut self, user_state: &SynthGraphState, selected: &[NodeId]) {
+        let mut previous = Vec::new();
+        for node_id in selected {
+            let Some(inputs) = user_state.node_ui_inputs.get(node_id) else {
+                continue;
+            };
+
+            for (name, input) in inputs {
+                if self.locked.contains(&(*node_id, name.clone())) {
+                    continue;
+                }
+                let Some(value) = input.value() else {
+                    continue;
+                };
+
+                let change = (rand::random::<f32>() * 2.0 - 1.0) * self.amount / 100.0;
+                let mutated = value + value * change;
+                input.set_value(if input.smoothed() {
+                    mutated
+                } else {
+                    mutated.round()
+                });
+                previous.push((*node_id, name.clone(), value));
+            }
+        }
+
+        if !previous.is_empty() {
+            self.history.push(previous);
+            let overflow = self.history.len().saturating_sub(HISTORY);
+            self.history.drain(..overflow);
+        }
+    }
+
+    /// Restores the values from before the last mutation.
+    pub fn undo(&mut self, user_state: &SynthGraphState) {
+        let Some(previous) = self.history.pop() else {
+            return;
+        };
+
+        for (node_id, name, value) in previous {
+            let input = user_state
+                .node_ui_inputs
+                .get(&node_id)
+                .and_then(|inputs| inputs.get(&name));
+            if let Some(input) = input {
+                input.set_value(value);
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use eframe::egui::{self, Color32, Pos2, Rect, Vec2};
+use egui_graph_edit::NodeId;
+use serde::{Deserialize, Serialize};
+
+use crate::{graph::SynthEditorState, layout::NODE_SIZE};
+
+const MARGIN: f32 = 24.0;
+const TITLE_HEIGHT: f32 = 22.0;
+const MIN_SIZE: Vec2 = Vec2::new(80.0, 40.0);
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum NoteKind {
+    /// Outline grouping the nodes inside of it
+    Frame,
+    /// Filled rectangle with text
+    Sticky,
+}
+
+/// Comment drawn behind the nodes, in graph coordinates.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Note {
+    pub kind: NoteKind,
+    pub title: String,
+    pub body: String,
+    pub color: Color32,
+    pub rect: Rect,
+    /// Nodes moved together with the note
+    pub attached: Vec<NodeId>,
+}
+
+/// Frames and sticky notes of the patch, saved with it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Notes {
+    pub notes: Vec<Note>,
+    #[serde(skip)]
+    editing: Option<usize>,
+}
+
+impl Notes {
+    /// Adds a sticky note at `pos`, in graph coordinates.
+    pub fn add_sticky(&mut self, pos: Pos2) {
+        self.add_text(pos, "Note", "");
+        self.editing = Some(self.notes.len() - 1);
+    }
+
+    /// Adds a sticky note with its text, without opening it for editing.
+    pub fn add_text(&mut self, pos: Pos2, title: &str, body: &str) {
+        self.notes.push(Note {
+            kind: NoteKind::Sticky,
+            title: title.into(),
+            body: body.into(),
+            color: Color32::from_rgb(0xd8, 0xc0, 0x58),
+            rect: Rect::from_min_size(pos, Vec2::new(180.0, 120.0)),
+            attached: Vec::new(),
+        });
+    }
+
+    /// Adds a frame around the selected nodes, attached to it.
+    pub fn add_frame(&mut self, editor: &SynthEditorState, fallback: Pos2) {
+        let rect = editor
+            .selected_nodes
+            .iter()
+            .filter_map(|node_id| editor.node_positions.get(*node_id))
+            .map(|pos| Rect::from_min_size(*pos, NODE_SIZE))
+            .reduce(|a, b| a.union(b))
+            .map(|rect| {
+                let mut rect = rect.expand(MARGIN);
+                rect.min.y -= TITLE_HEIGHT;
+                rect
+            })
+            .unwrap_or(Rect::from_min_size(fallback, Vec2::new(400.0, 300.0)));
+
+        self.notes.push(Note {
+            kind: NoteKind::Frame,
+            title: "Frame".into(),
+            body: String::new(),
+            color: Color32::from_rgb(0x58, 0x90, 0xd8),
+            rect,
+            attached: editor.selected_nodes.clone(),
+        });
+        self.editing = Some(self.notes.len() - 1);
+    }
+
+    /// Paints the notes, called before the graph so they end up behind
+    /// the nodes. `origin` is the screen position of the graph's origin.
+    pub fn paint(&self, painter: &egui::Painter, origin: Pos2) {
+        for note in &self.notes {
+            let rect = note.rect.translate(origin.to_vec2());
+            let title_rect = Rect::from_min_size(rect.min, Vec2::new(rect.width(), TITLE_HEIGHT));
+            let fill = match note.kind {
+                NoteKind::Frame => note.color.gamma_multiply(0.12),
+                NoteKind::Sticky => note.color.gamma_multiply(0.85),
+            };
+
+            painter.rect_filled(rect, 4.0, fill);
+            painter.rect_filled(title_rect, 4.0, note.color.gamma_multiply(0.6));
+            painter.rect_stroke(rect, 4.0, egui::Stroke::new(1.0, note.color));
+
+            let text_color = contrast(note.color);
+            painter.text(
+                title_rect.left_center() + Vec2::new(6.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                &note.title,
+                egui::FontId::proportional(14.0),
+                text_color,
+            );
+
+            if !note.body.is_empty() {
+                let galley = painter.layout(
+                    note.body.clone(),
+                    egui::FontId::proportional(13.0),
+                    match note.kind {
+                        NoteKind::Frame => note.color,
+                        NoteKind::Sticky => Color32::BLACK,
+                    },
+                    rect.width() - 12.0,
+                );
+                let pos = title_rect.left_bottom() + Vec2::new(6.0, 4.0);
+                painter
+                    .with_clip_rect(rect)
+                    .galley(pos, galley, Color32::BLACK);
+            }
+        }
+    }
+
+    /// Handles for moving, resizing and editing the notes, with the editor
+    /// of the one being edited.
+    pub fn show(&mut self, ctx: &egui::Context, editor: &mut SynthEditorState, origin: Pos2) {
+        for (k, note) in self.notes.iter_mut().enumerate() {
+            let rect = note.rect.translate(origin.to_vec2());
+
+            // The title bar drags the note and everything attached to it
+            let moved = egui::Area::new(egui::Id::new(("note-title", k)))
+                .fixed_pos(rect.min)
+                .order(egui::Order::Middle)
+                .show(ctx, |ui| {
+                    let size = Vec2::new((rect.width() - 24.0).max(0.0), TITLE_HEIGHT);
+                    let (_, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+                    if response.double_clicked() {
+                        self.editing = Some(k);
+                    }
+                    response
+                        .on_hover_cursor(egui::CursorIcon::Grab)
+                        .drag_delta()
+                })
+                .inner;
+            if moved != Vec2::ZERO {
+                note.rect = note.rect.translate(moved);
+                for node_id in &note.attached {
+                    if let Some(pos) = editor.node_positions.get_mut(*node_id) {
+                        *pos += moved;
+                    }
+                }
+            }
+
+            egui::Area::new(egui::Id::new(("note-edit", k)))
+                .fixed_pos(rect.right_top() - Vec2::new(22.0, 0.0))
+                .order(egui::Order::Middle)
+                .show(ctx, |ui| {
+                    if ui.small_button("✏").on_hover_text("Edit").clicked() {
+                        self.editing = Some(k);
+                    }
+                });
+
+            let resized = egui::Area::new(egui::Id::new(("note-resize", k)))
+                .fixed_pos(rect.right_bottom() - Vec2::splat(12.0))
+                .order(egui::Order::Middle)
+                .show(ctx, |ui| {
+                    let (handle, response) =
+                        ui.allocate_exact_size(Vec2::splat(12.0), egui::Sense::drag());
+                    ui.painter().line_segment(
+                        [handle.left_bottom(), handle.right_top()],
+                        egui::Stroke::new(1.0, note.color),
+                    );
+                    response
+                        .on_hover_cursor(egui::CursorIcon::ResizeNwSe)
+                        .drag_delta()
+                })
+                .inner;
+            if resized != Vec2::ZERO {
+                note.rect.max = (note.rect.max + resized).max(note.rect.min + MIN_SIZE);
+            }
+        }
+
+        self.show_editing(ctx, editor);
+    }
+
+    fn show_editing(&mut self, ctx: &egui::Context, editor: &SynthEditorState) {
+        let Some(k) = self.editing else {
+            return;
+        };
+        let Some(note) = self.notes.get_mut(k) else {
+            self.editing = None;
+            return;
+        };
+
+        let mut open = true;
+        let mut delete = false;
+        egui::Window::new("Note")
+            .id(egui::Id::new("note-editor"))
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Title");
+                    ui.text_edit_singleline(&mut note.title);
+                    egui::color_picker::color_edit_button_srgba(
+                        ui,
+                        &mut note.color,
+                        egui::color_picker::Alpha::Opaque,
+                    );
+                });
+                ui.text_edit_multiline(&mut note.body);
+
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut note.kind, NoteKind::Frame, "Frame");
+                    ui.selectable_value(&mut note.kind, NoteKind::Sticky, "Sticky");
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} nodes attached", note.attached.len()));
+                    if ui.button("Attach selected").clicked() {
+                        for node_id in &editor.selected_nodes {
+                            if !note.attached.contains(node_id) {
+                                note.attached.push(*node_id);
+                            }
+                        }
+                    }
+                    if ui.button("Detach all").clicked() {
+                        note.attached.clear();
+                    }
+                });
+
+                delete = ui.button("Delete").clicked();
+            });
+
+        // Deleted nodes
+        note.attached
+            .retain(|node_id| editor.graph.nodes.contains_key(*node_id));
+
+        if delete {
+            self.notes.remove(k);
+        }
+        if delete || !open {
+            self.editing = None;
+        }
+    }
+}
+
+// Legible text on top of `color`
+fn contrast(color: Color32) -> Color32 {
+    let [r, g, b, _] = color.to_array();
+    let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    if luma > 140.0 {
+        Color32::BLACK
+    } else {
+        Color32::WHITE
+    }
+}
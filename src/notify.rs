@@ -0,0 +1,69 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use eframe::egui;
+
+// How long a message stays on screen
+const SHOW_FOR: Duration = Duration::from_secs(8);
+
+// Messages reported from anywhere, picked up by the editor on its next frame
+static PENDING: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Shows `message` to the user, also printed for runs without a window.
+pub fn report(message: impl Into<String>) {
+    let message = message.into();
+    println!("{message}");
+    PENDING.lock().unwrap().push(message);
+}
+
+/// Reports an error together with what was being done when it happened.
+pub fn error(context: &str, e: impl std::fmt::Display) {
+    report(format!("{context}: {e}"));
+}
+
+/// Messages shown in the corner of the editor until they expire or are
+/// clicked away.
+#[derive(Debug, Default)]
+pub struct Notifications {
+    shown: Vec<(String, Instant)>,
+}
+
+impl Notifications {
+    pub fn show(&mut self, ctx: &egui::Context) {
+        for message in PENDING.lock().unwrap().drain(..) {
+            // Repeated failures renew the message instead of stacking up
+            self.shown.retain(|(shown, _)| *shown != message);
+            self.shown.push((message, Instant::now()));
+        }
+
+        self.shown.retain(|(_, since)| since.elapsed() < SHOW_FOR);
+        if self.shown.is_empty() {
+            return;
+        }
+
+        let mut dismissed = None;
+        egui::Area::new(egui::Id::new("notifications"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for (k, (message, _)) in self.shown.iter().enumerate() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.set_max_width(360.0);
+                        let label = egui::Label::new(
+                            egui::RichText::new(message).color(ui.visuals().warn_fg_color),
+                        )
+                        .sense(egui::Sense::click());
+                        if ui.add(label).on_hover_text("Click to dismiss").clicked() {
+                            dismissed = Some(k);
+                        }
+                    });
+                }
+            });
+
+        if let Some(k) = dismissed {
+            self.shown.remove(k);
+        }
+    }
+}
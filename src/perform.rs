@@ -0,0 +1,80 @@
+use eframe::egui;
+
+use crate::graph::{SynthGraph, SynthGraphState};
+
+// Width of a node card, scopes are stretched to fill it
+const CARD_WIDTH: f32 = 360.0;
+
+/// Stage view of the patch, replacing the graph editor.
+///
+/// Only parameters, node configs and scopes are shown, so the topology can't
+/// be changed while playing: nodes can't be added, deleted or rewired.
+#[derive(Debug, Default)]
+pub struct Performance {
+    pub enabled: bool,
+}
+
+impl Performance {
+    pub fn show(&self, ui: &mut egui::Ui, graph: &SynthGraph, user_state: &mut SynthGraphState) {
+        // Bigger targets are easier to hit on stage
+        let spacing = &mut ui.style_mut().spacing;
+        spacing.interact_size *= 1.5;
+        spacing.slider_width *= 1.5;
+        spacing.item_spacing *= 1.5;
+
+        let ports = crate::graph::output_choices(graph);
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for (node_id, node) in &graph.nodes {
+                    ui.push_id(node_id, |ui| {
+                        ui.group(|ui| {
+                            ui.set_width(CARD_WIDTH);
+                            ui.vertical(|ui| {
+                                ui.heading(&node.label);
+
+                                if let Some(inputs) = user_state.node_ui_inputs.get(&node_id) {
+                                    for (name, input_id) in &node.inputs {
+                                        let Some(input) = inputs.get(name) else {
+                                            continue;
+                                        };
+
+                                        ui.push_id(name, |ui| {
+                                            ui.horizontal(|ui| {
+                                                input.show_name(ui, name);
+                                                input.show_always(ui, true);
+                                                if graph.connection(*input_id).is_some() {
+                                                    ui.weak("connected");
+                                                } else {
+                                                    input.show_disconnected(ui, true);
+                                                }
+                                            });
+                                        });
+                                    }
+                                }
+
+                                if let Some(config) = user_state
+                                    .node_configs
+                                    .get(&node_id)
+                                    .and_then(|wk| wk.upgrade())
+                                {
+                                    config.show(ui, &mut user_state.ctx);
+                                }
+
+                                let mut ui_state = node.user_data.ui.borrow_mut();
+                                for (name, state) in ui_state.out_states.iter_mut() {
+                                    let Some(scope) = &mut state.scope else {
+                                        continue;
+                                    };
+
+                                    ui.label(name);
+                                    ui.push_id((node_id, name), |ui| scope.show(ui, &ports));
+                                }
+                            });
+                        });
+                    });
+                }
+            });
+        });
+    }
+}
@@ -1,16 +1,27 @@
 use std::{
-    collections::HashMap,
-    sync::mpsc::{channel, Receiver, Sender, TryRecvError},
-    time::Duration,
+    collections::{HashMap, VecDeque},
+    sync::{
+        mpsc::{channel, Receiver, Sender, TryRecvError},
+        Arc, Mutex,
+    },
+    thread::Thread,
 };
 
 use bimap::BiHashMap;
 use egui_graph_edit::NodeId;
 use thunderdome::Index;
 
-use crate::compute::{
-    node::{Node, NodeEvent},
-    OutputPort, Runtime, Value,
+use crate::{
+    audio::{self, AudioSettings},
+    compute::{
+        denormal,
+        node::{Node, NodeEvent},
+        quarantine::Quarantine,
+        OutputPort, Profile, Runtime, Value,
+    },
+    jack_io::JackIo,
+    model::RuntimeLink,
+    sink::{AudioOut, OutputGuard, Sink, SinkId, Sinks},
 };
 
 #[derive(Debug)]
@@ -30,29 +41,157 @@ pub enum RtRequest {
         dst: Index,
         inputs: Vec<Option<OutputPort>>,
     },
-    Play(Option<OutputPort>),
-    Record(Index, usize),
+    AddSink {
+        id: SinkId,
+        port: Option<OutputPort>,
+        gain: f32,
+        sink: Box<dyn Sink>,
+    },
+    AddTap(SinkId, Box<dyn Sink>),
+    RemoveSink(SinkId),
+    SetSinkPort(SinkId, Option<OutputPort>),
+    SetSinkGain(SinkId, f32),
+    SetPlayback(Box<dyn Sink>),
+    SetBuffering {
+        block_size: usize,
+        latency: f32,
+        low_latency: bool,
+    },
+    SetSmoothing(f32),
+    SetQuarantine(Quarantine),
+    SetDenormalProtection(bool),
+    SetMuted(Vec<(Index, usize)>),
+    SetBypassed(Vec<Index>),
+    SetProfiling(bool),
+    Panic,
+    Record(Index, usize, Arc<RecordBuffer>),
+    SetRecordDecimation(Index, usize, usize),
     StopRecording(Index, usize),
     CloneRuntime,
     Shutdown,
 }
 
+// Samples kept per recorded port while the editor doesn't collect them
+const RECORD_CAPACITY: usize = 10 * 44100;
+
+/// Latest samples of a recorded port, shared by the runtime thread and the
+/// editor, with the samples each of them stands for. The oldest ones are
+/// dropped once full, so nothing piles up while the editor isn't drawing.
+#[derive(Debug, Default)]
+pub struct RecordBuffer(Mutex<(VecDeque<Value>, usize)>);
+
+impl RecordBuffer {
+    fn append(&self, block: &mut Vec<Value>) {
+        let (queue, _) = &mut *self.0.lock().unwrap();
+        let overflow = (queue.len() + block.len()).saturating_sub(RECORD_CAPACITY);
+        queue.drain(..overflow.min(queue.len()));
+
+        let skip = block.len().saturating_sub(RECORD_CAPACITY);
+        queue.extend(block.drain(..).skip(skip));
+    }
+
+    // Values taken before the change would be read at the wrong rate
+    fn reset(&self, step: usize) {
+        *self.0.lock().unwrap() = (VecDeque::new(), step);
+    }
+
+    fn take(&self) -> (Vec<Value>, usize) {
+        let (queue, step) = &mut *self.0.lock().unwrap();
+        (queue.drain(..).collect(), (*step).max(1))
+    }
+}
+
+/// Port recorded on the runtime thread. With a step above one, floats are
+/// binned to the minimum and maximum of every `2 * step` samples, so long
+/// scope windows don't ship every sample to the editor.
+#[derive(Debug)]
+struct Recording {
+    block: Vec<Value>,
+    buffer: Arc<RecordBuffer>,
+    step: usize,
+    // Minimum and maximum of the bin being filled, with its length
+    bin: (f32, f32),
+    bin_len: usize,
+}
+
+impl Recording {
+    fn new(buffer: Arc<RecordBuffer>) -> Self {
+        Recording {
+            block: Vec::new(),
+            buffer,
+            step: 1,
+            bin: (0.0, 0.0),
+            bin_len: 0,
+        }
+    }
+
+    fn push(&mut self, value: Value) {
+        match value {
+            Value::Float(x) if self.step > 1 => {
+                self.bin = if self.bin_len == 0 {
+                    (x, x)
+                } else {
+                    (self.bin.0.min(x), self.bin.1.max(x))
+                };
+                self.bin_len += 1;
+
+                if self.bin_len == 2 * self.step {
+                    self.block.push(Value::Float(self.bin.0));
+                    self.block.push(Value::Float(self.bin.1));
+                    self.bin_len = 0;
+                }
+            }
+            value => self.block.push(value),
+        }
+    }
+
+    fn set_step(&mut self, step: usize) {
+        self.step = step;
+        self.bin_len = 0;
+        self.block.clear();
+        self.buffer.reset(step);
+    }
+}
+
+// Samples a profile sent to the editor covers at least
+const PROFILE_STEPS: usize = 44100 / 4;
+
+// Buffering tried first in low latency mode
+const LOW_LATENCY_BLOCK: usize = 64;
+const LOW_LATENCY_SECS: f32 = 0.015;
+
 pub enum RtResponse {
     Inserted(NodeId, Index),
     NodeEvents(Vec<(Index, Vec<NodeEvent>)>),
     RuntimeCloned(Runtime),
-    Samples(OutputPort, Vec<Value>),
+    Profiled(Profile),
+    Quarantined(Vec<(Index, usize)>),
+    BusNames(Vec<String>),
     Step,
 }
 
 pub struct RuntimeRemote {
     tx: Sender<RtRequest>,
     rx: Receiver<RtResponse>,
+    rt_thread: Thread,
+    // Dropping the stream stops playback
+    _stream: rodio::OutputStream,
+    _input: Option<rodio::cpal::Stream>,
+    _jack: Option<JackIo>,
+    output_guard: Arc<OutputGuard>,
+    output_rate: u32,
     must_wait: bool,
     mapping: BiHashMap<NodeId, Index>,
-    recordings: HashMap<OutputPort, Vec<Value>>,
+    next_sink: SinkId,
+    recordings: HashMap<OutputPort, Arc<RecordBuffer>>,
+    // Last decimation asked for per recorded port
+    record_steps: HashMap<OutputPort, usize>,
     node_events: Vec<(Index, Vec<NodeEvent>)>,
     runtime: Option<Runtime>,
+    profiling: bool,
+    profile: Option<Profile>,
+    quarantined: Vec<(Index, usize)>,
+    bus_names: Option<Vec<String>>,
 }
 
 impl RuntimeRemote {
@@ -60,104 +199,190 @@ impl RuntimeRemote {
         let (cmd_tx, cmd_rx) = channel();
         let (resp_tx, resp_rx) = channel();
 
-        let mut record = None;
-        let buf_size = 512;
-        let mut buf = vec![0.0; buf_size];
-
-        let (stream, handle) = rodio::OutputStream::try_default().unwrap();
-        std::mem::forget(stream);
+        let settings = AudioSettings::default();
+        let mut block_size = settings.block_size;
+        let mut latency = settings.latency;
+        // Latency actually aimed for, below `latency` in low latency mode
+        let mut target = latency;
+        // Whether playback received samples since it last changed, an empty
+        // queue afterwards means the runtime fell behind
+        let mut primed = false;
+
+        let (stream, handle, output_rate) = audio::open_stream(&settings).unwrap();
+
+        let mut sinks = Sinks::default();
+        let output_guard = Arc::new(OutputGuard::default());
+        let playback = AudioOut::new(
+            &handle,
+            Arc::clone(&output_guard),
+            settings.resample.then_some(output_rate),
+        )
+        .unwrap();
+        sinks.insert(SinkId::PLAYBACK, None, 1.0, Box::new(playback));
+
+        // Samples of the current block, handed to the shared buffer after it
+        let mut recording = HashMap::<OutputPort, Recording>::new();
+
+        let rt_thread = std::thread::spawn(move || {
+            denormal::enter_thread();
+
+            'rt: loop {
+                loop {
+                    let cmd = match cmd_rx.try_recv() {
+                        Ok(cmd) => cmd,
+                        Err(TryRecvError::Empty) => break,
+                        Err(TryRecvError::Disconnected) => break 'rt,
+                    };
+
+                    match cmd {
+                        RtRequest::Insert { id, inputs, node } => {
+                            let idx = rt.insert(inputs, node);
+                            resp_tx.send(RtResponse::Inserted(id, idx)).ok();
+                        }
+                        RtRequest::Remove(index) => {
+                            rt.remove(index);
+                            recording.retain(|rec, _| rec.node != index);
+                            sinks.disconnect_node(index);
+                        }
+                        RtRequest::SetInput { src, dst, port } => {
+                            rt.set_input(dst, port, src);
+                        }
+                        RtRequest::SetAllInputs { dst, inputs } => {
+                            rt.set_all_inputs(dst, inputs);
+                        }
+                        RtRequest::AddSink {
+                            id,
+                            port,
+                            gain,
+                            sink,
+                        } => {
+                            sinks.insert(id, port, gain, sink);
+                        }
+                        RtRequest::AddTap(id, sink) => {
+                            sinks.insert_tap(id, sink);
+                        }
+                        RtRequest::RemoveSink(id) => {
+                            sinks.remove(id);
+                        }
+                        RtRequest::SetSinkPort(id, port) => {
+                            sinks.set_port(id, port);
+                        }
+                        RtRequest::SetSinkGain(id, gain) => {
+                            sinks.set_gain(id, gain);
+                        }
+                        RtRequest::SetPlayback(sink) => {
+                            sinks.replace(SinkId::PLAYBACK, sink);
+                            primed = false;
+                        }
+                        RtRequest::SetBuffering {
+                            block_size: new_block_size,
+                            latency: new_latency,
+                            low_latency,
+                        } => {
+                            latency = new_latency;
+                            if low_latency {
+                                block_size = new_block_size.min(LOW_LATENCY_BLOCK);
+                                target = latency.min(LOW_LATENCY_SECS);
+                            } else {
+                                block_size = new_block_size;
+                                target = latency;
+                            }
+                            primed = false;
+                        }
+                        RtRequest::SetSmoothing(ms) => {
+                            rt.set_smoothing(ms);
+                        }
+                        RtRequest::SetQuarantine(quarantine) => {
+                            rt.set_quarantine(quarantine);
+                        }
+                        RtRequest::SetDenormalProtection(protected) => {
+                            // Flushing to zero is set per thread, this one runs the patch
+                            denormal::set_protected(protected);
+                        }
+                        RtRequest::SetMuted(muted) => {
+                            rt.set_muted(&muted);
+                        }
+                        RtRequest::SetBypassed(bypassed) => {
+                            rt.set_bypassed(&bypassed);
+                        }
+                        RtRequest::SetProfiling(profiling) => {
+                            rt.set_profiling(profiling);
+                        }
+                        RtRequest::Panic => {
+                            rt.panic();
+                        }
+                        RtRequest::Record(index, port, buffer) => {
+                            recording.insert(OutputPort::new(index, port), Recording::new(buffer));
+                        }
+                        RtRequest::SetRecordDecimation(index, port, step) => {
+                            if let Some(rec) = recording.get_mut(&OutputPort::new(index, port)) {
+                                rec.set_step(step);
+                            }
+                        }
+                        RtRequest::StopRecording(index, port) => {
+                            recording.remove(&OutputPort::new(index, port));
+                        }
+                        RtRequest::CloneRuntime => {
+                            resp_tx.send(RtResponse::RuntimeCloned(rt.clone())).ok();
+                        }
+                        RtRequest::Shutdown => {
+                            break 'rt;
+                        }
+                    }
 
-        let sink = rodio::Sink::try_new(&handle).unwrap();
-        while sink.len() as f32 * buf_size as f32 / 44100.0 < 0.1 {
-            let source = rodio::buffer::SamplesBuffer::new(1, 44100, buf.clone());
-            sink.append(source);
-        }
-        sink.play();
+                    resp_tx.send(RtResponse::Step).ok();
+                }
 
-        let mut recording = HashMap::<OutputPort, Vec<Value>>::new();
+                // Woken up by the sinks as they drain and by new requests
+                if sinks.queued_secs().unwrap_or_default() > target * 0.8 {
+                    std::thread::park();
+                    continue;
+                }
 
-        std::thread::spawn(move || {
-            loop {
-                while sink.len() as f32 * buf_size as f32 / 44100.0 > 0.08 {
-                    std::thread::sleep(Duration::from_millis(10));
+                // Missed the deadline, back off towards the configured latency
+                if primed && sinks.queued_secs() == Some(0.0) && target < latency {
+                    target = (target * 2.0).min(latency);
+                    println!("Playback underrun, buffering {:.0} ms", target * 1000.0);
                 }
 
-                while sink.len() as f32 * buf_size as f32 / 44100.0 < 0.1 {
-                    for s in &mut buf {
+                while sinks.queued_secs().unwrap_or_default() < target {
+                    let evs = rt.sync_configs();
+                    if !evs.is_empty() {
+                        resp_tx.send(RtResponse::NodeEvents(evs)).ok();
+                    }
+
+                    for _ in 0..block_size {
                         let evs = rt.step();
                         if !evs.is_empty() {
                             resp_tx.send(RtResponse::NodeEvents(evs)).ok();
                         }
 
-                        *s = record
-                            .map(|idx| rt.peek(idx))
-                            .as_ref()
-                            .and_then(Value::as_float)
-                            .unwrap_or_default();
+                        sinks.sample(&rt);
 
-                        for (input, buffer) in &mut recording {
-                            let value = rt.peek(*input);
-                            buffer.push(value);
+                        for (input, rec) in &mut recording {
+                            rec.push(rt.peek(*input));
                         }
                     }
 
-                    let source = rodio::buffer::SamplesBuffer::new(1, 44100, buf.clone());
-                    sink.append(source);
+                    sinks.flush();
+                    primed = true;
                 }
 
-                for (input, buffer) in &mut recording {
-                    if !buffer.is_empty() {
-                        resp_tx
-                            .send(RtResponse::Samples(*input, std::mem::take(buffer)))
-                            .ok();
+                for rec in recording.values_mut() {
+                    if !rec.block.is_empty() {
+                        rec.buffer.append(&mut rec.block);
                     }
                 }
-
-                let cmd = match cmd_rx.try_recv() {
-                    Ok(cmd) => cmd,
-                    Err(TryRecvError::Empty) => continue,
-                    Err(TryRecvError::Disconnected) => break,
-                };
-
-                match cmd {
-                    RtRequest::Insert { id, inputs, node } => {
-                        let idx = rt.insert(inputs, node);
-                        resp_tx.send(RtResponse::Inserted(id, idx)).ok();
-                    }
-                    RtRequest::Remove(index) => {
-                        rt.remove(index);
-                        recording.retain(|rec, _| rec.node != index);
-
-                        if let Some(OutputPort { node, .. }) = record {
-                            if node == index {
-                                record = None;
-                            }
-                        }
-                    }
-                    RtRequest::SetInput { src, dst, port } => {
-                        rt.set_input(dst, port, src);
-                    }
-                    RtRequest::SetAllInputs { dst, inputs } => {
-                        rt.set_all_inputs(dst, inputs);
-                    }
-                    RtRequest::Play(node) => {
-                        record = node;
-                    }
-                    RtRequest::Record(index, port) => {
-                        recording.insert(OutputPort::new(index, port), Vec::new());
-                    }
-                    RtRequest::StopRecording(index, port) => {
-                        recording.remove(&OutputPort::new(index, port));
-                    }
-                    RtRequest::CloneRuntime => {
-                        resp_tx.send(RtResponse::RuntimeCloned(rt.clone())).ok();
-                    }
-                    RtRequest::Shutdown => {
-                        break;
-                    }
+                if let Some(profile) = rt.take_profile(PROFILE_STEPS) {
+                    resp_tx.send(RtResponse::Profiled(profile)).ok();
+                }
+                let quarantined = rt.take_quarantined();
+                if !quarantined.is_empty() {
+                    resp_tx.send(RtResponse::Quarantined(quarantined)).ok();
+                }
+                if let Some(names) = rt.bus_names() {
+                    resp_tx.send(RtResponse::BusNames(names)).ok();
                 }
-
-                resp_tx.send(RtResponse::Step).ok();
             }
 
             println!("Runtime stopped");
@@ -166,88 +391,259 @@ impl RuntimeRemote {
         RuntimeRemote {
             tx: cmd_tx,
             rx: resp_rx,
+            rt_thread: rt_thread.thread().clone(),
+            _stream: stream,
+            _input: None,
+            _jack: None,
+            output_guard,
+            output_rate,
             must_wait: false,
             mapping: mapping
                 .into_iter()
                 .map(|(id, bits)| (id, Index::from_bits(bits).unwrap()))
                 .collect(),
+            next_sink: SinkId::PLAYBACK.next(),
             recordings: HashMap::new(),
+            record_steps: HashMap::new(),
             node_events: Vec::new(),
             runtime: None,
+            profiling: false,
+            profile: None,
+            quarantined: Vec::new(),
+            // Clears the names of the runtime replaced
+            bus_names: Some(Vec::new()),
         }
     }
 
+    fn request(&self, req: RtRequest) {
+        self.tx.send(req).ok();
+        self.rt_thread.unpark();
+    }
+
     pub fn start() -> Self {
         Self::with_rt_and_mapping(Runtime::new(), Vec::new())
     }
 
     pub fn insert(&mut self, id: NodeId, node: Box<dyn Node>) {
         let inputs = vec![None; node.inputs().len()];
-        self.tx.send(RtRequest::Insert { id, inputs, node }).ok();
+        self.request(RtRequest::Insert { id, inputs, node });
         self.must_wait = true;
     }
 
     pub fn remove(&mut self, id: NodeId) {
         let idx = self.mapping.get_by_left(&id).cloned().unwrap();
-        self.tx.send(RtRequest::Remove(idx)).ok();
+        self.request(RtRequest::Remove(idx));
         self.mapping.remove_by_left(&id);
+        self.recordings.retain(|port, _| port.node != idx);
         self.must_wait = true;
     }
 
     pub fn set_inputs(&mut self, dst: NodeId, inputs: Vec<Option<OutputPort>>) {
-        self.tx
-            .send(RtRequest::SetAllInputs {
-                dst: *self.mapping.get_by_left(&dst).unwrap(),
-                inputs,
-            })
-            .ok();
+        self.request(RtRequest::SetAllInputs {
+            dst: *self.mapping.get_by_left(&dst).unwrap(),
+            inputs,
+        });
     }
 
     pub fn connect(&mut self, src: NodeId, src_port: usize, dst: NodeId, dst_port: usize) {
         let src = self.mapping.get_by_left(&src).cloned().unwrap();
         let dst = self.mapping.get_by_left(&dst).cloned().unwrap();
-        self.tx
-            .send(RtRequest::SetInput {
-                src: Some(OutputPort::new(src, src_port)),
-                dst,
-                port: dst_port,
-            })
-            .ok();
+        self.request(RtRequest::SetInput {
+            src: Some(OutputPort::new(src, src_port)),
+            dst,
+            port: dst_port,
+        });
     }
 
     pub fn disconnect(&mut self, dst: NodeId, port: usize) {
         let dst = self.mapping.get_by_left(&dst).cloned().unwrap();
-        self.tx
-            .send(RtRequest::SetInput {
-                src: None,
-                dst,
-                port,
-            })
-            .ok();
+        self.request(RtRequest::SetInput {
+            src: None,
+            dst,
+            port,
+        });
     }
 
-    pub fn play(&mut self, id: Option<(NodeId, usize)>) {
-        let input = id.and_then(|(id, port)| {
+    fn output_port(&self, id: Option<(NodeId, usize)>) -> Option<OutputPort> {
+        id.and_then(|(id, port)| {
             self.mapping
                 .get_by_left(&id)
                 .cloned()
                 .map(|idx| OutputPort::new(idx, port))
+        })
+    }
+
+    /// Reopens playback, live input and JACK ports with new settings, the
+    /// patch keeps running.
+    pub fn set_audio(&mut self, settings: &AudioSettings) -> anyhow::Result<()> {
+        let (stream, handle, output_rate) = audio::open_stream(settings)?;
+        let playback = AudioOut::new(
+            &handle,
+            Arc::clone(&self.output_guard),
+            settings.resample.then_some(output_rate),
+        )?;
+        self.output_rate = output_rate;
+
+        self.request(RtRequest::SetPlayback(Box::new(playback)));
+        self.request(RtRequest::SetBuffering {
+            block_size: settings.block_size,
+            latency: settings.latency,
+            low_latency: settings.low_latency,
         });
-        self.tx.send(RtRequest::Play(input)).ok();
+        self.request(RtRequest::SetSmoothing(settings.smoothing));
+        self.request(RtRequest::SetQuarantine(settings.quarantine));
+        self.request(RtRequest::SetDenormalProtection(
+            settings.denormal_protection,
+        ));
+        self._stream = stream;
+
+        // Stop the old capture first, devices may not allow two streams
+        self._input = None;
+        if settings.input_enabled {
+            self._input = Some(audio::open_input(settings)?);
+        }
+
+        self._jack = None;
+        if settings.jack.enabled {
+            self._jack = Some(JackIo::start(&settings.jack)?);
+        }
+
+        Ok(())
+    }
+
+    pub fn output_rate(&self) -> u32 {
+        self.output_rate
+    }
+
+    pub fn output_guard(&self) -> &OutputGuard {
+        &self.output_guard
+    }
+
+    pub fn play(&mut self, id: Option<(NodeId, usize)>) {
+        self.set_sink_port(SinkId::PLAYBACK, id);
+    }
+
+    pub fn add_sink(
+        &mut self,
+        id: Option<(NodeId, usize)>,
+        gain: f32,
+        sink: Box<dyn Sink>,
+    ) -> SinkId {
+        let sink_id = self.next_sink;
+        self.next_sink = sink_id.next();
+
+        let port = self.output_port(id);
+        self.request(RtRequest::AddSink {
+            id: sink_id,
+            port,
+            gain,
+            sink,
+        });
+
+        sink_id
+    }
+
+    /// Adds a sink hearing what playback plays, before the master volume.
+    pub fn add_tap(&mut self, sink: Box<dyn Sink>) -> SinkId {
+        let sink_id = self.next_sink;
+        self.next_sink = sink_id.next();
+        self.request(RtRequest::AddTap(sink_id, sink));
+
+        sink_id
+    }
+
+    pub fn remove_sink(&mut self, id: SinkId) {
+        self.request(RtRequest::RemoveSink(id));
+    }
+
+    pub fn set_sink_port(&mut self, sink: SinkId, id: Option<(NodeId, usize)>) {
+        let port = self.output_port(id);
+        self.request(RtRequest::SetSinkPort(sink, port));
+    }
+
+    pub fn set_sink_gain(&mut self, sink: SinkId, gain: f32) {
+        self.request(RtRequest::SetSinkGain(sink, gain));
+    }
+
+    /// Silences the given inputs without disconnecting them.
+    pub fn set_muted(&mut self, muted: &[(NodeId, usize)]) {
+        let muted = muted
+            .iter()
+            .filter_map(|(id, port)| Some((*self.mapping.get_by_left(id)?, *port)))
+            .collect();
+        self.request(RtRequest::SetMuted(muted));
+    }
+
+    /// Passes the first input of the given nodes through instead of running them.
+    pub fn set_bypassed(&mut self, bypassed: &[NodeId]) {
+        let bypassed = bypassed
+            .iter()
+            .filter_map(|id| Some(*self.mapping.get_by_left(id)?))
+            .collect();
+        self.request(RtRequest::SetBypassed(bypassed));
+    }
+
+    /// Times every node while on, for the inspector. Off by default, as
+    /// timing costs more than many nodes do.
+    pub fn set_profiling(&mut self, profiling: bool) {
+        if profiling != self.profiling {
+            self.profiling = profiling;
+            self.profile = None;
+            self.request(RtRequest::SetProfiling(profiling));
+        }
+    }
+
+    /// Latest profile of the runtime, while profiling.
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref().filter(|_| self.profiling)
+    }
+
+    /// Releases all notes and clears all delays in the patch.
+    pub fn panic(&mut self) {
+        self.request(RtRequest::Panic);
     }
 
     pub fn record(&mut self, id: NodeId, port: usize) {
         let idx = *self.mapping.get_by_left(&id).unwrap();
-        self.tx.send(RtRequest::Record(idx, port)).ok();
+        let buffer = Arc::new(RecordBuffer::default());
+        self.recordings
+            .insert(OutputPort::new(idx, port), Arc::clone(&buffer));
+        self.record_steps.remove(&OutputPort::new(idx, port));
+        self.request(RtRequest::Record(idx, port, buffer));
+    }
+
+    pub fn recording(&self, id: NodeId, port: usize) -> bool {
+        self.mapping
+            .get_by_left(&id)
+            .is_some_and(|idx| self.recordings.contains_key(&OutputPort::new(*idx, port)))
     }
 
     pub fn stop_recording(&mut self, id: NodeId, port: usize) {
         let idx = *self.mapping.get_by_left(&id).unwrap();
-        self.tx.send(RtRequest::StopRecording(idx, port)).ok();
+        self.recordings.remove(&OutputPort::new(idx, port));
+        self.record_steps.remove(&OutputPort::new(idx, port));
+        self.request(RtRequest::StopRecording(idx, port));
+    }
+
+    /// Has a recorded port send one value per `step` samples, binned to
+    /// their extremes, for scopes showing more samples than pixels.
+    pub fn set_record_decimation(&mut self, id: NodeId, port: usize, step: usize) {
+        let Some(idx) = self.mapping.get_by_left(&id).cloned() else {
+            return;
+        };
+        let out_port = OutputPort::new(idx, port);
+        if !self.recordings.contains_key(&out_port)
+            || self.record_steps.get(&out_port) == Some(&step)
+        {
+            return;
+        }
+
+        self.record_steps.insert(out_port, step);
+        self.request(RtRequest::SetRecordDecimation(idx, port, step));
     }
 
     pub fn shutdown(&mut self) {
-        self.tx.send(RtRequest::Shutdown).ok();
+        self.request(RtRequest::Shutdown);
     }
 
     pub fn process(&mut self, resp: RtResponse) {
@@ -261,11 +657,14 @@ impl RuntimeRemote {
             RtResponse::RuntimeCloned(runtime) => {
                 self.runtime = Some(runtime);
             }
-            RtResponse::Samples(index, samples) => {
-                self.recordings
-                    .entry(index)
-                    .or_default()
-                    .extend(samples.into_iter());
+            RtResponse::Profiled(profile) => {
+                self.profile = Some(profile);
+            }
+            RtResponse::Quarantined(quarantined) => {
+                self.quarantined.extend(quarantined);
+            }
+            RtResponse::BusNames(names) => {
+                self.bus_names = Some(names);
             }
             RtResponse::Step => {}
         }
@@ -275,6 +674,18 @@ impl RuntimeRemote {
         std::mem::take(&mut self.node_events)
     }
 
+    /// Nodes whose NaN or infinite outputs were fixed since the last call,
+    /// with the samples they were in.
+    pub fn quarantined(&mut self) -> Vec<(Index, usize)> {
+        std::mem::take(&mut self.quarantined)
+    }
+
+    /// Names of the buses Send nodes feed, if they changed since the last
+    /// call.
+    pub fn bus_names(&mut self) -> Option<Vec<String>> {
+        self.bus_names.take()
+    }
+
     pub fn wait(&mut self) {
         if self.must_wait {
             while let Ok(resp) = self.rx.recv() {
@@ -302,7 +713,7 @@ impl RuntimeRemote {
     }
 
     pub fn save_state(&mut self) -> (Runtime, Vec<(NodeId, u64)>) {
-        self.tx.send(RtRequest::CloneRuntime).ok();
+        self.request(RtRequest::CloneRuntime);
         loop {
             if let Some(rt) = self.runtime.take() {
                 let mapping = self
@@ -318,15 +729,65 @@ impl RuntimeRemote {
         }
     }
 
-    pub fn recordings(&mut self) -> Vec<(OutputPort, Vec<Value>)> {
+    /// Samples recorded since the last call, at most the latest ten seconds
+    /// of each port, with the samples each value stands for.
+    pub fn recordings(&mut self) -> Vec<(OutputPort, Vec<Value>, usize)> {
         self.recordings
-            .iter_mut()
-            .filter(|(_, buf)| !buf.is_empty())
-            .map(|(k, v)| (*k, std::mem::take(v)))
+            .iter()
+            .map(|(port, buffer)| {
+                let (samples, step) = buffer.take();
+                (*port, samples, step)
+            })
+            .filter(|(_, samples, _)| !samples.is_empty())
             .collect()
     }
 }
 
+impl RuntimeLink for RuntimeRemote {
+    fn insert(&mut self, id: NodeId, node: Box<dyn Node>) {
+        RuntimeRemote::insert(self, id, node)
+    }
+
+    fn remove(&mut self, id: NodeId) {
+        RuntimeRemote::remove(self, id)
+    }
+
+    fn set_inputs(&mut self, dst: NodeId, inputs: Vec<Option<OutputPort>>) {
+        RuntimeRemote::set_inputs(self, dst, inputs)
+    }
+
+    fn connect(&mut self, src: NodeId, src_port: usize, dst: NodeId, dst_port: usize) {
+        RuntimeRemote::connect(self, src, src_port, dst, dst_port)
+    }
+
+    fn disconnect(&mut self, dst: NodeId, port: usize) {
+        RuntimeRemote::disconnect(self, dst, port)
+    }
+
+    fn play(&mut self, id: Option<(NodeId, usize)>) {
+        RuntimeRemote::play(self, id)
+    }
+
+    fn record(&mut self, id: NodeId, port: usize) {
+        RuntimeRemote::record(self, id, port)
+    }
+
+    fn stop_recording(&mut self, id: NodeId, port: usize) {
+        RuntimeRemote::stop_recording(self, id, port)
+    }
+
+    fn id_to_index(&self, id: NodeId) -> Option<Index> {
+        RuntimeRemote::id_to_index(self, id)
+    }
+}
+
+impl Drop for RuntimeRemote {
+    fn drop(&mut self) {
+        // The runtime thread may be parked, a closed channel won't wake it
+        self.shutdown();
+    }
+}
+
 impl Default for RuntimeRemote {
     fn default() -> Self {
         RuntimeRemote::start()
@@ -0,0 +1,254 @@
+use std::{fs::File, path::Path};
+
+use anyhow::{anyhow, Result};
+use egui_graph_edit::NodeId;
+use thunderdome::Index;
+
+use crate::{
+    compute::{deterministic, OutputPort, Runtime},
+    graph::{SynthEditorState, SynthGraphState},
+};
+
+// Samples rendered between config syncs, like the default runtime block
+const BLOCK: usize = 512;
+// Largest difference between a render and its reference counted as equal
+const TOLERANCE: f32 = 1e-6;
+
+/// Renders the played output of a saved patch without an audio device.
+pub fn render_patch(path: &Path, secs: f32) -> Result<Vec<f32>> {
+    let _deterministic = deterministic::enable();
+
+    let file = File::open(path)?;
+    let ((mut rt, mapping), _editor, user_state): (
+        (Runtime, Vec<(NodeId, u64)>),
+        SynthEditorState,
+        SynthGraphState,
+    ) = serde_json::from_reader(file)?;
+
+    // Without a played port there has to be a Main Out, checked once rendered
+    let output = match user_state.rt_playback {
+        Some((node_id, port)) => {
+            let index = mapping
+                .iter()
+                .find(|(id, _)| *id == node_id)
+                .and_then(|(_, bits)| Index::from_bits(*bits))
+                .ok_or(anyhow!("Played node is missing from the runtime"))?;
+            Some(OutputPort::new(index, port))
+        }
+        None => None,
+    };
+
+    rt.reseed(deterministic::SEED);
+    user_state.tuning.apply();
+
+    let len = (secs * 44100.0) as usize;
+    let mut samples = Vec::with_capacity(len);
+    while samples.len() < len {
+        // Node events only matter to the editor
+        rt.sync_configs();
+
+        for _ in 0..BLOCK.min(len - samples.len()) {
+            rt.step();
+            let main = rt.bus(0);
+            if main.is_none() && output.is_none() {
+                return Err(anyhow!("{} has no played output", path.display()));
+            }
+            samples.push(main.unwrap_or_else(|| {
+                output
+                    .and_then(|output| rt.peek(output).as_float())
+                    .unwrap_or_default()
+            }));
+        }
+    }
+
+    Ok(samples)
+}
+
+pub fn write_wav(path: &Path, samples: &[f32]) -> Result<()> {
+    let header = wav::Header::new(wav::WAV_FORMAT_IEEE_FLOAT, 1, 44100, 32);
+    let mut file = File::create(path)?;
+    wav::write(
+        header,
+        &wav::BitDepth::ThirtyTwoFloat(samples.to_vec()),
+        &mut file,
+    )?;
+
+    Ok(())
+}
+
+pub fn read_wav(path: &Path) -> Result<Vec<f32>> {
+    let mut file = File::open(path)?;
+    let (_header, data) = wav::read(&mut file)?;
+
+    match data {
+        wav::BitDepth::ThirtyTwoFloat(samples) => Ok(samples),
+        _ => Err(anyhow!("{} isn't a 32-bit float WAV", path.display())),
+    }
+}
+
+/// First sample at which a render differs from its reference.
+pub fn first_mismatch(render: &[f32], reference: &[f32]) -> Option<usize> {
+    if let Some(k) = render
+        .iter()
+        .zip(reference)
+        .position(|(a, b)| (a - b).abs() > TOLERANCE)
+    {
+        return Some(k);
+    }
+
+    (render.len() != reference.len()).then_some(render.len().min(reference.len()))
+}
+
+/// Handles `render <patch> <out.wav> [secs]` and `check <patch> <ref.wav>
+/// [secs]`, returns `None` when the arguments aren't a render command.
+pub fn run_cli(args: &[String]) -> Option<Result<bool>> {
+    let (command, patch, wav_path) = match args {
+        [command, patch, wav_path, ..] => (command.as_str(), patch, wav_path),
+        _ => return None,
+    };
+    let secs = args
+        .get(3)
+        .and_then(|secs| secs.parse().ok())
+        .unwrap_or(5.0);
+
+    let result = match command {
+        "render" => render_patch(Path::new(patch), secs)
+            .and_then(|samples| write_wav(Path::new(wav_path), &samples))
+            .map(|()| true),
+        "check" => render_patch(Path::new(patch), secs).and_then(|samples| {
+            let reference = read_wav(Path::new(wav_path))?;
+            match first_mismatch(&samples, &reference) {
+                Some(k) => {
+                    println!("{patch} differs from {wav_path} at sample {k}");
+                    Ok(false)
+                }
+                None => Ok(true),
+            }
+        }),
+        _ => return None,
+    };
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use rand::Rng;
+
+    use super::*;
+    use crate::{
+        compute::node,
+        factory::{self, Patch},
+        graph::AllSynthNodeTemplates,
+    };
+
+    const SECS: f32 = 0.1;
+    // Long enough for a few beats, gates and reverb tails
+    const GOLDEN_SECS: f32 = 2.0;
+
+    // Saves `patch` like the editor does, after playing `steps` samples
+    fn save(patch: &Patch, file: &str, steps: usize) -> PathBuf {
+        let templates = AllSynthNodeTemplates::new(node::node_lists());
+        let mut state = factory::build(patch, &templates).unwrap();
+        for _ in 0..steps {
+            state.0 .0.step();
+        }
+
+        let path = std::env::temp_dir().join(format!("modal-{}-{file}", std::process::id()));
+        serde_json::to_writer(File::create(&path).unwrap(), &state).unwrap();
+
+        path
+    }
+
+    fn render(patch: &Patch, file: &str, steps: usize) -> Vec<f32> {
+        let path = save(patch, file, steps);
+        let samples = render_patch(&path, SECS).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        samples
+    }
+
+    fn factory_patch(name: &str) -> &'static Patch {
+        factory::PATCHES
+            .iter()
+            .find(|patch| patch.name == name)
+            .unwrap()
+    }
+
+    // Compares a render of `patch` with its reference in tests/renders, the
+    // references are recorded instead with MODAL_BLESS set
+    fn golden(patch: &Patch, name: &str) {
+        let path = save(patch, &format!("{name}.json"), 0);
+        let samples = render_patch(&path, GOLDEN_SECS).unwrap();
+        std::fs::remove_file(path).unwrap();
+
+        let wav_path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/renders")
+            .join(format!("{name}.wav"));
+        if std::env::var_os("MODAL_BLESS").is_some() {
+            std::fs::create_dir_all(wav_path.parent().unwrap()).unwrap();
+            write_wav(&wav_path, &samples).unwrap();
+            return;
+        }
+
+        let reference = read_wav(&wav_path).unwrap_or_else(|err| {
+            panic!(
+                "{}: {err}, record it with MODAL_BLESS=1",
+                wav_path.display()
+            )
+        });
+        assert_eq!(first_mismatch(&samples, &reference), None, "{name}");
+    }
+
+    // Outputs reach the Main Out a sample late
+    fn delayed(samples: impl Iterator<Item = f32>) -> Vec<f32> {
+        std::iter::once(0.0)
+            .chain(samples)
+            .take((SECS * 44100.0) as usize)
+            .collect()
+    }
+
+    #[test]
+    fn constant() {
+        let samples = render(&factory::CONSTANT, "constant.json", 0);
+        let reference = delayed(std::iter::repeat(0.25));
+
+        assert_eq!(first_mismatch(&samples, &reference), None);
+    }
+
+    #[test]
+    fn noise() {
+        let samples = render(&factory::NOISE, "noise.json", 0);
+        // The noise is the first node
+        let mut rng = deterministic::seeded(deterministic::SEED);
+        let reference = delayed(std::iter::repeat_with(|| rng.gen_range(-1.0..=1.0)));
+
+        assert_eq!(first_mismatch(&samples, &reference), None);
+    }
+
+    #[test]
+    fn noise_played_before_saving() {
+        let fresh = render(&factory::NOISE, "noise-fresh.json", 0);
+        let played = render(&factory::NOISE, "noise-played.json", 4410);
+
+        // The first sample is the last one output before saving
+        assert_eq!(first_mismatch(&played[1..], &fresh[1..]), None);
+    }
+
+    #[test]
+    fn golden_drum_kit() {
+        golden(factory_patch("Drum kit"), "drum-kit");
+    }
+
+    #[test]
+    fn golden_sidechain_ducking() {
+        golden(factory_patch("Sidechain ducking"), "sidechain-ducking");
+    }
+
+    #[test]
+    fn golden_tone() {
+        golden(&factory::TONE, "tone");
+    }
+}
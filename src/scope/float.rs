@@ -1,7 +1,8 @@
 use std::{collections::VecDeque, fmt::Debug};
 
 use eframe::egui;
-use egui_plot::{Line, Plot, PlotPoints};
+use egui_graph_edit::NodeId;
+use egui_plot::{HLine, Line, Plot, PlotPoints, Points, VLine};
 use itertools::Itertools;
 use num_traits::Zero;
 use rustfft::{num_complex::Complex32, FftPlanner};
@@ -11,8 +12,35 @@ use serde::{Deserialize, Serialize};
 pub enum FloatScopeMode {
     TimeSeries,
     Fft,
+    Xy,
 }
 
+/// Edge the time series waits for, so periodic signals stand still.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TriggerSlope {
+    #[default]
+    Off,
+    Rising,
+    Falling,
+}
+
+fn default_timebase() -> f32 {
+    1.0
+}
+
+fn default_step() -> usize {
+    1
+}
+
+// Points drawn of the shown window at most
+const MAX_POINTS: usize = 1000;
+// Latest samples plotted against each other in XY mode
+const XY_LEN: usize = 2048;
+
+/// Output port other scopes can be plotted against, as a node and the name
+/// of its output, with a label to pick it by.
+pub type PortChoice = ((NodeId, String), String);
+
 struct MyPlanner(FftPlanner<f32>);
 
 impl Default for MyPlanner {
@@ -33,14 +61,65 @@ impl Debug for MyPlanner {
     }
 }
 
+// Only the length of the memory is saved, the samples start out silent.
+mod memory_len {
+    use std::collections::VecDeque;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    // Older patches stored all the samples
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Memory {
+        Len(usize),
+        Samples(VecDeque<f32>),
+    }
+
+    pub fn serialize<S: Serializer>(memory: &VecDeque<f32>, s: S) -> Result<S::Ok, S::Error> {
+        memory.len().serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<VecDeque<f32>, D::Error> {
+        let len = match Memory::deserialize(d)? {
+            Memory::Len(len) => len,
+            Memory::Samples(samples) => samples.len(),
+        };
+
+        Ok(std::iter::repeat(0.0).take(len).collect())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FloatScope {
     // general
     mode: FloatScopeMode,
 
+    // time series
+    /// Seconds shown, at most the whole memory
+    #[serde(default = "default_timebase")]
+    timebase: f32,
+    #[serde(default)]
+    trigger: TriggerSlope,
+    #[serde(default)]
+    trigger_level: f32,
+    // New samples are dropped while frozen
+    #[serde(skip)]
+    frozen: bool,
+    // Time and amplitude of the two cursors
+    #[serde(skip)]
+    cursors: [Option<[f64; 2]>; 2],
+    // Samples each value of the memory stands for, above one when the
+    // recording is decimated
+    #[serde(skip, default = "default_step")]
+    step: usize,
+    // Width of the last plot, in points
+    #[serde(skip)]
+    columns: f32,
+
     // fft
     freq_range: (usize, usize),
 
+    #[serde(with = "memory_len")]
     memory: VecDeque<f32>,
     rolling_min: VecDeque<f32>,
     rolling_max: VecDeque<f32>,
@@ -49,6 +128,14 @@ pub struct FloatScope {
     fft_planner: MyPlanner,
     #[serde(skip)]
     scratch: Vec<Complex32>,
+
+    // xy
+    #[serde(default)]
+    partner: Option<(NodeId, String)>,
+    #[serde(default)]
+    goniometer: bool,
+    #[serde(skip)]
+    partner_memory: VecDeque<f32>,
 }
 
 impl FloatScope {
@@ -56,6 +143,13 @@ impl FloatScope {
         let rolling_len = 180;
         FloatScope {
             mode: FloatScopeMode::TimeSeries,
+            timebase: default_timebase(),
+            trigger: TriggerSlope::Off,
+            trigger_level: 0.0,
+            frozen: false,
+            cursors: [None; 2],
+            step: 1,
+            columns: 0.0,
             freq_range: (100, 5000),
             memory: std::iter::repeat(0.0).take(44100).collect(),
             rolling_min: std::iter::repeat(-1.0).take(rolling_len).collect(),
@@ -63,28 +157,135 @@ impl FloatScope {
             rolling_len,
             fft_planner: MyPlanner(FftPlanner::new()),
             scratch: Vec::new(),
+            partner: None,
+            goniometer: false,
+            partner_memory: VecDeque::new(),
         }
     }
 
-    fn show_timeseries(&self, ui: &mut egui::Ui) {
-        let len_t = self.memory.len() as f32 / 44100.0;
-        let chunk_sz = 44;
+    /// Start of the shown window in the memory, and whether it starts at a
+    /// trigger. Without an edge the latest samples are shown.
+    fn rate(&self) -> f32 {
+        44100.0 / self.step as f32
+    }
+
+    fn window(&self, len: usize) -> (usize, bool) {
+        let latest = self.memory.len() - len;
+        let level = self.trigger_level;
+        let crossed = |i: usize| {
+            let (a, b) = (self.memory[i - 1], self.memory[i]);
+            match self.trigger {
+                TriggerSlope::Off => false,
+                TriggerSlope::Rising => a < level && b >= level,
+                TriggerSlope::Falling => a > level && b <= level,
+            }
+        };
+
+        match (1..=latest).rev().find(|i| crossed(*i)) {
+            Some(start) => (start, true),
+            None => (latest, false),
+        }
+    }
+
+    fn show_trigger(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let mut ms = self.timebase * 1000.0;
+            let max_ms = self.memory.len() as f32 / self.rate() * 1000.0;
+            ui.label("timebase");
+            if ui
+                .add(
+                    egui::DragValue::new(&mut ms)
+                        .speed(1.0)
+                        .range(1.0..=max_ms)
+                        .suffix(" ms"),
+                )
+                .changed()
+            {
+                self.timebase = ms / 1000.0;
+            }
+
+            egui::ComboBox::new("scope-trigger", "")
+                .selected_text(format!("{:?}", self.trigger))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.trigger, TriggerSlope::Off, "Off");
+                    ui.selectable_value(&mut self.trigger, TriggerSlope::Rising, "Rising");
+                    ui.selectable_value(&mut self.trigger, TriggerSlope::Falling, "Falling");
+                })
+                .response
+                .on_hover_text("Trigger edge");
+            if self.trigger != TriggerSlope::Off {
+                ui.add(
+                    egui::DragValue::new(&mut self.trigger_level)
+                        .speed(0.01)
+                        .prefix("level: "),
+                );
+            }
+
+            let label = if self.frozen { "▶ Run" } else { "⏸ Freeze" };
+            if ui.button(label).clicked() {
+                self.frozen = !self.frozen;
+            }
+        });
+    }
+
+    fn show_cursors(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| match self.cursors {
+            [Some(a), Some(b)] => {
+                let dt = b[0] - a[0];
+                let dv = b[1] - a[1];
+                let freq = if dt.abs() > f64::EPSILON {
+                    format!(", 1/Δt {:.1} Hz", 1.0 / dt.abs())
+                } else {
+                    String::new()
+                };
+                ui.label(format!("Δt {:.2} ms{freq}, Δv {dv:.3}", dt * 1000.0));
+                if ui.small_button("Clear").clicked() {
+                    self.cursors = [None; 2];
+                }
+            }
+            [Some(a), None] => {
+                ui.label(format!("t {:.2} ms, v {:.3}", a[0] * 1000.0, a[1]));
+                if ui.small_button("Clear").clicked() {
+                    self.cursors = [None; 2];
+                }
+            }
+            _ => {
+                ui.weak("Click to place cursors, right click for the second one");
+            }
+        });
+    }
+
+    fn show_timeseries(&mut self, ui: &mut egui::Ui) {
+        self.show_trigger(ui);
+
+        self.timebase = self
+            .timebase
+            .min(self.memory.len() as f32 / self.rate())
+            .max(1.0 / 1000.0);
+        let rate = self.rate();
+        let len = ((self.timebase * rate) as usize).clamp(1, self.memory.len());
+        let (start, triggered) = self.window(len);
+        let len_t = len as f32 / rate;
+
+        let chunk_sz = (len / MAX_POINTS).max(1);
         let xys: PlotPoints = self
             .memory
-            .iter()
+            .range(start..start + len)
             .chunks(chunk_sz)
             .into_iter()
             .map(|chunk| chunk.max_by(|l, r| l.abs().total_cmp(&r.abs())).unwrap())
             .enumerate()
             .map(|(i, y)| {
-                let t = (i * chunk_sz) as f32 / 44100.0 - len_t;
+                // Triggered windows start at the edge, others end now
+                let t = (i * chunk_sz) as f32 / rate;
+                let t = if triggered { t } else { t - len_t };
 
                 [t as f64, *y as f64]
             })
             .collect();
 
-        let min = xys.points()[0].x - 0.1;
-        let max = xys.points().last().unwrap().x + 0.1;
+        let min = xys.points()[0].x - 0.1 * len_t as f64;
+        let max = xys.points().last().unwrap().x + 0.1 * len_t as f64;
         let line = Line::new(xys);
 
         let min_y = self
@@ -103,7 +304,9 @@ impl FloatScope {
 
         let h = max_y - min_y;
 
-        Plot::new("plot")
+        let trigger = (self.trigger != TriggerSlope::Off).then_some(self.trigger_level);
+        let cursors = self.cursors;
+        let response = Plot::new("plot")
             .include_x(min)
             .include_x(max)
             .include_y(min_y - h / 10.0)
@@ -117,7 +320,30 @@ impl FloatScope {
             .view_aspect(2.0)
             .show(ui, |ui| {
                 ui.line(line);
+                if let Some(level) = trigger {
+                    ui.hline(HLine::new(level).style(egui_plot::LineStyle::dashed_loose()));
+                }
+                for [t, v] in cursors.into_iter().flatten() {
+                    ui.vline(VLine::new(t));
+                    ui.hline(HLine::new(v));
+                }
             });
+
+        self.columns = response.response.rect.width();
+
+        let pointer = response
+            .response
+            .hover_pos()
+            .map(|pos| response.transform.value_from_position(pos));
+        if let Some(point) = pointer {
+            if response.response.clicked() {
+                self.cursors[0] = Some([point.x, point.y]);
+            } else if response.response.secondary_clicked() {
+                self.cursors[1] = Some([point.x, point.y]);
+            }
+        }
+
+        self.show_cursors(ui);
     }
 
     fn show_fft(&mut self, ui: &mut egui::Ui) {
@@ -143,7 +369,7 @@ impl FloatScope {
             .plan_fft_forward(ys.len())
             .process_with_scratch(&mut ys, &mut self.scratch);
 
-        let hz_per_i = 44100.0 / (ys.len() as f32);
+        let hz_per_i = self.rate() / (ys.len() as f32);
         let start_i = (self.freq_range.0 as f32 / hz_per_i).round() as usize;
         let end_i = (self.freq_range.1 as f32 / hz_per_i).round() as usize;
         let xys: PlotPoints = ys
@@ -180,8 +406,105 @@ impl FloatScope {
             });
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui) {
-        let mut mem_s = self.memory.len() as f32 / 44100.0;
+    fn show_xy(&mut self, ui: &mut egui::Ui, ports: &[PortChoice]) {
+        ui.horizontal(|ui| {
+            let selected = ports
+                .iter()
+                .find(|(port, _)| Some(port) == self.partner.as_ref())
+                .map(|(_, label)| label.as_str())
+                .unwrap_or("None");
+            let before = self.partner.clone();
+            egui::ComboBox::new("scope-xy-partner", "Y")
+                .selected_text(selected)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.partner, None, "None");
+                    for (port, label) in ports {
+                        ui.selectable_value(&mut self.partner, Some(port.clone()), label);
+                    }
+                });
+            if self.partner != before {
+                self.partner_memory.clear();
+            }
+
+            ui.checkbox(&mut self.goniometer, "Goniometer")
+                .on_hover_text("Mid up and side across, as this output and Y were left and right");
+        });
+
+        let len = XY_LEN.min(self.memory.len()).min(self.partner_memory.len());
+        let xs = self.memory.iter().skip(self.memory.len() - len);
+        let ys = self
+            .partner_memory
+            .iter()
+            .skip(self.partner_memory.len() - len);
+        let pairs: Vec<(f32, f32)> = xs.zip(ys).map(|(x, y)| (*x, *y)).collect();
+
+        let (xy, xx, yy) = pairs.iter().fold((0.0, 0.0, 0.0), |(xy, xx, yy), (x, y)| {
+            (xy + x * y, xx + x * x, yy + y * y)
+        });
+        let correlation = if xx > 0.0 && yy > 0.0 {
+            xy / (xx * yy).sqrt()
+        } else {
+            0.0
+        };
+        let color = if correlation < 0.0 {
+            egui::Color32::RED
+        } else {
+            ui.visuals().text_color()
+        };
+        ui.label(egui::RichText::new(format!("correlation {correlation:+.2}")).color(color));
+
+        let points: PlotPoints = pairs
+            .iter()
+            .map(|(x, y)| {
+                if self.goniometer {
+                    let side = (x - y) / std::f32::consts::SQRT_2;
+                    let mid = (x + y) / std::f32::consts::SQRT_2;
+                    [side as f64, mid as f64]
+                } else {
+                    [*x as f64, *y as f64]
+                }
+            })
+            .collect();
+
+        Plot::new("plot")
+            .include_x(-1.0)
+            .include_x(1.0)
+            .include_y(-1.0)
+            .include_y(1.0)
+            .data_aspect(1.0)
+            .view_aspect(1.0)
+            .show_x(false)
+            .show_y(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .allow_boxed_zoom(false)
+            .allow_drag(false)
+            .show(ui, |ui| {
+                ui.points(Points::new(points).radius(1.0));
+            });
+    }
+
+    /// Port plotted against this one in XY mode.
+    pub fn partner(&self) -> Option<&(NodeId, String)> {
+        self.partner
+            .as_ref()
+            .filter(|_| self.mode == FloatScopeMode::Xy)
+    }
+
+    /// Samples per value the recording should send: a pair of extremes per
+    /// column of the time series is all it can show. The spectrum and XY
+    /// plots need every sample.
+    pub fn step(&self) -> usize {
+        if self.mode != FloatScopeMode::TimeSeries || self.columns <= 0.0 {
+            return 1;
+        }
+
+        let samples = self.timebase * 44100.0;
+        (samples / (2.0 * self.columns)).max(1.0) as usize
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, ports: &[PortChoice]) {
+        let mut mem_s = self.memory.len() as f32 / self.rate();
         let drag = egui::DragValue::new(&mut mem_s)
             .speed(0.01)
             .range(0.01..=120.0);
@@ -191,7 +514,7 @@ impl FloatScope {
             ui.label("s");
         });
 
-        let new_mem = (mem_s * 44100.0).round() as usize;
+        let new_mem = ((mem_s * self.rate()).round() as usize).max(1);
         if new_mem < self.memory.len() {
             self.memory.drain(0..self.memory.len() - new_mem);
         } else {
@@ -204,16 +527,42 @@ impl FloatScope {
             .selected_text(format!("{:?}", self.mode))
             .show_ui(ui, |ui| {
                 ui.selectable_value(&mut self.mode, FloatScopeMode::TimeSeries, "TimeSeries");
-                ui.selectable_value(&mut self.mode, FloatScopeMode::Fft, "Fft")
+                ui.selectable_value(&mut self.mode, FloatScopeMode::Fft, "Fft");
+                ui.selectable_value(&mut self.mode, FloatScopeMode::Xy, "Xy")
             });
 
         match self.mode {
             FloatScopeMode::TimeSeries => self.show_timeseries(ui),
             FloatScopeMode::Fft => self.show_fft(ui),
+            FloatScopeMode::Xy => self.show_xy(ui, ports),
+        }
+    }
+
+    /// Samples of the partner port, in step with the ones of [`Self::feed`].
+    pub fn feed_partner(&mut self, data: impl Iterator<Item = f32>, step: usize) {
+        if self.frozen || step != 1 {
+            return;
         }
+
+        self.partner_memory.extend(data);
+        let overflow = self.partner_memory.len().saturating_sub(XY_LEN);
+        self.partner_memory.drain(..overflow);
     }
 
-    pub fn feed(&mut self, data: impl Iterator<Item = f32>) {
+    /// `step` is the samples each value stands for.
+    pub fn feed(&mut self, data: impl Iterator<Item = f32>, step: usize) {
+        if self.frozen {
+            return;
+        }
+
+        // The memory keeps its length in seconds, starting out silent again
+        if step != self.step {
+            let secs = self.memory.len() as f32 / self.rate();
+            self.step = step;
+            let len = ((secs * self.rate()).round() as usize).max(1);
+            self.memory = std::iter::repeat(0.0).take(len).collect();
+        }
+
         for pt in data {
             self.memory.pop_front();
             self.memory.push_back(pt);
@@ -0,0 +1,164 @@
+use eframe::egui::{self, Color32};
+
+use crate::compute::Value;
+
+// Samples folded into one step of the meter
+const DECIMATE: usize = 64;
+// Lowest level shown, in dBFS
+const FLOOR_DB: f32 = -60.0;
+// Fall of the peak once the hold is over, in dB per second
+const PEAK_FALL_DB: f32 = 20.0;
+// Time the highest peak stays up, in seconds
+const HOLD_SECS: f32 = 1.5;
+// Integration time of the RMS reading, in seconds
+const RMS_SECS: f32 = 0.3;
+
+fn db(level: f32) -> f32 {
+    20.0 * level.max(1e-6).log10()
+}
+
+/// Peak and RMS level of a float output. Samples are reduced to one step per
+/// `DECIMATE`, so a meter costs next to nothing next to a scope.
+#[derive(Debug, Clone, Default)]
+pub struct Meter {
+    peak: f32,
+    // Mean square with the RMS ballistics
+    square: f32,
+    held: f32,
+    // Steps until the held peak starts to fall
+    hold_left: usize,
+    clipped: bool,
+    // Partial step carried over between feeds
+    chunk_peak: f32,
+    chunk_square: f32,
+    chunk_len: usize,
+}
+
+impl Meter {
+    /// Samples per value a meter is fine with, binned extremes keep peaks.
+    pub const STEP: usize = DECIMATE / 2;
+
+    /// `step` is the samples each value stands for.
+    pub fn feed(&mut self, data: &[Value], step: usize) {
+        for sample in data.iter().filter_map(Value::as_float) {
+            let level = sample.abs();
+            // Blown up signals count as clipping too
+            self.clipped |= !level.is_finite() || level >= 1.0;
+            if !level.is_finite() {
+                continue;
+            }
+
+            self.chunk_peak = self.chunk_peak.max(level);
+            self.chunk_square += level * level * step as f32;
+            self.chunk_len += step;
+            if self.chunk_len >= DECIMATE {
+                self.step();
+            }
+        }
+    }
+
+    fn step(&mut self) {
+        let secs = self.chunk_len as f32 / 44100.0;
+        let fall = 10f32.powf(-PEAK_FALL_DB * secs / 20.0);
+
+        self.peak = self.chunk_peak.max(self.peak * fall);
+        let square = self.chunk_square / self.chunk_len as f32;
+        self.square += (square - self.square) * (1.0 - (-secs / RMS_SECS).exp());
+
+        if self.peak >= self.held {
+            self.held = self.peak;
+            self.hold_left = (HOLD_SECS / secs) as usize;
+        } else if self.hold_left > 0 {
+            self.hold_left -= 1;
+        } else {
+            self.held *= fall;
+        }
+
+        self.chunk_peak = 0.0;
+        self.chunk_square = 0.0;
+        self.chunk_len = 0;
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        let peak_db = db(self.peak);
+        let rms_db = db(self.square.sqrt());
+
+        ui.horizontal(|ui| {
+            let size = egui::vec2(160.0, 14.0);
+            let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            let visuals = ui.visuals();
+            let x = |db: f32| {
+                let t = ((db - FLOOR_DB) / -FLOOR_DB).clamp(0.0, 1.0);
+                rect.left() + t * rect.width()
+            };
+
+            painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+            let level = |db: f32| {
+                let mut bar = rect;
+                bar.set_right(x(db));
+                bar
+            };
+            let peak_color = if peak_db > -6.0 {
+                Color32::YELLOW
+            } else {
+                Color32::DARK_GREEN
+            };
+            painter.rect_filled(level(peak_db), 0.0, peak_color);
+            painter.rect_filled(
+                level(rms_db).shrink2(egui::vec2(0.0, 3.0)),
+                0.0,
+                Color32::GREEN,
+            );
+
+            let held = x(db(self.held));
+            painter.line_segment(
+                [
+                    egui::pos2(held, rect.top()),
+                    egui::pos2(held, rect.bottom()),
+                ],
+                egui::Stroke::new(1.5, visuals.strong_text_color()),
+            );
+
+            for tick in [-48.0, -36.0, -24.0, -12.0, -6.0] {
+                let tick = x(tick);
+                painter.line_segment(
+                    [
+                        egui::pos2(tick, rect.bottom() - 3.0),
+                        egui::pos2(tick, rect.bottom()),
+                    ],
+                    visuals.window_stroke,
+                );
+            }
+
+            let clip = egui::RichText::new("CLIP").small().color(if self.clipped {
+                Color32::RED
+            } else {
+                visuals.weak_text_color()
+            });
+            if ui
+                .add(egui::Label::new(clip).sense(egui::Sense::click()))
+                .on_hover_text("Held once the output reaches full scale, click to reset")
+                .clicked()
+            {
+                self.clipped = false;
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let reading = |db: f32| {
+                if db <= FLOOR_DB {
+                    "-∞".to_string()
+                } else {
+                    format!("{db:.1}")
+                }
+            };
+            ui.weak(format!(
+                "peak {} dBFS, rms {} dBFS",
+                reading(peak_db),
+                reading(rms_db)
+            ));
+        });
+    }
+}
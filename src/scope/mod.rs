@@ -1,4 +1,5 @@
 use eframe::egui;
+use egui_graph_edit::NodeId;
 use float::FloatScope;
 use midi::MidiScope;
 use serde::{Deserialize, Serialize};
@@ -6,8 +7,12 @@ use serde::{Deserialize, Serialize};
 use crate::compute::{Value, ValueKind};
 
 mod float;
+mod meter;
 mod midi;
 
+pub use float::PortChoice;
+pub use meter::Meter;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Scope {
     Float(FloatScope),
@@ -20,15 +25,40 @@ impl Scope {
         Scope::Unknown
     }
 
-    pub fn show(&mut self, ui: &mut egui::Ui) {
+    /// `ports` are the outputs an XY scope can be plotted against.
+    pub fn show(&mut self, ui: &mut egui::Ui, ports: &[PortChoice]) {
         match self {
-            Scope::Float(fscope) => fscope.show(ui),
+            Scope::Float(fscope) => fscope.show(ui, ports),
             Scope::Midi(mscope) => mscope.show(ui),
             Scope::Unknown => {}
         }
     }
 
-    pub fn feed(&mut self, mut data: Vec<Value>) {
+    /// Port the scope is plotted against, recorded along with its own.
+    pub fn partner(&self) -> Option<&(NodeId, String)> {
+        match self {
+            Scope::Float(fscope) => fscope.partner(),
+            _ => None,
+        }
+    }
+
+    pub fn feed_partner(&mut self, data: &[Value], step: usize) {
+        if let Scope::Float(fscope) = self {
+            fscope.feed_partner(data.iter().filter_map(Value::as_float), step);
+        }
+    }
+
+    /// Samples per value the scope wants its recording to send.
+    pub fn step(&self) -> usize {
+        match self {
+            Scope::Float(fscope) => fscope.step(),
+            _ => 1,
+        }
+    }
+
+    /// `step` is the samples each float value stands for, midi is never
+    /// decimated.
+    pub fn feed(&mut self, mut data: Vec<Value>, step: usize) {
         data.retain(|value| !matches!(value, Value::None | Value::Disconnected));
 
         if data.is_empty() {
@@ -65,6 +95,7 @@ impl Scope {
                 data[start_at..]
                     .iter()
                     .map(|value| value.as_float().unwrap()),
+                step,
             ),
             Scope::Midi(mscope) => mscope.feed(
                 data[start_at..]
@@ -0,0 +1,217 @@
+use std::{fs::File, path::PathBuf};
+
+use anyhow::{anyhow, Result};
+use eframe::egui::{self, ThemePreference};
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+
+use crate::{audio::AudioSettings, commands::Keymap, library::Library, notify, tour::TourStep};
+
+const FILE: &str = "settings.json";
+/// Zoom of the whole interface allowed, apart from the zoom of the graph
+pub const UI_SCALE: std::ops::RangeInclusive<f32> = 0.5..=3.0;
+// Change of the interface zoom per shortcut press
+const UI_SCALE_STEP: f32 = 1.1;
+
+fn path() -> Option<PathBuf> {
+    eframe::storage_dir("Modal").map(|dir| dir.join(FILE))
+}
+
+/// Preferences of the application, kept in a file of their own so they
+/// don't travel with the last session or any patch.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: ThemePreference,
+    pub ui_scale: f32,
+    /// Seconds between saves of the session
+    pub autosave_secs: f32,
+    /// Folder patch and MIDI file dialogs open in
+    pub patch_dir: Option<PathBuf>,
+    pub audio: AudioSettings,
+    pub keymap: Keymap,
+    pub tour: TourStep,
+    pub library: Library,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            theme: ThemePreference::System,
+            ui_scale: 1.0,
+            autosave_secs: 30.0,
+            patch_dir: None,
+            audio: AudioSettings::default(),
+            keymap: Keymap::default(),
+            tour: TourStep::default(),
+            library: Library::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Reads the settings file, `None` if there is none yet.
+    pub fn load() -> Option<Self> {
+        let path = path()?;
+        let file = File::open(&path).ok()?;
+
+        match serde_json::from_reader(file) {
+            Ok(settings) => Some(settings),
+            Err(e) => {
+                notify::error(&format!("Failed to read {}", path.display()), e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = path().ok_or(anyhow!("No folder to keep the settings in"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        serde_json::to_writer_pretty(File::create(path)?, self)?;
+
+        Ok(())
+    }
+
+    pub fn apply_ui(&self, ctx: &egui::Context) {
+        ctx.set_theme(self.theme);
+        ctx.set_zoom_factor(self.ui_scale);
+    }
+
+    /// Zooms the interface in by a step, or out for negative `steps`.
+    pub fn zoom(&mut self, ctx: &egui::Context, steps: i32) {
+        let scale = self.ui_scale * UI_SCALE_STEP.powi(steps);
+        self.ui_scale = scale.clamp(*UI_SCALE.start(), *UI_SCALE.end());
+        ctx.set_zoom_factor(self.ui_scale);
+    }
+
+    pub fn reset_zoom(&mut self, ctx: &egui::Context) {
+        self.ui_scale = 1.0;
+        ctx.set_zoom_factor(self.ui_scale);
+    }
+
+    /// File dialog opening in the patch folder.
+    pub fn file_dialog(&self) -> FileDialog {
+        match &self.patch_dir {
+            Some(dir) => FileDialog::new().set_directory(dir),
+            None => FileDialog::new(),
+        }
+    }
+}
+
+/// Window editing the settings that have no other place in the editor.
+#[derive(Debug, Default)]
+pub struct SettingsDialog {
+    pub open: bool,
+}
+
+impl SettingsDialog {
+    /// `audio`, `shortcuts` and `library` are the open states of the dialogs
+    /// covering the rest of the settings.
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        settings: &mut Settings,
+        audio: &mut bool,
+        shortcuts: &mut bool,
+        library: &mut bool,
+    ) {
+        let mut open = self.open;
+
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("settings").num_columns(2).show(ui, |ui| {
+                    ui.label("Theme");
+                    let before = settings.theme;
+                    egui::ComboBox::from_id_salt("settings-theme")
+                        .selected_text(format!("{:?}", settings.theme))
+                        .show_ui(ui, |ui| {
+                            for theme in [
+                                ThemePreference::System,
+                                ThemePreference::Dark,
+                                ThemePreference::Light,
+                            ] {
+                                ui.selectable_value(
+                                    &mut settings.theme,
+                                    theme,
+                                    format!("{theme:?}"),
+                                );
+                            }
+                        });
+                    if settings.theme != before {
+                        ctx.set_theme(settings.theme);
+                    }
+                    ui.end_row();
+
+                    ui.label("UI zoom");
+                    let mut percent = settings.ui_scale * 100.0;
+                    let zoom = ui
+                        .add(
+                            egui::Slider::new(
+                                &mut percent,
+                                UI_SCALE.start() * 100.0..=UI_SCALE.end() * 100.0,
+                            )
+                            .fixed_decimals(0)
+                            .suffix(" %"),
+                        )
+                        .on_hover_text(
+                            "Size of the whole editor, apart from the zoom of the graph",
+                        );
+                    if zoom.drag_stopped() || (zoom.changed() && !zoom.dragged()) {
+                        settings.ui_scale = percent / 100.0;
+                        ctx.set_zoom_factor(settings.ui_scale);
+                    }
+                    ui.end_row();
+
+                    ui.label("Autosave");
+                    ui.add(
+                        egui::DragValue::new(&mut settings.autosave_secs)
+                            .range(5.0..=600.0)
+                            .suffix(" s"),
+                    )
+                    .on_hover_text("Interval between saves of the session");
+                    ui.end_row();
+
+                    ui.label("Tour");
+                    if ui
+                        .button("Restart")
+                        .on_hover_text("Show the guided tour of the editor again")
+                        .clicked()
+                    {
+                        settings.tour = TourStep::default();
+                    }
+                    ui.end_row();
+
+                    ui.label("Patch folder");
+                    ui.horizontal(|ui| {
+                        let folder = settings
+                            .patch_dir
+                            .as_ref()
+                            .map(|dir| dir.display().to_string())
+                            .unwrap_or("None".into());
+                        if ui.button(folder).clicked() {
+                            if let Some(dir) = FileDialog::new().pick_folder() {
+                                settings.patch_dir = Some(dir);
+                            }
+                        }
+                        if settings.patch_dir.is_some() && ui.small_button("✖").clicked() {
+                            settings.patch_dir = None;
+                        }
+                    });
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.toggle_value(audio, "Audio…");
+                    ui.toggle_value(shortcuts, "Shortcuts…");
+                    ui.toggle_value(library, "Node library…");
+                });
+            });
+
+        self.open = open;
+    }
+}
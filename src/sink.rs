@@ -0,0 +1,429 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::Thread,
+    time::Duration,
+};
+
+use rodio::{buffer::SamplesBuffer, Source};
+use rubato::{FftFixedIn, Resampler};
+use serde::{Deserialize, Serialize};
+use thunderdome::Index;
+
+use crate::compute::{bus, extern_out, OutputPort, Runtime, Value};
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct SinkId(u32);
+
+impl SinkId {
+    /// Speaker output, created together with the runtime thread
+    pub const PLAYBACK: SinkId = SinkId(0);
+
+    pub fn next(self) -> SinkId {
+        SinkId(self.0 + 1)
+    }
+}
+
+/// Destination for the samples of a single output port.
+pub trait Sink: Debug + Send {
+    /// Receives one block of samples, already scaled by the sink's gain.
+    fn consume(&mut self, block: &[f32]);
+
+    /// Seconds of audio queued ahead of the listener. Sinks reporting this
+    /// pace the runtime; the least buffered one decides when to step. The
+    /// runtime thread parks while they are full, so they must unpark it
+    /// whenever their queue shrinks.
+    fn queued_secs(&self) -> Option<f32> {
+        None
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    port: Option<OutputPort>,
+    // Takes whatever playback plays instead of its own port
+    tap: bool,
+    gain: f32,
+    block: Vec<f32>,
+    sink: Box<dyn Sink>,
+}
+
+/// Sinks registered on the runtime thread, each subscribed to one port.
+///
+/// Samples are collected every step, so all sinks see exactly the same
+/// samples, and handed over in blocks on [`Sinks::flush`].
+#[derive(Debug, Default)]
+pub struct Sinks {
+    entries: HashMap<SinkId, Entry>,
+}
+
+impl Sinks {
+    pub fn insert(&mut self, id: SinkId, port: Option<OutputPort>, gain: f32, sink: Box<dyn Sink>) {
+        self.entries.insert(
+            id,
+            Entry {
+                port,
+                tap: false,
+                gain,
+                block: Vec::new(),
+                sink,
+            },
+        );
+    }
+
+    /// Adds a sink receiving the samples of playback before its gain, so it
+    /// follows the played port and Main Out.
+    pub fn insert_tap(&mut self, id: SinkId, sink: Box<dyn Sink>) {
+        self.entries.insert(
+            id,
+            Entry {
+                port: None,
+                tap: true,
+                gain: 1.0,
+                block: Vec::new(),
+                sink,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, id: SinkId) {
+        // Playback paces the runtime, so it has to stay around
+        if id != SinkId::PLAYBACK {
+            self.entries.remove(&id);
+        }
+    }
+
+    /// Swaps the sink behind `id`, keeping its port and gain.
+    pub fn replace(&mut self, id: SinkId, sink: Box<dyn Sink>) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.sink = sink;
+            entry.block.clear();
+        }
+    }
+
+    pub fn set_port(&mut self, id: SinkId, port: Option<OutputPort>) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.port = port;
+        }
+    }
+
+    pub fn set_gain(&mut self, id: SinkId, gain: f32) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.gain = gain;
+        }
+    }
+
+    pub fn disconnect_node(&mut self, node: Index) {
+        for entry in self.entries.values_mut() {
+            if entry.port.map(|port| port.node) == Some(node) {
+                entry.port = None;
+            }
+        }
+    }
+
+    pub fn sample(&mut self, rt: &Runtime) {
+        for k in 1..=bus::AUX {
+            if let Some(sample) = rt.bus(k) {
+                extern_out::push(bus::aux_channel(k), sample);
+            }
+        }
+
+        let peek = |port: Option<OutputPort>| {
+            port.map(|port| rt.peek(port))
+                .as_ref()
+                .and_then(Value::as_float)
+                .unwrap_or_default()
+        };
+
+        // Patches with a Main Out play it instead of the toggled port
+        let played = rt.bus(0).unwrap_or_else(|| {
+            peek(
+                self.entries
+                    .get(&SinkId::PLAYBACK)
+                    .and_then(|entry| entry.port),
+            )
+        });
+
+        for (id, entry) in &mut self.entries {
+            let value = if *id == SinkId::PLAYBACK || entry.tap {
+                played
+            } else {
+                peek(entry.port)
+            };
+
+            entry.block.push(value * entry.gain);
+        }
+    }
+
+    pub fn flush(&mut self) {
+        for entry in self.entries.values_mut() {
+            entry.sink.consume(&entry.block);
+            entry.block.clear();
+        }
+    }
+
+    pub fn queued_secs(&self) -> Option<f32> {
+        self.entries
+            .values()
+            .filter_map(|entry| entry.sink.queued_secs())
+            .min_by(|a, b| a.total_cmp(b))
+    }
+}
+
+/// Master volume and mute of playback, saved with the patch.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Master {
+    pub volume: f32,
+    pub mute: bool,
+}
+
+impl Default for Master {
+    fn default() -> Self {
+        Master {
+            volume: 1.0,
+            mute: false,
+        }
+    }
+}
+
+impl Master {
+    /// Gain of the playback sink.
+    pub fn gain(&self) -> f32 {
+        if self.mute {
+            0.0
+        } else {
+            self.volume
+        }
+    }
+}
+
+/// Controls of the protection applied to playback, shared with the editor.
+#[derive(Debug, Default)]
+pub struct OutputGuard {
+    bypass: AtomicBool,
+    clipped: AtomicBool,
+}
+
+impl OutputGuard {
+    pub fn bypassed(&self) -> bool {
+        self.bypass.load(Ordering::Relaxed)
+    }
+
+    pub fn set_bypass(&self, bypass: bool) {
+        self.bypass.store(bypass, Ordering::Relaxed);
+    }
+
+    /// Whether the output exceeded full scale since the last call.
+    pub fn take_clipped(&self) -> bool {
+        self.clipped.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// DC-blocking high-pass followed by a soft limiter.
+#[derive(Debug)]
+struct Safety {
+    guard: Arc<OutputGuard>,
+    dc_x: f32,
+    dc_y: f32,
+}
+
+impl Safety {
+    // Pole of the DC blocker, roughly 35 Hz at 44.1 kHz
+    const DC_POLE: f32 = 0.995;
+    // Level above which the limiter starts to bend the signal
+    const KNEE: f32 = 0.9;
+
+    fn new(guard: Arc<OutputGuard>) -> Self {
+        Safety {
+            guard,
+            dc_x: 0.0,
+            dc_y: 0.0,
+        }
+    }
+
+    fn process(&mut self, block: &mut [f32]) {
+        let bypass = self.guard.bypassed();
+        let mut clipped = false;
+
+        for sample in block {
+            clipped |= !sample.is_finite() || sample.abs() > 1.0;
+            if bypass {
+                continue;
+            }
+
+            if !sample.is_finite() {
+                // A blown up patch would otherwise poison the filter forever
+                *sample = 0.0;
+                self.dc_x = 0.0;
+                self.dc_y = 0.0;
+                continue;
+            }
+
+            self.dc_y = *sample - self.dc_x + Self::DC_POLE * self.dc_y;
+            self.dc_x = *sample;
+
+            *sample = Self::limit(self.dc_y);
+        }
+
+        if clipped {
+            self.guard.clipped.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn limit(x: f32) -> f32 {
+        let level = x.abs();
+        if level <= Self::KNEE {
+            return x;
+        }
+
+        let headroom = 1.0 - Self::KNEE;
+        let limited = Self::KNEE + headroom * ((level - Self::KNEE) / headroom).tanh();
+
+        limited.copysign(x)
+    }
+}
+
+/// Converts blocks from the runtime rate to the rate of the device.
+struct Resample {
+    rate: u32,
+    // Rebuilt whenever the block length changes, the FFT is sized by it
+    inner: Option<(usize, FftFixedIn<f32>)>,
+}
+
+impl Resample {
+    fn process(&mut self, block: Vec<f32>) -> Vec<f32> {
+        let len = block.len();
+        if self.inner.as_ref().map(|(inner_len, _)| *inner_len) != Some(len) {
+            self.inner = FftFixedIn::new(44100, self.rate as usize, len, 2, 1)
+                .ok()
+                .map(|resampler| (len, resampler));
+        }
+
+        match &mut self.inner {
+            Some((_, resampler)) => match resampler.process(&[block], None) {
+                Ok(mut out) => out.remove(0),
+                Err(_) => Vec::new(),
+            },
+            // Left to rodio, which converts by linear interpolation
+            None => block,
+        }
+    }
+
+    fn active(&self) -> bool {
+        self.inner.is_some()
+    }
+}
+
+pub struct AudioOut {
+    sink: rodio::Sink,
+    safety: Safety,
+    resample: Option<Resample>,
+    block_secs: f32,
+}
+
+impl AudioOut {
+    /// `resample_to` is the rate of the device when blocks should be
+    /// converted with rubato rather than by rodio.
+    pub fn new(
+        handle: &rodio::OutputStreamHandle,
+        guard: Arc<OutputGuard>,
+        resample_to: Option<u32>,
+    ) -> Result<Self, rodio::PlayError> {
+        let sink = rodio::Sink::try_new(handle)?;
+        sink.play();
+
+        Ok(AudioOut {
+            sink,
+            safety: Safety::new(guard),
+            resample: resample_to
+                .filter(|rate| *rate != 44100)
+                .map(|rate| Resample { rate, inner: None }),
+            block_secs: 0.0,
+        })
+    }
+}
+
+impl Debug for AudioOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AudioOut")
+            .field("block_secs", &self.block_secs)
+            .field("resample_to", &self.resample.as_ref().map(|r| r.rate))
+            .finish()
+    }
+}
+
+impl Sink for AudioOut {
+    fn consume(&mut self, block: &[f32]) {
+        let mut block = block.to_vec();
+        self.safety.process(&mut block);
+        self.block_secs = block.len() as f32 / 44100.0;
+
+        let (rate, block) = match &mut self.resample {
+            Some(resample) => {
+                let block = resample.process(block);
+                let rate = if resample.active() {
+                    resample.rate
+                } else {
+                    44100
+                };
+                (rate, block)
+            }
+            None => (44100, block),
+        };
+
+        self.sink.append(Notify {
+            samples: SamplesBuffer::new(1, rate, block),
+            thread: std::thread::current(),
+        });
+    }
+
+    fn queued_secs(&self) -> Option<f32> {
+        Some(self.sink.len() as f32 * self.block_secs)
+    }
+}
+
+/// Block of samples which unparks the thread that queued it once played.
+///
+/// The wakeup happens on drop rather than on the last sample, because
+/// `rodio::Sink` only updates its length after the source is exhausted.
+struct Notify {
+    samples: SamplesBuffer<f32>,
+    thread: Thread,
+}
+
+impl Drop for Notify {
+    fn drop(&mut self) {
+        self.thread.unpark();
+    }
+}
+
+impl Iterator for Notify {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        self.samples.next()
+    }
+}
+
+impl Source for Notify {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.samples.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.samples.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.samples.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.samples.total_duration()
+    }
+}
@@ -0,0 +1,147 @@
+use std::{collections::HashMap, sync::Arc};
+
+use eframe::egui;
+use egui_graph_edit::NodeId;
+use serde::{Deserialize, Serialize};
+
+use crate::{compute::node::InputUi, graph::SynthGraphState};
+
+type UiInputs = HashMap<NodeId, HashMap<String, Arc<dyn InputUi>>>;
+
+/// Values of every input edited as a single number, the topology isn't
+/// part of it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    values: Vec<(NodeId, String, f32)>,
+}
+
+impl Snapshot {
+    fn capture(inputs: &UiInputs) -> Self {
+        let values = inputs
+            .iter()
+            .flat_map(|(node_id, inputs)| {
+                inputs.iter().filter_map(|(name, input)| {
+                    input.value().map(|value| (*node_id, name.clone(), value))
+                })
+            })
+            .collect();
+
+        Snapshot { values }
+    }
+
+    fn get(&self, node_id: NodeId, name: &str) -> Option<f32> {
+        self.values
+            .iter()
+            .find(|(id, input, _)| *id == node_id && input == name)
+            .map(|(_, _, value)| *value)
+    }
+}
+
+/// A and B snapshots of the patch, saved with it.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Snapshots {
+    pub a: Option<Snapshot>,
+    pub b: Option<Snapshot>,
+    /// Position between A at 0 and B at 1
+    pub morph: f32,
+}
+
+impl Snapshots {
+    /// Sets the inputs to the value at `morph`. Inputs stored in only one
+    /// snapshot keep its value, ones that aren't continuous take the value
+    /// of the nearer snapshot.
+    fn apply(&self, inputs: &UiInputs) {
+        let (a, b) = match (&self.a, &self.b) {
+            (Some(a), Some(b)) => (a, b),
+            (Some(only), None) | (None, Some(only)) => (only, only),
+            (None, None) => return,
+        };
+
+        for (node_id, ins) in inputs {
+            for (name, input) in ins {
+                let value = match (a.get(*node_id, name), b.get(*node_id, name)) {
+                    (Some(a), Some(b)) if input.smoothed() => a + (b - a) * self.morph,
+                    (Some(a), Some(b)) => {
+                        if self.morph < 0.5 {
+                            a
+                        } else {
+                            b
+                        }
+                    }
+                    (Some(only), None) | (None, Some(only)) => only,
+                    (None, None) => continue,
+                };
+                input.set_value(value);
+            }
+        }
+    }
+}
+
+/// Window storing the snapshots and morphing between them.
+#[derive(Debug, Default)]
+pub struct SnapshotEditor {
+    pub open: bool,
+}
+
+impl SnapshotEditor {
+    pub fn show(&mut self, ctx: &egui::Context, user_state: &mut SynthGraphState) {
+        let mut open = self.open;
+        let SynthGraphState {
+            snapshots,
+            node_ui_inputs,
+            ..
+        } = user_state;
+
+        egui::Window::new("Snapshots")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let mut changed = false;
+
+                egui::Grid::new("snapshots").num_columns(3).show(ui, |ui| {
+                    for (name, slot, at) in
+                        [("A", &mut snapshots.a, 0.0), ("B", &mut snapshots.b, 1.0)]
+                    {
+                        ui.label(name);
+                        if ui
+                            .button("Store")
+                            .on_hover_text("Take the current values")
+                            .clicked()
+                        {
+                            *slot = Some(Snapshot::capture(node_ui_inputs));
+                        }
+                        if ui
+                            .add_enabled(slot.is_some(), egui::Button::new("Recall"))
+                            .clicked()
+                        {
+                            snapshots.morph = at;
+                            changed = true;
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("A");
+                    changed |= ui
+                        .add(egui::Slider::new(&mut snapshots.morph, 0.0..=1.0).show_value(false))
+                        .changed();
+                    ui.label("B");
+                    if ui
+                        .button("⇄")
+                        .on_hover_text("Switch between A and B")
+                        .clicked()
+                    {
+                        snapshots.morph = if snapshots.morph < 0.5 { 1.0 } else { 0.0 };
+                        changed = true;
+                    }
+                });
+
+                if changed {
+                    snapshots.apply(node_ui_inputs);
+                }
+            });
+
+        self.open = open;
+    }
+}
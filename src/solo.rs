@@ -0,0 +1,89 @@
+use std::collections::HashSet;
+
+use egui_graph_edit::NodeId;
+
+use crate::graph::{SynthDataType, SynthGraph};
+
+// Connected inputs of a node, with their port and the node feeding them
+fn sources(graph: &SynthGraph, node_id: NodeId) -> Vec<(usize, NodeId, SynthDataType)> {
+    let Some(node) = graph.nodes.get(node_id) else {
+        return Vec::new();
+    };
+
+    node.inputs
+        .iter()
+        .enumerate()
+        .filter_map(|(port, (_, input_id))| {
+            let output = graph.try_get_output(graph.connection(*input_id)?)?;
+            Some((port, output.node, graph.try_get_input(*input_id)?.typ))
+        })
+        .collect()
+}
+
+/// Inputs to silence so only the soloed nodes are heard on `outputs`, the
+/// played node and every Main Out.
+///
+/// Nodes between a soloed node and an output keep the inputs fed through
+/// a soloed node and lose their other float inputs; MIDI and beats still
+/// pass, so the soloed branch keeps playing. Nothing is muted when no soloed
+/// node reaches an output.
+pub fn muted_inputs(graph: &SynthGraph, outputs: &[NodeId]) -> Vec<(NodeId, usize)> {
+    if outputs.is_empty() {
+        return Vec::new();
+    }
+
+    let soloed: HashSet<NodeId> = graph
+        .nodes
+        .iter()
+        .filter(|(_, node)| node.user_data.ui.borrow().solo)
+        .map(|(node_id, _)| node_id)
+        .collect();
+    if soloed.is_empty() {
+        return Vec::new();
+    }
+
+    // Everything feeding the output
+    let mut upstream: HashSet<NodeId> = outputs.iter().copied().collect();
+    let mut stack = outputs.to_vec();
+    while let Some(node_id) = stack.pop() {
+        for (_, src, _) in sources(graph, node_id) {
+            if upstream.insert(src) {
+                stack.push(src);
+            }
+        }
+    }
+
+    // Nodes carrying the signal of a soloed node, feedback loops included
+    let mut path: HashSet<NodeId> = soloed.intersection(&upstream).copied().collect();
+    loop {
+        let reached: Vec<_> = upstream
+            .iter()
+            .filter(|node_id| !path.contains(*node_id))
+            .filter(|node_id| {
+                sources(graph, **node_id)
+                    .iter()
+                    .any(|(_, src, _)| path.contains(src))
+            })
+            .copied()
+            .collect();
+        if reached.is_empty() {
+            break;
+        }
+        path.extend(reached);
+    }
+
+    let mut muted: Vec<_> = path
+        .iter()
+        .filter(|node_id| !soloed.contains(*node_id))
+        .flat_map(|node_id| {
+            sources(graph, *node_id)
+                .into_iter()
+                .filter(|(_, src, typ)| *typ == SynthDataType::Float && !path.contains(src))
+                .map(|(port, _, _)| (*node_id, port))
+        })
+        .collect();
+    // Stable order, so unchanged results can be told apart from new ones
+    muted.sort();
+
+    muted
+}
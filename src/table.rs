@@ -0,0 +1,72 @@
+use eframe::egui;
+
+use crate::graph::{SynthGraph, SynthGraphState};
+
+/// Grid of every stateful input in the patch, editable in place.
+#[derive(Debug, Default)]
+pub struct ParamTable {
+    pub open: bool,
+    filter: String,
+}
+
+impl ParamTable {
+    pub fn show(&mut self, ctx: &egui::Context, graph: &SynthGraph, user_state: &SynthGraphState) {
+        let mut open = self.open;
+
+        egui::Window::new("Parameters")
+            .open(&mut open)
+            .default_size([420.0, 480.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Filter");
+                    ui.text_edit_singleline(&mut self.filter);
+                });
+                ui.separator();
+
+                let filter = self.filter.to_lowercase();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("param-table")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (node_id, node) in &graph.nodes {
+                                let Some(ui_inputs) = user_state.node_ui_inputs.get(&node_id)
+                                else {
+                                    continue;
+                                };
+
+                                for (name, input_id) in &node.inputs {
+                                    let Some(input) = ui_inputs.get(name) else {
+                                        continue;
+                                    };
+
+                                    let matches = filter.is_empty()
+                                        || node.label.to_lowercase().contains(&filter)
+                                        || name.to_lowercase().contains(&filter);
+                                    if !matches {
+                                        continue;
+                                    }
+
+                                    ui.label(&node.label);
+                                    ui.label(name);
+                                    ui.push_id((node_id, name), |ui| {
+                                        ui.horizontal(|ui| {
+                                            input.show_always(ui, false);
+                                            if graph.connection(*input_id).is_some() {
+                                                ui.weak("connected");
+                                            } else {
+                                                input.show_disconnected(ui, false);
+                                            }
+                                        });
+                                    });
+                                    ui.end_row();
+                                }
+                            }
+                        });
+                });
+            });
+
+        self.open = open;
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use eframe::egui::{self, Color32, Id, Rect};
+use serde::{Deserialize, Serialize};
+
+/// Part of the editor a step of the tour points at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Target {
+    Canvas,
+    Play,
+    Scope,
+    File,
+}
+
+fn marks_id() -> Id {
+    Id::new("tour-marks")
+}
+
+/// Remembers where `target` is drawn this frame, the first of several wins.
+pub fn mark(ctx: &egui::Context, target: Target, rect: Rect) {
+    ctx.data_mut(|data| {
+        data.get_temp_mut_or_default::<HashMap<Target, Rect>>(marks_id())
+            .entry(target)
+            .or_insert(rect);
+    });
+}
+
+/// Progress through the guided tour for new users, kept in the settings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TourStep {
+    #[default]
+    Welcome,
+    Canvas,
+    Finder,
+    Play,
+    Scope,
+    File,
+    Done,
+}
+
+impl TourStep {
+    const ALL: [TourStep; 6] = [
+        TourStep::Welcome,
+        TourStep::Canvas,
+        TourStep::Finder,
+        TourStep::Play,
+        TourStep::Scope,
+        TourStep::File,
+    ];
+
+    fn index(self) -> usize {
+        TourStep::ALL
+            .iter()
+            .position(|step| *step == self)
+            .unwrap_or(TourStep::ALL.len())
+    }
+
+    fn next(self) -> Self {
+        TourStep::ALL
+            .get(self.index() + 1)
+            .copied()
+            .unwrap_or(TourStep::Done)
+    }
+
+    fn prev(self) -> Self {
+        TourStep::ALL[self.index().saturating_sub(1)]
+    }
+
+    fn target(self) -> Option<Target> {
+        match self {
+            TourStep::Canvas | TourStep::Finder => Some(Target::Canvas),
+            TourStep::Play => Some(Target::Play),
+            TourStep::Scope => Some(Target::Scope),
+            TourStep::File => Some(Target::File),
+            TourStep::Welcome | TourStep::Done => None,
+        }
+    }
+
+    fn text(self) -> (&'static str, &'static str) {
+        match self {
+            TourStep::Welcome => (
+                "Welcome to Modal",
+                "Sounds are made by connecting nodes. A few steps show where \
+                 everything is, skip them any time.",
+            ),
+            TourStep::Canvas => (
+                "Canvas",
+                "Nodes live here. Drag the background to pan, scroll to zoom and \
+                 drag from an output to an input to connect two nodes.",
+            ),
+            TourStep::Finder => (
+                "Node finder",
+                "Right click the canvas to place a node, type to search or \
+                 browse the categories. Try an Oscillator.",
+            ),
+            TourStep::Play => (
+                "Play",
+                "Every output of a node can be heard with its Play button, one \
+                 output at a time. Place a node to see it.",
+            ),
+            TourStep::Scope => (
+                "Scope",
+                "Scope plots what an output does over time, Meter shows how \
+                 loud it is.",
+            ),
+            TourStep::File => (
+                "File",
+                "Save and load patches here. Factory holds example patches to \
+                 start from.",
+            ),
+            TourStep::Done => ("", ""),
+        }
+    }
+}
+
+/// Draws the callout of `step` next to what it's about, and moves through
+/// the tour from its buttons. Called every frame, `hidden` while the editor
+/// isn't there to be toured.
+pub fn show(ctx: &egui::Context, step: &mut TourStep, hidden: bool) {
+    // Marks are made anew every frame, targets that went away aren't shown
+    let marks = ctx.data_mut(|data| data.remove_temp::<HashMap<Target, Rect>>(marks_id()));
+    if hidden || *step == TourStep::Done {
+        return;
+    }
+
+    let screen = ctx.screen_rect();
+    let rect = step
+        .target()
+        .and_then(|target| marks.as_ref()?.get(&target).copied());
+
+    let (pivot, pos) = match rect {
+        // Large targets get the callout over them
+        Some(rect) if rect.width() > screen.width() / 2.0 => {
+            (egui::Align2::CENTER_CENTER, rect.center())
+        }
+        Some(rect) if rect.bottom() + 160.0 < screen.bottom() => (
+            egui::Align2::LEFT_TOP,
+            rect.left_bottom() + egui::vec2(0.0, 12.0),
+        ),
+        Some(rect) => (
+            egui::Align2::LEFT_BOTTOM,
+            rect.left_top() - egui::vec2(0.0, 12.0),
+        ),
+        None => (egui::Align2::CENTER_CENTER, screen.center()),
+    };
+
+    if let Some(rect) = rect {
+        let painter = ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            Id::new("tour-highlight"),
+        ));
+        painter.rect_stroke(
+            rect.expand(4.0),
+            6.0,
+            egui::Stroke::new(3.0, Color32::from_rgb(0x4a, 0x9e, 0xff)),
+        );
+    }
+
+    let (title, body) = step.text();
+    egui::Area::new(Id::new("tour-callout"))
+        .order(egui::Order::Foreground)
+        .pivot(pivot)
+        .fixed_pos(pos)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_max_width(280.0);
+                ui.horizontal(|ui| {
+                    ui.strong(title);
+                    ui.weak(format!("{}/{}", step.index() + 1, TourStep::ALL.len()));
+                });
+                ui.label(body);
+
+                ui.horizontal(|ui| {
+                    if *step != TourStep::Welcome && ui.button("Back").clicked() {
+                        *step = step.prev();
+                    }
+                    let last = step.next() == TourStep::Done;
+                    if ui.button(if last { "Finish" } else { "Next" }).clicked() {
+                        *step = step.next();
+                    }
+                    if !last && ui.small_button("Skip tour").clicked() {
+                        *step = TourStep::Done;
+                    }
+                });
+            });
+        });
+}
@@ -72,7 +72,9 @@ pub mod serde_arena {
         let mut arena = Arena::new();
 
         for (bits, entry) in arena_deserializable {
-            arena.insert_at(Index::from_bits(bits).unwrap(), entry);
+            let index = Index::from_bits(bits)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid arena index {bits}")))?;
+            arena.insert_at(index, entry);
         }
 
         Ok(arena)
@@ -102,9 +104,9 @@ pub mod serde_smf {
     {
         let smf_deserializable = Smf::deserialize(d)?;
 
-        Ok(midly::Smf::parse(&smf_deserializable.0)
-            .unwrap()
-            .make_static())
+        let smf = midly::Smf::parse(&smf_deserializable.0).map_err(serde::de::Error::custom)?;
+
+        Ok(smf.make_static())
     }
 }
 
@@ -211,7 +213,9 @@ pub mod serde_thunderdome_index {
     {
         let deserializable = u64::deserialize(d)?;
 
-        Ok(Index::from_bits(deserializable).unwrap())
+        Index::from_bits(deserializable).ok_or_else(|| {
+            serde::de::Error::custom(format!("invalid arena index {deserializable}"))
+        })
     }
 }
 
@@ -243,30 +247,39 @@ pub fn enum_combo_box<
 >(
     ui: &mut eframe::egui::Ui,
     e: &mut E,
-) {
+) -> bool {
+    let mut changed = false;
+
     eframe::egui::ComboBox::from_id_salt(e.type_id())
         .selected_text(format!("{e}"))
         .show_ui(ui, |ui| {
             for variant in E::iter() {
                 let name = format!("{variant}");
-                ui.selectable_value(e, variant, name);
+                changed |= ui.selectable_value(e, variant, name).changed();
             }
         });
+
+    changed
 }
 
 pub mod perlin {
+    use rand::Rng;
     use serde::{Deserialize, Serialize};
 
+    use crate::compute::deterministic;
+
     #[derive(Clone, Debug, Serialize, Deserialize)]
     pub struct Perlin1D {
         rand_noise: Vec<f32>,
     }
 
     impl Perlin1D {
+        /// Gradients are drawn from the shared seed while deterministic.
         pub fn new() -> Self {
-            Perlin1D {
-                rand_noise: (0..44100).map(|_| rand::random()).collect(),
-            }
+            let mut rng = deterministic::rng();
+            let rand_noise = (0..44100).map(|_| rng.gen()).collect();
+
+            Perlin1D { rand_noise }
         }
 
         fn fade(t: f32) -> f32 {
@@ -308,10 +321,44 @@ pub fn toggle_button(label: &str, state: bool) -> eframe::egui::Button {
     }
 }
 
+/// Loads an image for the UI, a missing or broken one is reported and drawn
+/// as a single transparent pixel.
 pub fn load_image_from_path(path: impl AsRef<std::path::Path>) -> eframe::egui::ColorImage {
-    let image = image::io::Reader::open(path).unwrap().decode().unwrap();
+    let path = path.as_ref();
+    let decoded = image::io::Reader::open(path)
+        .map_err(anyhow::Error::from)
+        .and_then(|reader| Ok(reader.decode()?));
+    let image = match decoded {
+        Ok(image) => image,
+        Err(e) => {
+            crate::notify::error(&format!("Failed to load {}", path.display()), e);
+            return eframe::egui::ColorImage::new([1, 1], eframe::epaint::Color32::TRANSPARENT);
+        }
+    };
     let size = [image.width() as _, image.height() as _];
     let image_buffer = image.to_rgba8();
     let pixels = image_buffer.as_flat_samples();
     eframe::egui::ColorImage::from_rgba_unmultiplied(size, pixels.as_slice())
 }
+
+/// Reads a WAV file of any bit depth, averaged to mono, along with its
+/// sample rate.
+pub fn load_sample(path: impl AsRef<std::path::Path>) -> anyhow::Result<(Vec<f32>, u32)> {
+    let mut file = std::fs::File::open(path)?;
+    let (header, data) = wav::read(&mut file)?;
+
+    let samples: Vec<f32> = match data {
+        wav::BitDepth::Eight(data) => data.iter().map(|&s| (s as f32 - 128.0) / 128.0).collect(),
+        wav::BitDepth::Sixteen(data) => data.iter().map(|&s| s as f32 / 32768.0).collect(),
+        wav::BitDepth::TwentyFour(data) => data.iter().map(|&s| s as f32 / 8388608.0).collect(),
+        wav::BitDepth::ThirtyTwoFloat(data) => data,
+        wav::BitDepth::Empty => Vec::new(),
+    };
+    let channels = header.channel_count.max(1) as usize;
+    let mono = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    Ok((mono, header.sampling_rate))
+}
@@ -1,5 +1,33 @@
 use std::f32::consts::PI;
 
+// Correction smoothing a unit step at phase 0, `p` being the phase past it
+// and `dp` the phase advanced per sample (PolyBLEP)
+fn blep(p: f32, dp: f32) -> f32 {
+    if p < dp {
+        let x = p / dp;
+        -(1.0 - x).powi(2) / 2.0
+    } else if p > 1.0 - dp {
+        let x = (p - 1.0) / dp;
+        (1.0 + x).powi(2) / 2.0
+    } else {
+        0.0
+    }
+}
+
+// Same as `blep` for a unit change of the slope, the integral of `blep`
+// (PolyBLAMP)
+fn blamp(p: f32, dp: f32) -> f32 {
+    if p < dp {
+        let x = p / dp;
+        dp * (1.0 - x).powi(3) / 6.0
+    } else if p > 1.0 - dp {
+        let x = (p - 1.0) / dp;
+        dp * (1.0 + x).powi(3) / 6.0
+    } else {
+        0.0
+    }
+}
+
 enum PureWave {
     Sine,
     Triangle { skew: f32 },
@@ -32,6 +60,30 @@ impl PureWave {
             }
         }
     }
+
+    // `sample` with the jumps and corners smoothed for a wave advancing by
+    // `dt` per sample, so it doesn't alias
+    fn sample_bl(&self, t: f32, dt: f32) -> f32 {
+        let naive = self.sample(t);
+        let (p, dp) = (t / 2.0, (dt.abs() / 2.0).min(0.5));
+        if dp == 0.0 {
+            return naive;
+        }
+
+        match self {
+            PureWave::Sine => naive,
+            PureWave::Triangle { skew } => {
+                // Rises over 1/m of the period, falls over the rest
+                let m = 2.0 + (skew * 8.0).powf(2.0);
+                let corner = 2.0 * m + 2.0 * m / (m - 1.0);
+
+                naive + corner * (blamp(p, dp) - blamp((p - 1.0 / m).rem_euclid(1.0), dp))
+            }
+            PureWave::Square { duty } => {
+                naive + 2.0 * (blep(p, dp) - blep((p - duty).rem_euclid(1.0), dp))
+            }
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -43,21 +95,22 @@ enum WaveScaleType {
 }
 
 impl WaveScaleType {
-    fn sample(&self, ratio: f32, t: f32) -> f32 {
+    // Blend of the pure waves, each sampled by `sample`. `pw` is the duty
+    // of the square, the last range widens it up to 1.
+    fn mix(&self, ratio: f32, pw: f32, sample: impl Fn(PureWave) -> f32) -> f32 {
         match self {
             WaveScaleType::SineTriangle => {
-                PureWave::Sine.sample(t) * (1.0 - ratio)
-                    + PureWave::Triangle { skew: 0.0 }.sample(t) * ratio
+                sample(PureWave::Sine) * (1.0 - ratio)
+                    + sample(PureWave::Triangle { skew: 0.0 }) * ratio
             }
-            WaveScaleType::TriangleSawtooth => PureWave::Triangle { skew: ratio }.sample(t),
+            WaveScaleType::TriangleSawtooth => sample(PureWave::Triangle { skew: ratio }),
             WaveScaleType::SawtoothSquare => {
-                PureWave::Triangle { skew: 1.0 }.sample(t) * (1.0 - ratio)
-                    + PureWave::Square { duty: 0.5 }.sample(t) * ratio
-            }
-            WaveScaleType::SquareDuty => PureWave::Square {
-                duty: 0.5 + ratio / 2.0,
+                sample(PureWave::Triangle { skew: 1.0 }) * (1.0 - ratio)
+                    + sample(PureWave::Square { duty: pw }) * ratio
             }
-            .sample(t),
+            WaveScaleType::SquareDuty => sample(PureWave::Square {
+                duty: pw + ratio * (1.0 - pw),
+            }),
         }
     }
 }
@@ -91,7 +144,10 @@ impl WaveScale {
         }
     }
 
-    pub fn sample(&self, t: f32) -> f32 {
-        self.wave_ty.sample(self.ratio, t)
+    /// Band-limited sample at `t` of a phase advancing by `dt` per sample,
+    /// both in half periods. `pw` is the pulse width of the square.
+    pub fn sample(&self, t: f32, dt: f32, pw: f32) -> f32 {
+        self.wave_ty
+            .mix(self.ratio, pw, |wave| wave.sample_bl(t, dt))
     }
 }